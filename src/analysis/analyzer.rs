@@ -5,6 +5,7 @@ use {
         cpp,
         eslint,
         nextest,
+        passthrough,
         python,
         standard,
     },
@@ -32,6 +33,7 @@ pub enum AnalyzerRef {
     PythonUnittest,
     Cpp,
     CppDoctest,
+    Passthrough,
 }
 
 impl AnalyzerRef {
@@ -47,6 +49,23 @@ impl AnalyzerRef {
             Self::CargoJson => Box::new(cargo_json::CargoJsonAnalyzer::default()),
             Self::Cpp => Box::new(cpp::CppAnalyzer::default()),
             Self::CppDoctest => Box::new(cpp::CppDoctestAnalyzer::default()),
+            Self::Passthrough => Box::new(passthrough::PassthroughAnalyzer::default()),
+        }
+    }
+    /// The name of the analyzer, as in the configuration
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::CargoJson => "cargo_json",
+            Self::Nextest => "nextest",
+            Self::Eslint => "eslint",
+            Self::Biome => "biome",
+            Self::PythonPytest => "python_pytest",
+            Self::PythonRuff => "python_ruff",
+            Self::PythonUnittest => "python_unittest",
+            Self::Cpp => "cpp",
+            Self::CppDoctest => "cpp_doctest",
+            Self::Passthrough => "passthrough",
         }
     }
 }
@@ -64,4 +83,10 @@ pub trait Analyzer {
     );
 
     fn build_report(&mut self) -> anyhow::Result<Report>;
+
+    /// Build a report from the lines received so far, while the
+    /// command is still running, if the analyzer supports it
+    fn build_partial_report(&mut self) -> Option<anyhow::Result<Report>> {
+        None
+    }
 }
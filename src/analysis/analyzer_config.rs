@@ -0,0 +1,118 @@
+use {
+    crate::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The analyzer of a job, either given by its name, eg `"nextest"`, or
+/// with options, eg `{ name = "nextest", options = { leaks_as_failures = true } }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AnalyzerConfig {
+    Name(AnalyzerRef),
+    WithOptions(AnalyzerWithOptions),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalyzerWithOptions {
+    pub name: AnalyzerRef,
+    #[serde(default)]
+    pub options: AnalyzerOptions,
+}
+
+/// The options of an analyzer, each analyzer reading the ones it knows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AnalyzerOptions {
+    values: toml::Table,
+}
+
+impl AnalyzerConfig {
+    pub fn analyzer_ref(&self) -> AnalyzerRef {
+        match self {
+            Self::Name(analyzer_ref) => *analyzer_ref,
+            Self::WithOptions(config) => config.name,
+        }
+    }
+    pub fn options(&self) -> Option<&AnalyzerOptions> {
+        match self {
+            Self::Name(_) => None,
+            Self::WithOptions(config) => Some(&config.options),
+        }
+    }
+}
+
+impl From<AnalyzerRef> for AnalyzerConfig {
+    fn from(analyzer_ref: AnalyzerRef) -> Self {
+        Self::Name(analyzer_ref)
+    }
+}
+
+impl AnalyzerOptions {
+    /// The value of a boolean option, false when it's not set
+    pub fn bool(
+        &self,
+        key: &str,
+    ) -> bool {
+        match self.values.get(key) {
+            Some(toml::Value::Boolean(b)) => *b,
+            Some(value) => {
+                warn!("analyzer option {key:?} should be a boolean, not {value}");
+                false
+            }
+            None => false,
+        }
+    }
+    pub fn string(
+        &self,
+        key: &str,
+    ) -> Option<&str> {
+        match self.values.get(key) {
+            Some(toml::Value::String(s)) => Some(s),
+            Some(value) => {
+                warn!("analyzer option {key:?} should be a string, not {value}");
+                None
+            }
+            None => None,
+        }
+    }
+    /// The value of an option which is a list of strings, empty when
+    /// it's not set
+    pub fn strings(
+        &self,
+        key: &str,
+    ) -> Vec<String> {
+        match self.values.get(key) {
+            Some(toml::Value::Array(values)) => values
+                .iter()
+                .filter_map(|value| value.as_str().map(String::from))
+                .collect(),
+            Some(value) => {
+                warn!("analyzer option {key:?} should be a list of strings, not {value}");
+                Vec::new()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn test_analyzer_config_deserialization() {
+    #[derive(Deserialize)]
+    struct Conf {
+        analyzer: AnalyzerConfig,
+    }
+    let conf: Conf = toml::from_str(r#"analyzer = "nextest""#).unwrap();
+    assert_eq!(conf.analyzer, AnalyzerConfig::Name(AnalyzerRef::Nextest));
+    assert!(conf.analyzer.options().is_none());
+    let conf: Conf = toml::from_str(
+        r#"analyzer = { name = "standard", options = { ignored_deprecations = ["old_crate"] } }"#,
+    )
+    .unwrap();
+    assert_eq!(conf.analyzer.analyzer_ref(), AnalyzerRef::Standard);
+    let options = conf.analyzer.options().unwrap();
+    assert_eq!(options.strings("ignored_deprecations"), vec!["old_crate"]);
+    assert!(!options.bool("leaks_as_failures"));
+}
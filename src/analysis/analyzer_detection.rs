@@ -0,0 +1,128 @@
+use crate::*;
+
+/// Programs which run the tool given after them
+const LAUNCHERS: &[&str] = &["bunx", "env", "npx", "pipx", "pnpx", "time"];
+
+/// Programs which run the tool given after their `run`/`exec` subcommand
+const RUNNERS: &[&str] = &["bun", "hatch", "npm", "pdm", "pnpm", "poetry", "uv", "yarn"];
+
+/// Guess the analyzer understanding the output of the command, from its
+/// tokens, eg `cargo nextest run` or `npx eslint src`.
+///
+/// Return None when the tool isn't recognized. Tokens are also split on
+/// spaces, so that commands run through a shell are recognized too.
+pub fn detect_analyzer(command: &[String]) -> Option<AnalyzerRef> {
+    let tokens: Vec<&str> = command
+        .iter()
+        .flat_map(|token| token.split_whitespace())
+        .collect();
+    let mut tokens = tokens.as_slice();
+    // we skip the shell, the env variables, and the launchers
+    loop {
+        match tokens {
+            ["sh" | "bash" | "zsh" | "cmd" | "pwsh", flag, rest @ ..]
+                if flag.starts_with(['-', '/']) =>
+            {
+                tokens = rest;
+            }
+            [token, rest @ ..]
+                if token.contains('=') || LAUNCHERS.contains(&program_name(token)) =>
+            {
+                tokens = rest;
+            }
+            [runner, "run" | "exec" | "x", rest @ ..]
+                if RUNNERS.contains(&program_name(runner)) =>
+            {
+                tokens = rest;
+            }
+            [python, "-m", rest @ ..] if program_name(python).starts_with("python") => {
+                return match rest.first() {
+                    Some(&"pytest") => Some(AnalyzerRef::PythonPytest),
+                    Some(&"unittest") => Some(AnalyzerRef::PythonUnittest),
+                    Some(&"ruff") => Some(AnalyzerRef::PythonRuff),
+                    _ => None,
+                };
+            }
+            _ => break,
+        }
+    }
+    let (program, args) = tokens.split_first()?;
+    match program_name(program) {
+        "cargo" => {
+            // the toolchain (eg `+nightly`) may come before the subcommand
+            let subcommand = args.iter().find(|arg| !arg.starts_with('+'));
+            if subcommand == Some(&"nextest") {
+                Some(AnalyzerRef::Nextest)
+            } else {
+                Some(AnalyzerRef::Standard)
+            }
+        }
+        "cargo-nextest" => Some(AnalyzerRef::Nextest),
+        "pytest" | "py.test" => Some(AnalyzerRef::PythonPytest),
+        "ruff" => Some(AnalyzerRef::PythonRuff),
+        "eslint" => Some(AnalyzerRef::Eslint),
+        "biome" | "@biomejs/biome" => Some(AnalyzerRef::Biome),
+        "gcc" | "g++" | "clang" | "clang++" | "c++" => Some(AnalyzerRef::Cpp),
+        _ => None,
+    }
+}
+
+/// The name of the program, without its directory
+fn program_name(token: &str) -> &str {
+    if token.starts_with('@') {
+        return token; // a npm package, eg `@biomejs/biome`
+    }
+    token.rsplit(['/', '\\']).next().unwrap_or(token)
+}
+
+#[test]
+fn test_detect_analyzer() {
+    let detect = |command: &str| {
+        let tokens: Vec<String> = command.split(' ').map(String::from).collect();
+        detect_analyzer(&tokens)
+    };
+    assert_eq!(
+        detect("cargo clippy --all-targets"),
+        Some(AnalyzerRef::Standard)
+    );
+    assert_eq!(
+        detect("cargo +nightly miri run"),
+        Some(AnalyzerRef::Standard)
+    );
+    assert_eq!(
+        detect("cargo nextest run --hide-progress-bar"),
+        Some(AnalyzerRef::Nextest)
+    );
+    assert_eq!(
+        detect("/usr/bin/cargo +stable nextest run"),
+        Some(AnalyzerRef::Nextest)
+    );
+    assert_eq!(
+        detect("npx eslint --color libs/*"),
+        Some(AnalyzerRef::Eslint)
+    );
+    assert_eq!(
+        detect("npx @biomejs/biome lint ./libs"),
+        Some(AnalyzerRef::Biome)
+    );
+    assert_eq!(detect("uv run pytest -x"), Some(AnalyzerRef::PythonPytest));
+    assert_eq!(
+        detect("python3 -m unittest discover"),
+        Some(AnalyzerRef::PythonUnittest)
+    );
+    assert_eq!(
+        detect("FORCE_COLOR=1 ruff check"),
+        Some(AnalyzerRef::PythonRuff)
+    );
+    assert_eq!(detect("g++ -Wall src/main.cpp"), Some(AnalyzerRef::Cpp));
+    assert_eq!(
+        detect_analyzer(&[
+            "sh".to_string(),
+            "-c".to_string(),
+            "cargo nextest run".to_string()
+        ]),
+        Some(AnalyzerRef::Nextest),
+    );
+    assert_eq!(detect("go test ./..."), None);
+    assert_eq!(detect("python3 unittest_runner.py"), None);
+}
@@ -0,0 +1,86 @@
+use crate::*;
+
+/// Mark as captured the lines a failed test printed before panicking,
+/// and precede them with a line telling their number.
+///
+/// Captured lines aren't displayed unless the item is expanded. The
+/// items must have their indexes.
+pub fn fold_captured_outputs(lines: &mut Vec<Line>) {
+    let mut idx = 0;
+    while idx < lines.len() {
+        if lines[idx].line_type != LineType::Title(Kind::TestFail) {
+            idx += 1;
+            continue;
+        }
+        let item_idx = lines[idx].item_idx;
+        let start = idx + 1;
+        let mut end = start;
+        let mut panic_found = false;
+        while end < lines.len() && lines[end].item_idx == item_idx {
+            if lines[end].line_type == LineType::Location {
+                panic_found = lines[end].content.to_raw().contains(" panicked at ");
+                break;
+            }
+            end += 1;
+        }
+        if !panic_found {
+            idx = end;
+            continue;
+        }
+        // the blank lines separating the output from the panic aren't part of it
+        let mut captured_end = end;
+        while captured_end > start && lines[captured_end - 1].content.is_blank() {
+            captured_end -= 1;
+        }
+        if captured_end == start {
+            idx = end;
+            continue;
+        }
+        let captured = &mut lines[start..captured_end];
+        let count = captured.len();
+        for line in captured {
+            line.line_type = LineType::Captured;
+        }
+        let plural = if count > 1 { "s" } else { "" };
+        lines.insert(
+            start,
+            Line {
+                item_idx,
+                line_type: LineType::Normal,
+                content: TLine::italic(format!("captured output: {count} line{plural}")),
+            },
+        );
+        idx = end + 1;
+    }
+}
+
+#[test]
+fn test_fold_captured_outputs() {
+    let line = |line_type, raw: &str| Line::from_raw(1, line_type, raw);
+    let mut lines = vec![
+        line(LineType::Title(Kind::TestFail), "failed: tests::a"),
+        line(LineType::Normal, "some debug print"),
+        line(LineType::Normal, "another one"),
+        line(LineType::Normal, ""),
+        line(
+            LineType::Location,
+            "thread 'tests::a' panicked at src/lib.rs:5:9:",
+        ),
+        line(LineType::Normal, "assertion failed: false"),
+    ];
+    fold_captured_outputs(&mut lines);
+    let types: Vec<LineType> = lines.iter().map(|line| line.line_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            LineType::Title(Kind::TestFail),
+            LineType::Normal,
+            LineType::Captured,
+            LineType::Captured,
+            LineType::Normal,
+            LineType::Location,
+            LineType::Normal,
+        ]
+    );
+    assert_eq!(lines[1].content.to_raw(), "captured output: 2 lines");
+}
@@ -10,24 +10,36 @@ use {
     cargo_json_export::*,
     cargo_metadata::{
         Message,
-        diagnostic::Diagnostic,
+        diagnostic::{
+            Diagnostic,
+            DiagnosticLevel,
+        },
     },
+    std::collections::HashMap,
 };
 
 /// An analyzer able to read the output
 /// of `cargo check --message-format=json-diagnostic-rendered-ansi`
 ///
-/// In order to be useful, this analyzer would have to read more than
-/// just the 'rendered' field of the diagnostic. It would have to read
-/// for example the spans, detect what's a suggestion, etc.
-/// See https://github.com/Canop/bacon/issues/249
+/// Items are built from the structured diagnostics (level, primary span),
+/// while their content is the 'rendered' field, so that the visible result
+/// is the same as with the standard analyzer.
 ///
-/// There are so many problems with this approach, though, that I'm
-/// not sure this is worth it.
+/// Lines which aren't cargo messages (eg the output of the test
+/// binaries) are analyzed as the standard analyzer would.
 #[derive(Default)]
 pub struct CargoJsonAnalyzer {
     lines: Vec<CommandOutputLine>,
+    /// past this number of lines (if not 0), only the structural ones are kept
+    max_lines: usize,
+    parallel_analysis: bool,
+    /// the crates whose deprecation warnings are removed
+    ignored_deprecations: Vec<String>,
+    errors: Vec<Line>,
+    warnings: Vec<Line>,
     exports: Vec<CargoJsonExport>,
+    /// machine-applicable suggestions, by location of the diagnostic
+    suggestions: HashMap<String, Vec<Suggestion>>,
 }
 
 impl Analyzer for CargoJsonAnalyzer {
@@ -36,7 +48,13 @@ impl Analyzer for CargoJsonAnalyzer {
         mission: &Mission,
     ) {
         self.lines.clear();
+        self.max_lines = mission.job.max_output_lines();
+        self.parallel_analysis = mission.job.parallel_analysis();
+        self.ignored_deprecations = mission.analyzer_options().strings("ignored_deprecations");
+        self.errors.clear();
+        self.warnings.clear();
         self.exports.clear();
+        self.suggestions.clear();
         for (name, export_settings) in &mission.settings.exports.exports {
             if export_settings.exporter == Exporter::Analyser {
                 let export = CargoJsonExport::new(name.clone(), export_settings);
@@ -50,35 +68,81 @@ impl Analyzer for CargoJsonAnalyzer {
         cmd_line: CommandOutputLine,
         command_output: &mut CommandOutput,
     ) {
-        let Some(content) = cmd_line.content.if_unstyled() else {
-            return; // right now we're not expecting styled output
-        };
-        match serde_json::from_str::<Message>(content) {
-            Ok(message) => {
+        let message = cmd_line
+            .content
+            .if_unstyled()
+            .filter(|content| content.starts_with('{'))
+            .and_then(|content| serde_json::from_str::<Message>(content).ok());
+        match message {
+            Some(message) => {
                 self.receive_cargo_message(message, cmd_line.origin, command_output);
             }
-            Err(err) => {
-                let line = TLine::from_tty(&format!("Error parsing JSON: {}", err));
-                let cmd_line = CommandOutputLine {
-                    content: line,
-                    origin: cmd_line.origin,
-                };
+            None => {
+                // not a cargo message, eg a line of a test binary
+                let line_analyzer = standard::StandardLineAnalyzer {};
+                if self.max_lines == 0
+                    || self.lines.len() < self.max_lines
+                    || line_analyzer.is_structural(&cmd_line)
+                {
+                    self.lines.push(cmd_line.clone());
+                }
                 command_output.push(cmd_line);
             }
         }
     }
 
     fn build_report(&mut self) -> Result<Report> {
-        let line_analyzer = standard::StandardLineAnalyzer {};
-        let mut report = standard::build_report(&self.lines, line_analyzer)?;
+        let errors = std::mem::take(&mut self.errors);
+        let warnings = std::mem::take(&mut self.warnings);
+        let mut report = self.merge_report(errors, warnings)?;
+        report.remove_deprecation_warnings(&self.ignored_deprecations);
         for export in self.exports.drain(..) {
             report.analyzer_exports.insert(export.name, export.export);
         }
+        report.suggestions = std::mem::take(&mut self.suggestions);
         Ok(report)
     }
+
+    fn build_partial_report(&mut self) -> Option<Result<Report>> {
+        Some(
+            self.merge_report(self.errors.clone(), self.warnings.clone())
+                .map(|mut report| {
+                    report.remove_deprecation_warnings(&self.ignored_deprecations);
+                    report
+                }),
+        )
+    }
 }
 
 impl CargoJsonAnalyzer {
+    /// Build the report of the lines which aren't cargo messages, and
+    /// merge the items built from the diagnostics into it
+    fn merge_report(
+        &self,
+        errors: Vec<Line>,
+        mut warnings: Vec<Line>,
+    ) -> Result<Report> {
+        let line_analyzer = standard::StandardLineAnalyzer {};
+        let mut report =
+            standard::build_report(&self.lines, line_analyzer, self.parallel_analysis)?;
+        // as in the standard analyzer, errors come first, then test failures,
+        // then warnings
+        let mut lines = errors;
+        lines.append(&mut report.lines);
+        lines.append(&mut warnings);
+        let mut item_idx = 0;
+        for line in &mut lines {
+            if matches!(line.line_type, LineType::Title(_)) {
+                item_idx += 1;
+            }
+            line.item_idx = item_idx;
+        }
+        let passed_tests = report.stats.passed_tests;
+        report.stats = Stats::from(&lines);
+        report.stats.passed_tests = passed_tests;
+        report.lines = lines;
+        Ok(report)
+    }
     fn receive_cargo_message(
         &mut self,
         message: Message,
@@ -88,7 +152,15 @@ impl CargoJsonAnalyzer {
         match message {
             Message::CompilerArtifact(_) => {}
             Message::CompilerMessage(compiler_message) => {
-                self.receive_diagnostic(compiler_message.message, origin, command_output);
+                let diagnostic = compiler_message.message;
+                let suggestions = Suggestion::from_diagnostic(&diagnostic);
+                if !suggestions.is_empty() {
+                    if let Some(key) = suggestions_key(&diagnostic) {
+                        self.suggestions.entry(key).or_default().extend(suggestions);
+                    }
+                }
+                self.export_diagnostic(&diagnostic);
+                self.receive_diagnostic(diagnostic, origin, command_output);
             }
             Message::BuildScriptExecuted(_) => {}
             Message::BuildFinished(_) => {}
@@ -98,35 +170,131 @@ impl CargoJsonAnalyzer {
             }
         }
     }
+    fn export_diagnostic(
+        &mut self,
+        diagnostic: &Diagnostic,
+    ) {
+        for export in &mut self.exports {
+            export.receive_diagnostic(diagnostic);
+        }
+        for child in &diagnostic.children {
+            self.export_diagnostic(child);
+        }
+    }
     fn receive_diagnostic(
         &mut self,
         diagnostic: Diagnostic,
         origin: CommandStream,
         command_output: &mut CommandOutput,
     ) {
-        for export in &mut self.exports {
-            export.receive_diagnostic(&diagnostic);
+        let Some(rendered) = &diagnostic.rendered else {
+            return;
+        };
+        let cmd_lines: Vec<CommandOutputLine> = rendered
+            .trim_end()
+            .lines()
+            .map(|line| CommandOutputLine {
+                content: TLine::from_tty(line),
+                origin,
+            })
+            .collect();
+        for cmd_line in &cmd_lines {
+            command_output.push(cmd_line.clone());
         }
-        let Diagnostic {
-            //message,
-            //code,
-            //level,
-            //spans,
-            children,
-            //suggestion,
-            rendered,
-            ..
-        } = diagnostic;
-        if let Some(rendered) = rendered {
-            for line in rendered.trim().lines() {
-                let content = TLine::from_tty(line);
-                let cmd_line = CommandOutputLine { content, origin };
-                command_output.push(cmd_line.clone());
-                self.lines.push(cmd_line);
-            }
+        let Some((kind, lines)) = diagnostic_lines(&diagnostic, cmd_lines) else {
+            return;
+        };
+        match kind {
+            Kind::Error => self.errors.extend(lines),
+            _ => self.warnings.extend(lines),
+        }
+    }
+}
+
+/// Build the report lines of a diagnostic, from its rendered lines,
+/// or return `None` when the diagnostic isn't an error or a warning
+/// (or is a summary like "aborting due to 2 previous errors")
+fn diagnostic_lines(
+    diagnostic: &Diagnostic,
+    cmd_lines: Vec<CommandOutputLine>,
+) -> Option<(Kind, Vec<Line>)> {
+    let kind = match diagnostic.level {
+        DiagnosticLevel::Error | DiagnosticLevel::Ice => Kind::Error,
+        DiagnosticLevel::Warning => Kind::Warning,
+        _ => {
+            return None;
         }
-        for child in children {
-            self.receive_diagnostic(child, origin, command_output);
+    };
+    if diagnostic.message.starts_with("aborting due to") || cmd_lines.is_empty() {
+        return None;
+    }
+    let location = suggestions_key(diagnostic);
+    let mut lines: Vec<Line> = Vec::with_capacity(cmd_lines.len() + 1);
+    let mut location_found = false;
+    for (idx, cmd_line) in cmd_lines.into_iter().enumerate() {
+        let mut line = Line {
+            item_idx: 0, // filled when building the report
+            line_type: LineType::Normal,
+            content: cmd_line.content,
+        };
+        if idx == 0 {
+            line.line_type = LineType::Title(kind);
+        } else if !location_found {
+            if let Some(location) = &location {
+                let raw = line.content.to_raw();
+                if raw.trim_start().starts_with("--> ") && raw.ends_with(location.as_str()) {
+                    line.line_type = LineType::Location;
+                    location_found = true;
+                }
+            }
         }
+        lines.push(line);
+    }
+    if let (false, Some(location)) = (location_found, location) {
+        // no rendered line gives the location of the primary span, we add one
+        lines.insert(
+            1,
+            Line {
+                item_idx: 0,
+                line_type: LineType::Location,
+                content: location_line(&location),
+            },
+        );
+    }
+    Some((kind, lines))
+}
+
+fn location_line(location: &str) -> TLine {
+    TLine {
+        strings: vec![
+            TString::new("", " "),
+            TString::new(CSI_BOLD_BLUE, "--> "),
+            TString::new("", location),
+        ],
     }
 }
+
+#[test]
+fn test_diagnostic_lines() {
+    let json = r#"{"$message_type":"diagnostic","message":"unused variable: `old`","code":{"code":"unused_variables","explanation":null},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":20,"byte_end":23,"line_start":2,"line_end":2,"column_start":9,"column_end":12,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[],"rendered":"\u001b[1m\u001b[33mwarning\u001b[0m\u001b[1m: unused variable: `old`\u001b[0m\n \u001b[1m\u001b[94m--> \u001b[0msrc/main.rs:2:9\n  \u001b[1m\u001b[94m|\u001b[0m\n"}"#;
+    let diagnostic: Diagnostic = serde_json::from_str(json).unwrap();
+    let cmd_lines = diagnostic
+        .rendered
+        .as_ref()
+        .unwrap()
+        .trim_end()
+        .lines()
+        .map(|line| CommandOutputLine {
+            content: TLine::from_tty(line),
+            origin: CommandStream::StdOut,
+        })
+        .collect();
+    let (kind, lines) = diagnostic_lines(&diagnostic, cmd_lines).unwrap();
+    assert_eq!(kind, Kind::Warning);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].line_type, LineType::Title(Kind::Warning));
+    assert_eq!(lines[0].title_message(), Some("unused variable: `old`"));
+    assert_eq!(lines[1].line_type, LineType::Location);
+    assert_eq!(lines[1].location(), Some("src/main.rs:2:9"));
+    assert_eq!(lines[2].line_type, LineType::Normal);
+}
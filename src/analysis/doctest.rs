@@ -0,0 +1,141 @@
+use {
+    crate::*,
+    lazy_regex::regex_captures,
+};
+
+/// The key of a doctest, as given by rustdoc, eg
+/// `src/lib.rs - foo::Bar::new (line 12)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoctestKey<'k> {
+    /// path of the file containing the doc comment
+    pub path: &'k str,
+    /// the documented item, `None` for a module level documentation
+    pub item: Option<&'k str>,
+    /// line of the start of the code block in the file
+    pub line: usize,
+}
+
+impl<'k> DoctestKey<'k> {
+    pub fn parse(key: &'k str) -> Option<Self> {
+        let (_, path, item, line) = regex_captures!(r"^(\S+) - (?:(.+) )?\(line (\d+)\)$", key)?;
+        Some(Self {
+            path,
+            item: (!item.is_empty()).then_some(item),
+            line: line.parse().ok()?,
+        })
+    }
+    /// The location of the code block, eg `src/lib.rs:12`
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.path, self.line)
+    }
+    pub fn title(&self) -> TLine {
+        let mut title = TLine::default();
+        title.add_tstring(CSI_BOLD_ORANGE, "failed doctest");
+        title.add_tstring("", ": ");
+        if let Some(item) = self.item {
+            title.add_tstring(CSI_BOLD_ORANGE, item);
+            title.add_tstring("", " in ");
+        }
+        title.add_tstring("", self.location());
+        title
+    }
+}
+
+/// Return the first lines of the item of a test failure: the title and,
+/// for a doctest, the location of the code block
+pub fn failure_lines(key: &str) -> Vec<Line> {
+    let Some(doctest) = DoctestKey::parse(key) else {
+        return vec![Line {
+            item_idx: 0, // will be filled later
+            line_type: LineType::Title(Kind::TestFail),
+            content: TLine::failed(key),
+        }];
+    };
+    vec![
+        Line {
+            item_idx: 0,
+            line_type: LineType::Title(Kind::DoctestFail),
+            content: doctest.title(),
+        },
+        Line {
+            item_idx: 0,
+            line_type: LineType::Location,
+            content: burp::location_line(doctest.location()),
+        },
+    ]
+}
+
+/// Keep only the most precise location of the doctest failures, which
+/// must have been built with [failure_lines] and given their items indexes.
+///
+/// Compilation errors of doctests come with a correct location, which is
+/// kept instead of the one of the code block, but the location of a panic
+/// points in the code generated by rustdoc and is ignored.
+pub fn fix_doctest_locations(lines: &mut Vec<Line>) {
+    let mut idx = 0;
+    while idx < lines.len() {
+        if lines[idx].line_type != LineType::Title(Kind::DoctestFail) {
+            idx += 1;
+            continue;
+        }
+        let block_location_idx = idx + 1;
+        let item_idx = lines[idx].item_idx;
+        let mut has_precise_location = false;
+        idx += 2;
+        while idx < lines.len() && lines[idx].item_idx == item_idx {
+            let line = &mut lines[idx];
+            if line.line_type == LineType::Location {
+                if line.content.to_raw().contains(" panicked at ") {
+                    line.line_type = LineType::Normal;
+                } else {
+                    has_precise_location = true;
+                }
+            }
+            idx += 1;
+        }
+        if has_precise_location {
+            lines.remove(block_location_idx);
+            idx -= 1;
+        }
+    }
+}
+
+#[test]
+fn test_parse_doctest_key() {
+    assert_eq!(
+        DoctestKey::parse("src/mode.rs - mode::Mode::new (line 121)"),
+        Some(DoctestKey {
+            path: "src/mode.rs",
+            item: Some("mode::Mode::new"),
+            line: 121,
+        }),
+    );
+    assert_eq!(
+        DoctestKey::parse("src/lib.rs - (line 6)"),
+        Some(DoctestKey {
+            path: "src/lib.rs",
+            item: None,
+            line: 6,
+        }),
+    );
+    assert_eq!(DoctestKey::parse("tests::another"), None);
+}
+
+#[test]
+fn test_fix_doctest_locations() {
+    let location = |raw: &str| Line::from_raw(0, LineType::Location, raw);
+    let mut lines = failure_lines("src/lib.rs - add_one (line 5)");
+    lines.push(location("thread 'main' panicked at src/lib.rs:5:1:"));
+    lines.append(&mut failure_lines("src/lib.rs - bad (line 14)"));
+    lines.push(location("  --> src/lib.rs:15:14"));
+    let mut item_idx = 0;
+    for line in &mut lines {
+        if matches!(line.line_type, LineType::Title(_)) {
+            item_idx += 1;
+        }
+        line.item_idx = item_idx;
+    }
+    fix_doctest_locations(&mut lines);
+    let locations: Vec<_> = lines.iter().filter_map(|line| line.location()).collect();
+    assert_eq!(locations, vec!["src/lib.rs:5", "src/lib.rs:15:14"]);
+}
@@ -6,19 +6,31 @@ use {
     crate::*,
     anyhow::Result,
     eslint_line_analyzer::*,
+    lazy_regex::*,
 };
 
 #[derive(Debug, Default)]
 pub struct EslintAnalyzer {
     lines: Vec<CommandOutputLine>,
+    /// whether eslint is run with `--format unix`, as told by the
+    /// `format` analyzer option, instead of the default stylish format
+    unix_format: bool,
 }
 
 impl Analyzer for EslintAnalyzer {
     fn start(
         &mut self,
-        _mission: &Mission,
+        mission: &Mission,
     ) {
         self.lines.clear();
+        self.unix_format = match mission.analyzer_options().string("format") {
+            None | Some("stylish") => false,
+            Some("unix") => true,
+            Some(format) => {
+                warn!("unsupported eslint format: {format:?}");
+                false
+            }
+        };
     }
 
     fn receive_line(
@@ -31,7 +43,11 @@ impl Analyzer for EslintAnalyzer {
     }
 
     fn build_report(&mut self) -> Result<Report> {
-        build_report(&self.lines)
+        if self.unix_format {
+            Ok(build_unix_format_report(&self.lines))
+        } else {
+            build_report(&self.lines)
+        }
     }
 }
 
@@ -88,3 +104,54 @@ pub fn build_report(cmd_lines: &[CommandOutputLine]) -> anyhow::Result<Report> {
     }
     Ok(items.report())
 }
+
+/// Build a report from the output of eslint with `--format unix`, where
+/// each problem is on one line, like
+/// "/src/main.js:3:7: 'a' is assigned a value but never used. [Error/no-unused-vars]"
+pub fn build_unix_format_report(cmd_lines: &[CommandOutputLine]) -> Report {
+    let mut items = ItemAccumulator::default();
+    for cmd_line in cmd_lines {
+        let raw = cmd_line.content.to_raw();
+        let Some((_, path, line, col, message, severity, rule)) = regex_captures!(
+            r"^(\S.*?):(\d+):(\d+): (.*) \[(Error|Warning)/([^\]]+)\]$",
+            raw.trim_end(),
+        ) else {
+            continue;
+        };
+        let (kind, csi, label) = if severity == "Error" {
+            (Kind::Error, CSI_BOLD_RED, "error")
+        } else {
+            (Kind::Warning, CSI_BOLD_YELLOW, "warning")
+        };
+        items.start_item(kind);
+        let mut title = TLine::default();
+        title.add_tstring(csi, label);
+        title.add_tstring(CSI_BOLD, format!(": {message} ({rule})"));
+        items.push_line(LineType::Title(kind), title);
+        items.push_line(
+            LineType::Location,
+            burp::location_line(format!("{path}:{line}:{col}")),
+        );
+    }
+    items.report()
+}
+
+#[test]
+fn test_unix_format_report() {
+    let cmd_lines: Vec<CommandOutputLine> = [
+        "/src/main.js:3:7: 'a' is assigned a value but never used. [Error/no-unused-vars]",
+        "/src/main.js:9:1: Unexpected console statement. [Warning/no-console]",
+        "",
+        "2 problems",
+    ]
+    .iter()
+    .map(|raw| CommandOutputLine {
+        content: TLine::from_raw(raw.to_string()),
+        origin: CommandStream::StdOut,
+    })
+    .collect();
+    let report = build_unix_format_report(&cmd_lines);
+    assert_eq!(report.stats.errors, 1);
+    assert_eq!(report.stats.warnings, 1);
+    assert_eq!(report.lines[1].location(), Some("/src/main.js:3:7"));
+}
@@ -76,7 +76,11 @@ impl ItemAccumulator {
             suggest_backtrace: false,
             output: Default::default(),
             failure_keys: Vec::new(),
+            passed_keys: Vec::new(),
             analyzer_exports: Default::default(),
+            trigger_paths: Default::default(),
+            suggestions: Default::default(),
+            hook_success: None,
         }
     }
 }
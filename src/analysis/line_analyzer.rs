@@ -7,4 +7,14 @@ pub trait LineAnalyzer {
         &self,
         line: &CommandOutputLine,
     ) -> LineAnalysis;
+
+    /// Tell whether the line must be kept, even when the output is too
+    /// long, because it's needed to build the items (eg a title or a
+    /// location)
+    fn is_structural(
+        &self,
+        line: &CommandOutputLine,
+    ) -> bool {
+        self.analyze_line(line).line_type != LineType::Normal || line.content.is_blank()
+    }
 }
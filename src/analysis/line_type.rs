@@ -18,6 +18,14 @@ pub enum Kind {
     Error,
     /// a test failure
     TestFail,
+    /// the failure of a doctest, to compile or at execution
+    DoctestFail,
+    /// a diagnostic which is neither an error nor a warning,
+    /// eg a warning demoted in the job's severity overrides
+    Info,
+    /// a warning located outside of the workspace, eg in a
+    /// dependency, which the user can't fix
+    External,
     /// a sum of errors and/or warnings, typically occuring
     /// at the end of the compilation of a package
     Sum,
@@ -46,6 +54,10 @@ pub enum LineType {
     /// Raw line, unclassified
     Raw(CommandStream),
 
+    /// a line printed by a failed test, only displayed
+    /// when the item is expanded
+    Captured,
+
     /// Continuation of a previous line
     Continuation {
         /// offset to count back to get to first (starting at 1)
@@ -86,7 +98,7 @@ impl LineType {
     }
     pub fn is_summary(self) -> bool {
         match self {
-            Self::Normal | Self::Raw(_) => false,
+            Self::Normal | Self::Raw(_) | Self::Captured => false,
             Self::Continuation { summary, .. } => summary,
             _ => true,
         }
@@ -113,6 +125,13 @@ impl LineType {
                     item_idx
                 )?;
             }
+            Self::Title(Kind::DoctestFail) => {
+                write!(
+                    w,
+                    "\u{1b}[1m\u{1b}[38;5;235m\u{1b}[48;5;172m{:^3}\u{1b}[0m\u{1b}[0m",
+                    item_idx
+                )?;
+            }
             Self::Title(Kind::Warning) => {
                 write!(
                     w,
@@ -120,6 +139,12 @@ impl LineType {
                     format!("{:^3}", item_idx).black().bold().on_yellow()
                 )?;
             }
+            Self::Title(Kind::Info) => {
+                write!(w, "{}", format!("{:^3}", item_idx).black().bold().on_blue())?;
+            }
+            Self::Title(Kind::External) => {
+                write!(w, "{}", format!("{:^3}", item_idx).black().on_grey())?;
+            }
             _ => {}
         }
         Ok(())
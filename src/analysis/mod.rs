@@ -1,22 +1,35 @@
 mod analyzer;
+mod analyzer_config;
+mod analyzer_detection;
 mod biome;
+mod captured_output;
 mod cargo_json;
 mod cpp;
+mod doctest;
 mod eslint;
 mod item_accumulator;
 mod line_analysis;
 mod line_analyzer;
 mod line_type;
 mod nextest;
+mod parallel_analysis;
+mod passthrough;
 mod python;
+mod severity;
 mod standard;
 mod stats;
 
 pub use {
     analyzer::*,
+    analyzer_config::*,
+    analyzer_detection::*,
+    captured_output::*,
+    doctest::*,
     item_accumulator::*,
     line_analysis::*,
     line_analyzer::*,
     line_type::*,
+    parallel_analysis::*,
+    severity::*,
     stats::*,
 };
@@ -9,14 +9,29 @@ use {
 #[derive(Debug, Default)]
 pub struct NextestAnalyzer {
     lines: Vec<CommandOutputLine>,
+    /// past this number of lines (if not 0), only the structural ones are kept
+    max_lines: usize,
+    parallel_analysis: bool,
+    leaks_as_failures: bool,
+}
+
+impl NextestAnalyzer {
+    fn line_analyzer(&self) -> NextestLineAnalyzer {
+        let mut line_analyzer = NextestLineAnalyzer::default();
+        line_analyzer.leaks_as_failures = self.leaks_as_failures;
+        line_analyzer
+    }
 }
 
 impl Analyzer for NextestAnalyzer {
     fn start(
         &mut self,
-        _mission: &Mission,
+        mission: &Mission,
     ) {
         self.lines.clear();
+        self.max_lines = mission.job.max_output_lines();
+        self.parallel_analysis = mission.job.parallel_analysis();
+        self.leaks_as_failures = mission.analyzer_options().bool("leaks_as_failures");
     }
 
     fn receive_line(
@@ -24,12 +39,24 @@ impl Analyzer for NextestAnalyzer {
         line: CommandOutputLine,
         command_output: &mut CommandOutput,
     ) {
-        self.lines.push(line.clone());
+        if self.max_lines == 0
+            || self.lines.len() < self.max_lines
+            || self.line_analyzer().is_structural(&line)
+        {
+            self.lines.push(line.clone());
+        }
         command_output.push(line);
     }
 
     fn build_report(&mut self) -> Result<Report> {
-        let line_analyzer = NextestLineAnalyzer::default();
-        crate::analysis::standard::build_report(&self.lines, line_analyzer)
+        crate::analysis::standard::build_report(
+            &self.lines,
+            self.line_analyzer(),
+            self.parallel_analysis,
+        )
+    }
+
+    fn build_partial_report(&mut self) -> Option<Result<Report>> {
+        Some(self.build_report())
     }
 }
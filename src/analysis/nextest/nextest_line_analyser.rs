@@ -13,6 +13,9 @@ const CSI_ERROR: &str = "\u{1b}[31;1m";
 #[derive(Debug, Default)]
 pub struct NextestLineAnalyzer {
     default_analyzer: StandardLineAnalyzer,
+    /// whether the tests leaking handles are failures, as set by the
+    /// `leaks_as_failures` analyzer option
+    pub leaks_as_failures: bool,
 }
 
 impl LineAnalyzer for NextestLineAnalyzer {
@@ -27,6 +30,11 @@ impl LineAnalyzer for NextestLineAnalyzer {
         if let Some((key, pass)) = as_test_result(content) {
             return LineAnalysis::test_result(key, pass);
         }
+        if self.leaks_as_failures {
+            if let Some(key) = as_leak(content) {
+                return LineAnalysis::test_result(key, false);
+            }
+        }
         if is_canceling(content) {
             return LineAnalysis::of_type(LineType::SectionEnd);
         }
@@ -123,6 +131,19 @@ fn is_canceling(content: &TLine) -> bool {
     first.csi == CSI_ERROR && first.raw.trim() == "Canceling"
 }
 
+/// return the key of the test, when the line tells it passed but
+/// leaked handles (like "    LEAK [   0.103s] bacon tests::spawning")
+fn as_leak(content: &TLine) -> Option<String> {
+    let mut strings = content.strings.iter();
+    if strings.next()?.raw.trim() != "LEAK" {
+        return None;
+    }
+    match strings.next() {
+        Some(s) if s.csi.is_empty() => {}
+        _ => return None,
+    }
+    extract_key_after_crate_name_v1(strings)
+}
 /// return the key and whether the tests passes, when the line is a test
 /// result (like "    PASS [   0.003s] bacon tests::failing_test3")
 ///
@@ -207,7 +228,7 @@ fn test_canceling() {
             TString::new("", " test still running"),
         ],
     };
-    assert_eq!(is_canceling(&content), true);
+    assert!(is_canceling(&content));
 }
 
 #[test]
@@ -230,6 +251,32 @@ fn test_as_test_result() {
     );
 }
 
+#[test]
+fn test_leaks_as_failures() {
+    let line = CommandOutputLine {
+        content: TLine {
+            strings: vec![
+                TString::new("\u{1b}[35;1m", "        LEAK"),
+                TString::new("", " [   0.103s] "),
+                TString::new("\u{1b}[35;1m", "bacon"),
+                TString::new("", " "),
+                TString::new("\u{1b}[36m", "tests::spawning"),
+            ],
+        },
+        origin: CommandStream::StdOut,
+    };
+    let analyzer = NextestLineAnalyzer::default();
+    assert_eq!(analyzer.analyze_line(&line).line_type, LineType::Normal);
+    let analyzer = NextestLineAnalyzer {
+        leaks_as_failures: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        analyzer.analyze_line(&line),
+        LineAnalysis::test_result("tests::spawning".to_string(), false),
+    );
+}
+
 #[test]
 fn test_recognize_test_run_failed() {
     let content = TLine {
@@ -0,0 +1,108 @@
+use {
+    crate::*,
+    std::{
+        ops::Range,
+        thread,
+    },
+};
+
+/// Under this number of lines, spreading the analysis on several
+/// threads isn't worth it
+const PARALLEL_ANALYSIS_MIN_LINES: usize = 10_000;
+
+const MAX_ANALYSIS_THREADS: usize = 8;
+
+/// Analyze the lines, with a pool of threads when they're numerous
+/// and `parallel` is true.
+///
+/// The lines are split in chunks ending on blank lines, which end
+/// the diagnostics, and the analyses are returned in the order of
+/// the lines, so that the result doesn't depend on the number of threads
+pub fn analyze_lines<L: LineAnalyzer + Sync>(
+    lines: &[CommandOutputLine],
+    line_analyzer: &L,
+    parallel: bool,
+) -> Vec<LineAnalysis> {
+    let threads = if parallel && lines.len() >= PARALLEL_ANALYSIS_MIN_LINES {
+        thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_ANALYSIS_THREADS)
+    } else {
+        1
+    };
+    if threads < 2 {
+        return lines
+            .iter()
+            .map(|line| line_analyzer.analyze_line(line))
+            .collect();
+    }
+    let chunks = chunk_ranges(lines, threads);
+    debug!("analyzing {} lines in {} chunks", lines.len(), chunks.len());
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks
+            .into_iter()
+            .map(|range| {
+                scope.spawn(move || {
+                    lines[range]
+                        .iter()
+                        .map(|line| line_analyzer.analyze_line(line))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("analysis thread panicked"))
+            .collect()
+    })
+}
+
+/// Split the lines in about `count` ranges, each one ending, when
+/// possible, with a blank line
+fn chunk_ranges(
+    lines: &[CommandOutputLine],
+    count: usize,
+) -> Vec<Range<usize>> {
+    let target = lines.len().div_ceil(count).max(1);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = (start + target).min(lines.len());
+        // no diagnostic is cut, unless it's unreasonably long
+        let max_end = (end + target).min(lines.len());
+        while end < max_end && !lines[end - 1].content.is_blank() {
+            end += 1;
+        }
+        ranges.push(start..end);
+        start = end;
+    }
+    ranges
+}
+
+#[test]
+fn test_parallel_analysis() {
+    let mut lines = Vec::new();
+    for i in 0..PARALLEL_ANALYSIS_MIN_LINES {
+        let content = match i % 5 {
+            0 => format!("\u{1b}[1m\u{1b}[33mwarning\u{1b}[0m: unused variable {i}"),
+            1 => format!("  --> src/lib.rs:{i}:5"),
+            4 => String::new(),
+            _ => format!("   | some code {i}"),
+        };
+        lines.push(CommandOutputLine {
+            content: TLine::from_tty(&content),
+            origin: CommandStream::StdErr,
+        });
+    }
+    let ranges = chunk_ranges(&lines, 3);
+    assert_eq!(ranges.first().unwrap().start, 0);
+    assert_eq!(ranges.last().unwrap().end, lines.len());
+    for range in &ranges[..ranges.len() - 1] {
+        assert!(lines[range.end - 1].content.is_blank());
+    }
+    let line_analyzer = super::standard::StandardLineAnalyzer {};
+    assert_eq!(
+        analyze_lines(&lines, &line_analyzer, true),
+        analyze_lines(&lines, &line_analyzer, false),
+    );
+}
@@ -0,0 +1,41 @@
+//! An analyzer which doesn't look for items, for commands whose
+//! output is to be read as a terminal would display it
+
+use {
+    crate::*,
+    anyhow::Result,
+};
+
+#[derive(Debug, Default)]
+pub struct PassthroughAnalyzer {}
+
+impl Analyzer for PassthroughAnalyzer {
+    fn start(
+        &mut self,
+        _mission: &Mission,
+    ) {
+    }
+
+    fn receive_line(
+        &mut self,
+        line: CommandOutputLine,
+        command_output: &mut CommandOutput,
+    ) {
+        command_output.push(line);
+    }
+
+    fn build_report(&mut self) -> Result<Report> {
+        Ok(Report {
+            lines: Vec::new(),
+            stats: Stats::default(),
+            suggest_backtrace: false,
+            output: Default::default(),
+            failure_keys: Vec::new(),
+            passed_keys: Vec::new(),
+            analyzer_exports: Default::default(),
+            trigger_paths: Vec::new(),
+            suggestions: Default::default(),
+            hook_success: None,
+        })
+    }
+}
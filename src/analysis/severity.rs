@@ -0,0 +1,50 @@
+use {
+    crate::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The severity a diagnostic can be given, overriding the
+/// one the tool decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    /// displayed but neither counted as error nor warning
+    Info,
+}
+
+impl Severity {
+    pub fn kind(self) -> Kind {
+        match self {
+            Self::Error => Kind::Error,
+            Self::Warning => Kind::Warning,
+            Self::Info => Kind::Info,
+        }
+    }
+    /// The label starting the title of the diagnostic, with its style
+    pub fn title_label(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Error => (CSI_BOLD_RED, "error"),
+            Self::Warning => (CSI_BOLD_YELLOW, "warning"),
+            Self::Info => (CSI_BOLD_BLUE, "info"),
+        }
+    }
+}
+
+#[test]
+fn test_severity_deserialization() {
+    use std::collections::HashMap;
+    let severities: HashMap<String, Severity> = toml::from_str(
+        r#"
+        "clippy::unwrap_used" = "error"
+        "clippy::needless_return" = "info"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(severities["clippy::unwrap_used"], Severity::Error);
+    assert_eq!(severities["clippy::needless_return"], Severity::Info);
+}
@@ -14,14 +14,23 @@ use {
 #[derive(Debug, Default)]
 pub struct StandardAnalyzer {
     lines: Vec<CommandOutputLine>,
+    /// past this number of lines (if not 0), only the structural ones are kept
+    max_lines: usize,
+    parallel_analysis: bool,
+    /// the crates whose deprecation warnings are removed, as set by
+    /// the `ignored_deprecations` analyzer option
+    ignored_deprecations: Vec<String>,
 }
 
 impl Analyzer for StandardAnalyzer {
     fn start(
         &mut self,
-        _mission: &Mission,
+        mission: &Mission,
     ) {
         self.lines.clear();
+        self.max_lines = mission.job.max_output_lines();
+        self.parallel_analysis = mission.job.parallel_analysis();
+        self.ignored_deprecations = mission.analyzer_options().strings("ignored_deprecations");
     }
 
     fn receive_line(
@@ -29,12 +38,24 @@ impl Analyzer for StandardAnalyzer {
         line: CommandOutputLine,
         command_output: &mut CommandOutput,
     ) {
-        self.lines.push(line.clone());
+        let line_analyzer = StandardLineAnalyzer {};
+        if self.max_lines == 0
+            || self.lines.len() < self.max_lines
+            || line_analyzer.is_structural(&line)
+        {
+            self.lines.push(line.clone());
+        }
         command_output.push(line);
     }
 
     fn build_report(&mut self) -> Result<Report> {
         let line_analyzer = StandardLineAnalyzer {};
-        build_report(&self.lines, line_analyzer)
+        let mut report = build_report(&self.lines, line_analyzer, self.parallel_analysis)?;
+        report.remove_deprecation_warnings(&self.ignored_deprecations);
+        Ok(report)
+    }
+
+    fn build_partial_report(&mut self) -> Option<Result<Report>> {
+        Some(self.build_report())
     }
 }
@@ -61,14 +61,17 @@ fn analyze_line(cmd_line: &CommandOutputLine) -> LineAnalysis {
         } else if regex_is_match!(r#"^\s+--> [^:\s'"]+:\d+:\d+$"#, content) {
             // this comes up in test failures to compile
             LineType::Location
-        } else if regex_is_match!(r#"^thread '.+' panicked at [^:\s'"]+:\d+:\d+:$"#, content) {
+        } else if regex_is_match!(
+            r#"^thread '.+'(?: \(\d+\))? panicked at [^:\s'"]+:\d+:\d+:$"#,
+            content
+        ) {
             // this comes up in test failures
             LineType::Location
         } else {
             LineType::Normal
         }
     } else {
-        let ts0 = content.strings.get(0);
+        let ts0 = content.strings.first();
         let ts1 = content.strings.get(1);
         match (ts0, ts1) {
             (Some(title), Some(body)) => {
@@ -123,7 +126,7 @@ fn analyze_line(cmd_line: &CommandOutputLine) -> LineAnalysis {
             }
             (Some(content), None) => {
                 if regex_is_match!(
-                    r#"^thread '.+' panicked at [^:\s'"]+:\d+:\d+:$"#,
+                    r#"^thread '.+'(?: \(\d+\))? panicked at [^:\s'"]+:\d+:\d+:$"#,
                     &content.raw
                 ) {
                     // this comes up in nextest failures
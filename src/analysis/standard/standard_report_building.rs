@@ -3,9 +3,10 @@ use {
     rustc_hash::FxHashMap,
 };
 
-pub fn build_report<L: LineAnalyzer>(
+pub fn build_report<L: LineAnalyzer + Sync>(
     cmd_lines: &[CommandOutputLine],
     line_analyzer: L,
+    parallel: bool,
 ) -> anyhow::Result<Report> {
     #[derive(Debug, Default)]
     struct Failure {
@@ -17,13 +18,14 @@ pub fn build_report<L: LineAnalyzer>(
     let mut fails = Vec::new();
     let mut failures: FxHashMap<String, Failure> = Default::default();
     let mut passed_tests = 0;
+    let mut passed_keys = Vec::new();
     let mut cur_err_kind = None; // the current kind among stderr lines
     let mut is_in_out_fail = false;
     let mut suggest_backtrace = false;
-    for cmd_line in cmd_lines {
-        let line_analysis = line_analyzer.analyze_line(cmd_line);
+    let line_analyses = analyze_lines(cmd_lines, &line_analyzer, parallel);
+    for (cmd_line, line_analysis) in cmd_lines.iter().zip(line_analyses) {
         let line_type = line_analysis.line_type;
-        let mut line = Line {
+        let line = Line {
             item_idx: 0, // will be filled later
             line_type,
             content: cmd_line.content.clone(),
@@ -35,10 +37,11 @@ pub fn build_report<L: LineAnalyzer>(
             (LineType::TestResult(r), Some(key)) => {
                 if r {
                     passed_tests += 1;
-                } else if !failures.contains_key(&key) {
+                    passed_keys.push(key);
+                } else {
                     // we should receive the test failure section later,
                     // right now we just whitelist it
-                    failures.insert(key, Failure::default());
+                    failures.entry(key).or_default();
                 }
             }
             (LineType::Title(Kind::TestFail), Some(key)) => {
@@ -51,8 +54,7 @@ pub fn build_report<L: LineAnalyzer>(
                     continue;
                 }
                 failure.has_title = true;
-                line.content = TLine::failed(&key);
-                fails.push(line);
+                fails.append(&mut failure_lines(&key));
             }
             (LineType::Normal, None) => {
                 if line.content.is_blank() {
@@ -113,11 +115,7 @@ pub fn build_report<L: LineAnalyzer>(
         if failure.has_title {
             continue;
         }
-        fails.push(Line {
-            item_idx: 0, // will be filled later
-            line_type: LineType::Title(Kind::TestFail),
-            content: TLine::failed(key),
-        });
+        fails.append(&mut failure_lines(key));
         fails.push(Line {
             item_idx: 0,
             line_type: LineType::Normal,
@@ -136,6 +134,8 @@ pub fn build_report<L: LineAnalyzer>(
         }
         line.item_idx = item_idx;
     }
+    fix_doctest_locations(&mut lines);
+    fold_captured_outputs(&mut lines);
     // we compute the stats at end because some lines may
     // have been read but not added (at start or end)
     let mut stats = Stats::from(&lines);
@@ -148,7 +148,11 @@ pub fn build_report<L: LineAnalyzer>(
         suggest_backtrace,
         output: Default::default(),
         failure_keys,
+        passed_keys,
         analyzer_exports: Default::default(),
+        trigger_paths: Default::default(),
+        suggestions: Default::default(),
+        hook_success: None,
     };
     Ok(report)
 }
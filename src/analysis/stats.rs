@@ -22,7 +22,7 @@ impl From<&Vec<Line>> for Stats {
             match line.line_type {
                 LineType::Title(Kind::Error) => stats.errors += 1,
                 LineType::Title(Kind::Warning) => stats.warnings += 1,
-                LineType::Title(Kind::TestFail) => stats.test_fails += 1,
+                LineType::Title(Kind::TestFail | Kind::DoctestFail) => stats.test_fails += 1,
                 LineType::Location => stats.location_lines += 1,
                 _ => stats.normal_lines += 1,
             }
@@ -44,7 +44,43 @@ impl Stats {
     pub fn items(&self) -> usize {
         self.warnings + self.errors + self.test_fails
     }
+    /// The badges of the counts, as displayed in the top bar
+    pub fn badges(&self) -> Vec<TString> {
+        let mut badges = Vec::new();
+        if self.errors > 0 {
+            badges.push(TString::num_badge(self.errors, "error", 235, 9));
+        }
+        if self.test_fails > 0 {
+            badges.push(TString::num_badge(self.test_fails, "fail", 235, 208));
+        } else if self.passed_tests > 0 {
+            badges.push(TString::badge("pass!", 254, 2));
+        }
+        if self.warnings > 0 {
+            badges.push(TString::num_badge(self.warnings, "warning", 235, 11));
+        }
+        badges
+    }
     pub fn can_scope_tests(&self) -> bool {
         self.passed_tests > 0 && self.test_fails > 0
     }
+    /// A short human readable summary, eg "2 error(s), 1 warning(s)"
+    pub fn summary(&self) -> String {
+        let mut counts = Vec::new();
+        if self.errors > 0 {
+            counts.push(format!("{} error(s)", self.errors));
+        }
+        if self.test_fails > 0 {
+            counts.push(format!("{} failed test(s)", self.test_fails));
+        }
+        if self.warnings > 0 {
+            counts.push(format!("{} warning(s)", self.warnings));
+        }
+        if !counts.is_empty() {
+            counts.join(", ")
+        } else if self.passed_tests > 0 {
+            format!("{} test(s) passed", self.passed_tests)
+        } else {
+            "no problem".to_string()
+        }
+    }
 }
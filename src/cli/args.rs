@@ -9,6 +9,7 @@ use {
         Parser,
     },
     clap_complete::ArgValueCandidates,
+    std::path::PathBuf,
     termimad::ansi,
 };
 
@@ -25,7 +26,7 @@ Complete documentation at https://dystroy.org/bacon
 ";
 
 /// Launch arguments
-#[derive(Debug, Parser)]
+#[derive(Debug, Clone, Parser)]
 #[command(
     author,
     about,
@@ -50,6 +51,17 @@ pub struct Args {
     #[clap(long)]
     pub headless: bool,
 
+    /// What to print in headless mode: `raw` (the command's output),
+    /// `plain` (a line per diagnostic and a summary line), or
+    /// `json-stream` (newline delimited JSON events)
+    #[clap(long, value_name = "output")]
+    pub output: Option<HeadlessOutput>,
+
+    /// Run the job once, without user interface, then exit with a
+    /// code telling whether it succeeded (eg for CI or git hooks)
+    #[clap(long)]
+    pub once: bool,
+
     /// Start in summary mode
     #[clap(short = 's', long)]
     pub summary: bool,
@@ -85,13 +97,45 @@ pub struct Args {
     /// List available jobs
     #[clap(short = 'l', long)]
     pub list_jobs: bool,
-    #[clap(long, hide = true)]
-    pub completion_list_jobs: bool,
+
+    /// With `--list-jobs`, print the jobs as JSON, with `--log`, print
+    /// the runs as JSON lines
+    #[clap(long)]
+    pub json: bool,
+
+    /// With `--list-jobs`, print only the job names, one per line,
+    /// followed by a tab and the description when there's one
+    #[clap(long)]
+    pub porcelain: bool,
+
+    /// Print the runs of the project recorded in the run log
+    /// (see the `run_log` config)
+    #[clap(long)]
+    pub log: bool,
+
+    /// Print the configuration properties, with the file (or env var)
+    /// each of them comes from
+    #[clap(long)]
+    pub show_config_origin: bool,
+
+    /// Print the merged settings and what the job (the default one, or
+    /// the one given as argument) would run, without running it
+    #[clap(long)]
+    pub print_config: bool,
+
+    /// Check the project, the configuration, file watching, the terminal,
+    /// and sound, and print what can be fixed
+    #[clap(long)]
+    pub doctor: bool,
 
     /// Don't access the network
     #[clap(long)]
     pub offline: bool,
 
+    /// Run `cargo metadata` even when its cached result is up to date
+    #[clap(long)]
+    pub no_metadata_cache: bool,
+
     /// Create a bacon.toml file, ready to be customized
     #[clap(long)]
     pub init: bool,
@@ -100,6 +144,11 @@ pub struct Args {
     #[clap(short = 'j', long, value_name = "job", add = ArgValueCandidates::new(crate::cli::completions::list_jobs))]
     pub job: Option<ConcreteJobRef>,
 
+    /// Profile to apply, defined in a `[profile.<name>]` section of
+    /// the configuration
+    #[clap(long, value_name = "profile")]
+    pub profile: Option<String>,
+
     /// Ignore features of both the package and the bacon job
     #[clap(long)]
     pub no_default_features: bool,
@@ -130,6 +179,70 @@ pub struct Args {
     #[clap(long, value_name = "project", value_hint = clap::ValueHint::DirPath)]
     pub project: Option<String>,
 
+    /// Project root of a multi-root session: several `--root` may be
+    /// given, only the current one is watched and run
+    #[clap(long, value_name = "root", value_hint = clap::ValueHint::DirPath)]
+    pub root: Vec<String>,
+
+    /// Also run the job when receiving the SIGUSR1 signal
+    #[clap(long)]
+    pub trigger_signal: bool,
+
+    /// Also run the job on each line received on stdin
+    #[clap(long)]
+    pub trigger_stdin: bool,
+
+    /// Also run the job on each line written to this FIFO (named pipe)
+    #[clap(long, value_name = "fifo", value_hint = clap::ValueHint::FilePath)]
+    pub trigger_fifo: Option<PathBuf>,
+
+    /// Listen on this localhost port to HTTP POST requests running
+    /// the current job (`/`) or a named one (`/job/<name>`)
+    #[clap(long, value_name = "port")]
+    pub trigger_port: Option<u16>,
+
+    /// Don't run the job on file changes (only on other triggers)
+    #[clap(long)]
+    pub no_file_triggers: bool,
+
+    /// Listen on this unix socket to JSON-RPC requests controlling bacon
+    #[clap(long, value_name = "socket", value_hint = clap::ValueHint::FilePath)]
+    pub control_socket: Option<PathBuf>,
+
+    /// Serve, on this localhost port, a web page showing the report
+    #[clap(long, value_name = "port")]
+    pub dashboard_port: Option<u16>,
+
+    /// Post the new diagnostics as review comments of this GitHub
+    /// pull request (see the `[github]` config)
+    #[clap(long, value_name = "number")]
+    pub github_pr: Option<u64>,
+
+    /// Wait for the other bacon instances using the same target
+    /// directory to finish their job before running one
+    #[clap(long)]
+    pub exclusive_runs: bool,
+
+    /// Attach to the bacon session listening on this control socket,
+    /// eg a headless one, instead of running jobs
+    #[clap(long, value_name = "socket", value_hint = clap::ValueHint::FilePath)]
+    pub attach: Option<PathBuf>,
+
+    /// Record the outputs of the commands, the keys, and the file changes
+    /// in this file, to replay the session with `--replay`
+    #[clap(long, value_name = "file", value_hint = clap::ValueHint::FilePath)]
+    pub record: Option<PathBuf>,
+
+    /// Replay the session recorded in this file, instead of running
+    /// the commands and watching the files
+    #[clap(long, value_name = "file", value_hint = clap::ValueHint::FilePath, conflicts_with = "record")]
+    pub replay: Option<PathBuf>,
+
+    /// Run as a Model Context Protocol server, on stdio, giving AI agents
+    /// access to the bacon listening on the control socket
+    #[clap(long)]
+    pub mcp: bool,
+
     /// Configuration passed as a TOML string
     #[clap(long)]
     pub config_toml: Option<String>,
@@ -180,6 +293,12 @@ impl Args {
             }
             _ => {}
         }
+        if let Some(first_root) = self.root.first() {
+            if self.project.is_some() {
+                bail!("A project can't be given with --root");
+            }
+            self.project = Some(first_root.clone());
+        }
         Ok(())
     }
     pub fn print_help(&self) {
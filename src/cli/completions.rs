@@ -1,8 +1,27 @@
 use {
-    clap_complete::CompletionCandidate,
-    std::process::Command,
+    clap_complete::{
+        CompletionCandidate,
+        env::{
+            Bash,
+            Elvish,
+            EnvCompleter,
+            Fish,
+            Powershell,
+            Shells,
+            Zsh,
+        },
+    },
+    std::{
+        ffi::OsString,
+        io,
+        path::Path,
+        process::Command,
+    },
 };
 
+/// The shells for which `COMPLETE=<shell> bacon` writes a registration script
+pub const SHELLS: Shells = Shells(&[&Bash, &Elvish, &Fish, &Nushell, &Powershell, &Zsh]);
+
 fn with_self_command(
     f: impl FnOnce(Command) -> Option<Vec<CompletionCandidate>>
 ) -> Vec<CompletionCandidate> {
@@ -12,10 +31,118 @@ fn with_self_command(
         .unwrap_or_default()
 }
 
+/// Complete the job names with the ones of the configuration, as listed
+/// by `bacon --list-jobs --porcelain`, in the project of the command line
 pub fn list_jobs() -> Vec<CompletionCandidate> {
+    let args: Vec<String> = std::env::args().collect();
     with_self_command(|mut c| {
-        let output = c.arg("--completion-list-jobs").output().ok()?;
+        c.arg("--list-jobs").arg("--porcelain");
+        if let Some(project) = project_arg(&args) {
+            c.arg("--project").arg(project);
+        }
+        let output = c.output().ok()?;
         let output: String = String::from_utf8(output.stdout).ok()?;
-        Some(output.split('\0').map(CompletionCandidate::new).collect())
+        Some(
+            output
+                .lines()
+                .map(|line| match line.split_once('\t') {
+                    Some((name, description)) => {
+                        CompletionCandidate::new(name).help(Some(description.to_string().into()))
+                    }
+                    None => CompletionCandidate::new(line),
+                })
+                .collect(),
+        )
     })
 }
+
+/// Find the value of `--project` in the command line being completed
+fn project_arg(args: &[String]) -> Option<&str> {
+    let mut args = args.iter().skip_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--project" {
+            return args.next().map(|s| s.as_str());
+        }
+        if let Some(project) = arg.strip_prefix("--project=") {
+            return Some(project);
+        }
+    }
+    None
+}
+
+/// Nushell completion adapter, registered as external completer
+/// (clap_complete doesn't provide it)
+#[derive(Debug, Clone, Copy)]
+pub struct Nushell;
+
+impl EnvCompleter for Nushell {
+    fn name(&self) -> &'static str {
+        "nu"
+    }
+    fn is(
+        &self,
+        name: &str,
+    ) -> bool {
+        name == "nu" || name == "nushell"
+    }
+    fn write_registration(
+        &self,
+        var: &str,
+        _name: &str,
+        bin: &str,
+        completer: &str,
+        buf: &mut dyn io::Write,
+    ) -> Result<(), io::Error> {
+        // the previous external completer, if any, still handles the other commands
+        writeln!(
+            buf,
+            r#"let bacon_fallback_completer = $env.config.completions.external.completer?
+$env.config.completions.external.enable = true
+$env.config.completions.external.completer = {{|spans|
+    if ($spans | first) == {bin:?} {{
+        {var}=nu ^{completer:?} -- ...$spans
+        | lines
+        | each {{|line|
+            let parts = $line | split row "\t"
+            {{value: $parts.0, description: $parts.1?}}
+        }}
+    }} else if $bacon_fallback_completer != null {{
+        do $bacon_fallback_completer $spans
+    }}
+}}"#
+        )
+    }
+    fn write_complete(
+        &self,
+        cmd: &mut clap::Command,
+        args: Vec<OsString>,
+        current_dir: Option<&Path>,
+        buf: &mut dyn io::Write,
+    ) -> Result<(), io::Error> {
+        let index = args.len() - 1;
+        let completions = clap_complete::engine::complete(cmd, args, index, current_dir)?;
+        for candidate in completions {
+            write!(buf, "{}", candidate.get_value().to_string_lossy())?;
+            if let Some(help) = candidate.get_help() {
+                let help = help.to_string();
+                write!(buf, "\t{}", help.lines().next().unwrap_or_default())?;
+            }
+            writeln!(buf)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_project_arg() {
+    let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+    assert_eq!(
+        project_arg(&args("bacon -- bacon --project ../app cl")),
+        Some("../app")
+    );
+    assert_eq!(
+        project_arg(&args("bacon -- bacon --project=../app")),
+        Some("../app")
+    );
+    assert_eq!(project_arg(&args("bacon -- bacon -j cl")), None);
+}
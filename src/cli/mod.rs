@@ -13,6 +13,7 @@ use {
     std::{
         fs,
         io::Write,
+        path::Path,
     },
     termimad::crossterm::{
         QueueableCommand,
@@ -31,22 +32,25 @@ use termimad::crossterm::event::{
 };
 
 /// The Write type used by all GUI writing functions
-pub type W = std::io::BufWriter<std::io::Stdout>;
+pub type W = ScreenWriter;
 
 /// return the writer used by the application
 pub fn writer() -> W {
-    std::io::BufWriter::new(std::io::stdout())
+    ScreenWriter::new()
 }
 
 pub fn run() -> anyhow::Result<()> {
     if std::env::var_os("COMPLETE").is_some() {
-        clap_complete::CompleteEnv::with_factory(Args::command).complete();
+        clap_complete::CompleteEnv::with_factory(Args::command)
+            .shells(completions::SHELLS)
+            .complete();
     }
 
     let mut args: Args = Args::parse();
     args.fix()?;
     info!("args: {:#?}", &args);
-    let headless = args.headless;
+    // the once mode has no TUI
+    let headless = args.headless || args.once;
 
     if args.help {
         args.print_help();
@@ -72,13 +76,29 @@ pub fn run() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    if let Some(socket) = &args.attach {
+        // the attached viewer doesn't need a project
+        return attach(socket, &prefs_keybindings());
+    }
+
+    if args.doctor {
+        if !run_doctor(&args) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let context = Context::new(&args)?;
     debug!("mission context: {:#?}", &context);
 
     if args.init {
         let package_config_path = context.package_config_path();
         if !package_config_path.exists() {
-            fs::write(&package_config_path, DEFAULT_PACKAGE_CONFIG.trim_start())?;
+            let config = match context.nature {
+                ContextNature::Cargo => ProjectProbe::new(&context).config(),
+                nature => nature.default_config().trim_start().to_string(),
+            };
+            fs::write(&package_config_path, config)?;
             eprintln!("bacon project configuration file written.");
         } else {
             eprintln!("bacon configuration file already exists.");
@@ -87,34 +107,131 @@ pub fn run() -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let settings = Settings::read(&args, &context)?;
+    if args.log {
+        return print_run_log(&context.package_directory, args.json);
+    }
 
-    if args.list_jobs {
-        print_jobs(&settings);
+    if args.show_config_origin {
+        ConfigOrigins::read(&args, &context)?.print(&mut std::io::stdout())?;
+        return Ok(());
+    }
+
+    if let Some(path) = &args.replay {
+        let session = SessionFile::read(path)?;
+        if args.job.is_none() {
+            args.job = session.first_job().map(ConcreteJobRef::from);
+        }
+        start_session_replay(session);
+    }
+    if let Some(path) = &args.record {
+        start_session_recording(path)?;
+    }
+
+    let mut event_source = None;
+    let settings = match Settings::read(&args, &context) {
+        Err(e) if launches_tui(&args) && e.is::<ConfigErrors>() => {
+            // the problems are displayed until fixed, instead of exiting
+            let mut w = writer();
+            enter_alternate_screen(&mut w)?;
+            let user_events = tui::app::user_event_source()?;
+            let settings =
+                run_config_error_screen(&mut w, &args, &context, &user_events, e.downcast()?);
+            leave_alternate_screen(&mut w)?;
+            event_source = Some(user_events);
+            match settings? {
+                Some(settings) => settings,
+                None => return Ok(()),
+            }
+        }
+        settings => settings?,
+    };
+
+    if args.print_config {
+        print_config(&mut std::io::stdout(), &context, &settings)?;
         return Ok(());
     }
-    if args.completion_list_jobs {
-        for job in settings.jobs.keys() {
-            print!("{job}\0");
+    if args.list_jobs {
+        if args.json {
+            print_jobs_json(&mut std::io::stdout(), &settings)?;
+        } else if args.porcelain {
+            print_jobs_porcelain(&mut std::io::stdout(), &settings)?;
+        } else {
+            print_jobs(&settings);
         }
         return Ok(());
     }
+    if args.mcp {
+        return run_mcp_server(&settings);
+    }
+
+    if settings.instances.attach && !headless {
+        // another instance runs the jobs, this one is just a viewer
+        if let Some(socket) = context.target_directory().and_then(executor_socket) {
+            info!("attaching to the instance listening on {socket:?}");
+            return attach(&socket, &settings.keybindings);
+        }
+    }
 
     let mut w = writer();
     if !headless {
-        w.queue(EnterAlternateScreen)?;
-        w.queue(cursor::Hide)?;
-        #[cfg(windows)]
-        w.queue(EnableMouseCapture)?;
-        w.flush()?;
+        enter_alternate_screen(&mut w)?;
     }
-    let result = tui::app::run(&mut w, settings, &args, context, headless);
+    let result = tui::app::run(&mut w, settings, &args, context, headless, event_source);
+    flush_debounced_exports();
     if !headless {
-        #[cfg(windows)]
-        w.queue(DisableMouseCapture)?;
-        w.queue(cursor::Show)?;
-        w.queue(LeaveAlternateScreen)?;
+        leave_alternate_screen(&mut w)?;
     }
     w.flush()?;
+    if result? == Some(false) {
+        // the job failed, in --once mode
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Whether the launch arguments lead to the TUI, once the settings are read
+fn launches_tui(args: &Args) -> bool {
+    !(args.headless || args.once || args.print_config || args.list_jobs || args.mcp)
+}
+
+/// The keybindings of the global preferences, for when there's no project
+fn prefs_keybindings() -> KeyBindings {
+    let mut settings = Settings::default();
+    if let Some(path) = bacon_prefs_path().filter(|path| path.exists()) {
+        match Config::from_path(&path) {
+            Ok(config) => settings.apply_config(&config),
+            Err(e) => warn!("prefs not applied: {e:#}"),
+        }
+    }
+    settings.keybindings
+}
+
+/// Run a viewer attached to the bacon session listening on the socket
+fn attach(
+    socket: &Path,
+    keybindings: &KeyBindings,
+) -> anyhow::Result<()> {
+    let mut w = writer();
+    enter_alternate_screen(&mut w)?;
+    let result = run_attached(&mut w, socket, keybindings);
+    leave_alternate_screen(&mut w)?;
     result
 }
+
+fn enter_alternate_screen(w: &mut W) -> anyhow::Result<()> {
+    w.queue(EnterAlternateScreen)?;
+    w.queue(cursor::Hide)?;
+    #[cfg(windows)]
+    w.queue(EnableMouseCapture)?;
+    w.flush()?;
+    Ok(())
+}
+
+fn leave_alternate_screen(w: &mut W) -> anyhow::Result<()> {
+    #[cfg(windows)]
+    w.queue(DisableMouseCapture)?;
+    w.queue(cursor::Show)?;
+    w.queue(LeaveAlternateScreen)?;
+    w.flush()?;
+    Ok(())
+}
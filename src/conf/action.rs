@@ -177,7 +177,7 @@ fn test_action_string_round_trip() {
         Action::Export("my export".to_string()),
     ];
     for action in actions {
-        println!("action: {}", action.to_string());
+        println!("action: {}", action);
         assert_eq!(action.to_string().parse(), Ok(action));
     }
 }
@@ -1,5 +1,8 @@
 use {
-    super::Config,
+    super::{
+        Config,
+        ConfigFile,
+    },
     anyhow::*,
     serde::Deserialize,
     std::path::Path,
@@ -24,7 +27,11 @@ pub fn load_config_from_cargo_toml(cargo_file_path: &Path) -> Result<Vec<Config>
         return Ok(Vec::default());
     }
     let cargo_toml = std::fs::read_to_string(cargo_file_path)?;
-    let mut cargo: CargoWrappedConfig = toml::from_str(&cargo_toml)?;
+    let mut file = ConfigFile::new(cargo_file_path, &cargo_toml);
+    file.check_keys("workspace.metadata.bacon");
+    file.check_keys("package.metadata.bacon");
+    let cargo = file.deserialize::<CargoWrappedConfig>();
+    let (mut cargo, warnings) = file.finish(cargo)?;
     let mut configs = Vec::new();
     let worskpace_config = cargo
         .workspace
@@ -42,5 +49,9 @@ pub fn load_config_from_cargo_toml(cargo_file_path: &Path) -> Result<Vec<Config>
     if let Some(config) = worskpace_config {
         configs.push(config);
     }
+    // the warnings are about the file, not a specific config
+    if let Some(config) = configs.first_mut() {
+        config.warnings = warnings;
+    }
     Ok(configs)
 }
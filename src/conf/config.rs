@@ -1,6 +1,9 @@
 use {
     crate::*,
-    anyhow::*,
+    anyhow::{
+        Context as _,
+        Result,
+    },
     lazy_regex::regex_is_match,
     serde::Deserialize,
     std::{
@@ -19,8 +22,22 @@ use {
 pub struct Config {
     pub additional_alias_args: Option<Vec<String>>,
 
+    #[serde(default)]
+    pub control: ControlConfig,
+
+    #[serde(default)]
+    pub dashboard: DashboardConfig,
+
     pub default_job: Option<ConcreteJobRef>,
 
+    /// Default jobs by directory, relative to the directory of the
+    /// configuration file, eg `"crates/web" = "web-check"`
+    #[serde(default)]
+    pub defaults: HashMap<String, ConcreteJobRef>,
+
+    /// How to open a location in an editor
+    pub editor: Option<EditorConfig>,
+
     /// Default config for a job
     #[serde(flatten)]
     pub all_jobs: Job,
@@ -29,26 +46,75 @@ pub struct Config {
     #[deprecated(since = "2.22.0", note = "use exports.locations")]
     pub export: Option<ExportConfig>,
 
+    /// Minimal delay between two writes of the auto exports
+    pub export_debounce: Option<Period>,
+
     #[deprecated(since = "2.9.0", note = "use exports.locations.auto")]
     pub export_locations: Option<bool>,
 
     #[serde(default)]
     pub exports: HashMap<String, ExportConfig>,
 
+    #[serde(default)]
+    pub github: GithubConfig,
+
     pub help_line: Option<bool>,
 
+    /// The kind of URL of the hyperlinks on locations
+    pub hyperlink_scheme: Option<HyperlinkScheme>,
+
+    /// Whether to make the locations of the report clickable
+    /// hyperlinks, in terminals supporting them
+    pub hyperlinks: Option<bool>,
+
+    /// Paths of configuration files applied before this one
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    #[serde(default)]
+    pub instances: InstancesConfig,
+
     #[serde(default)]
     pub jobs: HashMap<String, Job>,
 
     pub keybindings: Option<KeyBindings>,
 
+    #[serde(default)]
+    pub nvim: NvimConfig,
+
+    /// Whether to keep the last report of each job, to display it on
+    /// the next launch until the first execution finishes
+    pub persist_reports: Option<bool>,
+
+    /// Whether to keep the last job, and the scroll position and filters
+    /// of each job, to restore them on the next launch
+    pub persist_ui_state: Option<bool>,
+
+    /// Configurations applied over the other ones when the profile is selected
+    #[serde(default)]
+    pub profile: HashMap<String, Config>,
+
     pub reverse: Option<bool>,
 
+    /// Whether to append a line per execution to the run log
+    pub run_log: Option<bool>,
+
     pub summary: Option<bool>,
 
+    #[serde(default)]
+    pub triggers: TriggersConfig,
+
     #[deprecated(since = "2.0.0", note = "use keybindings")]
     pub vim_keys: Option<bool>,
 
+    /// The problems found in the file which didn't prevent loading it,
+    /// eg unknown keys
+    #[serde(skip)]
+    pub warnings: Vec<ConfigProblem>,
+
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+
     pub wrap: Option<bool>,
 }
 
@@ -70,31 +136,110 @@ impl Config {
         }
     }
     /// Load a configuration item filling the provided path in TOML
+    ///
+    /// The error, when the file is invalid, is a [ConfigErrors] locating
+    /// each problem. Unknown keys don't make the file invalid but are
+    /// kept in `warnings`.
     pub fn from_path(path: &Path) -> Result<Self> {
-        let conf = toml::from_str::<Self>(&fs::read_to_string(path)?)
-            .with_context(|| format!("Failed to parse configuration file at {:?}", path))?;
-        for (name, job) in &conf.jobs {
-            if !regex_is_match!(r#"^[\w-]+$"#, name) {
-                bail!("Invalid configuration : Illegal job name : {:?}", name);
-            }
-            if job.command.is_empty() {
-                bail!("Invalid configuration : empty command for job {:?}", name);
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read configuration file at {:?}", path))?;
+        let mut file = ConfigFile::new(path, &content);
+        file.check_keys("");
+        let conf = file.deserialize::<Self>();
+        if let Some(conf) = &conf {
+            let mut names: Vec<&String> = conf.jobs.keys().collect();
+            names.sort();
+            for name in names {
+                if !regex_is_match!(r#"^[\w-]+$"#, name) {
+                    file.add_problem(&["jobs", name], "illegal job name");
+                }
+                if conf.jobs[name].command.is_empty() {
+                    file.add_problem(&["jobs", name], "empty command");
+                }
             }
         }
+        let (mut conf, warnings) = file.finish(conf)?;
+        conf.warnings = warnings;
         Ok(conf)
     }
     pub fn default_package_config() -> Self {
         toml::from_str(DEFAULT_PACKAGE_CONFIG).unwrap()
     }
+    /// The default configuration for this kind of project
+    pub fn default_config_of(nature: ContextNature) -> Self {
+        toml::from_str(nature.default_config()).unwrap()
+    }
     pub fn default_prefs() -> Self {
         toml::from_str(DEFAULT_PREFS).unwrap()
     }
+    /// Build the configuration item defined by a `BACON_*` env var, eg
+    /// `BACON_SUMMARY=true`, nested keys being separated with a double
+    /// underscore, eg `BACON_EXPORTS__LOCATIONS__AUTO=true`.
+    ///
+    /// The value is read as a TOML value, or as a string when it's not one.
+    pub fn from_env_var(
+        name: &str,
+        value: &str,
+    ) -> Result<Self> {
+        env_var_toml(name, value)
+            .try_into()
+            .with_context(|| format!("Invalid value in env var {name}"))
+    }
+}
+
+/// Build the TOML table of the configuration defined by a `BACON_*` env var
+pub fn env_var_toml(
+    name: &str,
+    value: &str,
+) -> toml::Value {
+    let key = name
+        .strip_prefix(ENV_VAR_PREFIX)
+        .unwrap_or(name)
+        .to_ascii_lowercase();
+    let mut value = format!("v = {value}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()));
+    for segment in key.rsplit("__") {
+        let mut table = toml::Table::new();
+        table.insert(segment.to_string(), value);
+        value = toml::Value::Table(table);
+    }
+    value
 }
 
+/// Prefix of the env vars overriding settings
+pub const ENV_VAR_PREFIX: &str = "BACON_";
+
 #[test]
 fn test_default_files() {
     let mut settings = Settings::default();
     settings.apply_config(&Config::default_prefs());
     settings.apply_config(&Config::default_package_config());
     settings.check().unwrap();
+    for nature in [
+        ContextNature::Node,
+        ContextNature::Go,
+        ContextNature::Python,
+    ] {
+        let mut settings = Settings::default();
+        settings.apply_config(&Config::default_prefs());
+        settings.apply_config(&Config::default_config_of(nature));
+        settings.check().unwrap();
+    }
+}
+
+#[test]
+fn test_config_from_env_var() {
+    let config = Config::from_env_var("BACON_SUMMARY", "true").unwrap();
+    assert_eq!(config.summary, Some(true));
+    let config = Config::from_env_var("BACON_DEFAULT_JOB", "clippy").unwrap();
+    assert_eq!(
+        config.default_job,
+        Some(ConcreteJobRef::from_job_name("clippy"))
+    );
+    let config = Config::from_env_var("BACON_EXPORTS__LOCATIONS__AUTO", "true").unwrap();
+    assert_eq!(config.exports["locations"].auto, Some(true));
+    assert!(Config::from_env_var("BACON_WRAP", "maybe").is_err());
 }
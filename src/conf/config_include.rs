@@ -0,0 +1,97 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    std::path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// Return the configuration files to load for the given one: the files
+/// it includes with `include = [...]` (recursively, each one before the
+/// file including it) then the file itself, so that local properties
+/// override the shared ones.
+///
+/// The path of an included file is relative to the directory of the file
+/// including it, or to the home directory when it starts with `~/`.
+pub fn with_includes(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    add_with_includes(path, &mut Vec::new(), &mut paths)?;
+    Ok(paths)
+}
+
+fn add_with_includes(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        bail!("Invalid configuration : {path:?} includes itself");
+    }
+    stack.push(canonical);
+    for config in Config::from_path_detect(path)? {
+        for include in &config.include {
+            let included = resolve_include(path, include);
+            let Ok(included) = included.canonicalize() else {
+                bail!("Invalid configuration : file {included:?} included by {path:?} not found");
+            };
+            add_with_includes(&included, stack, paths)?;
+        }
+    }
+    stack.pop();
+    paths.push(path.to_path_buf());
+    Ok(())
+}
+
+fn resolve_include(
+    including_path: &Path,
+    include: &str,
+) -> PathBuf {
+    if let Some(relative) = include.strip_prefix("~/") {
+        if let Some(dirs) = directories_next::BaseDirs::new() {
+            return dirs.home_dir().join(relative);
+        }
+    }
+    match including_path.parent() {
+        Some(dir) => dir.join(include),
+        None => PathBuf::from(include),
+    }
+}
+
+#[test]
+fn test_with_includes() {
+    use std::fs;
+    let root = std::env::temp_dir().join(format!("bacon-test-include-{}", std::process::id()));
+    let write = |path: &str, content: &str| {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    };
+    write("common.toml", "summary = true\n");
+    write("team/keys.toml", "include = [\"../common.toml\"]\n");
+    write(
+        "proj/bacon.toml",
+        "include = [\"../team/keys.toml\"]\nwrap = false\n",
+    );
+    let names: Vec<String> = with_includes(&root.join("proj/bacon.toml"))
+        .unwrap()
+        .iter()
+        .map(|path| {
+            path.strip_prefix(root.canonicalize().unwrap())
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(names, ["common.toml", "team/keys.toml", "proj/bacon.toml"]);
+    write("a.toml", "include = [\"b.toml\"]\n");
+    write("b.toml", "include = [\"a.toml\"]\n");
+    assert!(with_includes(&root.join("a.toml")).is_err());
+    write("c.toml", "include = [\"missing.toml\"]\n");
+    assert!(with_includes(&root.join("c.toml")).is_err());
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,169 @@
+use {
+    crate::*,
+    anyhow::{
+        Context as _,
+        Result,
+    },
+    std::{
+        collections::BTreeMap,
+        fs,
+        io::Write,
+    },
+};
+
+/// The value of each configuration property, with the configuration
+/// element it comes from, as displayed by `bacon --show-config-origin`
+#[derive(Debug, Default)]
+pub struct ConfigOrigins {
+    /// the values and origins, by dotted path of the property
+    properties: BTreeMap<String, (String, String)>,
+}
+
+impl ConfigOrigins {
+    /// Read the configuration elements in the same order as `Settings::read`
+    pub fn read(
+        args: &Args,
+        context: &Context,
+    ) -> Result<Self> {
+        let mut origins = Self::default();
+        let default_config: toml::Value = toml::from_str(context.nature.default_config())?;
+        origins.add("default configuration", &default_config);
+        let mut configs = Vec::new();
+        for path in config_paths(context) {
+            if !path.exists() {
+                continue;
+            }
+            for path in with_includes(&path)? {
+                let content: toml::Value = toml::from_str(&fs::read_to_string(&path)?)
+                    .with_context(|| format!("Failed to parse {path:?}"))?;
+                let origin = path.to_string_lossy();
+                if path.file_name().is_some_and(|name| name == "Cargo.toml") {
+                    for section in ["workspace", "package"] {
+                        if let Some(config) = content
+                            .get(section)
+                            .and_then(|section| section.get("metadata"))
+                            .and_then(|metadata| metadata.get("bacon"))
+                        {
+                            configs.push((
+                                format!("{origin} ({section}.metadata.bacon)"),
+                                config.clone(),
+                            ));
+                        }
+                    }
+                } else {
+                    configs.push((origin.to_string(), content));
+                }
+            }
+        }
+        for (origin, config) in &configs {
+            origins.add(origin, config);
+        }
+        if let Some(profile) = &args.profile {
+            for (origin, config) in &configs {
+                if let Some(config) = config.get("profile").and_then(|p| p.get(profile)) {
+                    origins.add(&format!("{origin} (profile {profile})"), config);
+                }
+            }
+        }
+        for (name, value) in setting_env_vars() {
+            origins.add(&format!("env var {name}"), &env_var_toml(&name, &value));
+        }
+        if let Some(toml) = &args.config_toml {
+            origins.add("--config-toml", &toml::from_str(toml)?);
+        }
+        Ok(origins)
+    }
+    /// Register the properties of a configuration element, overriding
+    /// the ones of the previous elements
+    fn add(
+        &mut self,
+        origin: &str,
+        config: &toml::Value,
+    ) {
+        if let Some(jobs) = config.get("jobs").and_then(toml::Value::as_table) {
+            // a job definition replaces the previous one as a whole
+            for name in jobs.keys() {
+                let prefix = format!("jobs.{name}.");
+                self.properties.retain(|path, _| !path.starts_with(&prefix));
+            }
+        }
+        self.add_value(origin, "", config);
+    }
+    fn add_value(
+        &mut self,
+        origin: &str,
+        path: &str,
+        value: &toml::Value,
+    ) {
+        match value {
+            toml::Value::Table(table) if !table.is_empty() => {
+                for (key, value) in table {
+                    let key = if key
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                    {
+                        key.clone()
+                    } else {
+                        format!("{key:?}")
+                    };
+                    let path = if path.is_empty() {
+                        key
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    self.add_value(origin, &path, value);
+                }
+            }
+            _ => {
+                self.properties
+                    .insert(path.to_string(), (value.to_string(), origin.to_string()));
+            }
+        }
+    }
+    pub fn print(
+        &self,
+        w: &mut impl Write,
+    ) -> Result<()> {
+        for (path, (value, origin)) in &self.properties {
+            writeln!(w, "{path} = {value}  # {origin}")?;
+        }
+        writeln!(w, "# launch arguments may override those properties")?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_config_origins() {
+    let mut origins = ConfigOrigins::default();
+    let prefs: toml::Value = toml::from_str(
+        r#"
+        summary = true
+        [jobs.check]
+        command = ["cargo", "check"]
+        need_stdout = false
+        "#,
+    )
+    .unwrap();
+    let package: toml::Value = toml::from_str(
+        r#"
+        summary = false
+        [jobs.check]
+        command = ["cargo", "check", "--all-targets"]
+        [keybindings]
+        ctrl-c = "job:check"
+        "#,
+    )
+    .unwrap();
+    origins.add("prefs", &prefs);
+    origins.add("package", &package);
+    let mut output = Vec::new();
+    origins.print(&mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"jobs.check.command = ["cargo", "check", "--all-targets"]  # package
+keybindings.ctrl-c = "job:check"  # package
+summary = false  # package
+# launch arguments may override those properties
+"#,
+    );
+}
@@ -0,0 +1,549 @@
+use {
+    crate::*,
+    serde::{
+        Deserialize,
+        Deserializer,
+        de::{
+            self,
+            DeserializeOwned,
+            Visitor,
+        },
+        forward_to_deserialize_any,
+    },
+    std::{
+        fmt,
+        ops::Range,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    toml_edit::{
+        ImDocument,
+        TableLike,
+    },
+};
+
+/// The keys of a bacon configuration which aren't job properties.
+///
+/// They can't be listed by serde, because the default job is flattened in [Config].
+const CONFIG_KEYS: &[&str] = &[
+    "additional_alias_args",
+    "control",
+    "dashboard",
+    "default_job",
+    "defaults",
+    "editor",
+    "export",
+    "export_debounce",
+    "export_locations",
+    "exports",
+    "github",
+    "help_line",
+    "hyperlink_scheme",
+    "hyperlinks",
+    "include",
+    "instances",
+    "jobs",
+    "keybindings",
+    "nvim",
+    "persist_reports",
+    "persist_ui_state",
+    "profile",
+    "reverse",
+    "run_log",
+    "summary",
+    "triggers",
+    "vim_keys",
+    "webhook",
+    "wrap",
+];
+
+/// A problem found in a configuration file, located as precisely as possible
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub file: PathBuf,
+    /// the dotted path of the faulty key, eg `jobs.check.allow_warning`
+    pub key_path: Option<String>,
+    /// the line of the problem, starting at 1
+    pub line: Option<usize>,
+    pub message: String,
+    /// the known key the faulty one is probably a misspelling of
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}", self.file.to_string_lossy())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        if let Some(key_path) = &self.key_path {
+            write!(f, ": `{key_path}`")?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean `{suggestion}`?)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The problems found in the configuration files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for problem in &self.problems {
+            write!(f, "\n  {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+impl ConfigErrors {
+    /// A one line description, for the status line
+    pub fn summary(&self) -> String {
+        problems_summary(&self.problems)
+    }
+}
+
+/// A one line description of a list of problems
+pub fn problems_summary(problems: &[ConfigProblem]) -> String {
+    let mut summary = problems
+        .first()
+        .map(|problem| problem.to_string())
+        .unwrap_or_default();
+    if problems.len() > 1 {
+        summary.push_str(&format!(" (and {} other problems)", problems.len() - 1));
+    }
+    summary
+}
+
+/// What's expected in a table of the configuration
+#[derive(Debug, Clone, Copy)]
+enum TableSchema {
+    /// a bacon configuration, eg a whole bacon.toml file or a profile
+    Config,
+    Job,
+    /// a table with the fields of a struct
+    Fields(fn() -> &'static [&'static str]),
+    /// a table whose values are tables with the given schema, eg `jobs`
+    Map(&'static TableSchema),
+    /// a table whose keys aren't checked, eg `env` or `keybindings`
+    Free,
+}
+
+impl TableSchema {
+    /// Return the allowed keys, or None when they aren't checked
+    fn keys(self) -> Option<Vec<&'static str>> {
+        match self {
+            Self::Config => {
+                let mut keys = CONFIG_KEYS.to_vec();
+                keys.extend(fields_of::<Job>());
+                Some(keys)
+            }
+            Self::Job => Some(fields_of::<Job>().to_vec()),
+            Self::Fields(fields) => {
+                let fields = fields();
+                // an empty list means serde couldn't tell the fields
+                (!fields.is_empty()).then(|| fields.to_vec())
+            }
+            Self::Map(_) | Self::Free => None,
+        }
+    }
+    /// Return the schema of the table at the given key, if it's checked
+    fn child(
+        self,
+        key: &str,
+    ) -> Option<Self> {
+        match (self, key) {
+            (Self::Config, "jobs") => Some(Self::Map(&Self::Job)),
+            (Self::Config, "profile") => Some(Self::Map(&Self::Config)),
+            (Self::Config, "exports") => Some(Self::Map(&Self::Fields(fields_of::<ExportConfig>))),
+            (Self::Config, "export") => Some(Self::Fields(fields_of::<ExportConfig>)),
+            (Self::Config, "control") => Some(Self::Fields(fields_of::<ControlConfig>)),
+            (Self::Config, "dashboard") => Some(Self::Fields(fields_of::<DashboardConfig>)),
+            (Self::Config, "editor") => Some(Self::Fields(fields_of::<EditorCommandsConfig>)),
+            (Self::Config, "github") => Some(Self::Fields(fields_of::<GithubConfig>)),
+            (Self::Config, "instances") => Some(Self::Fields(fields_of::<InstancesConfig>)),
+            (Self::Config, "nvim") => Some(Self::Fields(fields_of::<NvimConfig>)),
+            (Self::Config, "triggers") => Some(Self::Fields(fields_of::<TriggersConfig>)),
+            (Self::Config, "webhook") => Some(Self::Fields(fields_of::<WebhookConfig>)),
+            (Self::Config, "defaults" | "keybindings") => Some(Self::Free),
+            (Self::Config, key) => Self::Job.child(key),
+            (Self::Job, "sound") => Some(Self::Fields(fields_of::<SoundConfig>)),
+            (Self::Job, "push") => Some(Self::Fields(fields_of::<PushConfig>)),
+            (Self::Job, "hooks") => Some(Self::Fields(fields_of::<HooksConfig>)),
+            (Self::Job, "analyzer") => Some(Self::Fields(fields_of::<AnalyzerWithOptions>)),
+            (Self::Job, "env" | "severity") => Some(Self::Free),
+            (Self::Map(schema), _) => Some(*schema),
+            _ => None,
+        }
+    }
+}
+
+/// A configuration file being read, with the problems found while
+/// checking and deserializing it
+pub struct ConfigFile<'c> {
+    path: &'c Path,
+    content: &'c str,
+    /// the parsed document, None on a syntax error
+    document: Option<ImDocument<&'c str>>,
+    problems: Vec<ConfigProblem>,
+    /// the problems which don't prevent using the configuration, eg
+    /// unknown keys
+    warnings: Vec<ConfigProblem>,
+}
+
+impl<'c> ConfigFile<'c> {
+    pub fn new(
+        path: &'c Path,
+        content: &'c str,
+    ) -> Self {
+        let mut file = Self {
+            path,
+            content,
+            document: None,
+            problems: Vec::new(),
+            warnings: Vec::new(),
+        };
+        match ImDocument::parse(content) {
+            Ok(document) => {
+                file.document = Some(document);
+            }
+            Err(e) => {
+                let line = e.span().map(|span| file.line_of(span.start));
+                file.problems.push(ConfigProblem {
+                    file: path.to_path_buf(),
+                    key_path: None,
+                    line,
+                    message: e.message().to_string(),
+                    suggestion: None,
+                });
+            }
+        }
+        file
+    }
+    /// Warn about the unknown keys of the bacon configuration at the given
+    /// dotted path (the empty path being the whole file)
+    pub fn check_keys(
+        &mut self,
+        root: &str,
+    ) {
+        let Some(document) = &self.document else {
+            return;
+        };
+        let mut table: &dyn TableLike = document.as_table();
+        for key in root.split('.').filter(|key| !key.is_empty()) {
+            let Some(child) = table.get(key).and_then(|item| item.as_table_like()) else {
+                return;
+            };
+            table = child;
+        }
+        let mut warnings = Vec::new();
+        self.check_table(table, TableSchema::Config, root, &mut warnings);
+        self.warnings.extend(warnings);
+    }
+    fn check_table(
+        &self,
+        table: &dyn TableLike,
+        schema: TableSchema,
+        path: &str,
+        problems: &mut Vec<ConfigProblem>,
+    ) {
+        let known_keys = schema.keys();
+        for (key, item) in table.iter() {
+            let key_path = join_key(path, key);
+            if let Some(known_keys) = &known_keys {
+                if !known_keys.contains(&key) {
+                    let line = table
+                        .get_key_value(key)
+                        .and_then(|(key, _)| key.span())
+                        .map(|span| self.line_of(span.start));
+                    problems.push(ConfigProblem {
+                        file: self.path.to_path_buf(),
+                        key_path: Some(key_path),
+                        line,
+                        message: "unknown key".to_string(),
+                        suggestion: closest_key(key, known_keys).map(|s| s.to_string()),
+                    });
+                    continue;
+                }
+            }
+            let child_table = item.as_table_like();
+            if let (Some(child_schema), Some(child_table)) = (schema.child(key), child_table) {
+                self.check_table(child_table, child_schema, &key_path, problems);
+            }
+        }
+    }
+    /// Deserialize the file, recording the problem, if any
+    pub fn deserialize<T: DeserializeOwned>(&mut self) -> Option<T> {
+        // a syntax error is already reported
+        self.document.as_ref()?;
+        match toml::from_str(self.content) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                let span = e.span();
+                self.problems.push(ConfigProblem {
+                    file: self.path.to_path_buf(),
+                    key_path: span.clone().and_then(|span| self.key_path_at(span)),
+                    line: span.map(|span| self.line_of(span.start)),
+                    message: e.message().to_string(),
+                    suggestion: None,
+                });
+                None
+            }
+        }
+    }
+    /// Record a problem on the given key
+    pub fn add_problem(
+        &mut self,
+        key_path: &[&str],
+        message: impl Into<String>,
+    ) {
+        let line = self
+            .document
+            .as_ref()
+            .and_then(|document| {
+                let mut table: &dyn TableLike = document.as_table();
+                let mut span = None;
+                for key in key_path {
+                    let (key, item) = table.get_key_value(key)?;
+                    span = key.span();
+                    if let Some(child) = item.as_table_like() {
+                        table = child;
+                    }
+                }
+                span
+            })
+            .map(|span| self.line_of(span.start));
+        self.problems.push(ConfigProblem {
+            file: self.path.to_path_buf(),
+            key_path: Some(key_path.join(".")),
+            line,
+            message: message.into(),
+            suggestion: None,
+        });
+    }
+    /// Return the deserialized value with the warnings, or the problems
+    /// found (the warnings included, as they often explain the errors)
+    pub fn finish<T>(
+        self,
+        value: Option<T>,
+    ) -> Result<(T, Vec<ConfigProblem>), ConfigErrors> {
+        let sort = |problems: &mut Vec<ConfigProblem>| {
+            problems.sort_by_key(|problem| problem.line.unwrap_or(usize::MAX));
+        };
+        let mut warnings = self.warnings;
+        sort(&mut warnings);
+        match value {
+            Some(value) if self.problems.is_empty() => Ok((value, warnings)),
+            _ => {
+                let mut problems = self.problems;
+                problems.extend(warnings);
+                sort(&mut problems);
+                Err(ConfigErrors { problems })
+            }
+        }
+    }
+    fn line_of(
+        &self,
+        offset: usize,
+    ) -> usize {
+        self.content[..offset.min(self.content.len())]
+            .matches('\n')
+            .count()
+            + 1
+    }
+    /// Return the path of the deepest key whose key or value contains the span
+    fn key_path_at(
+        &self,
+        span: Range<usize>,
+    ) -> Option<String> {
+        fn find(
+            table: &dyn TableLike,
+            offset: usize,
+            path: &str,
+        ) -> Option<String> {
+            for (key, item) in table.iter() {
+                let key_path = join_key(path, key);
+                if let Some(child) = item.as_table_like() {
+                    if let Some(found) = find(child, offset, &key_path) {
+                        return Some(found);
+                    }
+                }
+                let contains =
+                    |span: Option<Range<usize>>| span.is_some_and(|s| s.contains(&offset));
+                let key_span = table.get_key_value(key).and_then(|(key, _)| key.span());
+                if contains(item.span()) || contains(key_span) {
+                    return Some(key_path);
+                }
+            }
+            None
+        }
+        let document = self.document.as_ref()?;
+        find(document.as_table(), span.start, "")
+    }
+}
+
+fn join_key(
+    path: &str,
+    key: &str,
+) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Return the known key which is the most probably meant in place of the
+/// unknown one
+fn closest_key<'k>(
+    key: &str,
+    known_keys: &[&'k str],
+) -> Option<&'k str> {
+    let max_distance = (key.len() / 3).max(1);
+    known_keys
+        .iter()
+        .map(|known| (strsim::levenshtein(key, known), *known))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, known)| known)
+}
+
+/// Return the field names of a struct deserialized by serde, or an empty
+/// list if they can't be known (eg because of a flattened field)
+fn fields_of<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldsRecorder(&mut fields));
+    fields
+}
+
+/// A deserializer failing immediately, just recording the fields of the
+/// struct it's asked to deserialize
+struct FieldsRecorder<'f>(&'f mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldsRecorder<'_> {
+    type Error = de::value::Error;
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("fields recorded"))
+    }
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[test]
+fn test_config_validation() {
+    let content = r#"
+        default_job = "clippy"
+        summery = true
+
+        [jobs.clippy]
+        command = ["cargo", "clippy"]
+        allow_warning = true
+
+        [jobs.test]
+        command = ["cargo", "test"]
+        need_stdout = "yes"
+
+        [keybindings]
+        ctrl-t = "job:test"
+    "#;
+    let path = Path::new("bacon.toml");
+    let mut file = ConfigFile::new(path, content);
+    file.check_keys("");
+    let config = file.deserialize::<Config>();
+    let problems = file.finish(config).unwrap_err().problems;
+    assert_eq!(problems.len(), 3);
+    assert_eq!(problems[0].key_path.as_deref(), Some("summery"));
+    assert_eq!(problems[0].line, Some(3));
+    assert_eq!(problems[0].suggestion.as_deref(), Some("summary"));
+    let allow_warning = problems
+        .iter()
+        .find(|problem| problem.key_path.as_deref() == Some("jobs.clippy.allow_warning"))
+        .unwrap();
+    assert_eq!(allow_warning.line, Some(7));
+    assert_eq!(allow_warning.suggestion.as_deref(), Some("allow_warnings"));
+    let need_stdout = &problems[2];
+    assert_eq!(
+        need_stdout.key_path.as_deref(),
+        Some("jobs.test.need_stdout")
+    );
+    assert_eq!(need_stdout.line, Some(11));
+
+    for default in [DEFAULT_PREFS, DEFAULT_PACKAGE_CONFIG] {
+        let mut file = ConfigFile::new(path, default);
+        file.check_keys("");
+        let config = file.deserialize::<Config>();
+        let (_, warnings) = file.finish(config).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    // unknown keys don't prevent the configuration from loading
+    let content = r#"
+        summery = true
+
+        [jobs.clippy]
+        command = ["cargo", "clippy"]
+        allow_warning = true
+    "#;
+    let mut file = ConfigFile::new(path, content);
+    file.check_keys("");
+    let config = file.deserialize::<Config>();
+    let (config, warnings) = file.finish(config).unwrap();
+    assert_eq!(config.jobs["clippy"].command, ["cargo", "clippy"]);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].key_path.as_deref(), Some("summery"));
+    assert_eq!(warnings[0].line, Some(2));
+    assert_eq!(warnings[0].suggestion.as_deref(), Some("summary"));
+    assert_eq!(
+        warnings[1].key_path.as_deref(),
+        Some("jobs.clippy.allow_warning")
+    );
+    assert_eq!(warnings[1].suggestion.as_deref(), Some("allow_warnings"));
+}
+
+#[test]
+fn test_job_alias_spelling() {
+    let content = r#"
+        [jobs.check]
+        command = ["cargo", "check"]
+        alias = ["c"]
+    "#;
+    let mut file = ConfigFile::new(Path::new("bacon.toml"), content);
+    file.check_keys("");
+    let config = file.deserialize::<Config>();
+    let (config, warnings) = file.finish(config).unwrap();
+    assert!(warnings.is_empty());
+    let aliases = config.jobs["check"].aliases.as_deref();
+    assert_eq!(aliases, Some(&["c".to_string()][..]));
+}
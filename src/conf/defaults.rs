@@ -1,3 +1,9 @@
 pub static DEFAULT_PREFS: &str = include_str!("../../defaults/default-prefs.toml");
 
 pub static DEFAULT_PACKAGE_CONFIG: &str = include_str!("../../defaults/default-bacon.toml");
+
+pub static DEFAULT_NODE_CONFIG: &str = include_str!("../../defaults/default-node.toml");
+
+pub static DEFAULT_GO_CONFIG: &str = include_str!("../../defaults/default-go.toml");
+
+pub static DEFAULT_PYTHON_CONFIG: &str = include_str!("../../defaults/default-python.toml");
@@ -31,9 +31,13 @@ impl Default for KeyBindings {
         bindings.set(key!(ctrl - q), Internal::Quit);
         bindings.set(key!(q), Internal::Quit);
         bindings.set(key!(F5), Internal::Refresh);
+        bindings.set(key!(F12), Internal::TogglePerfHud);
         bindings.set(key!(s), Internal::ToggleSummary);
         bindings.set(key!(w), Internal::ToggleWrap);
         bindings.set(key!(b), Internal::ToggleBacktrace("1"));
+        bindings.set(key!(o), Internal::ToggleCapturedOutput);
+        bindings.set(key!(shift - o), Internal::OpenInEditor);
+        bindings.set(key!(shift - i), Internal::ToggleStdinForwarding);
         bindings.set(key!(Home), Internal::Scroll(ScrollCommand::Top));
         bindings.set(key!(End), Internal::Scroll(ScrollCommand::Bottom));
         bindings.set(key!(Up), Internal::Scroll(ScrollCommand::Lines(-1)));
@@ -44,7 +48,11 @@ impl Default for KeyBindings {
             key!(Space),
             Internal::Scroll(ScrollCommand::MilliPages(800)),
         );
+        bindings.set(key!(e), Internal::Explain);
         bindings.set(key!(f), Internal::ScopeToFailures);
+        bindings.set(key!(shift - f), Internal::ToggleFailedTestsFirst);
+        bindings.set(key!(shift - t), Internal::PickTests);
+        bindings.set(key!(shift - p), Internal::PickPackage);
         bindings.set(key!(esc), Internal::Back);
         bindings.set(key!(ctrl - d), JobRef::Default);
         bindings.set(key!(i), JobRef::Initial);
@@ -55,6 +63,8 @@ impl Default for KeyBindings {
         bindings.set(key!(tab), Internal::NextMatch);
         bindings.set(key!(backtab), Internal::PreviousMatch);
         bindings.set(key!(shift - backtab), Internal::PreviousMatch);
+        bindings.set(key!(ctrl - right), Internal::NextRoot);
+        bindings.set(key!(ctrl - left), Internal::PreviousRoot);
 
         // keybindings for some common jobs
         bindings.set(key!(a), JobRef::from_job_name("check-all"));
@@ -2,8 +2,12 @@ mod action;
 mod auto_refresh;
 mod cargo_wrapped_config;
 mod config;
+mod config_include;
+mod config_origins;
+mod config_validation;
 mod defaults;
 mod keybindings;
+mod project_probe;
 
 mod settings;
 
@@ -12,8 +16,12 @@ pub use {
     auto_refresh::*,
     cargo_wrapped_config::*,
     config::*,
+    config_include::*,
+    config_origins::*,
+    config_validation::*,
     defaults::*,
     keybindings::*,
+    project_probe::*,
     settings::*,
 };
 
@@ -29,6 +37,16 @@ pub fn bacon_prefs_path() -> Option<std::path::PathBuf> {
         .map(|project_dir| project_dir.config_dir().join("prefs.toml"))
 }
 
+/// If the system has one, return the directory where bacon keeps its
+/// state between launches (`$XDG_STATE_HOME/bacon` when defined)
+pub fn bacon_state_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_STATE_HOME") {
+        return Some(Path::new(&dir).join("bacon"));
+    }
+    directories_next::ProjectDirs::from("org", "dystroy", "bacon")
+        .map(|project_dir| project_dir.data_local_dir().join("state"))
+}
+
 /// Return the path given by the env var, if it exists (doesn't check whether
 /// it's a correct configuration file)
 pub fn config_path_from_env(env_var_name: &str) -> Option<PathBuf> {
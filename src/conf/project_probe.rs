@@ -0,0 +1,354 @@
+use {
+    crate::*,
+    lazy_regex::regex_captures,
+    std::{
+        env,
+        fmt::Write as _,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// What `bacon --init` found in a cargo project, to write a configuration
+/// file with the relevant jobs instead of the generic default one
+#[derive(Debug, Default)]
+pub struct ProjectProbe {
+    /// the members of the workspace, when there are several ones
+    pub workspace_members: Vec<String>,
+    /// the file (or manifest section) configuring clippy, if any
+    pub clippy_config: Option<String>,
+    /// whether cargo-nextest is installed or configured
+    pub nextest: bool,
+    /// the wasm target the project is built for, if any
+    pub wasm_target: Option<String>,
+    /// the recipes of the justfile which take no parameter
+    pub just_recipes: Vec<String>,
+    /// whether the package has a binary to run
+    pub runnable: bool,
+}
+
+impl ProjectProbe {
+    pub fn new(context: &Context) -> Self {
+        let mut directories = vec![context.package_directory.clone()];
+        directories.extend(context.workspace_root.clone());
+        let manifests: Vec<toml::Table> = directories
+            .iter()
+            .filter_map(|dir| fs::read_to_string(dir.join("Cargo.toml")).ok())
+            .filter_map(|content| content.parse().ok())
+            .collect();
+        let workspace_members = context.workspace_members();
+        Self {
+            workspace_members: if workspace_members.len() > 1 {
+                workspace_members.to_vec()
+            } else {
+                Vec::new()
+            },
+            clippy_config: find_clippy_config(&directories, &manifests),
+            nextest: directories
+                .iter()
+                .any(|dir| dir.join(".config/nextest.toml").exists())
+                || is_in_path("cargo-nextest"),
+            wasm_target: find_wasm_target(&directories, &manifests),
+            just_recipes: directories
+                .iter()
+                .find_map(|dir| find_justfile(dir))
+                .and_then(|path| fs::read_to_string(path).ok())
+                .map(|content| parameterless_recipes(&content))
+                .unwrap_or_default(),
+            runnable: context.package_directory.join("src/main.rs").exists()
+                || context.package_directory.join("src/bin").is_dir(),
+        }
+    }
+    /// Build the content of the bacon.toml file, with comments explaining
+    /// the choices
+    pub fn config(&self) -> String {
+        let mut findings = Vec::new();
+        if !self.workspace_members.is_empty() {
+            findings.push(format!(
+                "a workspace with {} members",
+                self.workspace_members.len()
+            ));
+        }
+        if let Some(clippy_config) = &self.clippy_config {
+            findings.push(format!("a clippy configuration ({clippy_config})"));
+        }
+        if self.nextest {
+            findings.push("cargo-nextest".to_string());
+        }
+        if let Some(wasm_target) = &self.wasm_target {
+            findings.push(format!("the {wasm_target} target"));
+        }
+        if !self.just_recipes.is_empty() {
+            findings.push("a justfile".to_string());
+        }
+        let mut c = String::new();
+        c.push_str("# This is a configuration file for the bacon tool, written\n");
+        c.push_str("# by `bacon --init` for this project\n#\n");
+        c.push_str("# Complete help on configuration: https://dystroy.org/bacon/config/\n");
+        c.push_str("#\n# Default jobs, not redefined here, are listed with `bacon --list-jobs`\n");
+        if !findings.is_empty() {
+            let _ = writeln!(c, "#\n# Found in the project: {}", findings.join(", "));
+        }
+        let default_job = if self.clippy_config.is_some() {
+            "clippy-all"
+        } else {
+            "check"
+        };
+        let _ = writeln!(c, "\ndefault_job = \"{default_job}\"");
+        c.push_str("env.CARGO_TERM_COLOR = \"always\"\n");
+        let workspace: &[&str] = if self.workspace_members.is_empty() {
+            &[]
+        } else {
+            c.push_str("\n# The cargo jobs apply to all the members of the workspace:\n");
+            let _ = writeln!(c, "#   {}", self.workspace_members.join(", "));
+            c.push_str("# Hit shift-P to scope them to one of the members.\n");
+            &["--workspace"]
+        };
+        let cargo = |subcommand: &[&str], args: &[&str]| -> Vec<String> {
+            ["cargo"]
+                .iter()
+                .chain(subcommand)
+                .chain(workspace)
+                .chain(args)
+                .map(|s| s.to_string())
+                .collect()
+        };
+        write_job(&mut c, "check", None, &cargo(&["check"], &[]), &[]);
+        write_job(
+            &mut c,
+            "check-all",
+            None,
+            &cargo(&["check"], &["--all-targets"]),
+            &[],
+        );
+        let clippy_comment = self
+            .clippy_config
+            .as_ref()
+            .map(|config| format!("Lints are configured in {config}"));
+        write_job(
+            &mut c,
+            "clippy-all",
+            clippy_comment.as_deref(),
+            &cargo(&["clippy"], &["--all-targets"]),
+            &[],
+        );
+        if self.nextest {
+            write_job(
+                &mut c,
+                "nextest",
+                Some("Tests are run with nextest, also on the 't' key"),
+                &cargo(
+                    &["nextest", "run"],
+                    &["--hide-progress-bar", "--failure-output", "final"],
+                ),
+                &["need_stdout = true", "analyzer = \"nextest\""],
+            );
+        } else {
+            write_job(
+                &mut c,
+                "test",
+                None,
+                &cargo(&["test"], &[]),
+                &["need_stdout = true"],
+            );
+        }
+        if let Some(wasm_target) = &self.wasm_target {
+            write_job(
+                &mut c,
+                "check-wasm",
+                Some("Check the build for the wasm target"),
+                &cargo(&["check"], &["--target", wasm_target]),
+                &[],
+            );
+        }
+        if self.runnable {
+            write_job(
+                &mut c,
+                "run",
+                Some("Put the launch parameters of your program after a `--` separator"),
+                &["cargo".to_string(), "run".to_string()],
+                &[
+                    "need_stdout = true",
+                    "allow_warnings = true",
+                    "background = true",
+                ],
+            );
+        }
+        for recipe in &self.just_recipes {
+            write_job(
+                &mut c,
+                &format!("just-{recipe}"),
+                None,
+                &["just".to_string(), recipe.clone()],
+                &["need_stdout = true", "allow_warnings = true"],
+            );
+        }
+        c.push_str("\n[keybindings]\n");
+        c.push_str("# alt-m = \"job:my-job\"\n");
+        c.push_str("c = \"job:clippy-all\"\n");
+        if self.nextest {
+            c.push_str("t = \"job:nextest\"\n");
+        }
+        c
+    }
+}
+
+fn write_job(
+    c: &mut String,
+    name: &str,
+    comment: Option<&str>,
+    command: &[String],
+    properties: &[&str],
+) {
+    c.push('\n');
+    if let Some(comment) = comment {
+        let _ = writeln!(c, "# {comment}");
+    }
+    let command = toml::Value::Array(command.iter().cloned().map(toml::Value::String).collect());
+    let _ = writeln!(c, "[jobs.{name}]");
+    let _ = writeln!(c, "command = {command}");
+    if properties.is_empty() {
+        c.push_str("need_stdout = false\n");
+    }
+    for property in properties {
+        let _ = writeln!(c, "{property}");
+    }
+}
+
+fn is_in_path(executable: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&path).any(|dir| {
+        dir.join(executable).is_file() || dir.join(format!("{executable}.exe")).is_file()
+    })
+}
+
+fn find_clippy_config(
+    directories: &[PathBuf],
+    manifests: &[toml::Table],
+) -> Option<String> {
+    for dir in directories {
+        for name in ["clippy.toml", ".clippy.toml"] {
+            if dir.join(name).exists() {
+                return Some(name.to_string());
+            }
+        }
+    }
+    for manifest in manifests {
+        if manifest
+            .get("lints")
+            .and_then(|lints| lints.get("clippy"))
+            .is_some()
+        {
+            return Some("[lints.clippy]".to_string());
+        }
+        if manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("lints"))
+            .and_then(|lints| lints.get("clippy"))
+            .is_some()
+        {
+            return Some("[workspace.lints.clippy]".to_string());
+        }
+    }
+    None
+}
+
+/// Return the wasm target of the cargo config, or the default one when
+/// the project depends on wasm-bindgen
+fn find_wasm_target(
+    directories: &[PathBuf],
+    manifests: &[toml::Table],
+) -> Option<String> {
+    for dir in directories {
+        let config = fs::read_to_string(dir.join(".cargo/config.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok());
+        let target = config
+            .as_ref()
+            .and_then(|config| config.get("build"))
+            .and_then(|build| build.get("target"))
+            .and_then(|target| target.as_str());
+        if let Some(target) = target.filter(|target| target.starts_with("wasm32")) {
+            return Some(target.to_string());
+        }
+    }
+    let depends_on_wasm_bindgen = manifests.iter().any(|manifest| {
+        ["dependencies", "target"].iter().any(|key| {
+            manifest
+                .get(*key)
+                .is_some_and(|value| value.to_string().contains("wasm-bindgen"))
+        }) || manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("dependencies"))
+            .and_then(|dependencies| dependencies.get("wasm-bindgen"))
+            .is_some()
+    });
+    depends_on_wasm_bindgen.then(|| "wasm32-unknown-unknown".to_string())
+}
+
+fn find_justfile(dir: &Path) -> Option<PathBuf> {
+    ["justfile", "Justfile", ".justfile"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Return the names of the recipes which can be run without argument
+fn parameterless_recipes(justfile: &str) -> Vec<String> {
+    justfile
+        .lines()
+        .filter_map(|line| {
+            let (_, name, params) =
+                regex_captures!(r"^@?([a-zA-Z_][\w-]*)([^:=]*):(?:[^=]|$)", line)?;
+            (params.trim().is_empty() && !name.starts_with('_')).then(|| name.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_project_probe_config() {
+    let probe = ProjectProbe {
+        workspace_members: vec!["app".to_string(), "core".to_string()],
+        clippy_config: Some("clippy.toml".to_string()),
+        nextest: true,
+        wasm_target: Some("wasm32-unknown-unknown".to_string()),
+        just_recipes: parameterless_recipes(
+            "set shell := [\"bash\"]\n\
+            version := \"1\"\n\
+            build:\n    cargo build\n\
+            @lint: build\n    cargo clippy\n\
+            deploy env:\n    ./deploy.sh {{env}}\n\
+            _helper:\n    true\n",
+        ),
+        runnable: false,
+    };
+    assert_eq!(probe.just_recipes, ["build", "lint"]);
+    let content = probe.config();
+    let config: Config = toml::from_str(&content).unwrap();
+    assert_eq!(
+        config.jobs["check-wasm"].command,
+        [
+            "cargo",
+            "check",
+            "--workspace",
+            "--target",
+            "wasm32-unknown-unknown"
+        ]
+    );
+    assert!(config.jobs.contains_key("just-lint"));
+    assert!(!config.jobs.contains_key("run"));
+    let mut settings = Settings::default();
+    settings.apply_config(&Config::default_prefs());
+    settings.apply_config(&Config::default_package_config());
+    settings.apply_config(&config);
+    settings.check().unwrap();
+    assert_eq!(
+        settings.default_job,
+        ConcreteJobRef::from_job_name("clippy-all")
+    );
+}
@@ -6,10 +6,23 @@ use {
     },
     std::{
         collections::HashMap,
-        path::PathBuf,
+        env,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
 };
 
+/// The `BACON_*` env vars which don't define settings
+static NON_SETTING_ENV_VARS: &[&str] = &[
+    "BACON_CONFIG",
+    "BACON_LOG",
+    "BACON_NOTIFICATION_BODY",
+    "BACON_NOTIFICATION_TITLE",
+    "BACON_PREFS",
+];
+
 /// The settings used in the application.
 ///
 /// They're immutable during the execution of the missions.
@@ -22,19 +35,103 @@ pub struct Settings {
     /// Path of the files which were used to build the settings
     /// (note that not all settings come from files)
     pub config_files: Vec<PathBuf>,
+    /// The problems of the configuration files which didn't prevent
+    /// loading them, eg unknown keys
+    pub config_warnings: Vec<ConfigProblem>,
+    pub control: ControlSettings,
+    pub dashboard: DashboardSettings,
     pub default_job: ConcreteJobRef,
+    /// The default jobs of the `[defaults]` tables, by absolute directory
+    pub directory_default_jobs: Vec<(PathBuf, ConcreteJobRef)>,
+    pub editor: EditorSettings,
     pub exports: ExportsSettings,
     pub features: Option<String>, // comma separated list
+    pub github: GithubSettings,
+    pub headless_output: HeadlessOutput,
     pub help_line: bool,
+    pub hyperlink_scheme: HyperlinkScheme,
+    pub hyperlinks: bool,
+    pub instances: InstancesSettings,
     pub jobs: HashMap<String, Job>,
     pub keybindings: KeyBindings,
     pub no_default_features: bool,
+    pub nvim: NvimSettings,
+    /// whether to quit after the first execution
+    pub once: bool,
+    /// whether to keep the last report of each job between launches
+    pub persist_reports: bool,
+    /// whether to restore the job and its display state on launch
+    pub persist_ui_state: bool,
+    /// the profile applied over the configuration files, if any
+    pub profile: Option<String>,
+    /// names of the profiles defined in configuration files
+    pub profiles: Vec<String>,
     pub reverse: bool,
+    /// whether to record the executions in the run log
+    pub run_log: bool,
     pub summary: bool,
+    pub triggers: TriggersSettings,
+    pub webhook: WebhookSettings,
     pub wrap: bool,
     pub all_jobs: Job,
 }
 
+/// The directory the relative paths of a configuration file are relative
+/// to: the one of the file, or of its `.config` directory
+fn config_base_directory(path: &Path) -> &Path {
+    let Some(dir) = path.parent() else {
+        return path;
+    };
+    if dir.file_name().is_some_and(|name| name == ".config") {
+        dir.parent().unwrap_or(dir)
+    } else {
+        dir
+    }
+}
+
+/// Return the paths of the configuration files which may exist, in the
+/// order they're applied
+pub fn config_paths(context: &Context) -> Vec<PathBuf> {
+    let mut paths = vec![bacon_prefs_path(), config_path_from_env("BACON_PREFS")];
+    for dir in context.config_directories_above_workspace() {
+        paths.push(Some(dir.join("bacon.toml")));
+        paths.push(Some(dir.join(".config/bacon.toml")));
+    }
+    paths.push(context.workspace_cargo_path());
+    paths.push(context.workspace_config_path());
+    paths.push(context.workspace_dot_config_path());
+    for dir in context.config_directories_in_workspace() {
+        paths.push(Some(dir.join("bacon.toml")));
+        paths.push(Some(dir.join(".config/bacon.toml")));
+    }
+    paths.push(Some(context.package_cargo_path()));
+    paths.push(Some(context.package_config_path()));
+    paths.push(Some(context.package_dot_config_path()));
+    paths.push(config_path_from_env("BACON_CONFIG"));
+    paths.into_iter().flatten().collect()
+}
+
+/// Return the `BACON_*` env vars defining settings, sorted so that
+/// the result doesn't depend on the order of the environment
+pub fn setting_env_vars() -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = env::vars()
+        .filter(|(name, _)| name.starts_with(ENV_VAR_PREFIX))
+        .filter(|(name, _)| !NON_SETTING_ENV_VARS.contains(&name.as_str()))
+        .collect();
+    vars.sort();
+    vars
+}
+
+fn parse_env_bool(
+    name: &str,
+    value: &str,
+) -> Result<bool> {
+    match value.parse() {
+        Ok(b) => Ok(b),
+        Err(_) => bail!("Invalid value in env var {name}: expected true or false"),
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -42,17 +139,36 @@ impl Default for Settings {
             additional_job_args: Default::default(),
             additional_alias_args: Default::default(),
             summary: false,
+            triggers: Default::default(),
+            webhook: Default::default(),
+            control: Default::default(),
+            dashboard: Default::default(),
+            nvim: Default::default(),
+            once: false,
+            persist_reports: false,
+            persist_ui_state: true,
+            profile: None,
+            profiles: Default::default(),
+            github: Default::default(),
+            headless_output: Default::default(),
             wrap: true,
             reverse: false,
+            run_log: false,
             help_line: true,
+            hyperlink_scheme: Default::default(),
+            hyperlinks: false,
+            instances: Default::default(),
             no_default_features: Default::default(),
             all_features: Default::default(),
             features: Default::default(),
             keybindings: Default::default(),
             jobs: Default::default(),
             default_job: Default::default(),
+            directory_default_jobs: Default::default(),
+            editor: Default::default(),
             exports: Default::default(),
             config_files: Default::default(),
+            config_warnings: Default::default(),
             all_jobs: Default::default(),
         }
     }
@@ -66,13 +182,19 @@ impl Settings {
     /// * the default `bacon.toml` file (embedded in the binary)
     /// * the global `prefs.toml`, from user config directory
     /// * the file whose path is in environment variable `BACON_PREFS`
+    /// * the `bacon.toml` and `.config/bacon.toml` files of the parent directories
+    ///   of the workspace, up to the home directory or the git repository root
     /// * the workspace.metadata.bacon config in the workspace level `Cargo.toml` file
     /// * the workspace level `bacon.toml` file in workspace-root/bacon.toml
     /// * the workspace level `bacon.toml` file in workspace-root/.config/.bacon.toml
+    /// * the `bacon.toml` and `.config/bacon.toml` files of the directories between
+    ///   the workspace root and the package
     /// * the package.metadata.bacon config in the package level `Cargo.toml` file
     /// * the package level `bacon.toml` file in package-root/.bacon.toml
     /// * the package level `bacon.toml` file in package-root/.config/.bacon.toml
     /// * the file whose path is in environment variable `BACON_CONFIG`
+    /// * the `[profile.<name>]` sections of the profile given with `--profile`
+    /// * the `BACON_*` environment variables, eg `BACON_DEFAULT_JOB`
     /// * the content of the `--config-toml` argument
     /// * args given as arguments, coming from the cli call
     pub fn read(
@@ -81,33 +203,72 @@ impl Settings {
     ) -> Result<Self> {
         let mut settings = Settings::default();
 
-        let default_package_config = Config::default_package_config();
+        let default_package_config = Config::default_config_of(context.nature);
         settings.apply_config(&default_package_config);
 
-        let paths = vec![
-            bacon_prefs_path(),
-            config_path_from_env("BACON_PREFS"),
-            context.workspace_cargo_path(),
-            context.workspace_config_path(),
-            context.workspace_dot_config_path(),
-            Some(context.package_cargo_path()),
-            Some(context.package_config_path()),
-            Some(context.package_dot_config_path()),
-            config_path_from_env("BACON_CONFIG"),
-        ];
-        for path in paths.into_iter().flatten() {
-            if path.exists() {
+        let mut profile_configs = Vec::new();
+        // the problems of all the files are reported together
+        let mut problems = Vec::new();
+        for path in config_paths(context) {
+            if !path.exists() {
+                continue;
+            }
+            // the included files are read, and checked, while listed
+            let paths = match with_includes(&path) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    problems.extend(e.downcast::<ConfigErrors>()?.problems);
+                    continue;
+                }
+            };
+            for path in paths {
                 let configs = Config::from_path_detect(&path)?;
                 if !configs.is_empty() {
                     info!("config loaded from {:?}", path);
                     settings.register_config_file(path.clone());
+                    let dir = config_base_directory(&path);
                     for config in configs {
+                        for warning in &config.warnings {
+                            warn!("config warning: {warning}");
+                        }
+                        settings
+                            .config_warnings
+                            .extend(config.warnings.iter().cloned());
                         settings.apply_config(&config);
+                        settings.add_directory_default_jobs(dir, &config.defaults);
+                        if let Some(profile) = args.profile.as_ref() {
+                            profile_configs.extend(
+                                config
+                                    .profile
+                                    .get(profile)
+                                    .cloned()
+                                    .map(|c| (dir.to_path_buf(), c)),
+                            );
+                        }
                     }
                 }
             }
         }
 
+        if !problems.is_empty() {
+            return Err(ConfigErrors { problems }.into());
+        }
+
+        settings.profiles.sort();
+        if let Some(profile) = &args.profile {
+            if !settings.profiles.contains(profile) {
+                bail!("Unknown profile: {profile:?}");
+            }
+            for (dir, config) in &profile_configs {
+                settings.apply_config(config);
+                settings.add_directory_default_jobs(dir, &config.defaults);
+            }
+            settings.profile = Some(profile.clone());
+        }
+
+        settings.apply_directory_default_job(&context.package_directory);
+        settings.apply_env_vars(setting_env_vars())?;
+
         if let Some(toml) = &args.config_toml {
             let config = toml::from_str(toml)?;
             info!("config loaded from --config-toml: {:#?}", &config);
@@ -120,6 +281,33 @@ impl Settings {
         Ok(settings)
     }
 
+    /// Apply the settings defined by `BACON_*` env vars, eg `BACON_SUMMARY=true`
+    /// or `BACON_FEATURES=serde,cli`
+    pub fn apply_env_vars<I: IntoIterator<Item = (String, String)>>(
+        &mut self,
+        vars: I,
+    ) -> Result<()> {
+        for (name, value) in vars {
+            debug!("setting from env var {name}={value:?}");
+            // some settings can't be set in configuration files
+            match name.as_str() {
+                "BACON_FEATURES" => {
+                    self.features = Some(value);
+                }
+                "BACON_ALL_FEATURES" => {
+                    self.all_features = parse_env_bool(&name, &value)?;
+                }
+                "BACON_NO_DEFAULT_FEATURES" => {
+                    self.no_default_features = parse_env_bool(&name, &value)?;
+                }
+                _ => {
+                    self.apply_config(&Config::from_env_var(&name, &value)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn register_config_file(
         &mut self,
         path: PathBuf,
@@ -134,6 +322,11 @@ impl Settings {
         config: &Config,
     ) {
         self.all_jobs.apply(&config.all_jobs);
+        for name in config.profile.keys() {
+            if !self.profiles.contains(name) {
+                self.profiles.push(name.clone());
+            }
+        }
         if let Some(b) = config.summary {
             self.summary = b;
         }
@@ -146,6 +339,21 @@ impl Settings {
         if let Some(b) = config.help_line {
             self.help_line = b;
         }
+        if let Some(b) = config.persist_reports {
+            self.persist_reports = b;
+        }
+        if let Some(b) = config.persist_ui_state {
+            self.persist_ui_state = b;
+        }
+        if let Some(b) = config.run_log {
+            self.run_log = b;
+        }
+        if let Some(scheme) = &config.hyperlink_scheme {
+            self.hyperlink_scheme = scheme.clone();
+        }
+        if let Some(b) = config.hyperlinks {
+            self.hyperlinks = b;
+        }
         #[allow(deprecated)] // for compatibility
         if config.vim_keys == Some(true) {
             self.keybindings.add_vim_keys();
@@ -163,7 +371,17 @@ impl Settings {
         if let Some(default_job) = &config.default_job {
             self.default_job = default_job.clone();
         }
+        if let Some(editor) = &config.editor {
+            self.editor.apply_config(editor);
+        }
         self.exports.apply_config(config);
+        self.triggers.apply_config(&config.triggers);
+        self.control.apply_config(&config.control);
+        self.dashboard.apply_config(&config.dashboard);
+        self.webhook.apply_config(&config.webhook);
+        self.nvim.apply_config(&config.nvim);
+        self.github.apply_config(&config.github);
+        self.instances.apply_config(&config.instances);
     }
     pub fn apply_args(
         &mut self,
@@ -208,6 +426,48 @@ impl Settings {
         if args.all_features {
             self.all_features = true;
         }
+        if args.trigger_signal {
+            self.triggers.signal = true;
+        }
+        if args.trigger_stdin {
+            self.triggers.stdin = true;
+        }
+        if args.trigger_fifo.is_some() {
+            self.triggers.fifo.clone_from(&args.trigger_fifo);
+        }
+        if args.trigger_port.is_some() {
+            self.triggers.http_port = args.trigger_port;
+        }
+        if args.no_file_triggers {
+            self.triggers.files = false;
+        }
+        if args.record.is_some() || args.replay.is_some() {
+            // the state of previous sessions would make the replay differ
+            self.persist_reports = false;
+            self.persist_ui_state = false;
+        }
+        if args.replay.is_some() {
+            self.triggers.files = false;
+        }
+        if args.control_socket.is_some() {
+            self.control.socket.clone_from(&args.control_socket);
+        }
+        if args.dashboard_port.is_some() {
+            self.dashboard.port = args.dashboard_port;
+        }
+        if args.exclusive_runs {
+            self.instances.exclusive_runs = true;
+        }
+        if args.once {
+            self.once = true;
+        }
+        if let Some(output) = args.output {
+            self.headless_output = output;
+        }
+        if args.github_pr.is_some() {
+            self.github.review = true;
+            self.github.pull_request = args.github_pr;
+        }
         if args.features.is_some() {
             self.features.clone_from(&args.features);
         }
@@ -215,15 +475,171 @@ impl Settings {
             .clone_from(&args.additional_job_args);
     }
 
+    /// Return the profile coming after the current one, cycling through
+    /// the defined profiles and no profile
+    pub fn next_profile(&self) -> Option<String> {
+        let idx = match &self.profile {
+            Some(profile) => self
+                .profiles
+                .iter()
+                .position(|p| p == profile)
+                .map_or(0, |i| i + 1),
+            None => 0,
+        };
+        self.profiles.get(idx).cloned()
+    }
+
+    /// Register the `[defaults]` of a configuration, whose relative
+    /// directories are relative to the given one
+    pub fn add_directory_default_jobs(
+        &mut self,
+        base: &Path,
+        defaults: &HashMap<String, ConcreteJobRef>,
+    ) {
+        for (dir, job) in defaults {
+            let dir = base.join(dir);
+            self.directory_default_jobs.retain(|(d, _)| *d != dir);
+            self.directory_default_jobs.push((dir, job.clone()));
+        }
+    }
+
+    /// Make the default job the one of the deepest directory of the
+    /// `[defaults]` containing the given one, if any
+    pub fn apply_directory_default_job(
+        &mut self,
+        launch_dir: &Path,
+    ) {
+        let default_job = self
+            .directory_default_jobs
+            .iter()
+            .filter(|(dir, _)| launch_dir.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.components().count());
+        if let Some((dir, job)) = default_job {
+            info!("default job {job} from the defaults for {dir:?}");
+            self.default_job = job.clone();
+        }
+    }
+
+    /// Tell whether the referenced job can be run, either because it's
+    /// an alias or a job defined in settings
+    pub fn has_job(
+        &self,
+        job_ref: &ConcreteJobRef,
+    ) -> bool {
+        match &job_ref.name_or_alias {
+            NameOrAlias::Alias(_) => true,
+            NameOrAlias::Name(name) => self.job_name(name).is_some(),
+        }
+    }
+
+    /// Return the name of the job with this name or, failing that,
+    /// with this name among its aliases
+    pub fn job_name<'s>(
+        &'s self,
+        name: &'s str,
+    ) -> Option<&'s str> {
+        if self.jobs.contains_key(name) {
+            return Some(name);
+        }
+        let mut names: Vec<&String> = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.has_alias(name))
+            .map(|(job_name, _)| job_name)
+            .collect();
+        // in case of ambiguity, the choice must not depend on the map order
+        names.sort();
+        names.first().map(|job_name| job_name.as_str())
+    }
+
     pub fn check(&self) -> Result<()> {
+        if !self.triggers.files
+            && !self.triggers.has_manual_triggers()
+            && self.control.socket.is_none()
+        {
+            bail!("Invalid configuration : file triggers disabled but no other trigger defined");
+        }
         if self.jobs.is_empty() {
             bail!("Invalid configuration : no job found");
         }
         if let NameOrAlias::Name(name) = &self.default_job.name_or_alias {
-            if !self.jobs.contains_key(name) {
+            if self.job_name(name).is_none() {
                 bail!("Invalid configuration : default job ({name:?}) not found in jobs");
             }
         }
         Ok(())
     }
 }
+
+#[test]
+fn test_job_name() {
+    let job = |aliases: &[&str]| Job {
+        aliases: Some(aliases.iter().map(|alias| alias.to_string()).collect()),
+        ..Default::default()
+    };
+    let mut settings = Settings::default();
+    settings.jobs.insert("check".to_string(), job(&["c"]));
+    settings
+        .jobs
+        .insert("clippy".to_string(), job(&["cl", "c"]));
+    settings
+        .jobs
+        .insert("test".to_string(), job(&["check", "t"]));
+    settings.jobs.insert("run".to_string(), job(&[]));
+    assert_eq!(settings.job_name("run"), Some("run"));
+    assert_eq!(settings.job_name("cl"), Some("clippy"));
+    assert_eq!(settings.job_name("t"), Some("test"));
+    // when several jobs have the alias, the first one by name is chosen
+    assert_eq!(settings.job_name("c"), Some("check"));
+    // a job name takes precedence over an alias
+    assert_eq!(settings.job_name("check"), Some("check"));
+    assert_eq!(settings.job_name("r"), None);
+    assert!(settings.has_job(&ConcreteJobRef::from_job_name("cl")));
+    assert!(!settings.has_job(&ConcreteJobRef::from_job_name("r")));
+}
+
+#[test]
+fn test_apply_directory_default_job() {
+    let base = Path::new("/home/dev/ws");
+    let defaults: HashMap<String, ConcreteJobRef> = [
+        ("front", "check-wasm"),
+        ("front/app", "test"),
+        ("server", "clippy"),
+    ]
+    .into_iter()
+    .map(|(dir, job)| (dir.to_string(), ConcreteJobRef::from_job_name(job)))
+    .collect();
+    let default_job_in = |dir: &str| {
+        let mut settings = Settings {
+            default_job: ConcreteJobRef::from_job_name("check"),
+            ..Default::default()
+        };
+        settings.add_directory_default_jobs(base, &defaults);
+        settings.apply_directory_default_job(&base.join(dir));
+        settings.default_job
+    };
+    // the default job of the directory takes precedence over default_job
+    assert_eq!(
+        default_job_in("server"),
+        ConcreteJobRef::from_job_name("clippy")
+    );
+    assert_eq!(
+        default_job_in("server/src"),
+        ConcreteJobRef::from_job_name("clippy")
+    );
+    // the deepest directory wins
+    assert_eq!(
+        default_job_in("front/app/src"),
+        ConcreteJobRef::from_job_name("test")
+    );
+    assert_eq!(
+        default_job_in("front/lib"),
+        ConcreteJobRef::from_job_name("check-wasm")
+    );
+    // a directory only matches whole path components
+    assert_eq!(
+        default_job_in("server-old"),
+        ConcreteJobRef::from_job_name("check")
+    );
+    assert_eq!(default_job_in(""), ConcreteJobRef::from_job_name("check"));
+}
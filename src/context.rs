@@ -4,7 +4,6 @@ use {
         Result,
         bail,
     },
-    cargo_metadata::MetadataCommand,
     std::{
         env,
         fmt,
@@ -47,6 +46,16 @@ pub struct Context {
 struct CargoContext {
     pub cargo_toml_file: PathBuf,
     pub packages: Vec<cargo_metadata::Package>,
+    /// The directories of the local packages which aren't members of
+    /// the workspace (eg path dependencies in excluded directories)
+    pub foreign_package_directories: Vec<PathBuf>,
+    /// The name of the package of the package directory, if it's not
+    /// a virtual manifest
+    pub package_name: Option<String>,
+    /// The names of the members of the workspace, sorted
+    pub workspace_members: Vec<String>,
+    pub target_directory: PathBuf,
+    pub workspace_ignorer: WorkspaceIgnorer,
 }
 
 impl fmt::Debug for CargoContext {
@@ -56,6 +65,7 @@ impl fmt::Debug for CargoContext {
     ) -> fmt::Result {
         f.debug_struct("CargoContext")
             .field("cargo_toml_file", &self.cargo_toml_file)
+            .field("target_directory", &self.target_directory)
             .finish_non_exhaustive()
     }
 }
@@ -83,41 +93,66 @@ impl Context {
         // When the project is a Cargo project, some additional rules apply and
         // the Cargo.toml file(s) is/are used to determine the paths to watch.
         let mut cargo_toml_file = package_directory.join("Cargo.toml");
-        let nature = if cargo_toml_file.exists() && cargo_toml_file.is_file() {
-            ContextNature::Cargo
-        } else {
-            ContextNature::Other
-        };
+        let nature = ContextNature::detect(&package_directory);
 
         let mut workspace_root = None;
+        let mut package_name = None;
         let mut cargo_mission_location = None;
         if nature == ContextNature::Cargo {
-            let metadata = if args.offline {
-                MetadataCommand::new()
-                    .current_dir(&package_directory)
-                    .no_deps()
-                    .other_options(["--frozen".to_string(), "--offline".to_string()])
-                    .exec()
-            } else {
-                MetadataCommand::new()
-                    .current_dir(&package_directory)
-                    .exec()
-            };
-            let metadata = metadata?;
-            if let Some(resolved_root) = metadata.resolve.and_then(|resolve| resolve.root) {
-                cargo_toml_file = metadata
+            let metadata =
+                load_metadata(&package_directory, args.offline, !args.no_metadata_cache)?;
+            let root_package = match metadata.resolve.and_then(|resolve| resolve.root) {
+                Some(resolved_root) => Some(
+                    metadata
+                        .packages
+                        .iter()
+                        .find(|p| p.id == resolved_root)
+                        .expect("resolved manifest was not in package list"),
+                ),
+                // without resolution (eg offline), the package is found by its manifest
+                None => metadata
                     .packages
                     .iter()
-                    .find(|p| p.id == resolved_root)
-                    .map(|p| p.manifest_path.as_std_path().to_path_buf())
-                    .expect("resolved manifest was not in package list");
+                    .find(|p| p.manifest_path.as_std_path() == cargo_toml_file),
+            };
+            if let Some(root_package) = root_package {
+                cargo_toml_file = root_package.manifest_path.as_std_path().to_path_buf();
+                package_name = Some(root_package.name.to_string());
                 if metadata.workspace_root.as_std_path() != package_directory {
                     workspace_root = Some(metadata.workspace_root.as_std_path().to_path_buf());
                 }
             }
+            let package_directory_of = |package: &cargo_metadata::Package| {
+                package
+                    .manifest_path
+                    .parent()
+                    .map(|dir| dir.as_std_path().to_path_buf())
+            };
+            let local_packages = metadata.packages.iter().filter(|p| p.source.is_none());
+            let foreign_package_directories = local_packages
+                .clone()
+                .filter(|p| !metadata.workspace_members.contains(&p.id))
+                .filter_map(package_directory_of)
+                .collect();
+            let mut workspace_members: Vec<String> = metadata
+                .packages
+                .iter()
+                .filter(|p| metadata.workspace_members.contains(&p.id))
+                .map(|p| p.name.to_string())
+                .collect();
+            workspace_members.sort();
+            let workspace_ignorer = WorkspaceIgnorer::new(
+                &metadata.workspace_root.as_std_path().join("Cargo.toml"),
+                local_packages.filter_map(package_directory_of).collect(),
+            );
             cargo_mission_location = Some(CargoContext {
                 cargo_toml_file,
                 packages: metadata.packages,
+                foreign_package_directories,
+                package_name,
+                workspace_members,
+                target_directory: metadata.target_directory.into_std_path_buf(),
+                workspace_ignorer,
             });
         }
 
@@ -143,32 +178,38 @@ impl Context {
 
         let location_name = self.name.clone();
         let mut paths_to_watch: Vec<PathBuf> = Vec::new();
+        let mut watch_root_ignores: Vec<(PathBuf, Vec<String>)> = Vec::new();
         if let Some(path_to_watch) = &self.path_to_watch {
             paths_to_watch.push(path_to_watch.clone());
         } else {
             // Automatically watch all kinds of source files.
             // "watches", at this point, aren't full path, they still must be joined
             // with the right path which may depend on the
-            let mut watches = Vec::new();
-            if let Some(v) = &job.watch {
-                for watch in v.iter() {
-                    watches.push(watch.as_str());
-                }
-            }
+            let mut watches: Vec<WatchEntry> = job.watch.clone().unwrap_or_default();
             let add_default = job.default_watch.unwrap_or(true);
             if add_default {
                 for watch in DEFAULT_WATCHES {
-                    if !watches.contains(watch) {
-                        watches.push(watch);
+                    if !watches.iter().any(|w| w.path() == *watch) {
+                        watches.push((*watch).into());
                     }
                 }
             }
             debug!("watches: {watches:?}");
-            add_to_paths_to_watch(&watches, &self.package_directory, &mut paths_to_watch);
+            add_to_paths_to_watch(
+                &watches,
+                &self.package_directory,
+                &mut paths_to_watch,
+                &mut watch_root_ignores,
+            );
             if let Some(workspace_root) = &self.workspace_root {
                 // there's usually not much src at the workspace level but we must
                 // at least watch the Cargo.toml file
-                add_to_paths_to_watch(&watches, workspace_root, &mut paths_to_watch);
+                add_to_paths_to_watch(
+                    &watches,
+                    workspace_root,
+                    &mut paths_to_watch,
+                    &mut watch_root_ignores,
+                );
             }
             if let Some(location) = &self.cargo_mission_location {
                 for item in &location.packages {
@@ -182,6 +223,7 @@ impl Context {
                             &watches,
                             item_path.as_std_path(),
                             &mut paths_to_watch,
+                            &mut watch_root_ignores,
                         );
                         if item.manifest_path.exists() {
                             paths_to_watch.push(item.manifest_path.clone().into());
@@ -191,6 +233,18 @@ impl Context {
                     }
                 }
             }
+            if let Some(location) = &self.cargo_mission_location {
+                // excluded members and nested workspaces aren't part of the
+                // workspace, even when they're in a watched directory
+                let mut workspace_ignorer = location.workspace_ignorer.clone();
+                paths_to_watch.retain(|path| {
+                    let excluded = workspace_ignorer.excludes(path).unwrap_or(false);
+                    if excluded {
+                        debug!("not watching {path:?}, which isn't in the workspace");
+                    }
+                    !excluded
+                });
+            }
         }
 
         let execution_directory = self.package_directory.clone();
@@ -200,11 +254,64 @@ impl Context {
             execution_directory,
             package_directory: self.package_directory.clone(),
             workspace_directory: self.workspace_root.clone(),
+            target_directory: self
+                .cargo_mission_location
+                .as_ref()
+                .map(|location| location.target_directory.clone()),
             job,
             paths_to_watch,
+            watch_root_ignores,
+            workspace_ignorer: self
+                .cargo_mission_location
+                .as_ref()
+                .filter(|_| self.path_to_watch.is_none())
+                .map(|location| location.workspace_ignorer.clone()),
+            foreign_package_directories: self
+                .cargo_mission_location
+                .as_ref()
+                .map(|location| location.foreign_package_directories.clone())
+                .unwrap_or_default(),
+            workspace_members: self.workspace_members().to_vec(),
+            package: None,
+            package_name: self
+                .cargo_mission_location
+                .as_ref()
+                .and_then(|location| location.package_name.clone()),
+            hooked_command: None,
             settings,
         })
     }
+    /// Return the paths to the manifests of the local packages
+    /// (workspace members and path dependencies)
+    pub fn local_manifests(&self) -> Vec<&Path> {
+        let Some(location) = &self.cargo_mission_location else {
+            return Vec::new();
+        };
+        location
+            .packages
+            .iter()
+            .filter(|item| item.source.is_none())
+            .map(|item| item.manifest_path.as_std_path())
+            .collect()
+    }
+    /// The names of the members of the workspace, sorted
+    pub fn workspace_members(&self) -> &[String] {
+        self.cargo_mission_location
+            .as_ref()
+            .map_or(&[], |location| location.workspace_members.as_slice())
+    }
+    /// The target directory of cargo, if it's a cargo context
+    pub fn target_directory(&self) -> Option<&Path> {
+        self.cargo_mission_location
+            .as_ref()
+            .map(|location| location.target_directory.as_path())
+    }
+    /// Return the path to the manifest defining the workspace
+    /// (which may be the one of the package)
+    pub fn workspace_manifest(&self) -> PathBuf {
+        self.workspace_cargo_path()
+            .unwrap_or_else(|| self.package_cargo_path())
+    }
     pub fn workspace_cargo_path(&self) -> Option<PathBuf> {
         self.workspace_root.as_ref().map(|p| p.join("Cargo.toml"))
     }
@@ -218,6 +325,57 @@ impl Context {
             .as_ref()
             .map(|p| p.join(".config/bacon.toml"))
     }
+    /// Return the parent directories of the workspace (or of the package,
+    /// when not in a workspace) where configuration files are searched, from
+    /// the less specific to the most specific: up to the home directory or,
+    /// when not in it, up to the root of the git repository
+    pub fn config_directories_above_workspace(&self) -> Vec<PathBuf> {
+        let root = self
+            .workspace_root
+            .as_ref()
+            .unwrap_or(&self.package_directory);
+        let home = directories_next::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        let mut parents: Vec<PathBuf> = Vec::new();
+        if let Some(home) = home.filter(|home| root.starts_with(home)) {
+            if root != &home {
+                for dir in root.ancestors().skip(1) {
+                    parents.push(dir.to_path_buf());
+                    if dir == home {
+                        break;
+                    }
+                }
+            }
+        } else if !root.join(".git").exists() {
+            // outside the home, we don't go further than the repository
+            for dir in root.ancestors().skip(1) {
+                parents.push(dir.to_path_buf());
+                if dir.join(".git").exists() {
+                    break;
+                }
+            }
+            if !parents.last().is_some_and(|dir| dir.join(".git").exists()) {
+                parents.clear();
+            }
+        }
+        parents.reverse();
+        parents
+    }
+    /// Return the directories between the workspace root and the package
+    /// directory, from the less specific to the most specific
+    pub fn config_directories_in_workspace(&self) -> Vec<PathBuf> {
+        let Some(workspace_root) = &self.workspace_root else {
+            return Vec::new();
+        };
+        let mut dirs: Vec<PathBuf> = self
+            .package_directory
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(workspace_root) && *dir != workspace_root)
+            .map(Path::to_path_buf)
+            .collect();
+        dirs.reverse();
+        dirs
+    }
     pub fn package_cargo_path(&self) -> PathBuf {
         self.package_directory.join("Cargo.toml")
     }
@@ -230,13 +388,17 @@ impl Context {
 }
 
 fn add_to_paths_to_watch(
-    watches: &[&str],
+    watches: &[WatchEntry],
     base_path: &Path,
     paths_to_watch: &mut Vec<PathBuf>,
+    watch_root_ignores: &mut Vec<(PathBuf, Vec<String>)>,
 ) {
     for watch in watches {
-        let full_path = base_path.join(watch);
+        let full_path = base_path.join(watch.path());
         if !paths_to_watch.contains(&full_path) && full_path.exists() {
+            if !watch.ignore().is_empty() {
+                watch_root_ignores.push((full_path.clone(), watch.ignore().to_vec()));
+            }
             paths_to_watch.push(full_path);
         }
     }
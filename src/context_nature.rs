@@ -1,7 +1,62 @@
+use {
+    crate::*,
+    std::path::Path,
+};
+
 /// The kind of projec/context, as it impacts computing features,
 /// files to watch, etc.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContextNature {
     Cargo,
+    /// A JavaScript or TypeScript project, with a `package.json` file
+    Node,
+    /// A Go module, with a `go.mod` file
+    Go,
+    /// A Python project, with a `pyproject.toml` file
+    Python,
     Other,
 }
+
+impl ContextNature {
+    /// Detect the kind of project from the files of its directory
+    pub fn detect(package_directory: &Path) -> Self {
+        let has_file = |name: &str| package_directory.join(name).is_file();
+        if has_file("Cargo.toml") {
+            Self::Cargo
+        } else if has_file("package.json") {
+            Self::Node
+        } else if has_file("go.mod") {
+            Self::Go
+        } else if has_file("pyproject.toml") {
+            Self::Python
+        } else {
+            Self::Other
+        }
+    }
+    /// The configuration applied before the configuration files, with
+    /// the jobs making sense for this kind of project
+    pub fn default_config(self) -> &'static str {
+        match self {
+            Self::Node => DEFAULT_NODE_CONFIG,
+            Self::Go => DEFAULT_GO_CONFIG,
+            Self::Python => DEFAULT_PYTHON_CONFIG,
+            // the cargo jobs are also the defaults of the other projects,
+            // which must define their own jobs
+            Self::Cargo | Self::Other => DEFAULT_PACKAGE_CONFIG,
+        }
+    }
+}
+
+#[test]
+fn test_detect_context_nature() {
+    let directory = std::env::temp_dir().join(format!("bacon-test-nature-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    assert_eq!(ContextNature::detect(&directory), ContextNature::Other);
+    std::fs::write(directory.join("pyproject.toml"), "").unwrap();
+    assert_eq!(ContextNature::detect(&directory), ContextNature::Python);
+    std::fs::write(directory.join("package.json"), "{}").unwrap();
+    assert_eq!(ContextNature::detect(&directory), ContextNature::Node);
+    std::fs::write(directory.join("Cargo.toml"), "").unwrap();
+    assert_eq!(ContextNature::detect(&directory), ContextNature::Cargo);
+    let _ = std::fs::remove_dir_all(&directory);
+}
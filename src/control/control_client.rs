@@ -0,0 +1,138 @@
+use {
+    anyhow::{
+        Result,
+        anyhow,
+        bail,
+    },
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        io::{
+            BufRead,
+            BufReader,
+            Write,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+    termimad::crossbeam::channel::{
+        Receiver,
+        RecvTimeoutError,
+    },
+};
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+
+/// A client of the control API of a running bacon
+pub struct ControlClient {
+    socket: PathBuf,
+}
+
+impl ControlClient {
+    pub fn new(socket: &Path) -> Self {
+        Self {
+            socket: socket.to_path_buf(),
+        }
+    }
+    #[cfg(unix)]
+    fn connect(&self) -> Result<BufReader<Stream>> {
+        let stream = Stream::connect(&self.socket).map_err(|e| {
+            anyhow!(
+                "bacon isn't listening on {:?} ({e}): it must be running with this control socket",
+                self.socket
+            )
+        })?;
+        Ok(BufReader::new(stream))
+    }
+    #[cfg(not(unix))]
+    fn connect(&self) -> Result<BufReader<std::fs::File>> {
+        bail!("The control socket is only available on unix systems");
+    }
+    /// Call a method of the control API and return its result
+    pub fn call(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Value> {
+        let mut reader = self.connect()?;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        writeln!(reader.get_mut(), "{request}")?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut response: Value = serde_json::from_str(&line)?;
+        if let Some(error) = response.get("error") {
+            bail!("{}", error["message"].as_str().unwrap_or("unknown error"));
+        }
+        Ok(response["result"].take())
+    }
+    /// Subscribe to the state changes, and return the receiver of
+    /// the status notifications, which is disconnected when bacon
+    /// closes the connection
+    #[cfg(unix)]
+    pub fn subscribe(&self) -> Result<Receiver<Value>> {
+        let mut events = self.connect()?;
+        writeln!(
+            events.get_mut(),
+            r#"{{"jsonrpc": "2.0", "id": 1, "method": "subscribe"}}"#
+        )?;
+        let mut line = String::new();
+        events.read_line(&mut line)?;
+        let (sender, receiver) = termimad::crossbeam::channel::unbounded();
+        std::thread::spawn(move || {
+            for line in events.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let Ok(mut notification) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if sender.send(notification["params"].take()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(receiver)
+    }
+    #[cfg(not(unix))]
+    pub fn subscribe(&self) -> Result<Receiver<Value>> {
+        bail!("The control socket is only available on unix systems");
+    }
+    /// Run the job (the current one if none is given) and wait
+    /// for the end of its execution
+    pub fn run_and_wait(
+        &self,
+        job: Option<&str>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        // subscribing before asking for the run, not to miss its start
+        let statuses = self.subscribe()?;
+        self.call("run", json!({ "job": job }))?;
+        let mut started = false;
+        loop {
+            let status = statuses.recv_deadline(deadline).map_err(|e| match e {
+                RecvTimeoutError::Timeout => anyhow!("the execution didn't finish in {timeout:?}"),
+                RecvTimeoutError::Disconnected => anyhow!("bacon closed the connection"),
+            })?;
+            if status["state"] == "running" {
+                started = true;
+            } else if started {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use {
+    serde::Deserialize,
+    std::path::PathBuf,
+};
+
+/// The `[control]` part of a configuration: how other programs,
+/// eg editor plugins, may control bacon
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ControlConfig {
+    /// Path of the unix socket on which to listen to JSON-RPC requests
+    pub socket: Option<PathBuf>,
+}
@@ -0,0 +1,203 @@
+use {
+    crate::*,
+    serde_json::{
+        Value,
+        json,
+    },
+    termimad::crossbeam::channel::Sender,
+};
+
+/// A JSON-RPC error: its code and message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+/// What a client of the control API asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// List the jobs of the configuration, and the current one
+    ListJobs,
+    /// Run the current job again, or the given one
+    Run(Option<ConcreteJobRef>),
+    /// Return the state of the current job
+    GetStatus,
+    /// Return the report of the last execution
+    GetReport,
+    /// Return the counts and the first location of each item
+    GetDiagnostics,
+    /// Return the names of the tests which failed
+    GetFailedTests,
+    /// Receive a notification on each state change
+    Subscribe,
+}
+
+/// A command, with the channel on which to send the JSON-RPC result
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: Sender<Result<Value, RpcError>>,
+}
+
+impl RpcError {
+    pub fn new<S: Into<String>>(
+        code: i32,
+        message: S,
+    ) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+    pub fn parse_error() -> Self {
+        Self::new(-32700, "Parse error")
+    }
+    pub fn invalid_request() -> Self {
+        Self::new(-32600, "Invalid Request")
+    }
+    pub fn method_not_found() -> Self {
+        Self::new(-32601, "Method not found")
+    }
+    pub fn invalid_params<S: Into<String>>(message: S) -> Self {
+        Self::new(-32602, message)
+    }
+}
+
+impl ControlCommand {
+    /// Read the command from the method and params of a JSON-RPC request
+    pub fn from_rpc(
+        method: &str,
+        params: &Value,
+    ) -> Result<Self, RpcError> {
+        let job = match params.get("job") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(name)) => Some(ConcreteJobRef::from(name.as_str())),
+            Some(_) => {
+                return Err(RpcError::invalid_params("job must be a string"));
+            }
+        };
+        match method {
+            "list_jobs" => Ok(Self::ListJobs),
+            "run" => Ok(Self::Run(job)),
+            "set_job" => match job {
+                Some(job) => Ok(Self::Run(Some(job))),
+                None => Err(RpcError::invalid_params("missing job")),
+            },
+            "get_status" => Ok(Self::GetStatus),
+            "get_report" => Ok(Self::GetReport),
+            "get_diagnostics" => Ok(Self::GetDiagnostics),
+            "get_failed_tests" => Ok(Self::GetFailedTests),
+            "subscribe" => Ok(Self::Subscribe),
+            _ => Err(RpcError::method_not_found()),
+        }
+    }
+}
+
+impl ControlRequest {
+    /// Return the trigger to run, if the command asks for one and
+    /// its job exists
+    pub fn trigger(
+        &self,
+        state: &AppState<'_>,
+    ) -> Option<Trigger> {
+        match &self.command {
+            ControlCommand::Run(Some(job)) if !state.mission.settings.has_job(job) => None,
+            ControlCommand::Run(job) => Some(Trigger {
+                source: "control",
+                job: job.clone(),
+            }),
+            _ => None,
+        }
+    }
+    /// Send the result of the command, computed from the state
+    pub fn answer(
+        self,
+        state: &AppState<'_>,
+    ) {
+        let result = match &self.command {
+            ControlCommand::ListJobs => {
+                let mut jobs: Vec<&String> = state.mission.settings.jobs.keys().collect();
+                jobs.sort();
+                Ok(json!({
+                    "current": state.mission.concrete_job_ref.to_string(),
+                    "jobs": jobs,
+                }))
+            }
+            ControlCommand::Run(Some(job)) if !state.mission.settings.has_job(job) => {
+                Err(RpcError::invalid_params(format!("job not found: {job}")))
+            }
+            ControlCommand::Run(_) => Ok(json!({ "accepted": true })),
+            ControlCommand::GetStatus => Ok(JobStatus::of(state)
+                .map(|status| status.to_json())
+                .unwrap_or(Value::Null)),
+            ControlCommand::GetReport => match state.cmd_result.report() {
+                Some(report) => serde_json::to_value(report)
+                    .map_err(|e| RpcError::new(-32603, format!("Internal error: {e}"))),
+                None => Ok(Value::Null),
+            },
+            ControlCommand::GetDiagnostics => Ok(match state.cmd_result.report() {
+                Some(report) => json!({
+                    "status": JobStatus::of(state).map(|status| status.to_json()),
+                    "locations": report_locations(report),
+                }),
+                None => Value::Null,
+            }),
+            ControlCommand::GetFailedTests => Ok(match state.cmd_result.report() {
+                Some(report) => json!(report.failure_keys),
+                None => Value::Null,
+            }),
+            ControlCommand::Subscribe => Ok(json!({ "subscribed": true })),
+        };
+        // the client may be gone
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Build the JSON-RPC response to the request of the given id
+pub fn rpc_response(
+    id: Value,
+    result: Result<Value, RpcError>,
+) -> Value {
+    match result {
+        Ok(result) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result,
+        }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+    }
+}
+
+#[test]
+fn test_control_command_from_rpc() {
+    assert_eq!(
+        ControlCommand::from_rpc("run", &Value::Null),
+        Ok(ControlCommand::Run(None)),
+    );
+    assert_eq!(
+        ControlCommand::from_rpc("set_job", &json!({ "job": "clippy" })),
+        Ok(ControlCommand::Run(Some(ConcreteJobRef::from_job_name(
+            "clippy"
+        )))),
+    );
+    assert_eq!(
+        ControlCommand::from_rpc("set_job", &json!({}))
+            .unwrap_err()
+            .code,
+        -32602,
+    );
+    assert_eq!(
+        ControlCommand::from_rpc("run", &json!({ "job": 3 }))
+            .unwrap_err()
+            .code,
+        -32602,
+    );
+    assert_eq!(
+        ControlCommand::from_rpc("shutdown", &Value::Null),
+        Err(RpcError::method_not_found()),
+    );
+}
@@ -0,0 +1,258 @@
+use {
+    crate::*,
+    anyhow::Result,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        path::PathBuf,
+        sync::Mutex,
+    },
+    termimad::crossbeam::channel::{
+        Receiver,
+        Sender,
+        unbounded,
+    },
+};
+
+/// The channels to the clients which subscribed to events
+static SUBSCRIBERS: Mutex<Vec<Sender<String>>> = Mutex::new(Vec::new());
+
+/// The control API: a unix socket on which clients send JSON-RPC 2.0
+/// requests, one per line, and receive the responses and, when they
+/// subscribed, notifications of the state changes.
+///
+/// Each connection is handled in its own thread, and the requests
+/// needing the state are sent as messages to the app loop.
+pub struct ControlServer {
+    pub receiver: Receiver<ControlRequest>,
+    sender: Sender<ControlRequest>,
+    /// the path of the socket, removed when the server is dropped
+    socket: Option<PathBuf>,
+}
+
+impl ControlServer {
+    pub fn new(settings: &ControlSettings) -> Result<Self> {
+        let (sender, receiver) = unbounded();
+        if let Some(path) = &settings.socket {
+            listen(path, sender.clone())?;
+        }
+        Ok(Self {
+            receiver,
+            sender,
+            socket: settings.socket.clone(),
+        })
+    }
+    /// A sender of requests to the app, for other servers using
+    /// the control API, eg the dashboard
+    pub fn sender(&self) -> Sender<ControlRequest> {
+        self.sender.clone()
+    }
+    /// Register a channel receiving the notifications of the state changes
+    pub fn subscribe(subscriber: Sender<String>) {
+        if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+            subscribers.push(subscriber);
+        }
+    }
+    /// Notify the subscribed clients of the status of the job
+    pub fn broadcast_status(state: &AppState<'_>) {
+        let Ok(mut subscribers) = SUBSCRIBERS.lock() else {
+            return;
+        };
+        if subscribers.is_empty() {
+            return;
+        }
+        let Some(status) = JobStatus::of(state) else {
+            return;
+        };
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "status",
+            "params": status.to_json(),
+        })
+        .to_string();
+        subscribers.retain(|subscriber| subscriber.send(notification.clone()).is_ok());
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Some(path) = &self.socket {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn listen(
+    path: &std::path::Path,
+    sender: Sender<ControlRequest>,
+) -> Result<()> {
+    use std::{
+        fs,
+        os::unix::{
+            fs::PermissionsExt,
+            net::{
+                UnixListener,
+                UnixStream,
+            },
+        },
+    };
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            anyhow::bail!("Control socket {:?} is already used", path);
+        }
+        // a socket left by a bacon which didn't quit properly
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    // only the user may connect (the peer is checked too, as the socket
+    // could be connected to before this change of permissions)
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    info!("listening to control requests on {:?}", path);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) if !is_peer_same_user(&stream) => {
+                    warn!("refused a control connection from another user");
+                }
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, sender) {
+                            debug!("control connection ended: {e}");
+                        }
+                    });
+                }
+                Err(e) => {
+                    warn!("error on control connection: {}", e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Tell whether the process at the other end of the socket runs
+/// as the same user as bacon
+#[cfg(unix)]
+fn is_peer_same_user(stream: &std::os::unix::net::UnixStream) -> bool {
+    use std::os::unix::io::AsRawFd;
+    let fd = stream.as_raw_fd();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let peer_uid = {
+        let mut cred = libc::ucred {
+            pid: 0,
+            uid: 0,
+            gid: 0,
+        };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: cred and len are valid for writes, with the size of a ucred
+        let res = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                std::ptr::addr_of_mut!(cred).cast(),
+                &mut len,
+            )
+        };
+        (res == 0).then_some(cred.uid)
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let peer_uid = {
+        let mut uid: libc::uid_t = 0;
+        let mut gid: libc::gid_t = 0;
+        // SAFETY: uid and gid are valid for writes
+        let res = unsafe { libc::getpeereid(fd, &mut uid, &mut gid) };
+        (res == 0).then_some(uid)
+    };
+    // SAFETY: geteuid has no precondition
+    peer_uid == Some(unsafe { libc::geteuid() })
+}
+
+#[cfg(not(unix))]
+fn listen(
+    _path: &std::path::Path,
+    _sender: Sender<ControlRequest>,
+) -> Result<()> {
+    anyhow::bail!("The control socket is only available on unix systems");
+}
+
+/// Read the requests of the client, one per line, and write the
+/// responses (and notifications) from a dedicated thread
+#[cfg(unix)]
+fn handle_connection(
+    stream: std::os::unix::net::UnixStream,
+    sender: Sender<ControlRequest>,
+) -> Result<()> {
+    use std::io::{
+        BufRead,
+        BufReader,
+        Write,
+    };
+    let (out_sender, out_receiver) = unbounded::<String>();
+    let mut writer = stream.try_clone()?;
+    std::thread::spawn(move || {
+        for line in out_receiver {
+            if writeln!(writer, "{line}").is_err() {
+                break;
+            }
+        }
+    });
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&line, &sender, &out_sender) {
+            if out_sender.send(response.to_string()).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handle a JSON-RPC request and return the response, if the
+/// request isn't a notification
+#[cfg(unix)]
+fn handle_line(
+    line: &str,
+    sender: &Sender<ControlRequest>,
+    out_sender: &Sender<String>,
+) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(_) => {
+            return Some(rpc_response(Value::Null, Err(RpcError::parse_error())));
+        }
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Some(rpc_response(
+            id.unwrap_or_default(),
+            Err(RpcError::invalid_request()),
+        ));
+    };
+    let params = request.get("params").cloned().unwrap_or_default();
+    let result = match ControlCommand::from_rpc(method, &params) {
+        Ok(ControlCommand::Subscribe) => {
+            ControlServer::subscribe(out_sender.clone());
+            Ok(json!({ "subscribed": true }))
+        }
+        Ok(command) => {
+            let (reply, reply_receiver) = unbounded();
+            if sender.send(ControlRequest { command, reply }).is_err() {
+                return None;
+            }
+            reply_receiver
+                .recv()
+                .unwrap_or_else(|_| Err(RpcError::new(-32603, "Internal error")))
+        }
+        Err(e) => Err(e),
+    };
+    // requests without id are notifications, which get no response
+    id.map(|id| rpc_response(id, result))
+}
@@ -0,0 +1,21 @@
+use {
+    crate::*,
+    std::path::PathBuf,
+};
+
+/// Settings of the control API
+#[derive(Debug, Clone, Default)]
+pub struct ControlSettings {
+    pub socket: Option<PathBuf>,
+}
+
+impl ControlSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &ControlConfig,
+    ) {
+        if config.socket.is_some() {
+            self.socket.clone_from(&config.socket);
+        }
+    }
+}
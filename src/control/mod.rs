@@ -0,0 +1,13 @@
+mod control_client;
+mod control_config;
+mod control_request;
+mod control_server;
+mod control_settings;
+
+pub use {
+    control_client::*,
+    control_config::*,
+    control_request::*,
+    control_server::*,
+    control_settings::*,
+};
@@ -0,0 +1,17 @@
+use {
+    serde::Deserialize,
+    std::net::IpAddr,
+};
+
+/// The `[dashboard]` part of a configuration: an HTML page, served
+/// over HTTP, showing the report and the history of the executions
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DashboardConfig {
+    /// Port on which to serve the dashboard (none if not set)
+    pub port: Option<u16>,
+    /// Address on which to listen, localhost by default
+    pub address: Option<IpAddr>,
+    /// Secret required to access the dashboard, mandatory when the
+    /// address isn't a loopback one
+    pub token: Option<String>,
+}
@@ -0,0 +1,291 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    serde_json::Value,
+    std::{
+        collections::VecDeque,
+        io::Write,
+        net::{
+            TcpListener,
+            TcpStream,
+        },
+        sync::{
+            Arc,
+            Mutex,
+            atomic::{
+                AtomicUsize,
+                Ordering,
+            },
+        },
+        thread,
+        time::Duration,
+    },
+    termimad::crossbeam::channel::{
+        Sender,
+        unbounded,
+    },
+};
+
+/// The page of the dashboard, which polls the API below
+const DASHBOARD_HTML: &str = include_str!("../../resources/dashboard.html");
+
+/// How many finished executions are kept in the history
+const HISTORY_LENGTH: usize = 50;
+
+/// How long a request waits for the app to answer
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many connections are handled at the same time, the
+/// following ones being refused until one ends
+const MAX_CONNECTIONS: usize = 32;
+
+/// The cookie keeping the token, once it has been given in the URL
+const TOKEN_COOKIE: &str = "bacon-dashboard-token";
+
+/// The finished executions, most recent first
+type History = Arc<Mutex<VecDeque<Value>>>;
+
+/// What a request to the dashboard server asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DashboardRoute {
+    Page,
+    Status,
+    Report,
+    History,
+}
+
+/// Serve the dashboard, an HTML page showing the state of the job,
+/// its last report, and the history of the executions:
+/// - `GET /` returns the page
+/// - `GET /api/status`, `GET /api/report`, and `GET /api/history`
+///   return the JSON displayed by the page
+///
+/// The state is queried with the requests of the control API.
+///
+/// When a token is set, it must be given, either in the `token` query
+/// parameter of the page (it's then kept in a cookie) or as a bearer
+/// token. Without token, the dashboard only listens on a loopback
+/// address and only answers requests addressed to a loopback host.
+pub fn serve_dashboard(
+    settings: &DashboardSettings,
+    sender: Sender<ControlRequest>,
+) -> Result<()> {
+    let Some(port) = settings.port else {
+        return Ok(());
+    };
+    if !settings.address.is_loopback() && settings.token.is_none() {
+        bail!(
+            "the dashboard can't listen on {} without a token (see `token` in [dashboard])",
+            settings.address
+        );
+    }
+    let listener = TcpListener::bind((settings.address, port))?;
+    info!(
+        "serving the dashboard on http://{}:{}",
+        settings.address, port
+    );
+    let history = record_history();
+    let token = settings.token.clone();
+    let connections = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                        connections.fetch_sub(1, Ordering::SeqCst);
+                        debug!("too many dashboard connections");
+                        continue; // the stream is closed when dropped
+                    }
+                    let sender = sender.clone();
+                    let history = Arc::clone(&history);
+                    let token = token.clone();
+                    let connections = Arc::clone(&connections);
+                    thread::spawn(move || {
+                        if let Err(e) =
+                            handle_dashboard_request(&stream, token.as_deref(), &sender, &history)
+                        {
+                            debug!("error handling dashboard request: {e}");
+                        }
+                        connections.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(e) => {
+                    warn!("error on dashboard connection: {}", e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Subscribe to the status notifications and keep the ones of
+/// finished executions
+fn record_history() -> History {
+    let history = History::default();
+    let (subscriber, notifications) = unbounded::<String>();
+    ControlServer::subscribe(subscriber);
+    let recorded = Arc::clone(&history);
+    thread::spawn(move || {
+        for notification in notifications {
+            let Ok(mut notification) = serde_json::from_str::<Value>(&notification) else {
+                continue;
+            };
+            let status = notification["params"].take();
+            if status["state"] == "running" {
+                continue;
+            }
+            let Ok(mut history) = recorded.lock() else {
+                break;
+            };
+            history.push_front(status);
+            history.truncate(HISTORY_LENGTH);
+        }
+    });
+    history
+}
+
+fn handle_dashboard_request(
+    mut stream: &TcpStream,
+    token: Option<&str>,
+    sender: &Sender<ControlRequest>,
+    history: &History,
+) -> Result<()> {
+    let head = match HttpRequestHead::read(stream) {
+        Ok(head) => head,
+        Err(status) => {
+            write_empty_response(stream, status)?;
+            return Ok(());
+        }
+    };
+    let query_token = query_param(&head.request_line, "token");
+    let authorized = match token {
+        Some(token) => [query_token, head.cookie(TOKEN_COOKIE), head.bearer_token()]
+            .into_iter()
+            .flatten()
+            .any(|given| secrets_match(given, token)),
+        // without token, only loopback addresses are listened to, but a
+        // DNS rebinding attack could still make a browser send requests
+        None => head.has_loopback_host(),
+    };
+    if !authorized {
+        write_empty_response(stream, "403 Forbidden")?;
+        return Ok(());
+    }
+    let mut cookie_header = String::new();
+    let (status, content_type, body) = match parse_dashboard_request_line(&head.request_line) {
+        Ok(DashboardRoute::Page) => {
+            if let (Some(token), Some(_)) = (token, query_token) {
+                // so that the requests of the page are authorized too
+                cookie_header = format!(
+                    "Set-Cookie: {TOKEN_COOKIE}={token}; Path=/; HttpOnly; SameSite=Strict\r\n"
+                );
+            }
+            (
+                "200 OK",
+                "text/html; charset=utf-8",
+                DASHBOARD_HTML.to_string(),
+            )
+        }
+        Ok(DashboardRoute::History) => {
+            let history = history
+                .lock()
+                .map(|history| Value::from_iter(history.iter().cloned()))
+                .unwrap_or_default();
+            ("200 OK", "application/json", history.to_string())
+        }
+        Ok(route) => {
+            let command = if route == DashboardRoute::Status {
+                ControlCommand::GetStatus
+            } else {
+                ControlCommand::GetReport
+            };
+            match ask(sender, command) {
+                Some(value) => ("200 OK", "application/json", value.to_string()),
+                None => ("503 Service Unavailable", "text/plain", String::new()),
+            }
+        }
+        Err(status) => (status, "text/plain", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Cache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        cookie_header,
+        body,
+    )?;
+    Ok(())
+}
+
+/// Return the value of the parameter in the query of the request's path
+fn query_param<'r>(
+    request_line: &'r str,
+    name: &str,
+) -> Option<&'r str> {
+    let path = request_line.split_whitespace().nth(1)?;
+    let (_, query) = path.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| value)
+}
+
+/// Send the command to the app and wait for its answer
+fn ask(
+    sender: &Sender<ControlRequest>,
+    command: ControlCommand,
+) -> Option<Value> {
+    let (reply, reply_receiver) = unbounded();
+    sender.send(ControlRequest { command, reply }).ok()?;
+    reply_receiver.recv_timeout(ANSWER_TIMEOUT).ok()?.ok()
+}
+
+/// Return the route of the request, or the status of the error response
+fn parse_dashboard_request_line(request_line: &str) -> Result<DashboardRoute, &'static str> {
+    let mut tokens = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (tokens.next(), tokens.next()) else {
+        return Err("400 Bad Request");
+    };
+    if method != "GET" {
+        return Err("405 Method Not Allowed");
+    }
+    let path = path.split('?').next().unwrap_or_default();
+    match path {
+        "/" | "/index.html" => Ok(DashboardRoute::Page),
+        "/api/status" => Ok(DashboardRoute::Status),
+        "/api/report" => Ok(DashboardRoute::Report),
+        "/api/history" => Ok(DashboardRoute::History),
+        _ => Err("404 Not Found"),
+    }
+}
+
+#[test]
+fn test_parse_dashboard_request_line() {
+    assert_eq!(
+        parse_dashboard_request_line("GET / HTTP/1.1\r\n"),
+        Ok(DashboardRoute::Page),
+    );
+    assert_eq!(
+        parse_dashboard_request_line("GET /api/status?t=3 HTTP/1.1\r\n"),
+        Ok(DashboardRoute::Status),
+    );
+    assert_eq!(
+        parse_dashboard_request_line("POST /api/report HTTP/1.1\r\n"),
+        Err("405 Method Not Allowed"),
+    );
+    assert_eq!(
+        parse_dashboard_request_line("GET /api/other HTTP/1.1\r\n"),
+        Err("404 Not Found"),
+    );
+    assert_eq!(parse_dashboard_request_line(""), Err("400 Bad Request"));
+    assert_eq!(
+        query_param("GET /?t=3&token=s3cr3t HTTP/1.1\r\n", "token"),
+        Some("s3cr3t"),
+    );
+    assert_eq!(query_param("GET /api/status HTTP/1.1\r\n", "token"), None);
+}
@@ -0,0 +1,42 @@
+use {
+    crate::*,
+    std::net::{
+        IpAddr,
+        Ipv4Addr,
+    },
+};
+
+/// Settings of the web dashboard
+#[derive(Debug, Clone)]
+pub struct DashboardSettings {
+    pub port: Option<u16>,
+    pub address: IpAddr,
+    pub token: Option<String>,
+}
+
+impl Default for DashboardSettings {
+    fn default() -> Self {
+        Self {
+            port: None,
+            address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            token: None,
+        }
+    }
+}
+
+impl DashboardSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &DashboardConfig,
+    ) {
+        if config.port.is_some() {
+            self.port = config.port;
+        }
+        if let Some(address) = config.address {
+            self.address = address;
+        }
+        if config.token.is_some() {
+            self.token.clone_from(&config.token);
+        }
+    }
+}
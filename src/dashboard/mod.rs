@@ -0,0 +1,9 @@
+mod dashboard_config;
+mod dashboard_server;
+mod dashboard_settings;
+
+pub use {
+    dashboard_config::*,
+    dashboard_server::*,
+    dashboard_settings::*,
+};
@@ -0,0 +1,210 @@
+use {
+    crate::*,
+    anyhow::Result,
+    rustc_hash::FxHashSet,
+    std::{
+        env,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+        sync::{
+            Arc,
+            Mutex,
+        },
+    },
+};
+
+/// The set of files a build depends on, according to the `.d` dep-info
+/// files written by cargo in the target directory.
+///
+/// Those files may be outside the package (eg SQL or proto files
+/// included with `include_str!`). Files of dependencies fetched by
+/// cargo and of the toolchain aren't kept.
+pub struct DepInfo {
+    target_directory: PathBuf,
+    /// directory against which relative paths of dep-info files are resolved
+    root_directory: PathBuf,
+    /// directories whose files aren't interesting
+    excluded_directories: Vec<PathBuf>,
+    deps: Arc<Mutex<FxHashSet<PathBuf>>>,
+}
+
+impl DepInfo {
+    pub fn new(
+        target_directory: PathBuf,
+        root_directory: PathBuf,
+    ) -> Self {
+        let mut excluded_directories = vec![target_directory.clone()];
+        let home_dir = directories_next::BaseDirs::new().map(|d| d.home_dir().to_path_buf());
+        let tool_dirs = [("CARGO_HOME", ".cargo"), ("RUSTUP_HOME", ".rustup")];
+        for (env_var, default_dir) in tool_dirs {
+            if let Some(dir) = env::var_os(env_var) {
+                excluded_directories.push(PathBuf::from(dir));
+            } else if let Some(home_dir) = &home_dir {
+                excluded_directories.push(home_dir.join(default_dir));
+            }
+        }
+        Self {
+            target_directory,
+            root_directory,
+            excluded_directories,
+            deps: Default::default(),
+        }
+    }
+    /// Build an ignorer excluding the rust source files which aren't
+    /// known dependencies of the build (once some are known)
+    pub fn ignorer(&self) -> DepInfoIgnorer {
+        DepInfoIgnorer {
+            deps: Arc::clone(&self.deps),
+        }
+    }
+    /// Read the dep-info files of the target directory and return
+    /// the dependencies which weren't previously known
+    pub fn update(&self) -> Vec<PathBuf> {
+        let mut new_deps = Vec::new();
+        let mut deps = self.deps.lock().unwrap();
+        for dep_info_file in self.dep_info_files() {
+            let content = match fs::read_to_string(&dep_info_file) {
+                Ok(content) => content,
+                Err(e) => {
+                    debug!("failed to read dep-info file {:?}: {}", dep_info_file, e);
+                    continue;
+                }
+            };
+            for dep in parse_dep_info(&content) {
+                let dep = self.root_directory.join(dep); // no-op if dep is absolute
+                if self.excluded_directories.iter().any(|d| dep.starts_with(d)) {
+                    continue;
+                }
+                if !deps.contains(&dep) && dep.is_file() {
+                    deps.insert(dep.clone());
+                    new_deps.push(dep);
+                }
+            }
+        }
+        debug!("{} new dep-info dependencies", new_deps.len());
+        new_deps
+    }
+    /// Return the paths of the dep-info files of the `deps` directories,
+    /// which are either `target/<profile>/deps` or `target/<triple>/<profile>/deps`
+    fn dep_info_files(&self) -> Vec<PathBuf> {
+        let mut deps_dirs = Vec::new();
+        for dir in sub_dirs(&self.target_directory) {
+            let deps_dir = dir.join("deps");
+            if deps_dir.is_dir() {
+                deps_dirs.push(deps_dir);
+            } else {
+                for dir in sub_dirs(&dir) {
+                    let deps_dir = dir.join("deps");
+                    if deps_dir.is_dir() {
+                        deps_dirs.push(deps_dir);
+                    }
+                }
+            }
+        }
+        let mut files = Vec::new();
+        for deps_dir in deps_dirs {
+            let Ok(entries) = fs::read_dir(&deps_dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "d") {
+                    files.push(path);
+                }
+            }
+        }
+        files
+    }
+}
+
+fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+/// Parse the content of a dep-info file (in makefile syntax) and
+/// return the dependencies, as written (they may be relative)
+pub fn parse_dep_info(content: &str) -> Vec<PathBuf> {
+    let mut deps = Vec::new();
+    for line in content.lines() {
+        if line.starts_with('#') {
+            continue; // eg "# env-dep:"
+        }
+        // the separator is the first unescaped ": "
+        let Some(idx) = line.find(": ") else {
+            continue;
+        };
+        let mut dep = String::new();
+        let mut chars = line[idx + 2..].chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some(next) = chars.next() {
+                        if next != ' ' {
+                            dep.push('\\');
+                        }
+                        dep.push(next);
+                    }
+                }
+                ' ' => {
+                    if !dep.is_empty() {
+                        deps.push(PathBuf::from(std::mem::take(&mut dep)));
+                    }
+                }
+                _ => dep.push(c),
+            }
+        }
+        if !dep.is_empty() {
+            deps.push(PathBuf::from(dep));
+        }
+    }
+    deps
+}
+
+/// An ignorer excluding the rust files which aren't dependencies
+/// of the build, eg scratch files not declared as modules.
+///
+/// Doesn't exclude anything as long as no dependency is known.
+pub struct DepInfoIgnorer {
+    deps: Arc<Mutex<FxHashSet<PathBuf>>>,
+}
+
+impl Ignorer for DepInfoIgnorer {
+    fn excludes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        if !path.extension().is_some_and(|ext| ext == "rs") {
+            return Ok(false);
+        }
+        let deps = self.deps.lock().unwrap();
+        Ok(!deps.is_empty() && !deps.contains(path))
+    }
+}
+
+#[test]
+fn test_parse_dep_info() {
+    let content = r#"/ws/target/debug/deps/pack-1234.d: src/lib.rs src/a\ b.rs /ws/queries/get.sql
+
+src/lib.rs:
+src/a\ b.rs:
+/ws/queries/get.sql:
+
+# env-dep:CARGO_PKG_NAME=pack
+"#;
+    let deps = parse_dep_info(content);
+    let deps: FxHashSet<_> = deps.into_iter().collect();
+    assert_eq!(deps.len(), 3);
+    assert!(deps.contains(Path::new("src/lib.rs")));
+    assert!(deps.contains(Path::new("src/a b.rs")));
+    assert!(deps.contains(Path::new("/ws/queries/get.sql")));
+}
@@ -0,0 +1,377 @@
+use {
+    crate::*,
+    notify::Watcher as _,
+    std::{
+        env,
+        fs,
+        io::IsTerminal,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::Command,
+    },
+    termimad::MadSkin,
+};
+
+/// How bad is what `bacon --doctor` found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Ok,
+    Info,
+    Warning,
+    Error,
+}
+
+/// One of the checks of `bacon --doctor`, with what to do when it's
+/// not satisfying
+#[derive(Debug)]
+struct Finding {
+    severity: Severity,
+    subject: &'static str,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Finding {
+    fn new(
+        severity: Severity,
+        subject: &'static str,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            subject,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+    fn with_hint(
+        mut self,
+        hint: impl Into<String>,
+    ) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Check the environment of bacon (project, configuration, file watching,
+/// terminal, sound), print the findings with what to do about the problems,
+/// and return whether no error was found
+pub fn run_doctor(args: &Args) -> bool {
+    let mut findings = vec![check_cargo()];
+    check_project(args, &mut findings);
+    check_terminal(&mut findings);
+    let skin = MadSkin::default();
+    for finding in &findings {
+        let mark = match finding.severity {
+            Severity::Ok => "✓",
+            Severity::Info => "•",
+            Severity::Warning => "!",
+            Severity::Error => "✗",
+        };
+        skin.print_text(&format!(
+            "{mark} **{}**: {}",
+            finding.subject, finding.detail
+        ));
+        if let Some(hint) = &finding.hint {
+            skin.print_text(&format!("  → {hint}"));
+        }
+    }
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count();
+    let warnings = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Warning)
+        .count();
+    skin.print_text(&format!("\n{errors} error(s), {warnings} warning(s)"));
+    errors == 0
+}
+
+fn check_cargo() -> Finding {
+    match Command::new("cargo").arg("--version").output() {
+        Ok(output) if output.status.success() => Finding::new(
+            Severity::Ok,
+            "cargo",
+            String::from_utf8_lossy(&output.stdout).trim(),
+        ),
+        Ok(output) => Finding::new(
+            Severity::Error,
+            "cargo",
+            String::from_utf8_lossy(&output.stderr).trim(),
+        )
+        .with_hint("check your rust toolchain, eg with `rustup show`"),
+        Err(e) => Finding::new(Severity::Error, "cargo", format!("can't be run: {e}"))
+            .with_hint("install rust with rustup, or add cargo to your PATH"),
+    }
+}
+
+/// Check the project, its configuration, and the watching of its files
+fn check_project(
+    args: &Args,
+    findings: &mut Vec<Finding>,
+) {
+    let context = match Context::new(args) {
+        Ok(context) => context,
+        Err(e) => {
+            findings.push(
+                Finding::new(Severity::Error, "project", format!("{e:#}")).with_hint(
+                    "run bacon in the project directory, or give its path, and check that `cargo metadata` works there",
+                ),
+            );
+            return;
+        }
+    };
+    findings.push(Finding::new(
+        Severity::Ok,
+        "project",
+        format!(
+            "{} ({:?}) in {}",
+            context.name,
+            context.nature,
+            context.package_directory.to_string_lossy(),
+        ),
+    ));
+    let settings = match Settings::read(args, &context) {
+        Ok(settings) => settings,
+        Err(e) => {
+            findings.push(
+                Finding::new(Severity::Error, "configuration", format!("{e:#}")).with_hint(
+                    "fix the file, then run `bacon --show-config-origin` to see where each property comes from",
+                ),
+            );
+            return;
+        }
+    };
+    let files = settings
+        .config_files
+        .iter()
+        .map(|path| format!("`{}`", path.to_string_lossy()))
+        .collect::<Vec<_>>();
+    findings.push(Finding::new(
+        Severity::Ok,
+        "configuration",
+        if files.is_empty() {
+            format!("default, {} jobs", settings.jobs.len())
+        } else {
+            format!(
+                "{} jobs, read from {}",
+                settings.jobs.len(),
+                files.join(", ")
+            )
+        },
+    ));
+    check_sound(&settings, findings);
+    let job = JobStack::default().pick_job(&JobRef::Initial, &settings);
+    let Ok(Some((concrete_job_ref, job))) = job else {
+        return;
+    };
+    match context.mission(concrete_job_ref, &job, &settings) {
+        Ok(mission) => check_watcher(&mission.paths_to_watch, findings),
+        Err(e) => findings.push(Finding::new(Severity::Error, "mission", format!("{e:#}"))),
+    }
+}
+
+fn check_watcher(
+    paths: &[PathBuf],
+    findings: &mut Vec<Finding>,
+) {
+    let mut watcher = match notify::recommended_watcher(|_| {}) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            findings.push(
+                Finding::new(Severity::Error, "file watcher", format!("can't be created: {e}"))
+                    .with_hint("use `--trigger-stdin` or `--trigger-signal` to run the jobs without file watching"),
+            );
+            return;
+        }
+    };
+    let mut polled = 0;
+    for path in paths.iter().filter(|path| path.exists()) {
+        let mode = if path.is_dir() {
+            notify::RecursiveMode::Recursive
+        } else {
+            notify::RecursiveMode::NonRecursive
+        };
+        if let Err(e) = watcher.watch(path, mode) {
+            if !is_watch_limit_error(&e) {
+                findings.push(Finding::new(
+                    Severity::Error,
+                    "file watcher",
+                    format!("can't watch {}: {e}", path.to_string_lossy()),
+                ));
+                return;
+            }
+            polled += 1;
+        }
+    }
+    if polled > 0 {
+        findings.push(
+            Finding::new(
+                Severity::Warning,
+                "file watcher",
+                format!("watch limit reached, {polled} path(s) would be polled"),
+            )
+            .with_hint(watch_limit_hint()),
+        );
+    } else {
+        findings.push(Finding::new(
+            Severity::Ok,
+            "file watcher",
+            format!("{} path(s) watched", paths.len()),
+        ));
+    }
+    check_watch_limit(paths, findings);
+}
+
+fn watch_limit_hint() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "raise the limit, eg with `sudo sysctl fs.inotify.max_user_watches=524288`"
+    } else {
+        "raise the limit of file watches of your system"
+    }
+}
+
+/// Compare the number of directories to watch with the limit of inotify
+fn check_watch_limit(
+    paths: &[PathBuf],
+    findings: &mut Vec<Finding>,
+) {
+    let Some(limit) = fs::read_to_string("/proc/sys/fs/inotify/max_user_watches")
+        .ok()
+        .and_then(|content| content.trim().parse::<usize>().ok())
+    else {
+        return;
+    };
+    let needed: usize = paths.iter().map(|path| count_directories(path)).sum();
+    let detail = format!("{needed} directories to watch, limit of {limit} watches");
+    // the limit is shared with the other applications of the user
+    if needed * 2 > limit {
+        findings
+            .push(Finding::new(Severity::Warning, "inotify", detail).with_hint(watch_limit_hint()));
+    } else {
+        findings.push(Finding::new(Severity::Ok, "inotify", detail));
+    }
+}
+
+fn count_directories(path: &Path) -> usize {
+    if !path.is_dir() {
+        return 0;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return 1;
+    };
+    1 + entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| entry.file_name() != "target" && entry.file_name() != ".git")
+        .map(|entry| count_directories(&entry.path()))
+        .sum::<usize>()
+}
+
+fn check_sound(
+    settings: &Settings,
+    findings: &mut Vec<Finding>,
+) {
+    let enabled = settings.all_jobs.sound.is_enabled()
+        || settings.jobs.values().any(|job| job.sound.is_enabled());
+    match check_sound_output() {
+        Ok(()) => findings.push(Finding::new(
+            Severity::Ok,
+            "sound",
+            "audio output available",
+        )),
+        Err(e) if enabled => findings.push(
+            Finding::new(
+                Severity::Warning,
+                "sound",
+                format!("enabled in configuration but {e}"),
+            )
+            .with_hint("build bacon with `--features sound`, and check your audio output"),
+        ),
+        Err(e) => findings.push(Finding::new(Severity::Info, "sound", e.to_string())),
+    }
+}
+
+fn check_terminal(findings: &mut Vec<Finding>) {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let term_program = var("TERM_PROGRAM");
+    if std::io::stdout().is_terminal() {
+        findings.push(Finding::new(
+            Severity::Ok,
+            "terminal",
+            format!("TERM={term} TERM_PROGRAM={term_program}"),
+        ));
+    } else {
+        findings.push(
+            Finding::new(Severity::Warning, "terminal", "the output isn't a terminal")
+                .with_hint("use `--headless` when bacon's output is piped or logged"),
+        );
+    }
+    let colorterm = var("COLORTERM");
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        findings.push(Finding::new(Severity::Ok, "true colors", "supported"));
+    } else {
+        findings.push(
+            Finding::new(
+                Severity::Info,
+                "true colors",
+                "not advertised, colors may be approximated",
+            )
+            .with_hint("set `COLORTERM=truecolor` if your terminal supports it"),
+        );
+    }
+    let known_osc52 = [
+        "kitty",
+        "WezTerm",
+        "iTerm.app",
+        "ghostty",
+        "alacritty",
+        "foot",
+    ]
+    .iter()
+    .any(|name| term.contains(&name.to_lowercase()) || term_program == *name);
+    if env::var_os("TMUX").is_some() {
+        findings.push(
+            Finding::new(Severity::Info, "OSC52 clipboard", "running in tmux")
+                .with_hint("copying through tmux needs `set -g set-clipboard on`"),
+        );
+    } else if known_osc52 {
+        findings.push(Finding::new(Severity::Ok, "OSC52 clipboard", "supported"));
+    } else {
+        findings.push(Finding::new(
+            Severity::Info,
+            "OSC52 clipboard",
+            "support unknown for this terminal",
+        ));
+    }
+    if !cfg!(feature = "clipboard") {
+        findings.push(
+            Finding::new(
+                Severity::Info,
+                "clipboard",
+                "bacon is compiled without the clipboard feature",
+            )
+            .with_hint("build bacon with `--features clipboard` to copy the output"),
+        );
+    }
+    let kitty_protocol = env::var_os("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || ["WezTerm", "ghostty"].contains(&term_program.as_str());
+    if kitty_protocol {
+        findings.push(Finding::new(
+            Severity::Ok,
+            "kitty keyboard protocol",
+            "supported",
+        ));
+    } else {
+        findings.push(Finding::new(
+            Severity::Info,
+            "kitty keyboard protocol",
+            "not detected, some key combinations can't be distinguished",
+        ));
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+/// The `editor` part of a configuration: how to open a location,
+/// either as a command template, eg `"zed {file}:{line}:{column}"`,
+/// or as a table with per-OS commands and fallbacks
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EditorConfig {
+    Command(String),
+    Commands(EditorCommandsConfig),
+}
+
+/// The table form of the `editor` configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EditorCommandsConfig {
+    /// The command template, used when there's none for the OS
+    pub command: Option<String>,
+    pub linux: Option<String>,
+    pub macos: Option<String>,
+    pub windows: Option<String>,
+    /// Command templates tried in order when the previous
+    /// editors can't be launched
+    pub fallbacks: Option<Vec<String>>,
+}
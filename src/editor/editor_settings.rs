@@ -0,0 +1,244 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    lazy_regex::*,
+    std::{
+        env,
+        io,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::{
+            Command,
+            Stdio,
+        },
+        thread,
+    },
+};
+
+/// A position in a file, to open in the editor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorLocation {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A command to try to open a location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorTemplate {
+    pub template: String,
+    /// whether the editor runs in the terminal of bacon (which is then
+    /// suspended), as the ones of `$VISUAL` and `$EDITOR`, instead of
+    /// being launched in the background
+    pub in_terminal: bool,
+}
+
+/// Settings telling how to open a location in an editor
+#[derive(Debug, Clone, Default)]
+pub struct EditorSettings {
+    pub command: Option<String>,
+    pub linux: Option<String>,
+    pub macos: Option<String>,
+    pub windows: Option<String>,
+    pub fallbacks: Vec<String>,
+}
+
+impl EditorLocation {
+    /// Parse a location as given in reports, eg `src/main.rs:15:3`,
+    /// the relative path being made absolute
+    pub fn from_report_location(
+        location: &str,
+        mission: &Mission,
+    ) -> Self {
+        let (path, line, column) = split_location(location);
+        Self {
+            path: mission.make_absolute(PathBuf::from(path)),
+            line: line.parse().unwrap_or(1),
+            column: column.parse().unwrap_or(1),
+        }
+    }
+}
+
+impl EditorSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &EditorConfig,
+    ) {
+        match config {
+            EditorConfig::Command(command) => {
+                self.command = Some(command.clone());
+            }
+            EditorConfig::Commands(config) => {
+                if config.command.is_some() {
+                    self.command.clone_from(&config.command);
+                }
+                if config.linux.is_some() {
+                    self.linux.clone_from(&config.linux);
+                }
+                if config.macos.is_some() {
+                    self.macos.clone_from(&config.macos);
+                }
+                if config.windows.is_some() {
+                    self.windows.clone_from(&config.windows);
+                }
+                if let Some(fallbacks) = &config.fallbacks {
+                    self.fallbacks.clone_from(fallbacks);
+                }
+            }
+        }
+    }
+    fn os_command(&self) -> Option<&String> {
+        if cfg!(target_os = "linux") {
+            self.linux.as_ref()
+        } else if cfg!(target_os = "macos") {
+            self.macos.as_ref()
+        } else if cfg!(windows) {
+            self.windows.as_ref()
+        } else {
+            None
+        }
+    }
+    /// The command templates to try, in order: the one of the OS, the
+    /// general one, the fallbacks, then `$VISUAL` or `$EDITOR`
+    pub fn command_templates(&self) -> Vec<EditorTemplate> {
+        let mut templates: Vec<EditorTemplate> = self
+            .os_command()
+            .into_iter()
+            .chain(self.command.as_ref())
+            .chain(&self.fallbacks)
+            .map(|template| EditorTemplate {
+                template: template.clone(),
+                in_terminal: false,
+            })
+            .collect();
+        for var in ["VISUAL", "EDITOR"] {
+            if let Ok(editor) = env::var(var) {
+                if !editor.trim().is_empty() {
+                    templates.push(EditorTemplate {
+                        template: format!("{editor} {{file}}"),
+                        in_terminal: true,
+                    });
+                }
+            }
+        }
+        templates
+    }
+    /// Launch the first editor which can be launched on the location,
+    /// and return its command.
+    ///
+    /// The editors running in the terminal are waited for, with the TUI
+    /// suspended, while the other ones are left running.
+    pub fn open(
+        &self,
+        location: &EditorLocation,
+        dir: &Path,
+        w: &mut W,
+    ) -> Result<String> {
+        let templates = self.command_templates();
+        if templates.is_empty() {
+            bail!("No editor configured");
+        }
+        for template in &templates {
+            let tokens = expand_template(&template.template, location)?;
+            let Some((exe, args)) = tokens.split_first() else {
+                continue;
+            };
+            let mut command = Command::new(exe);
+            command.args(args).current_dir(dir);
+            let launched = if template.in_terminal {
+                run_outside_tui(w, || command.status())?.map(|status| {
+                    if !status.success() {
+                        warn!("editor {exe:?} exited with {status}");
+                    }
+                })
+            } else {
+                command
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .map(|mut child| {
+                        // the editor isn't waited for, but must not become a zombie
+                        thread::spawn(move || child.wait());
+                    })
+            };
+            match launched {
+                Ok(()) => {
+                    return Ok(tokens.join(" "));
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    debug!("editor {exe:?} not found");
+                }
+                Err(e) => {
+                    bail!("Failed to launch {exe:?}: {e}");
+                }
+            }
+        }
+        let templates: Vec<&str> = templates.iter().map(|t| t.template.as_str()).collect();
+        bail!(
+            "None of the editors could be launched: {}",
+            templates.join(", ")
+        );
+    }
+}
+
+/// Split the template in tokens, as a POSIX shell would, then replace
+/// the `{file}` (or `{path}`), `{line}`, and `{column}` placeholders in
+/// each of them
+fn expand_template(
+    template: &str,
+    location: &EditorLocation,
+) -> Result<Vec<String>> {
+    let path = location.path.to_string_lossy();
+    let line = location.line.to_string();
+    let column = location.column.to_string();
+    let Some(tokens) = shlex::split(template) else {
+        bail!("Invalid editor command: {template:?}");
+    };
+    let tokens = tokens
+        .iter()
+        .map(|token| {
+            regex_replace_all!(r#"\{([^\s}]+)\}"#, token, |whole: &str, key| {
+                match key {
+                    "file" | "path" => path.to_string(),
+                    "line" => line.clone(),
+                    "column" => column.clone(),
+                    _ => whole.to_string(),
+                }
+            })
+            .to_string()
+        })
+        .collect();
+    Ok(tokens)
+}
+
+#[test]
+fn test_expand_template() {
+    let location = EditorLocation {
+        path: PathBuf::from("/my project/src/main.rs"),
+        line: 15,
+        column: 3,
+    };
+    assert_eq!(
+        expand_template("zed {file}:{line}:{column}", &location).unwrap(),
+        vec!["zed", "/my project/src/main.rs:15:3"],
+    );
+    assert_eq!(
+        expand_template("code --goto {path}:{line}:{column}", &location).unwrap(),
+        vec!["code", "--goto", "/my project/src/main.rs:15:3"],
+    );
+    assert_eq!(
+        expand_template("idea --line {line} {file}", &location).unwrap(),
+        vec!["idea", "--line", "15", "/my project/src/main.rs"],
+    );
+    assert_eq!(
+        expand_template(r#""/opt/My Editor/bin/edit" -w {file}"#, &location).unwrap(),
+        vec!["/opt/My Editor/bin/edit", "-w", "/my project/src/main.rs"],
+    );
+    assert!(expand_template(r#"edit "{file}"#, &location).is_err());
+}
@@ -0,0 +1,137 @@
+use {
+    crate::*,
+    lazy_regex::*,
+    serde::{
+        Deserialize,
+        Deserializer,
+        de,
+    },
+    std::{
+        fmt::Write as _,
+        str::FromStr,
+    },
+};
+
+/// The kind of URL of the hyperlinks on locations, telling which
+/// application opens them when they're clicked
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HyperlinkScheme {
+    /// `file://` URL, opened by the application of the system
+    #[default]
+    File,
+    /// `vscode://file/` URL, with line and column
+    Vscode,
+    /// `idea://open?file=` URL of the JetBrains IDEs, with line
+    Idea,
+    /// A template where `{file}`, `{line}`, and `{column}` are replaced,
+    /// eg `"zed://file{file}:{line}:{column}"`
+    Template(String),
+}
+
+impl FromStr for HyperlinkScheme {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(Self::File),
+            "vscode" => Ok(Self::Vscode),
+            "idea" => Ok(Self::Idea),
+            _ if s.contains("{file}") => Ok(Self::Template(s.to_string())),
+            _ => Err(format!(
+                "invalid hyperlink scheme {s:?}: expected file, vscode, idea, or a template with {{file}}"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HyperlinkScheme {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl HyperlinkScheme {
+    /// The URL opening the location
+    pub fn url(
+        &self,
+        location: &EditorLocation,
+    ) -> String {
+        let path = encode_path(&location.path.to_string_lossy());
+        let EditorLocation { line, column, .. } = location;
+        match self {
+            Self::File => format!("file://{path}"),
+            Self::Vscode => format!("vscode://file{path}:{line}:{column}"),
+            Self::Idea => format!("idea://open?file={path}&line={line}"),
+            Self::Template(template) => {
+                regex_replace_all!(r#"\{([^\s}]+)\}"#, template, |whole: &str, key| {
+                    match key {
+                        "file" | "path" => path.clone(),
+                        "line" => line.to_string(),
+                        "column" => column.to_string(),
+                        _ => whole.to_string(),
+                    }
+                })
+                .to_string()
+            }
+        }
+    }
+}
+
+/// Percent-encode the characters of a path which can't be in a URL,
+/// the path being made to start with a `/` (eg for windows paths)
+fn encode_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut encoded = String::with_capacity(path.len() + 1);
+    if !path.starts_with('/') {
+        encoded.push('/');
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(b as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{b:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+/// The escape sequence opening an OSC 8 hyperlink
+pub fn osc8_start(url: &str) -> String {
+    format!("\u{1b}]8;;{url}\u{1b}\\")
+}
+
+/// The escape sequence closing an OSC 8 hyperlink
+pub const OSC8_END: &str = "\u{1b}]8;;\u{1b}\\";
+
+#[test]
+fn test_hyperlink_urls() {
+    let location = EditorLocation {
+        path: std::path::PathBuf::from("/my project/src/main.rs"),
+        line: 15,
+        column: 3,
+    };
+    assert_eq!(
+        HyperlinkScheme::File.url(&location),
+        "file:///my%20project/src/main.rs"
+    );
+    assert_eq!(
+        HyperlinkScheme::Vscode.url(&location),
+        "vscode://file/my%20project/src/main.rs:15:3"
+    );
+    assert_eq!(
+        HyperlinkScheme::Idea.url(&location),
+        "idea://open?file=/my%20project/src/main.rs&line=15"
+    );
+    let scheme: HyperlinkScheme = "zed://file{file}:{line}".parse().unwrap();
+    assert_eq!(
+        scheme.url(&location),
+        "zed://file/my%20project/src/main.rs:15"
+    );
+    assert!("zed".parse::<HyperlinkScheme>().is_err());
+}
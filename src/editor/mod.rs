@@ -0,0 +1,9 @@
+mod editor_config;
+mod editor_settings;
+mod hyperlink_scheme;
+
+pub use {
+    editor_config::*,
+    editor_settings::*,
+    hyperlink_scheme::*,
+};
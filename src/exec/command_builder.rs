@@ -1,26 +1,45 @@
-use std::{
-    collections::HashMap,
-    ffi::{
-        OsStr,
-        OsString,
+use {
+    crate::{
+        ResourceLimits,
+        ShellQuoting,
     },
-    path::{
-        Path,
-        PathBuf,
-    },
-    process::{
-        Command,
-        Stdio,
+    std::{
+        collections::HashMap,
+        ffi::{
+            OsStr,
+            OsString,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::{
+            Command,
+            Stdio,
+        },
     },
 };
 
+/// Placeholder, in job commands, for the paths of the changed files
+pub const CHANGED_FILES_PLACEHOLDER: &str = "{changed_files}";
+
+/// Placeholder, in job commands, for the path of a file listing
+/// the paths of the changed files, one per line
+pub const CHANGED_FILES_LIST_FILE_PLACEHOLDER: &str = "{changed_files_list_file}";
+
 #[derive(Debug, Clone)]
 pub struct CommandBuilder {
     exe: String,
     current_dir: Option<PathBuf>,
     args: Vec<OsString>,
     with_stdout: bool,
+    with_pty: bool,
+    with_stdin: bool,
+    limits: ResourceLimits,
     envs: HashMap<OsString, OsString>,
+    /// set when the command is run by a shell, to quote the
+    /// paths inserted in the script
+    shell_quoting: Option<ShellQuoting>,
 }
 
 impl CommandBuilder {
@@ -30,7 +49,11 @@ impl CommandBuilder {
             current_dir: None,
             args: Vec::new(),
             with_stdout: false,
+            with_pty: false,
+            with_stdin: false,
+            limits: ResourceLimits::default(),
             envs: Default::default(),
+            shell_quoting: None,
         }
     }
     pub fn build(&self) -> Command {
@@ -42,13 +65,18 @@ impl CommandBuilder {
         command.envs(&self.envs);
         command
             .envs(&self.envs)
-            .stdin(Stdio::null())
+            .stdin(if self.with_stdin {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
             .stderr(Stdio::piped())
             .stdout(if self.with_stdout {
                 Stdio::piped()
             } else {
                 Stdio::null()
             });
+        self.limits.apply_to(&mut command);
         command
     }
     pub fn with_stdout(
@@ -61,6 +89,53 @@ impl CommandBuilder {
     pub fn is_with_stdout(&self) -> bool {
         self.with_stdout
     }
+    /// The executable followed by the arguments
+    pub fn tokens(&self) -> Vec<String> {
+        std::iter::once(self.exe.clone())
+            .chain(
+                self.args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().to_string()),
+            )
+            .collect()
+    }
+    pub fn get_current_dir(&self) -> Option<&Path> {
+        self.current_dir.as_deref()
+    }
+    pub fn get_envs(&self) -> &HashMap<OsString, OsString> {
+        &self.envs
+    }
+    /// Set whether the command must run in a pseudo-terminal
+    /// (only possible on unix)
+    pub fn with_pty(
+        &mut self,
+        b: bool,
+    ) -> &mut Self {
+        self.with_pty = b;
+        self
+    }
+    pub fn is_with_pty(&self) -> bool {
+        self.with_pty
+    }
+    /// Set whether the command's stdin must be open to receive input
+    pub fn with_stdin(
+        &mut self,
+        b: bool,
+    ) -> &mut Self {
+        self.with_stdin = b;
+        self
+    }
+    pub fn is_with_stdin(&self) -> bool {
+        self.with_stdin
+    }
+    /// Set the priorities and limits to apply to the command's process
+    pub fn with_limits(
+        &mut self,
+        limits: ResourceLimits,
+    ) -> &mut Self {
+        self.limits = limits;
+        self
+    }
     pub fn current_dir<P: AsRef<Path>>(
         &mut self,
         dir: P,
@@ -88,6 +163,88 @@ impl CommandBuilder {
         }
         self
     }
+    /// Make the command run by the given shell, the executable and
+    /// arguments being joined with spaces into the script.
+    ///
+    /// The `verbatim` tokens, the ones written in the job's command, are
+    /// kept as is so that they can use the shell's syntax (pipes,
+    /// redirections, etc.) while the other ones, added by bacon (eg
+    /// features or arguments given after `--`), are quoted.
+    pub fn wrap_in_shell(
+        &mut self,
+        shell: &[String],
+        verbatim: &[String],
+    ) {
+        let Some((exe, shell_args)) = shell.split_first() else {
+            return;
+        };
+        let quoting = ShellQuoting::for_shell(exe);
+        let script: Vec<String> = self
+            .tokens()
+            .into_iter()
+            .map(|token| {
+                if verbatim.contains(&token) {
+                    token
+                } else {
+                    quoting.quote(&token)
+                }
+            })
+            .collect();
+        self.exe.clone_from(exe);
+        self.args = shell_args.iter().map(OsString::from).collect();
+        self.args.push(script.join(" ").into());
+        self.shell_quoting = Some(quoting);
+    }
+    pub fn has_arg_containing(
+        &self,
+        pattern: &str,
+    ) -> bool {
+        self.args
+            .iter()
+            .any(|arg| arg.to_str().is_some_and(|arg| arg.contains(pattern)))
+    }
+    /// Expand the changed files placeholders.
+    ///
+    /// An argument which is exactly `{changed_files}` is replaced with as many
+    /// arguments as there are changed files (possibly none), while the
+    /// placeholder is replaced with space separated paths when it's only part
+    /// of an argument.
+    ///
+    /// When the command is run by a shell, the paths are quoted.
+    pub fn expand_changed_files(
+        &mut self,
+        changed_files: &[PathBuf],
+        list_file: Option<&Path>,
+    ) {
+        let quote = |path: &Path| {
+            let path = path.to_string_lossy();
+            match self.shell_quoting {
+                Some(quoting) => quoting.quote(&path),
+                None => path.to_string(),
+            }
+        };
+        let mut args = Vec::with_capacity(self.args.len());
+        for arg in std::mem::take(&mut self.args) {
+            let Some(s) = arg.to_str() else {
+                args.push(arg);
+                continue;
+            };
+            if s == CHANGED_FILES_PLACEHOLDER {
+                args.extend(changed_files.iter().map(|p| p.as_os_str().to_os_string()));
+                continue;
+            }
+            let mut s = s.to_string();
+            if s.contains(CHANGED_FILES_PLACEHOLDER) {
+                let joined: Vec<String> = changed_files.iter().map(|p| quote(p)).collect();
+                s = s.replace(CHANGED_FILES_PLACEHOLDER, &joined.join(" "));
+            }
+            if let Some(list_file) = list_file {
+                s = s.replace(CHANGED_FILES_LIST_FILE_PLACEHOLDER, &quote(list_file));
+            }
+            args.push(s.into());
+        }
+        self.args = args;
+    }
     pub fn env<K, V>(
         &mut self,
         key: K,
@@ -117,3 +274,61 @@ impl CommandBuilder {
         self
     }
 }
+
+#[test]
+fn test_expand_changed_files() {
+    let mut builder = CommandBuilder::new("typos");
+    builder.args([
+        "--format=brief",
+        "{changed_files}",
+        "--files={changed_files}",
+        "@{changed_files_list_file}",
+    ]);
+    let changed_files = vec![PathBuf::from("/p/a.rs"), PathBuf::from("/p/b.md")];
+    builder.expand_changed_files(&changed_files, Some(Path::new("/tmp/list")));
+    assert_eq!(
+        builder.args,
+        vec![
+            OsString::from("--format=brief"),
+            OsString::from("/p/a.rs"),
+            OsString::from("/p/b.md"),
+            OsString::from("--files=/p/a.rs /p/b.md"),
+            OsString::from("@/tmp/list"),
+        ]
+    );
+    let mut builder = CommandBuilder::new("typos");
+    builder.args(["{changed_files}"]);
+    builder.expand_changed_files(&[], None);
+    assert!(builder.args.is_empty());
+}
+
+#[test]
+fn test_wrap_in_shell() {
+    let mut builder = CommandBuilder::new("cargo");
+    builder.args(["test", "2>&1", "|", "tee", "test.log"]);
+    let shell = ["bash".to_string(), "-c".to_string()];
+    builder.wrap_in_shell(&shell, &builder.tokens());
+    assert_eq!(
+        builder.tokens(),
+        vec!["bash", "-c", "cargo test 2>&1 | tee test.log"]
+    );
+    // the arguments added by bacon, and the changed files, are quoted
+    let mut builder = CommandBuilder::new("typos");
+    builder.args(["{changed_files}", "&&", "echo", "done"]);
+    let verbatim = builder.tokens();
+    builder.args(["--", "my file;ls"]);
+    builder.wrap_in_shell(&shell, &verbatim);
+    let changed_files = vec![
+        PathBuf::from("/p/with space.rs"),
+        PathBuf::from("/p/$(rm -rf ~).rs"),
+    ];
+    builder.expand_changed_files(&changed_files, None);
+    assert_eq!(
+        builder.tokens(),
+        vec![
+            "bash",
+            "-c",
+            "typos '/p/with space.rs' '/p/$(rm -rf ~).rs' && echo done -- 'my file;ls'",
+        ]
+    );
+}
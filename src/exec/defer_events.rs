@@ -0,0 +1,26 @@
+use {
+    crate::*,
+    serde::Deserialize,
+};
+
+/// What to do with the file changes (or other triggers) received
+/// while the job is running
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeferEvents {
+    /// Run the job again once the current execution is finished
+    Queue,
+    /// Immediately kill the current execution and restart the job
+    Restart,
+    /// Drop the events until the current execution is finished
+    Ignore,
+}
+
+impl From<OnChangeStrategy> for DeferEvents {
+    fn from(strategy: OnChangeStrategy) -> Self {
+        match strategy {
+            OnChangeStrategy::KillThenRestart => Self::Restart,
+            OnChangeStrategy::WaitThenRestart => Self::Queue,
+        }
+    }
+}
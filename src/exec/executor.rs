@@ -5,28 +5,57 @@ use {
             self,
             BufRead,
             BufReader,
+            Read,
+            Write,
+        },
+        path::{
+            Path,
+            PathBuf,
         },
         process::{
             Child,
             Command,
         },
+        sync::Mutex,
         thread,
-        time::Instant,
+        time::{
+            Duration,
+            Instant,
+        },
     },
+    tempfile::NamedTempFile,
     termimad::crossbeam::channel::{
         self,
         Receiver,
+        RecvTimeoutError,
         Sender,
     },
 };
 
+/// How often a task waiting for another bacon instance checks
+/// whether it's done
+const RUN_LOCK_POLL_PERIOD: Duration = Duration::from_millis(200);
+
+/// The threads of the killed tasks, which may still be waiting for
+/// their command to terminate
+static ENDING_TASKS: Mutex<Vec<thread::JoinHandle<()>>> = Mutex::new(Vec::new());
+
 /// an executor calling a cargo (or similar) command in a separate
 /// thread when asked to and sending the lines of output in a channel,
 /// and finishing by None.
 /// Channel sizes are designed to avoid useless computations.
 pub struct MissionExecutor {
-    command_builder: CommandBuilder,
+    /// the command, None while the command hook computes it
+    command_builder: Option<CommandBuilder>,
     kill_command: Option<Vec<String>>,
+    /// the signal to send before killing the command, with how long
+    /// to wait for it to terminate
+    kill_signal: Option<(KillSignal, Period)>,
+    /// the file to lock during the executions, if they must be
+    /// exclusive with the ones of other bacon instances
+    run_lock_path: Option<PathBuf>,
+    /// the label of the job, for the recorded sessions
+    job_label: String,
     line_sender: Sender<CommandExecInfo>,
     pub line_receiver: Receiver<CommandExecInfo>,
 }
@@ -37,8 +66,13 @@ pub struct TaskExecutor {
     /// the thread running the current task
     child_thread: thread::JoinHandle<()>,
     stop_sender: Sender<StopMessage>,
+    /// the sender of the input for the command, if its stdin is open
+    input_sender: Option<Sender<String>>,
     grace_period_start: Option<Instant>, // forgotten at end of grace period
     grace_period: Period,
+    /// whether the command was started without waiting for the end
+    /// of the grace period
+    speculative: bool,
 }
 
 /// A message sent to the child_thread on end
@@ -53,15 +87,39 @@ impl TaskExecutor {
     pub fn interrupt(self) {
         let _ = self.stop_sender.send(StopMessage::Kill);
     }
-    /// Kill the process, and wait until it finished
+    /// Kill the process, without waiting for it to terminate (which may
+    /// take the kill timeout): the next task waits for it before starting
+    /// its command
     pub fn die(self) {
         if let Err(e) = self.stop_sender.send(StopMessage::Kill) {
             debug!("failed to send 'die' signal: {e}");
         }
-        if self.child_thread.join().is_err() {
-            warn!("child_thread.join() failed"); // should not happen
+        if let Ok(mut ending_tasks) = ENDING_TASKS.lock() {
+            ending_tasks.push(self.child_thread);
         }
     }
+    /// Wait for the killed tasks to end, their commands being terminated
+    pub fn wait_for_ending_tasks() {
+        let ending_tasks = match ENDING_TASKS.lock() {
+            Ok(mut ending_tasks) => std::mem::take(&mut *ending_tasks),
+            Err(_) => return,
+        };
+        for child_thread in ending_tasks {
+            if child_thread.join().is_err() {
+                warn!("child_thread.join() failed"); // should not happen
+            }
+        }
+    }
+    /// Send a line to the stdin of the command, return false if
+    /// it isn't open
+    pub fn send_input(
+        &self,
+        line: &str,
+    ) -> bool {
+        self.input_sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(format!("{line}\n")).is_ok())
+    }
     pub fn is_in_grace_period(&mut self) -> bool {
         if let Some(grace_period_start) = self.grace_period_start {
             if grace_period_start.elapsed() < self.grace_period.duration {
@@ -71,52 +129,153 @@ impl TaskExecutor {
         }
         false
     }
+    /// Whether the changes received during the grace period should
+    /// restart the task instead of being ignored
+    pub fn is_speculative(&self) -> bool {
+        self.speculative
+    }
 }
 
 impl MissionExecutor {
     /// Prepare the executor (no task/process/thread is started at this point)
     pub fn new(mission: &Mission) -> anyhow::Result<Self> {
-        let command_builder = mission.get_command()?;
+        let command_builder = if mission.waits_for_command_hook() {
+            None
+        } else {
+            Some(mission.get_command()?)
+        };
         let kill_command = mission.kill_command();
+        let kill_signal = mission
+            .job
+            .kill_signal
+            .map(|signal| (signal, mission.job.kill_timeout()));
+        let run_lock_path = mission.run_lock_path();
+        let job_label = mission.concrete_job_ref.badge_label();
         let (line_sender, line_receiver) = channel::unbounded();
         Ok(Self {
             command_builder,
             kill_command,
+            kill_signal,
+            run_lock_path,
+            job_label,
             line_sender,
             line_receiver,
         })
     }
 
+    /// Build the command again, after the end of the command hook
+    pub fn update_command(
+        &mut self,
+        mission: &Mission,
+    ) -> anyhow::Result<()> {
+        self.command_builder = Some(mission.get_command()?);
+        Ok(())
+    }
+
     /// Start the job's command, once, with the given settings
+    ///
+    /// While the command hook computes the command, the task just waits
+    /// to be killed, the command being started by a new task afterwards.
     pub fn start(
         &mut self,
         task: Task,
     ) -> anyhow::Result<TaskExecutor> {
         info!("start task {task:?}");
+        record_session_event(|| {
+            Some(SessionEvent::Start {
+                job: self.job_label.clone(),
+            })
+        });
+        if let Some(execution) = next_replayed_execution() {
+            return Ok(self.replay(execution));
+        }
+        let Some(command_builder) = &self.command_builder else {
+            return Ok(self.wait_for_command());
+        };
         let grace_period = task.grace_period;
+        let speculative = task.speculative;
         let grace_period_start = if grace_period.is_zero() {
             None
         } else {
             Some(Instant::now())
         };
-        let mut command_builder = self.command_builder.clone();
+        let mut command_builder = command_builder.clone();
         command_builder.env("RUST_BACKTRACE", task.backtrace.unwrap_or("0"));
+        let list_file = if command_builder.has_arg_containing(CHANGED_FILES_LIST_FILE_PLACEHOLDER) {
+            Some(write_changed_files_list(&task.changed_files)?)
+        } else {
+            None
+        };
+        command_builder.expand_changed_files(
+            &task.changed_files,
+            list_file.as_ref().map(|file| file.path()),
+        );
         let kill_command = self.kill_command.clone();
+        let kill_signal = self.kill_signal;
+        let run_lock_path = self.run_lock_path.clone();
         let with_stdout = command_builder.is_with_stdout();
+        #[cfg(unix)]
+        let with_pty = command_builder.is_with_pty();
         let line_sender = self.line_sender.clone();
         let (stop_sender, stop_receiver) = channel::bounded(1);
+        let (input_sender, input_receiver) = if command_builder.is_with_stdin() {
+            let (sender, receiver) = channel::unbounded();
+            (Some(sender), Some(receiver))
+        } else {
+            (None, None)
+        };
+        #[cfg(unix)]
+        let with_stdin = input_sender.is_some();
         let err_stop_sender = stop_sender.clone();
 
         // Global task executor thread
         let child_thread = thread::spawn(move || {
+            // the commands of the previous tasks must not overlap this one
+            TaskExecutor::wait_for_ending_tasks();
             // before starting the command, we wait some time, so that a bunch
             // of quasi-simultaneous file events can be finished before the command
             // starts (during this time, no other command is started by bacon in app.rs)
-            if !grace_period.is_zero() {
+            if !grace_period.is_zero() && !speculative {
                 thread::sleep(grace_period.duration);
             }
+            // the lock is held until the end of the command
+            let _run_lock = match &run_lock_path {
+                Some(path) => match wait_run_lock(path, &stop_receiver) {
+                    Ok(Some(run_lock)) => Some(run_lock),
+                    Ok(None) => {
+                        debug!("interrupted while waiting for the run lock");
+                        return;
+                    }
+                    Err(e) => {
+                        warn!("failed to lock {path:?}: {e}");
+                        None
+                    }
+                },
+                None => None,
+            };
 
             let mut cmd = command_builder.build();
+            #[cfg(not(unix))]
+            ProcessGroup::prepare(&mut cmd);
+            #[cfg(unix)]
+            let pty = if with_pty {
+                match Pty::open().and_then(|pty| pty.attach(&mut cmd, with_stdin).map(|()| pty)) {
+                    Ok(pty) => Some(pty),
+                    Err(e) => {
+                        let _ = line_sender.send(CommandExecInfo::Error(
+                            anyhow::anyhow!(e).context("failed to open a pty"),
+                        ));
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+            // a command in a pty already leads its own session and group
+            #[cfg(unix)]
+            if pty.is_none() {
+                ProcessGroup::prepare(&mut cmd);
+            }
             let mut child = match cmd.spawn() {
                 Ok(child) => child,
                 Err(e) => {
@@ -126,72 +285,42 @@ impl MissionExecutor {
                     return;
                 }
             };
-
-            // thread piping the stdout lines
-            if with_stdout {
-                let sender = line_sender.clone();
-                let Some(stdout) = child.stdout.take() else {
-                    warn!("process has no stdout"); // unlikely
-                    return;
+            // the command holds copies of the pty's slave side, which
+            // must be closed for the end of the output to be detected
+            drop(cmd);
+            let group = ProcessGroup::new(&child);
+            if let Some(input_receiver) = input_receiver {
+                #[cfg(unix)]
+                let writer = match &pty {
+                    Some(pty) => pty
+                        .writer()
+                        .map(|writer| Box::new(writer) as Box<dyn Write + Send>),
+                    None => stdin_writer(&mut child),
                 };
-                let mut buf_reader = BufReader::new(stdout);
-                thread::spawn(move || {
-                    let mut line = String::new();
-                    loop {
-                        match buf_reader.read_line(&mut line) {
-                            Err(e) => {
-                                warn!("error : {e}");
-                            }
-                            Ok(0) => {
-                                // there won't be anything more, quitting
-                                break;
-                            }
-                            Ok(_) => {
-                                let response = CommandExecInfo::Line(RawCommandOutputLine {
-                                    content: line.clone(),
-                                    origin: CommandStream::StdOut,
-                                });
-                                if sender.send(response).is_err() {
-                                    break; // channel closed
-                                }
-                            }
-                        }
-                        line.clear();
-                    }
-                });
+                #[cfg(not(unix))]
+                let writer = stdin_writer(&mut child);
+                match writer {
+                    Ok(writer) => write_inputs(writer, input_receiver),
+                    Err(e) => warn!("no writable stdin: {e}"),
+                }
             }
 
-            // starting a thread to handle stderr lines until program
-            // ends (then ask the child_thread to send status)
-            let err_line_sender = line_sender.clone();
-            let stderr = child.stderr.take().expect("child missing stderr");
-            let mut buf_reader = BufReader::new(stderr);
-            thread::spawn(move || {
-                let mut line = String::new();
-                loop {
-                    match buf_reader.read_line(&mut line) {
-                        Err(e) => {
-                            warn!("error : {e}");
-                        }
-                        Ok(0) => {
-                            if let Err(e) = err_stop_sender.send(StopMessage::SendStatus) {
-                                warn!("sending stop message failed: {e}");
-                            }
-                            break;
-                        }
-                        Ok(_) => {
-                            let response = CommandExecInfo::Line(RawCommandOutputLine {
-                                content: line.clone(),
-                                origin: CommandStream::StdErr,
-                            });
-                            if err_line_sender.send(response).is_err() {
-                                break; // channel closed
-                            }
-                        }
-                    }
-                    line.clear();
-                }
-            });
+            #[cfg(unix)]
+            if let Some(pty) = pty {
+                // stdout and stderr are merged in the pty, we read them
+                // as stderr, where compilers write their diagnostics
+                let reader = pty.into_reader();
+                read_lines(
+                    reader,
+                    CommandStream::StdErr,
+                    line_sender.clone(),
+                    Some(err_stop_sender),
+                );
+            } else {
+                read_pipes(&mut child, with_stdout, &line_sender, err_stop_sender);
+            }
+            #[cfg(not(unix))]
+            read_pipes(&mut child, with_stdout, &line_sender, err_stop_sender);
 
             // now waiting for the stop event
             match stop_receiver.recv() {
@@ -204,33 +333,197 @@ impl MissionExecutor {
                     }
                     StopMessage::Kill => {
                         debug!("explicit interrupt received");
-                        kill(kill_command.as_deref(), &mut child);
+                        kill(kill_command.as_deref(), kill_signal, &mut child, &group);
                     }
                 },
                 Err(e) => {
                     debug!("recv error: {e}"); // probably just the executor dropped
-                    kill(kill_command.as_deref(), &mut child);
+                    kill(kill_command.as_deref(), kill_signal, &mut child, &group);
                 }
             }
             if let Err(e) = child.wait() {
                 warn!("waiting for child failed: {e}");
             }
+            // the list of changed files is removed with the end of the task
+            drop(list_file);
         });
         Ok(TaskExecutor {
             child_thread,
             stop_sender,
+            input_sender,
             grace_period_start,
             grace_period,
+            speculative,
         })
     }
+
+    /// Send the outputs of a recorded execution, with their recorded
+    /// delays, instead of running the command
+    /// A task doing nothing until killed, started while the command
+    /// isn't known
+    fn wait_for_command(&self) -> TaskExecutor {
+        let (stop_sender, stop_receiver) = channel::bounded(1);
+        let child_thread = thread::spawn(move || {
+            let _ = stop_receiver.recv();
+        });
+        TaskExecutor {
+            child_thread,
+            stop_sender,
+            input_sender: None,
+            grace_period_start: None,
+            grace_period: Duration::ZERO.into(),
+            speculative: false,
+        }
+    }
+    fn replay(
+        &self,
+        execution: RecordedExecution,
+    ) -> TaskExecutor {
+        let line_sender = self.line_sender.clone();
+        let (stop_sender, stop_receiver) = channel::bounded(1);
+        let child_thread = thread::spawn(move || {
+            let start = Instant::now();
+            for (delay, info) in execution.outputs {
+                match stop_receiver.recv_timeout(delay.saturating_sub(start.elapsed())) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => return, // killed, or the task executor was dropped
+                }
+                if line_sender.send(info).is_err() {
+                    return;
+                }
+            }
+        });
+        TaskExecutor {
+            child_thread,
+            stop_sender,
+            input_sender: None,
+            grace_period_start: None,
+            grace_period: Duration::ZERO.into(),
+            speculative: false,
+        }
+    }
+}
+
+/// Wait for the other bacon instances to release the run lock, and
+/// return it, or None if the task was interrupted meanwhile
+fn wait_run_lock(
+    path: &Path,
+    stop_receiver: &Receiver<StopMessage>,
+) -> io::Result<Option<RunLock>> {
+    let mut waiting = false;
+    loop {
+        if let Some(run_lock) = RunLock::try_acquire(path)? {
+            return Ok(Some(run_lock));
+        }
+        if !waiting {
+            info!("waiting for another bacon instance to finish its job");
+            waiting = true;
+        }
+        match stop_receiver.recv_timeout(RUN_LOCK_POLL_PERIOD) {
+            Err(RecvTimeoutError::Timeout) => {}
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// Start threads sending the lines of the stdout (if wanted) and stderr
+/// pipes of the child, the end of stderr meaning the end of the command
+fn read_pipes(
+    child: &mut Child,
+    with_stdout: bool,
+    line_sender: &Sender<CommandExecInfo>,
+    stop_sender: Sender<StopMessage>,
+) {
+    if with_stdout {
+        match child.stdout.take() {
+            Some(stdout) => read_lines(stdout, CommandStream::StdOut, line_sender.clone(), None),
+            None => warn!("process has no stdout"), // unlikely
+        }
+    }
+    let stderr = child.stderr.take().expect("child missing stderr");
+    read_lines(
+        stderr,
+        CommandStream::StdErr,
+        line_sender.clone(),
+        Some(stop_sender),
+    );
+}
+
+/// Start a thread sending the lines of the reader until its end, then
+/// asking the child_thread to send the status if a stop_sender is given
+fn read_lines<R: Read + Send + 'static>(
+    reader: R,
+    origin: CommandStream,
+    line_sender: Sender<CommandExecInfo>,
+    stop_sender: Option<Sender<StopMessage>>,
+) {
+    let mut buf_reader = BufReader::new(reader);
+    thread::spawn(move || {
+        let mut line = String::new();
+        loop {
+            match buf_reader.read_line(&mut line) {
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    warn!("error : {e}");
+                }
+                Ok(0) | Err(_) => {
+                    // there won't be anything more (a pty ends with an error)
+                    if let Some(stop_sender) = &stop_sender {
+                        if let Err(e) = stop_sender.send(StopMessage::SendStatus) {
+                            warn!("sending stop message failed: {e}");
+                        }
+                    }
+                    break;
+                }
+                Ok(_) => {
+                    let response = CommandExecInfo::Line(RawCommandOutputLine {
+                        content: line.clone(),
+                        origin,
+                    });
+                    if line_sender.send(response).is_err() {
+                        break; // channel closed
+                    }
+                }
+            }
+            line.clear();
+        }
+    });
+}
+
+fn stdin_writer(child: &mut Child) -> io::Result<Box<dyn Write + Send>> {
+    match child.stdin.take() {
+        Some(stdin) => Ok(Box::new(stdin)),
+        None => Err(io::Error::other("process has no stdin")),
+    }
+}
+
+/// Start a thread writing the received inputs to the command,
+/// until the sender is dropped or the command's input is closed
+fn write_inputs(
+    mut writer: Box<dyn Write + Send>,
+    input_receiver: Receiver<String>,
+) {
+    thread::spawn(move || {
+        for input in input_receiver {
+            if let Err(e) = writer
+                .write_all(input.as_bytes())
+                .and_then(|()| writer.flush())
+            {
+                warn!("writing input failed: {e}");
+                break;
+            }
+        }
+    });
 }
 
 /// kill the child process, either by using a specific command or by
-/// using the default platform kill method if the specific command
-/// failed or wasn't provided.
+/// killing its whole process group if the specific command failed or
+/// wasn't provided (after sending the kill signal and waiting for the
+/// processes to terminate, if there's a signal)
 fn kill(
     kill_command: Option<&[String]>,
+    kill_signal: Option<(KillSignal, Period)>,
     child: &mut Child,
+    group: &ProcessGroup,
 ) {
     if let Some(kill_command) = kill_command {
         info!("launch specific kill command {kill_command:?}");
@@ -238,8 +531,16 @@ fn kill(
             return;
         };
         warn!("specific kill command failed: {e}");
+    } else if let Some((signal, timeout)) = kill_signal {
+        info!("send {signal:?} to the command");
+        if let Err(e) = group.terminate(child, signal, timeout.duration) {
+            warn!("command couldn't be terminated: {e}");
+        }
+        return;
+    }
+    if let Err(e) = group.kill(child) {
+        warn!("command couldn't be killed: {e}");
     }
-    child.kill().expect("command couldn't be killed")
 }
 
 fn run_kill_command(
@@ -255,11 +556,25 @@ fn run_kill_command(
     let mut proc = kill.spawn()?;
     let status = proc.wait()?;
     if !status.success() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("kill command returned nonzero status: {status}"),
-        ));
+        return Err(io::Error::other(format!(
+            "kill command returned nonzero status: {status}"
+        )));
     }
     child.wait()?;
     Ok(())
 }
+
+/// Write the paths of the changed files, one per line, in a new
+/// temporary file, which is only readable by the user and removed
+/// when dropped
+fn write_changed_files_list(changed_files: &[PathBuf]) -> io::Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("bacon-changed-files-")
+        .suffix(".txt")
+        .tempfile()?;
+    for changed_file in changed_files {
+        writeln!(file, "{}", changed_file.to_string_lossy())?;
+    }
+    file.flush()?;
+    Ok(file)
+}
@@ -0,0 +1,70 @@
+use {
+    anyhow::anyhow,
+    serde::{
+        Deserialize,
+        Deserializer,
+        de,
+    },
+    std::str::FromStr,
+};
+
+/// A signal asking the command to terminate, sent before it's
+/// forcefully killed (read in config as eg "SIGTERM" or "int")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillSignal {
+    Hup,
+    Int,
+    Quit,
+    Term,
+    Usr1,
+    Usr2,
+}
+
+impl KillSignal {
+    #[cfg(unix)]
+    pub fn number(self) -> libc::c_int {
+        match self {
+            Self::Hup => libc::SIGHUP,
+            Self::Int => libc::SIGINT,
+            Self::Quit => libc::SIGQUIT,
+            Self::Term => libc::SIGTERM,
+            Self::Usr1 => libc::SIGUSR1,
+            Self::Usr2 => libc::SIGUSR2,
+        }
+    }
+}
+
+impl FromStr for KillSignal {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_uppercase();
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        match name {
+            "HUP" => Ok(Self::Hup),
+            "INT" => Ok(Self::Int),
+            "QUIT" => Ok(Self::Quit),
+            "TERM" => Ok(Self::Term),
+            "USR1" => Ok(Self::Usr1),
+            "USR2" => Ok(Self::Usr2),
+            _ => Err(anyhow!("Invalid kill signal: {}", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for KillSignal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[test]
+fn test_parse_kill_signal() {
+    assert_eq!("SIGTERM".parse::<KillSignal>().unwrap(), KillSignal::Term);
+    assert_eq!("int".parse::<KillSignal>().unwrap(), KillSignal::Int);
+    assert_eq!("SigUsr1".parse::<KillSignal>().unwrap(), KillSignal::Usr1);
+    assert!("SIGKILL".parse::<KillSignal>().is_err());
+}
@@ -1,13 +1,28 @@
 mod command_builder;
+mod defer_events;
 mod executor;
+mod kill_signal;
 mod on_change_strategy;
 mod period;
+mod process_group;
+#[cfg(unix)]
+mod pty;
+mod resource_limits;
+mod shell_quoting;
 mod task;
 
 pub use {
-    command_builder::CommandBuilder,
+    command_builder::*,
+    defer_events::*,
     executor::*,
+    kill_signal::*,
     on_change_strategy::*,
     period::*,
+    process_group::*,
+    resource_limits::*,
+    shell_quoting::*,
     task::Task,
 };
+
+#[cfg(unix)]
+pub use pty::Pty;
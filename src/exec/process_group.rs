@@ -0,0 +1,262 @@
+use {
+    crate::*,
+    std::{
+        io,
+        process::{
+            Child,
+            Command,
+        },
+        time::Duration,
+    },
+};
+
+/// The unix process groups which may still be running, to be killed
+/// when bacon is interrupted
+#[cfg(unix)]
+static LIVE_GROUPS: std::sync::Mutex<Vec<libc::pid_t>> = std::sync::Mutex::new(Vec::new());
+
+/// The child process and the processes it spawns (eg the test binaries
+/// and the servers they launch), so that they can be killed together.
+///
+/// On unix, the child is the leader of a new process group. On windows,
+/// it's started suspended and assigned to a job object before it runs, so
+/// that the processes it spawns are in the job too. The job is closed when
+/// the group is dropped, killing the remaining processes.
+pub struct ProcessGroup {
+    #[cfg(unix)]
+    pgid: libc::pid_t,
+    #[cfg(windows)]
+    job: Option<windows_sys::Win32::Foundation::HANDLE>,
+}
+
+impl ProcessGroup {
+    /// Prepare the command for its process to head a group
+    pub fn prepare(command: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use {
+                std::os::windows::process::CommandExt,
+                windows_sys::Win32::System::Threading::CREATE_SUSPENDED,
+            };
+            // the child is resumed once in the job, by ProcessGroup::new
+            command.creation_flags(CREATE_SUSPENDED);
+        }
+        #[cfg(not(any(unix, windows)))]
+        let _ = command;
+    }
+    /// Build the group of the spawned child
+    #[cfg(unix)]
+    pub fn new(child: &Child) -> Self {
+        let pgid = child.id() as libc::pid_t;
+        if let Ok(mut groups) = LIVE_GROUPS.lock() {
+            groups.push(pgid);
+        }
+        Self { pgid }
+    }
+    #[cfg(not(any(unix, windows)))]
+    pub fn new(_child: &Child) -> Self {
+        Self {}
+    }
+    /// Build the group of the spawned child, and resume it. If the job
+    /// object can't be made, only the child will be killed.
+    #[cfg(windows)]
+    pub fn new(child: &Child) -> Self {
+        let job = match create_job(child) {
+            Ok(job) => Some(job),
+            Err(e) => {
+                warn!("failed to create a job object: {e}");
+                None
+            }
+        };
+        if let Err(e) = resume_process(child.id()) {
+            warn!("failed to resume the command: {e}");
+        }
+        Self { job }
+    }
+    /// Kill the child and all the processes of its group
+    pub fn kill(
+        &self,
+        child: &mut Child,
+    ) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            // SAFETY: killpg has no effect on the memory of bacon
+            if unsafe { libc::killpg(self.pgid, libc::SIGKILL) } == 0 {
+                return Ok(());
+            }
+            // the group may be gone already
+            debug!("killpg failed: {}", io::Error::last_os_error());
+        }
+        #[cfg(windows)]
+        if let Some(job) = self.job {
+            use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+            // SAFETY: the job handle is valid until the group is dropped
+            if unsafe { TerminateJobObject(job, 1) } != 0 {
+                return Ok(());
+            }
+            debug!("TerminateJobObject failed: {}", io::Error::last_os_error());
+        }
+        child.kill()
+    }
+    /// Send the signal to the processes of the group, then kill
+    /// the ones still running after the timeout.
+    ///
+    /// Signals can't be sent on windows, where the group is
+    /// immediately killed.
+    pub fn terminate(
+        &self,
+        child: &mut Child,
+        signal: KillSignal,
+        timeout: Duration,
+    ) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            // SAFETY: killpg has no effect on the memory of bacon
+            if unsafe { libc::killpg(self.pgid, signal.number()) } != 0 {
+                debug!("killpg failed: {}", io::Error::last_os_error());
+                return self.kill(child);
+            }
+            let start = std::time::Instant::now();
+            while start.elapsed() < timeout {
+                // the leader, once reaped, isn't in the group anymore
+                child.try_wait()?;
+                // SAFETY: the null signal only checks the group still exists
+                if unsafe { libc::killpg(self.pgid, 0) } != 0 {
+                    return Ok(());
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            info!("processes still running after {timeout:?}, killing them");
+        }
+        #[cfg(not(unix))]
+        debug!("can't send {signal:?} (timeout: {timeout:?})");
+        self.kill(child)
+    }
+    /// Kill the groups which may still be running, before bacon exits
+    /// without dropping them.
+    ///
+    /// On windows, job objects are closed (and their processes killed)
+    /// by the system when bacon exits.
+    pub fn kill_all() {
+        #[cfg(unix)]
+        if let Ok(groups) = LIVE_GROUPS.lock() {
+            for &pgid in groups.iter() {
+                // SAFETY: killpg has no effect on the memory of bacon
+                unsafe {
+                    libc::killpg(pgid, libc::SIGKILL);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Ok(mut groups) = LIVE_GROUPS.lock() {
+            groups.retain(|&pgid| pgid != self.pgid);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(job) = self.job {
+            // SAFETY: the handle was created for the group and isn't used after
+            unsafe {
+                windows_sys::Win32::Foundation::CloseHandle(job);
+            }
+        }
+    }
+}
+
+/// Create a job object killing its processes when closed, and
+/// assign the child to it
+#[cfg(windows)]
+fn create_job(child: &Child) -> io::Result<windows_sys::Win32::Foundation::HANDLE> {
+    use {
+        std::os::windows::io::AsRawHandle,
+        windows_sys::Win32::{
+            Foundation::CloseHandle,
+            System::JobObjects::*,
+        },
+    };
+    // SAFETY: the job handle is checked, and the structure given to
+    // SetInformationJobObject is of the announced class and size
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        let configured = SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const _,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) != 0;
+        if !configured || AssignProcessToJobObject(job, child.as_raw_handle() as _) == 0 {
+            let e = io::Error::last_os_error();
+            CloseHandle(job);
+            return Err(e);
+        }
+        Ok(job)
+    }
+}
+
+/// Resume the threads of the process, which was created suspended
+/// (std doesn't give the handle of its main thread)
+#[cfg(windows)]
+fn resume_process(pid: u32) -> io::Result<()> {
+    use windows_sys::Win32::{
+        Foundation::{
+            CloseHandle,
+            INVALID_HANDLE_VALUE,
+        },
+        System::{
+            Diagnostics::ToolHelp::*,
+            Threading::{
+                OpenThread,
+                ResumeThread,
+                THREAD_SUSPEND_RESUME,
+            },
+        },
+    };
+    // SAFETY: the handles are checked and closed, and the entry given
+    // to Thread32First and Thread32Next has its announced size
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut resumed = false;
+        let mut found = Thread32First(snapshot, &mut entry) != 0;
+        while found {
+            if entry.th32OwnerProcessID == pid {
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                if !thread.is_null() {
+                    resumed |= ResumeThread(thread) != u32::MAX;
+                    CloseHandle(thread);
+                }
+            }
+            found = Thread32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+        if resumed {
+            Ok(())
+        } else {
+            Err(io::Error::other(
+                "no thread of the process could be resumed",
+            ))
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    fs::File,
+    io,
+    os::{
+        fd::{
+            FromRawFd,
+            OwnedFd,
+        },
+        unix::process::CommandExt,
+    },
+    process::{
+        Command,
+        Stdio,
+    },
+};
+
+/// A pseudo-terminal in which a command runs, so that it behaves
+/// (colors, progress bars) as when launched in a terminal.
+///
+/// The command's stdout and stderr are both written to the pty,
+/// whose output is then received as one stream.
+///
+/// This is unix only: there's no ConPTY based implementation, so the
+/// command is always piped on Windows.
+pub struct Pty {
+    master: File,
+    slave: OwnedFd,
+}
+
+impl Pty {
+    /// Open a pty whose size is the one of bacon's terminal
+    pub fn open() -> io::Result<Self> {
+        let (width, height) = termimad::terminal_size();
+        let size = libc::winsize {
+            ws_row: height.max(10),
+            ws_col: width.max(20),
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let mut master = -1;
+        let mut slave = -1;
+        // SAFETY: the fds are written by openpty, which doesn't keep the
+        // pointers, and they're owned by nobody else
+        unsafe {
+            if libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &size,
+            ) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self {
+                master: File::from_raw_fd(master),
+                slave: OwnedFd::from_raw_fd(slave),
+            })
+        }
+    }
+    /// Make the pty the stdout and stderr of the command, and its
+    /// stdin if it's to receive input.
+    ///
+    /// The pty is also the controlling terminal of the command, which
+    /// is made the leader of a new session, and thus of a new process
+    /// group: [ProcessGroup::prepare] must not be called on the command.
+    pub fn attach(
+        &self,
+        command: &mut Command,
+        with_stdin: bool,
+    ) -> io::Result<()> {
+        command.stdout(Stdio::from(self.slave.try_clone()?));
+        command.stderr(Stdio::from(self.slave.try_clone()?));
+        if with_stdin {
+            command.stdin(Stdio::from(self.slave.try_clone()?));
+        }
+        // SAFETY: the closure runs in the forked process, where it
+        // only does system calls, without allocating
+        unsafe {
+            command.pre_exec(|| {
+                // a process group leader can't create a session
+                if libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                // stdout is already the slave side of the pty
+                if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCSCTTY, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+    /// Return a writer of the input of the command
+    pub fn writer(&self) -> io::Result<File> {
+        self.master.try_clone()
+    }
+    /// Return the reader of the output of the command, which must
+    /// have been spawned (and dropped, as it holds copies of the
+    /// slave side) so that the end of the output can be detected
+    pub fn into_reader(self) -> File {
+        self.master
+    }
+}
+
+#[test]
+fn test_pty_controlling_terminal() {
+    let pty = Pty::open().unwrap();
+    let mut command = Command::new("sh");
+    // /dev/tty can only be opened by a process with a controlling terminal
+    command.args(["-c", "test -t 1 && echo ok > /dev/tty"]);
+    pty.attach(&mut command, false).unwrap();
+    let status = command.status().unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,167 @@
+use {
+    anyhow::anyhow,
+    lazy_regex::*,
+    serde::{
+        Deserialize,
+        Deserializer,
+        de,
+    },
+    std::{
+        process::Command,
+        str::FromStr,
+    },
+};
+
+/// The priorities and limits applied to the processes of a job's
+/// command, so that a heavy background job doesn't starve the
+/// rest of the system (only applied on unix)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    /// the niceness of the command, from -20 (highest priority)
+    /// to 19 (lowest priority)
+    pub nice: Option<i32>,
+    /// the IO scheduling priority (only on linux)
+    pub io_priority: Option<IoPriority>,
+    /// the maximal size of the address space of each process
+    pub memory_limit: Option<MemorySize>,
+}
+
+/// An IO scheduling priority
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IoPriority {
+    /// only get disk time when no other process needs it
+    Idle,
+    /// the lowest "best effort" priority
+    Low,
+    /// the default priority
+    Normal,
+}
+
+/// A size in bytes, read in config either as a number or
+/// as a string with a unit, eg "512M" or "4GB"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemorySize {
+    pub bytes: u64,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.nice.is_none() && self.io_priority.is_none() && self.memory_limit.is_none()
+    }
+    /// Make the command apply the limits to its process before executing
+    pub fn apply_to(
+        self,
+        command: &mut Command,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            // SAFETY: the closure runs in the forked process, where it
+            // only does system calls, without allocating
+            unsafe {
+                command.pre_exec(move || self.apply_to_current_process());
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = command;
+            warn!("resource limits are only supported on unix");
+        }
+    }
+    #[cfg(unix)]
+    fn apply_to_current_process(&self) -> std::io::Result<()> {
+        use std::io;
+        if let Some(nice) = self.nice {
+            // SAFETY: setpriority only changes the priority of this process
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(io_priority) = self.io_priority {
+            const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+            const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+            let (class, data) = match io_priority {
+                IoPriority::Idle => (3, 0),
+                IoPriority::Low => (2, 7),
+                IoPriority::Normal => (2, 4),
+            };
+            // SAFETY: ioprio_set only changes the priority of this process
+            let res = unsafe {
+                libc::syscall(
+                    libc::SYS_ioprio_set,
+                    IOPRIO_WHO_PROCESS,
+                    0,
+                    (class << IOPRIO_CLASS_SHIFT) | data,
+                )
+            };
+            if res != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        if let Some(memory_limit) = self.memory_limit {
+            let limit = libc::rlimit {
+                rlim_cur: memory_limit.bytes as libc::rlim_t,
+                rlim_max: memory_limit.bytes as libc::rlim_t,
+            };
+            // SAFETY: the limit is read by setrlimit, which doesn't keep it
+            if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MemorySize {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (_, n, unit) = regex_captures!(r"^(\d+)\s*([kmgt]?)(?:i?b)?$"i, s.trim())
+            .ok_or_else(|| anyhow!("Invalid memory size: {}", s))?;
+        let n: u64 = n.parse()?;
+        let shift = match unit.to_ascii_lowercase().as_str() {
+            "k" => 10,
+            "m" => 20,
+            "g" => 30,
+            "t" => 40,
+            _ => 0,
+        };
+        let bytes = n
+            .checked_mul(1 << shift)
+            .ok_or_else(|| anyhow!("Memory size too big: {}", s))?;
+        Ok(Self { bytes })
+    }
+}
+
+impl<'de> Deserialize<'de> for MemorySize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Bytes(u64),
+            String(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Bytes(bytes) => Ok(Self { bytes }),
+            Raw::String(s) => FromStr::from_str(&s).map_err(de::Error::custom),
+        }
+    }
+}
+
+#[test]
+fn test_parse_memory_size() {
+    let bytes = |s: &str| s.parse::<MemorySize>().map(|size| size.bytes).ok();
+    assert_eq!(bytes("1024"), Some(1024));
+    assert_eq!(bytes("512M"), Some(512 << 20));
+    assert_eq!(bytes("4GB"), Some(4 << 30));
+    assert_eq!(bytes("2 GiB"), Some(2 << 30));
+    assert_eq!(bytes("3kb"), Some(3 << 10));
+    assert_eq!(bytes("lots"), None);
+}
@@ -0,0 +1,79 @@
+/// How to quote an argument so that the shell running the
+/// command passes it unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellQuoting {
+    /// `sh`, `bash`, `zsh`, and the other POSIX shells
+    Posix,
+    /// the `cmd` of windows
+    Cmd,
+    /// `pwsh` and `powershell`
+    PowerShell,
+}
+
+impl ShellQuoting {
+    /// The quoting suited to the shell, from its executable
+    pub fn for_shell(exe: &str) -> Self {
+        let program = exe.rsplit(['/', '\\']).next().unwrap_or(exe);
+        let program = program.to_ascii_lowercase();
+        let program = program.strip_suffix(".exe").unwrap_or(&program);
+        match program {
+            "cmd" => Self::Cmd,
+            "pwsh" | "powershell" => Self::PowerShell,
+            _ => Self::Posix,
+        }
+    }
+    /// Quote the argument, if needed
+    pub fn quote(
+        self,
+        arg: &str,
+    ) -> String {
+        match self {
+            Self::Posix => {
+                // a nul byte can't be passed to a shell, it's removed
+                let arg = arg.replace('\0', "");
+                shlex::try_quote(&arg)
+                    .map(|quoted| quoted.into_owned())
+                    .unwrap_or(arg)
+            }
+            Self::Cmd => {
+                if !arg.is_empty() && !arg.contains(is_cmd_special) {
+                    return arg.to_string();
+                }
+                // a quoted % is still expanded by cmd, so it's put out of
+                // the quotes and escaped
+                let quoted = arg.replace('"', "\\\"").replace('%', "\"^%\"");
+                format!("\"{quoted}\"")
+            }
+            Self::PowerShell => {
+                if !arg.is_empty() && arg.chars().all(is_plain_char) {
+                    return arg.to_string();
+                }
+                format!("'{}'", arg.replace('\'', "''"))
+            }
+        }
+    }
+}
+
+fn is_plain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '\\' | ':' | '=' | ',')
+}
+
+fn is_cmd_special(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '&' | '|' | '<' | '>' | '(' | ')' | '^' | '%' | '!')
+}
+
+#[test]
+fn test_shell_quoting() {
+    let posix = ShellQuoting::for_shell("/bin/bash");
+    assert_eq!(posix, ShellQuoting::Posix);
+    assert_eq!(posix.quote("src/main.rs"), "src/main.rs");
+    assert_eq!(posix.quote("my file.rs"), "'my file.rs'");
+    assert_eq!(posix.quote("$(rm -rf ~).rs"), "'$(rm -rf ~).rs'");
+    assert_eq!(posix.quote("a;b"), "'a;b'");
+    let cmd = ShellQuoting::for_shell("C:\\Windows\\System32\\CMD.EXE");
+    assert_eq!(cmd, ShellQuoting::Cmd);
+    assert_eq!(cmd.quote("src\\main.rs"), "src\\main.rs");
+    assert_eq!(cmd.quote("a&b %PATH%"), "\"a&b \"^%\"PATH\"^%\"\"");
+    let pwsh = ShellQuoting::for_shell("pwsh");
+    assert_eq!(pwsh.quote("it's $(here)"), "'it''s $(here)'");
+}
@@ -1,8 +1,16 @@
-use crate::Period;
+use {
+    crate::Period,
+    std::path::PathBuf,
+};
 
 /// Settings for one execution of a job's command
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Task {
     pub backtrace: Option<&'static str>, // ("1" or "full")
     pub grace_period: Period,
+    /// whether the command starts without waiting for the end of the
+    /// grace period, being restarted on new changes during it
+    pub speculative: bool,
+    /// the paths of the file changes which triggered the execution
+    pub changed_files: Vec<PathBuf>,
 }
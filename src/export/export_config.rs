@@ -12,4 +12,7 @@ pub struct ExportConfig {
     pub auto: Option<bool>,
     pub path: Option<PathBuf>,
     pub line_format: Option<String>,
+    /// The address of a neovim server (eg its socket path) to be
+    /// told to reload the quickfix list after a quickfix export
+    pub nvim_server: Option<String>,
 }
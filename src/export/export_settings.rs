@@ -1,9 +1,6 @@
 use {
     crate::*,
-    std::{
-        fs::File,
-        path::PathBuf,
-    },
+    std::path::PathBuf,
 };
 
 /// Settings for one export
@@ -13,42 +10,84 @@ pub struct ExportSettings {
     pub auto: bool,
     pub path: PathBuf,
     pub line_format: String,
+    pub nvim_server: Option<String>,
 }
 impl ExportSettings {
-    pub fn do_export(
+    /// Build the content of the export, if there's something to export
+    pub fn render(
         &self,
         name: &str,
         state: &AppState<'_>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Option<RenderedExport>> {
         let path = if self.path.is_relative() {
             state.mission.package_directory.join(&self.path)
         } else {
             self.path.to_path_buf()
         };
-        info!("exporting to {:?}", path);
-        let Some(report) = state.cmd_result.report() else {
-            info!("No report to export");
-            return Ok(());
-        };
-        match self.exporter {
-            Exporter::Analyser => {
-                if let Some(export) = report.analyzer_exports.get(name) {
-                    std::fs::write(&path, export)?;
-                } else {
-                    info!("Analyzer didn't build export {:?}", name);
-                }
+        let mut nvim_server = None;
+        let content = match self.exporter {
+            Exporter::Status => {
+                let Some(status) = JobStatus::of(state) else {
+                    return Ok(None);
+                };
+                let mut line = status.line(&self.line_format);
+                line.push('\n');
+                line.into_bytes()
             }
-            Exporter::Analysis => {
-                error!("Aanlysis export not currently implemented");
+            Exporter::Waybar => {
+                let Some(status) = JobStatus::of(state) else {
+                    return Ok(None);
+                };
+                let json = status.waybar_json(&self.line_format).to_string();
+                format!("{json}\n").into_bytes()
             }
-            Exporter::JsonReport => {
-                let json = serde_json::to_string_pretty(&report)?;
-                std::fs::write(&path, json)?;
-            }
-            Exporter::Locations => {
-                let mut file = File::create(path)?;
-                report.write_locations(&mut file, &state.mission, &self.line_format)?;
+            exporter => {
+                let Some(report) = state.cmd_result.report() else {
+                    info!("No report to export");
+                    return Ok(None);
+                };
+                match exporter {
+                    Exporter::Analyser => {
+                        let Some(export) = report.analyzer_exports.get(name) else {
+                            info!("Analyzer didn't build export {:?}", name);
+                            return Ok(None);
+                        };
+                        export.clone().into_bytes()
+                    }
+                    Exporter::Analysis => {
+                        error!("Aanlysis export not currently implemented");
+                        return Ok(None);
+                    }
+                    Exporter::JsonReport => serde_json::to_string_pretty(&report)?.into_bytes(),
+                    Exporter::Html => ReportFormat::Html.render(report)?.into_bytes(),
+                    Exporter::Markdown => ReportFormat::Markdown.render(report)?.into_bytes(),
+                    Exporter::Locations | Exporter::Quickfix => {
+                        let mut content = Vec::new();
+                        report.write_locations(&mut content, &state.mission, &self.line_format)?;
+                        if exporter == Exporter::Quickfix {
+                            nvim_server.clone_from(&self.nvim_server);
+                        }
+                        content
+                    }
+                    Exporter::Status | Exporter::Waybar => unreachable!(),
+                }
             }
+        };
+        Ok(Some(RenderedExport {
+            path,
+            content,
+            nvim_server,
+        }))
+    }
+    /// Write the export now, replacing the file atomically
+    pub fn do_export(
+        &self,
+        name: &str,
+        state: &AppState<'_>,
+    ) -> anyhow::Result<()> {
+        if let Some(export) = self.render(name, state)? {
+            info!("exporting to {:?}", export.path);
+            export.write()?;
         }
         Ok(())
     }
@@ -0,0 +1,203 @@
+use {
+    crate::*,
+    std::{
+        collections::HashMap,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::{
+            Command,
+            Stdio,
+        },
+        sync::OnceLock,
+        thread,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+    termimad::crossbeam::channel::{
+        self,
+        Receiver,
+        RecvTimeoutError,
+        Sender,
+    },
+};
+
+/// The content of an export, ready to be written
+#[derive(Debug, Clone)]
+pub struct RenderedExport {
+    pub path: PathBuf,
+    pub content: Vec<u8>,
+    /// the neovim server to tell to reload the file, for a quickfix export
+    pub nvim_server: Option<String>,
+}
+
+enum ExportWriterMessage {
+    Write {
+        export: RenderedExport,
+        debounce: Duration,
+    },
+    /// Write all pending exports now, then acknowledge
+    Flush(Sender<()>),
+    /// Tell the neovim server to reload the quickfix file
+    ReloadNvimQuickfix { server: String, path: PathBuf },
+}
+
+static EXPORT_WRITER: OnceLock<Sender<ExportWriterMessage>> = OnceLock::new();
+
+/// The sender to the thread writing the exports, which is started
+/// on first use
+fn export_writer() -> &'static Sender<ExportWriterMessage> {
+    EXPORT_WRITER.get_or_init(|| {
+        let (sender, receiver) = channel::unbounded();
+        thread::spawn(move || run_export_writer(receiver));
+        sender
+    })
+}
+
+impl RenderedExport {
+    /// Write the file atomically, then tell neovim to reload it if needed.
+    ///
+    /// As neovim may be slow to answer, the reload is done by the
+    /// export writer thread.
+    pub fn write(&self) -> anyhow::Result<()> {
+        write_atomically(&self.path, &self.content)?;
+        if let Some(server) = &self.nvim_server {
+            let _ = export_writer().send(ExportWriterMessage::ReloadNvimQuickfix {
+                server: server.clone(),
+                path: self.path.clone(),
+            });
+        }
+        Ok(())
+    }
+    /// Write the export after the debounce delay, in a background thread.
+    ///
+    /// Exports to the same path received meanwhile replace this one, so
+    /// that the file is written at most once per delay
+    pub fn write_debounced(
+        self,
+        debounce: Duration,
+    ) {
+        let _ = export_writer().send(ExportWriterMessage::Write {
+            export: self,
+            debounce,
+        });
+    }
+}
+
+/// Write the exports waiting for the end of their debounce delay,
+/// eg before quitting
+pub fn flush_debounced_exports() {
+    let Some(sender) = EXPORT_WRITER.get() else {
+        return;
+    };
+    let (done_sender, done_receiver) = channel::bounded(1);
+    if sender.send(ExportWriterMessage::Flush(done_sender)).is_ok() {
+        let _ = done_receiver.recv();
+    }
+}
+
+/// Write the export, from the export writer thread
+fn write_export(export: &RenderedExport) {
+    info!("writing export {:?}", export.path);
+    if let Err(e) = write_atomically(&export.path, &export.content) {
+        error!("error while writing export {:?}: {:?}", export.path, e);
+        return;
+    }
+    if let Some(server) = &export.nvim_server {
+        reload_nvim_quickfix(server, &export.path);
+    }
+}
+
+fn run_export_writer(receiver: Receiver<ExportWriterMessage>) {
+    // the exports to write, by path, with when to write them
+    let mut pending: HashMap<PathBuf, (RenderedExport, Instant)> = HashMap::new();
+    loop {
+        let next_due = pending.values().map(|(_, due)| *due).min();
+        let message = match next_due {
+            Some(due) => receiver.recv_timeout(due.saturating_duration_since(Instant::now())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match message {
+            Ok(ExportWriterMessage::Write { export, debounce }) => {
+                let due = pending
+                    .remove(&export.path)
+                    .map_or_else(|| Instant::now() + debounce, |(_, due)| due);
+                pending.insert(export.path.clone(), (export, due));
+            }
+            Ok(ExportWriterMessage::Flush(done_sender)) => {
+                for (_, (export, _)) in pending.drain() {
+                    write_export(&export);
+                }
+                let _ = done_sender.send(());
+            }
+            Ok(ExportWriterMessage::ReloadNvimQuickfix { server, path }) => {
+                reload_nvim_quickfix(&server, &path);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                for (_, (export, _)) in pending.drain() {
+                    write_export(&export);
+                }
+                return;
+            }
+        }
+        let now = Instant::now();
+        let due_paths: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, (_, due))| *due <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in due_paths {
+            if let Some((export, _)) = pending.remove(&path) {
+                write_export(&export);
+            }
+        }
+    }
+}
+
+/// Tell the neovim server to load the quickfix file, without
+/// jumping to the first item
+fn reload_nvim_quickfix(
+    server: &str,
+    path: &Path,
+) {
+    // in vim single quoted strings, quotes are doubled
+    let path = path.to_string_lossy().replace('\'', "''");
+    let expr = format!("execute('cgetfile ' .. fnameescape('{path}'))");
+    let res = Command::new("nvim")
+        .args(["--server", server, "--remote-expr", &expr])
+        .stdin(Stdio::null())
+        .output();
+    match res {
+        Ok(output) if !output.status.success() => {
+            warn!(
+                "nvim didn't reload the quickfix list: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Err(e) => {
+            warn!("nvim couldn't be launched: {e}");
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn test_debounced_exports() {
+    let path = std::env::temp_dir().join(format!("bacon-test-export-{}", std::process::id()));
+    for content in ["first", "second", "last"] {
+        RenderedExport {
+            path: path.clone(),
+            content: content.as_bytes().to_vec(),
+            nvim_server: None,
+        }
+        .write_debounced(Duration::from_secs(60));
+    }
+    assert!(!path.exists());
+    flush_debounced_exports();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "last");
+    std::fs::remove_file(&path).unwrap();
+}
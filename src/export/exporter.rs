@@ -11,5 +11,25 @@ pub enum Exporter {
     #[serde(alias = "analyzis")]
     Analysis,
     JsonReport,
+    /// The report as a self-contained HTML page, with collapsible
+    /// sections and filtering
+    Html,
     Locations,
+    /// The report in GitHub-flavored markdown, to be pasted
+    /// in an issue or a PR
+    Markdown,
+    /// The locations, in a file for the quickfix list of vim or neovim
+    Quickfix,
+    /// A one line summary of the state of the job, updated when
+    /// executions start and finish
+    Status,
+    /// A JSON object for the custom modules of waybar
+    Waybar,
+}
+
+impl Exporter {
+    /// Whether the export must also be done when an execution starts
+    pub fn is_done_on_start(self) -> bool {
+        matches!(self, Self::Status | Self::Waybar)
+    }
 }
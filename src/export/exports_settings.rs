@@ -10,6 +10,9 @@ use {
 #[derive(Debug, Clone, Default)]
 pub struct ExportsSettings {
     pub exports: HashMap<String, ExportSettings>,
+    /// when set, the auto exports are written in the background, at
+    /// most once per period
+    pub debounce: Option<Period>,
 }
 
 impl ExportsSettings {
@@ -31,9 +34,40 @@ impl ExportsSettings {
         for (name, export) in &self.exports {
             if export.auto {
                 info!("doing auto export {:?}", name);
-                if let Err(e) = export.do_export(name, state) {
-                    error!("error while exporting {:?}: {:?}", name, e);
-                }
+                self.do_auto_export(name, export, state);
+            }
+        }
+    }
+
+    /// Do an auto export, now or, when a debounce is set, in the background
+    fn do_auto_export(
+        &self,
+        name: &str,
+        export: &ExportSettings,
+        state: &AppState<'_>,
+    ) {
+        let debounce = self.debounce.map(|p| p.duration).unwrap_or_default();
+        let res = if debounce.is_zero() {
+            export.do_export(name, state)
+        } else {
+            export
+                .render(name, state)
+                .map(|rendered| rendered.map_or((), |r| r.write_debounced(debounce)))
+        };
+        if let Err(e) = res {
+            error!("error while exporting {:?}: {:?}", name, e);
+        }
+    }
+
+    /// Do the auto exports which must be updated when an execution starts
+    pub fn do_start_exports(
+        &self,
+        state: &AppState<'_>,
+    ) {
+        for (name, export) in &self.exports {
+            if export.auto && export.exporter.is_done_on_start() {
+                info!("doing start export {:?}", name);
+                self.do_auto_export(name, export, state);
             }
         }
     }
@@ -65,6 +99,9 @@ impl ExportsSettings {
         &mut self,
         config: &Config,
     ) {
+        if config.export_debounce.is_some() {
+            self.debounce = config.export_debounce;
+        }
         // normal [exports] map
         for (name, ec) in &config.exports {
             if let Some(e) = self.exports.get_mut(name) {
@@ -80,6 +117,9 @@ impl ExportsSettings {
                 if let Some(lf) = &ec.line_format {
                     e.line_format = lf.clone();
                 }
+                if ec.nvim_server.is_some() {
+                    e.nvim_server.clone_from(&ec.nvim_server);
+                }
                 continue;
             }
             let exporter = match ec.exporter {
@@ -87,7 +127,12 @@ impl ExportsSettings {
                 None => match name.as_str() {
                     "analysis" => Exporter::Analysis,
                     "json-report" => Exporter::JsonReport,
+                    "html" => Exporter::Html,
                     "locations" => Exporter::Locations,
+                    "markdown" => Exporter::Markdown,
+                    "quickfix" => Exporter::Quickfix,
+                    "status" => Exporter::Status,
+                    "waybar" => Exporter::Waybar,
                     _ => {
                         warn!(
                             "Exporter not specified for export {:?}, using 'locations'",
@@ -102,10 +147,18 @@ impl ExportsSettings {
                 Exporter::Analyser => default_analyser_path(),
                 Exporter::Analysis => default_analysis_path(),
                 Exporter::Locations => default_locations_path(),
+                Exporter::Html => default_html_path(),
                 Exporter::JsonReport => default_json_report_path(),
+                Exporter::Markdown => default_markdown_path(),
+                Exporter::Quickfix => default_quickfix_path(),
+                Exporter::Status => default_status_path(),
+                Exporter::Waybar => default_waybar_path(),
             });
             let line_format = ec.line_format.clone().unwrap_or_else(|| match exporter {
                 Exporter::Locations => default_locations_line_format().to_string(),
+                Exporter::Quickfix => default_quickfix_line_format().to_string(),
+                Exporter::Status => default_status_line_format().to_string(),
+                Exporter::Waybar => default_waybar_text_format().to_string(),
                 _ => "".to_string(),
             });
             self.exports.insert(
@@ -115,6 +168,7 @@ impl ExportsSettings {
                     auto,
                     path,
                     line_format,
+                    nvim_server: ec.nvim_server.clone(),
                 },
             );
         }
@@ -182,6 +236,7 @@ fn default_analysis_export_settings() -> ExportSettings {
         auto: true,
         path: default_analysis_path(),
         line_format: "".to_string(), // not used
+        nvim_server: None,
     }
 }
 fn default_json_report_export_settings() -> ExportSettings {
@@ -190,6 +245,7 @@ fn default_json_report_export_settings() -> ExportSettings {
         auto: true,
         path: default_json_report_path(),
         line_format: "".to_string(), // not used
+        nvim_server: None,
     }
 }
 fn default_locations_export_settings() -> ExportSettings {
@@ -198,6 +254,7 @@ fn default_locations_export_settings() -> ExportSettings {
         auto: true,
         path: default_locations_path(),
         line_format: default_locations_line_format().to_string(),
+        nvim_server: None,
     }
 }
 
@@ -205,15 +262,43 @@ pub fn default_locations_line_format() -> &'static str {
     "{kind} {path}:{line}:{column} {message}"
 }
 
+/// A format matching the `%f:%l:%c: %t%*[^:]: %m` errorformat
+pub fn default_quickfix_line_format() -> &'static str {
+    "{path}:{line}:{column}: {kind}: {message}"
+}
+
+pub fn default_status_line_format() -> &'static str {
+    "{job} {state} {errors} {test_fails} {warnings} {timestamp}"
+}
+
+pub fn default_waybar_text_format() -> &'static str {
+    "{job} {errors}/{test_fails}/{warnings}"
+}
+
 pub fn default_analyser_path() -> PathBuf {
     PathBuf::from("bacon-analyser.json")
 }
 pub fn default_analysis_path() -> PathBuf {
     PathBuf::from("bacon-analysis.json")
 }
+pub fn default_html_path() -> PathBuf {
+    PathBuf::from("bacon-report.html")
+}
 pub fn default_json_report_path() -> PathBuf {
     PathBuf::from("bacon-report.json")
 }
 pub fn default_locations_path() -> PathBuf {
     PathBuf::from(".bacon-locations")
 }
+pub fn default_markdown_path() -> PathBuf {
+    PathBuf::from(".bacon-report.md")
+}
+pub fn default_quickfix_path() -> PathBuf {
+    PathBuf::from(".bacon-quickfix")
+}
+pub fn default_status_path() -> PathBuf {
+    PathBuf::from(".bacon-status")
+}
+pub fn default_waybar_path() -> PathBuf {
+    PathBuf::from("bacon-waybar.json")
+}
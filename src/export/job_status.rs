@@ -0,0 +1,218 @@
+use {
+    crate::*,
+    lazy_regex::*,
+    std::{
+        ffi::OsString,
+        fs,
+        io::{
+            self,
+            Write,
+        },
+        path::Path,
+        sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        time::{
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    },
+};
+
+/// The state of the job, as written in status exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Success,
+    Failure,
+}
+
+/// A small summary of the state of the job, for prompts and status bars
+#[derive(Debug, Clone)]
+pub struct JobStatus {
+    pub job: String,
+    pub state: JobState,
+    pub stats: Stats,
+    /// seconds since the unix epoch
+    pub timestamp: u64,
+}
+
+impl JobState {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Success => "success",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+impl JobStatus {
+    /// Build the status of the job, or None when no execution started
+    pub fn of(state: &AppState<'_>) -> Option<Self> {
+        let job_state = if state.is_computing() {
+            JobState::Running
+        } else {
+            match &state.cmd_result {
+                CommandResult::None => {
+                    return None;
+                }
+                _ if state.is_success() => JobState::Success,
+                _ => JobState::Failure,
+            }
+        };
+        let job = match &state.mission.concrete_job_ref.name_or_alias {
+            NameOrAlias::Name(name) => name.clone(),
+            NameOrAlias::Alias(alias) => alias.clone(),
+        };
+        let stats = state
+            .cmd_result
+            .report()
+            .map(|report| report.stats.clone())
+            .unwrap_or_default();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Some(Self {
+            job,
+            state: job_state,
+            stats,
+            timestamp,
+        })
+    }
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "job": self.job,
+            "state": self.state.name(),
+            "errors": self.stats.errors,
+            "test_fails": self.stats.test_fails,
+            "warnings": self.stats.warnings,
+            "passed_tests": self.stats.passed_tests,
+            "timestamp": self.timestamp,
+        })
+    }
+    /// A human readable line, eg "clippy: failure - 2 error(s)"
+    pub fn summary_line(&self) -> String {
+        match self.state {
+            JobState::Running => format!("{}: running", self.job),
+            state => format!("{}: {} - {}", self.job, state.name(), self.stats.summary()),
+        }
+    }
+    /// Build the line of the status file, replacing the `{key}` parts
+    /// of the line format
+    pub fn line(
+        &self,
+        line_format: &str,
+    ) -> String {
+        regex_replace_all!(r#"\{([^\s}]+)\}"#, line_format, |_, key| {
+            match key {
+                "errors" => self.stats.errors.to_string(),
+                "job" => self.job.clone(),
+                "passed_tests" => self.stats.passed_tests.to_string(),
+                "state" => self.state.name().to_string(),
+                "test_fails" => self.stats.test_fails.to_string(),
+                "timestamp" => self.timestamp.to_string(),
+                "warnings" => self.stats.warnings.to_string(),
+                _ => {
+                    debug!("unknown status key: {key:?}");
+                    String::new()
+                }
+            }
+        })
+        .to_string()
+    }
+    /// Build the JSON object read by waybar's custom modules (with
+    /// `"return-type": "json"`), whose class and alt are the state
+    pub fn waybar_json(
+        &self,
+        text_format: &str,
+    ) -> serde_json::Value {
+        let tooltip = match self.state {
+            JobState::Running => format!("{}: running", self.job),
+            _ => format!(
+                "{}: {}\n{}",
+                self.job,
+                self.state.name(),
+                self.stats.summary()
+            ),
+        };
+        serde_json::json!({
+            "text": self.line(text_format),
+            "alt": self.state.name(),
+            "tooltip": tooltip,
+            "class": self.state.name(),
+        })
+    }
+}
+
+/// Write the file so that its readers never see it partially written,
+/// by writing a temporary file then renaming it.
+///
+/// The temporary file has a name unique to the process and the write,
+/// so that bacon instances writing the same file don't mix their writes
+pub fn write_atomically(
+    path: &Path,
+    content: impl AsRef<[u8]>,
+) -> io::Result<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed),
+    ));
+    let tmp_path = path.with_file_name(tmp_name);
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .and_then(|mut file| file.write_all(content.as_ref()))
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+#[test]
+fn test_write_atomically() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("status.txt");
+    write_atomically(&path, "running").unwrap();
+    write_atomically(&path, "success").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "success");
+    // no temporary file is left
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_status_line() {
+    let status = JobStatus {
+        job: "clippy".to_string(),
+        state: JobState::Failure,
+        stats: Stats {
+            errors: 2,
+            warnings: 5,
+            ..Default::default()
+        },
+        timestamp: 1760000000,
+    };
+    assert_eq!(
+        status.line(default_status_line_format()),
+        "clippy failure 2 0 5 1760000000"
+    );
+    assert_eq!(
+        status.line("{state}: {errors}E {warnings}W {unknown}"),
+        "failure: 2E 5W "
+    );
+    let json = status.waybar_json(default_waybar_text_format());
+    assert_eq!(json["text"], "clippy 2/0/5");
+    assert_eq!(json["class"], "failure");
+    assert_eq!(json["tooltip"], "clippy: failure\n2 error(s), 5 warning(s)");
+    assert_eq!(
+        status.summary_line(),
+        "clippy: failure - 2 error(s), 5 warning(s)"
+    );
+}
@@ -1,11 +1,15 @@
 mod export_config;
 mod export_settings;
+mod export_writer;
 mod exporter;
 mod exports_settings;
+mod job_status;
 
 pub use {
     export_config::*,
     export_settings::*,
+    export_writer::*,
     exporter::*,
     exports_settings::*,
+    job_status::*,
 };
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+/// The `[github]` part of a configuration: how to publish the
+/// diagnostics as review comments of a pull request
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GithubConfig {
+    /// Whether to post the new diagnostics as review comments
+    pub review: Option<bool>,
+    /// The API token (by default the `GITHUB_TOKEN` env var)
+    pub token: Option<String>,
+    /// The repository, as `owner/name` (by default the
+    /// `GITHUB_REPOSITORY` env var)
+    pub repository: Option<String>,
+    /// The number of the pull request (by default read from the
+    /// `GITHUB_REF` env var, as set in GitHub Actions)
+    pub pull_request: Option<u64>,
+    /// The reviewed commit (by default the HEAD of the repository)
+    pub commit: Option<String>,
+    /// The base branch of the pull request, against which its diff is
+    /// computed (by default `origin/` then the `GITHUB_BASE_REF`
+    /// env var, or `origin/HEAD`)
+    pub base: Option<String>,
+    /// The URL of the API, for GitHub Enterprise (by default the
+    /// `GITHUB_API_URL` env var, or `https://api.github.com`)
+    pub api_url: Option<String>,
+}
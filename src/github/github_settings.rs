@@ -0,0 +1,346 @@
+use {
+    crate::*,
+    anyhow::{
+        Context,
+        Result,
+        anyhow,
+    },
+    rustc_hash::FxHasher,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        env,
+        fs,
+        hash::{
+            Hash,
+            Hasher,
+        },
+        io::Write,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::{
+            Command,
+            Stdio,
+        },
+        sync::Mutex,
+        thread,
+    },
+};
+
+/// The keys of the review comments already posted, by review URL,
+/// not to post them again on the next executions
+static POSTED_COMMENTS: Mutex<Option<HashMap<String, HashSet<String>>>> = Mutex::new(None);
+
+/// Settings of the publication of the diagnostics as review comments
+/// of a GitHub pull request
+#[derive(Debug, Clone, Default)]
+pub struct GithubSettings {
+    pub review: bool,
+    pub token: Option<String>,
+    pub repository: Option<String>,
+    pub pull_request: Option<u64>,
+    pub commit: Option<String>,
+    pub api_url: Option<String>,
+    pub base: Option<String>,
+}
+
+/// What's needed to post a review
+struct ReviewTarget {
+    token: String,
+    url: String,
+    /// the base branch of the pull request
+    base: String,
+}
+
+impl GithubSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &GithubConfig,
+    ) {
+        if let Some(b) = config.review {
+            self.review = b;
+        }
+        if config.token.is_some() {
+            self.token.clone_from(&config.token);
+        }
+        if config.repository.is_some() {
+            self.repository.clone_from(&config.repository);
+        }
+        if config.pull_request.is_some() {
+            self.pull_request = config.pull_request;
+        }
+        if config.commit.is_some() {
+            self.commit.clone_from(&config.commit);
+        }
+        if config.api_url.is_some() {
+            self.api_url.clone_from(&config.api_url);
+        }
+        if config.base.is_some() {
+            self.base.clone_from(&config.base);
+        }
+    }
+    /// Complete the settings with the environment variables of
+    /// GitHub Actions
+    fn target(&self) -> Result<ReviewTarget> {
+        let token = self
+            .token
+            .clone()
+            .or_else(|| env::var("GITHUB_TOKEN").ok())
+            .ok_or_else(|| anyhow!("no token (set it in [github] or as GITHUB_TOKEN)"))?;
+        let repository = self
+            .repository
+            .clone()
+            .or_else(|| env::var("GITHUB_REPOSITORY").ok())
+            .ok_or_else(|| anyhow!("no repository (set it in [github] or as GITHUB_REPOSITORY)"))?;
+        let pull_request = self
+            .pull_request
+            .or_else(|| {
+                // "refs/pull/<number>/merge" in actions triggered by a pull request
+                let git_ref = env::var("GITHUB_REF").ok()?;
+                git_ref
+                    .strip_prefix("refs/pull/")?
+                    .split('/')
+                    .next()?
+                    .parse()
+                    .ok()
+            })
+            .ok_or_else(|| anyhow!("no pull request (set it in [github] or with --github-pr)"))?;
+        let api_url = self
+            .api_url
+            .clone()
+            .or_else(|| env::var("GITHUB_API_URL").ok())
+            .unwrap_or_else(|| "https://api.github.com".to_string());
+        let base = self
+            .base
+            .clone()
+            .or_else(|| {
+                let base_ref = env::var("GITHUB_BASE_REF").ok()?;
+                (!base_ref.is_empty()).then(|| format!("origin/{base_ref}"))
+            })
+            .unwrap_or_else(|| "origin/HEAD".to_string());
+        Ok(ReviewTarget {
+            token,
+            base,
+            url: format!(
+                "{}/repos/{}/pulls/{}/reviews",
+                api_url.trim_end_matches('/'),
+                repository,
+                pull_request,
+            ),
+        })
+    }
+    /// Post, as a review of the pull request, the comments on the
+    /// diagnostics which weren't already posted, if it's configured.
+    ///
+    /// Only the locations are computed here, the git commands and the
+    /// post are done in a background thread
+    pub fn publish_review(
+        &self,
+        state: &AppState<'_>,
+    ) {
+        if !self.review {
+            return;
+        }
+        let Some(report) = state.cmd_result.report() else {
+            return;
+        };
+        let target = match self.target() {
+            Ok(target) => target,
+            Err(e) => {
+                warn!("failed to publish the review comments: {e}");
+                return;
+            }
+        };
+        let mission = &state.mission;
+        let items: Vec<(PathBuf, Value)> = report_locations(report)
+            .into_iter()
+            .filter_map(|item| {
+                let (path, _, _) = split_location(item["location"].as_str()?);
+                let path = mission.make_absolute(PathBuf::from(path));
+                Some((path, item))
+            })
+            .collect();
+        let directory = mission.package_directory.clone();
+        let job = mission.concrete_job_ref.badge_label();
+        let commit = self.commit.clone();
+        thread::spawn(move || {
+            if let Err(e) = post_review(target, &directory, commit, &job, items) {
+                warn!("failed to publish the review comments: {e}");
+            }
+        });
+    }
+}
+
+/// Post the comments on the items, given with the absolute path of
+/// their location, which are on lines of the diff of the pull request
+/// and weren't already posted
+fn post_review(
+    target: ReviewTarget,
+    directory: &Path,
+    commit: Option<String>,
+    job: &str,
+    items: Vec<(PathBuf, Value)>,
+) -> Result<()> {
+    let root = git(directory, &["rev-parse", "--show-toplevel"])?;
+    let root = PathBuf::from(root.trim());
+    let commit = match commit {
+        Some(commit) => commit,
+        None => git(&root, &["rev-parse", "HEAD"])?.trim().to_string(),
+    };
+    // GitHub refuses the comments on lines which aren't in the diff
+    // of the pull request, which starts at the merge base
+    let merge_base = git(&root, &["merge-base", &target.base, &commit]).with_context(|| {
+        format!(
+            "no merge base of {} and {commit} (is the history fetched, eg with `fetch-depth: 0`?)",
+            target.base,
+        )
+    })?;
+    let changed_lines = ChangedLines::compute(&root, merge_base.trim())?;
+    let mut comments: Vec<ReviewComment> = items
+        .iter()
+        .filter_map(|(path, item)| {
+            let comment = ReviewComment::of_item(item, |_| {
+                let path = path.strip_prefix(&root).ok()?;
+                Some(path.to_string_lossy().replace('\\', "/"))
+            })?;
+            changed_lines
+                .contains(path, comment.line)
+                .then_some(comment)
+        })
+        .collect();
+    retain_unposted(&target.url, &mut comments)?;
+    if comments.is_empty() {
+        debug!("no new review comment");
+        return Ok(());
+    }
+    let payload = json!({
+        "commit_id": commit,
+        "event": "COMMENT",
+        "body": format!(
+            "bacon found {} new diagnostic(s) running `{job}`",
+            comments.len(),
+        ),
+        "comments": comments.iter().map(ReviewComment::to_json).collect::<Value>(),
+    });
+    let mut curl = Command::new("curl");
+    curl.args(["--silent", "--show-error", "--fail", "--max-time", "20"])
+        // the headers are read from stdin, to keep the token out
+        // of the visible command line
+        .args(["--header", "@-"])
+        .arg("--data-binary")
+        .arg(payload.to_string())
+        .arg(&target.url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    let headers = format!(
+        "Authorization: Bearer {}\nAccept: application/vnd.github+json\nX-GitHub-Api-Version: 2022-11-28\n",
+        target.token,
+    );
+    if let Err(e) = send(curl, &headers) {
+        // so that they're tried again on the next execution
+        forget_posted(&target.url, &comments);
+        return Err(e.context("failed to post the review"));
+    }
+    info!("{} review comment(s) posted", comments.len());
+    save_posted(&target.url);
+    Ok(())
+}
+
+/// Return the path of the file keeping the keys of the comments already
+/// posted on the review target, if the system has a state directory
+fn posted_comments_path(url: &str) -> Option<PathBuf> {
+    let mut hasher = FxHasher::default();
+    url.hash(&mut hasher);
+    Some(
+        bacon_state_dir()?
+            .join("reviews")
+            .join(format!("{:016x}.json", hasher.finish())),
+    )
+}
+
+/// Keep only the comments not already posted on the review target,
+/// according to this session and the previous ones, and register
+/// them as posted
+fn retain_unposted(
+    url: &str,
+    comments: &mut Vec<ReviewComment>,
+) -> Result<()> {
+    let mut posted = POSTED_COMMENTS
+        .lock()
+        .map_err(|_| anyhow!("poisoned lock"))?;
+    let posted = posted
+        .get_or_insert_with(HashMap::new)
+        .entry(url.to_string())
+        .or_insert_with(|| {
+            posted_comments_path(url)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        });
+    comments.retain(|comment| posted.insert(comment.key()));
+    Ok(())
+}
+
+fn forget_posted(
+    url: &str,
+    comments: &[ReviewComment],
+) {
+    if let Ok(mut posted) = POSTED_COMMENTS.lock() {
+        if let Some(posted) = posted.as_mut().and_then(|posted| posted.get_mut(url)) {
+            for comment in comments {
+                posted.remove(&comment.key());
+            }
+        }
+    }
+}
+
+/// Write the keys of the comments posted on the review target, so that
+/// they're not posted again by the next sessions
+fn save_posted(url: &str) {
+    let Some(path) = posted_comments_path(url) else {
+        return;
+    };
+    let json = match POSTED_COMMENTS.lock() {
+        Ok(posted) => {
+            let Some(posted) = posted.as_ref().and_then(|posted| posted.get(url)) else {
+                return;
+            };
+            serde_json::to_string(posted)
+        }
+        Err(_) => return,
+    };
+    let written = json.map_err(anyhow::Error::from).and_then(|json| {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        write_atomically(&path, json)?;
+        Ok(())
+    });
+    if let Err(e) = written {
+        warn!("failed to save the posted review comments: {e}");
+    }
+}
+
+fn send(
+    mut curl: Command,
+    headers: &str,
+) -> Result<()> {
+    let mut child = curl.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(headers.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
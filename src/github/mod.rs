@@ -0,0 +1,9 @@
+mod github_config;
+mod github_settings;
+mod review_comment;
+
+pub use {
+    github_config::*,
+    github_settings::*,
+    review_comment::*,
+};
@@ -0,0 +1,68 @@
+use {
+    crate::*,
+    serde_json::{
+        Value,
+        json,
+    },
+};
+
+/// A comment of a pull request review, on a line of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewComment {
+    /// path of the file, relative to the root of the repository
+    pub path: String,
+    pub line: usize,
+    pub body: String,
+}
+
+impl ReviewComment {
+    /// Build the comment of an item of `report_locations`, the
+    /// `repo_path` function giving the path relative to the root of
+    /// the repository of a path of the report
+    pub fn of_item(
+        item: &Value,
+        repo_path: impl FnOnce(&str) -> Option<String>,
+    ) -> Option<Self> {
+        let kind = item["kind"].as_str()?;
+        let message = item["message"].as_str()?;
+        let location = item["location"].as_str()?;
+        let (path, line, _) = split_location(location);
+        Some(Self {
+            path: repo_path(path)?,
+            line: line.parse().ok()?,
+            body: format!("**{kind}**: {message}"),
+        })
+    }
+    /// What identifies the comment, so that it's not posted twice
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}", self.path, self.line, self.body)
+    }
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": self.path,
+            "line": self.line,
+            "side": "RIGHT",
+            "body": self.body,
+        })
+    }
+}
+
+#[test]
+fn test_review_comment_of_item() {
+    let item = json!({
+        "kind": "warning",
+        "location": "src/main.rs:3:9",
+        "message": "unused variable: `x`",
+    });
+    let comment = ReviewComment::of_item(&item, |path| Some(format!("crate/{path}")));
+    assert_eq!(
+        comment,
+        Some(ReviewComment {
+            path: "crate/src/main.rs".to_string(),
+            line: 3,
+            body: "**warning**: unused variable: `x`".to_string(),
+        }),
+    );
+    // files outside the repository can't be commented
+    assert_eq!(ReviewComment::of_item(&item, |_| None), None);
+}
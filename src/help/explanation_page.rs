@@ -0,0 +1,108 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    std::process::Command,
+    termimad::{
+        Area,
+        CompoundStyle,
+        FmtText,
+        MadSkin,
+        TextView,
+        crossterm::style::{
+            Attribute,
+            Color::*,
+        },
+    },
+};
+
+/// A scrollable page displaying the explanation of an error
+/// code (by `rustc --explain`) or of a clippy lint, or the
+/// preview of suggested changes
+pub struct ExplanationPage {
+    area: Area,
+    skin: MadSkin,
+    markdown: String,
+    scroll: usize,
+}
+
+impl ExplanationPage {
+    /// Get the explanation of the code, either a rustc error code
+    /// (eg `E0599`) or a clippy lint (eg `clippy::unwrap_used`)
+    pub fn new(code: &str) -> Result<Self> {
+        let output = if let Some(lint) = code.strip_prefix("clippy::") {
+            Command::new("cargo")
+                .args(["clippy", "--explain", lint])
+                .output()?
+        } else {
+            Command::new("rustc").args(["--explain", code]).output()?
+        };
+        if !output.status.success() {
+            bail!(
+                "no explanation for {}: {}",
+                code,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let explanation = String::from_utf8_lossy(&output.stdout);
+        Ok(Self::from_markdown(format!("# {code}\n\n{explanation}")))
+    }
+    pub fn from_markdown(markdown: String) -> Self {
+        let mut skin = MadSkin::default();
+        skin.bullet.set_fg(AnsiValue(204));
+        skin.italic = CompoundStyle::new(Some(AnsiValue(204)), None, Attribute::Bold.into());
+        Self {
+            area: Area::default(),
+            skin,
+            markdown,
+            scroll: 0,
+        }
+    }
+
+    /// draw the explanation in the given area
+    pub fn draw(
+        &mut self,
+        w: &mut W,
+        area: Area,
+    ) -> Result<()> {
+        self.area = area;
+        let fmt_text = FmtText::from(
+            &self.skin,
+            &self.markdown,
+            Some((self.area.width - 1) as usize),
+        );
+        let mut text_view = TextView::from(&self.area, &fmt_text);
+        self.scroll = text_view.set_scroll(self.scroll);
+        Ok(text_view.write_on(w)?)
+    }
+
+    pub fn apply_scroll_command(
+        &mut self,
+        cmd: ScrollCommand,
+    ) {
+        let fmt_text = FmtText::from(
+            &self.skin,
+            &self.markdown,
+            Some((self.area.width - 1) as usize),
+        );
+        let mut text_view = TextView::from(&self.area, &fmt_text);
+        text_view.set_scroll(self.scroll);
+        match cmd {
+            ScrollCommand::Top => {
+                text_view.scroll = 0;
+            }
+            ScrollCommand::Bottom => {
+                text_view.set_scroll(text_view.content_height());
+            }
+            ScrollCommand::Lines(lines) => {
+                text_view.try_scroll_lines(lines);
+            }
+            ScrollCommand::MilliPages(milli_pages) => {
+                text_view.try_scroll_pages(milli_pages as f64 / 1000f64);
+            }
+        }
+        self.scroll = text_view.scroll;
+    }
+}
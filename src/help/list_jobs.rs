@@ -1,5 +1,8 @@
 use {
     crate::*,
+    anyhow::Result,
+    serde::Serialize,
+    std::io::Write,
     termimad::{
         MadSkin,
         minimad::{
@@ -9,6 +12,15 @@ use {
     },
 };
 
+/// The jobs to list, that is the ones which aren't hidden
+fn visible_jobs(settings: &Settings) -> Vec<(&String, &Job)> {
+    settings
+        .jobs
+        .iter()
+        .filter(|(_, job)| !job.is_hidden())
+        .collect()
+}
+
 pub fn print_jobs(settings: &Settings) {
     static MD: &str = r#"
     |:-:|:-|
@@ -20,16 +32,143 @@ pub fn print_jobs(settings: &Settings) {
     |-|-|
     default job: ${default_job}
     "#;
+    static MD_WITH_DESCRIPTIONS: &str = r#"
+    |:-:|:-|:-|
+    |**job**|**command**|**description**|
+    |:-:|:-|:-|
+    ${jobs
+    |${job_name}|${job_command}|${job_description}|
+    }
+    |-|-|-|
+    default job: ${default_job}
+    "#;
     let mut expander = OwningTemplateExpander::new();
-    let mut jobs: Vec<_> = settings.jobs.iter().collect();
+    let mut jobs: Vec<_> = visible_jobs(settings);
     jobs.sort_by_key(|(name, _)| name.to_string());
     for (name, job) in &jobs {
+        let job_name = match &job.aliases {
+            Some(aliases) if !aliases.is_empty() => format!("{name} ({})", aliases.join(", ")),
+            _ => name.to_string(),
+        };
         expander
             .sub("jobs")
-            .set("job_name", name)
-            .set("job_command", job.command.join(" "));
+            .set("job_name", job_name)
+            .set("job_command", job.command.join(" "))
+            .set("job_description", job.description.as_deref().unwrap_or(""));
     }
     expander.set("default_job", &settings.default_job);
+    let md = if jobs.iter().any(|(_, job)| job.description.is_some()) {
+        MD_WITH_DESCRIPTIONS
+    } else {
+        MD
+    };
     let skin = MadSkin::default();
-    skin.print_owning_expander(&expander, &TextTemplate::from(MD));
+    skin.print_owning_expander(&expander, &TextTemplate::from(md));
+}
+
+/// A job, as listed by `bacon --list-jobs --json`
+#[derive(Debug, Serialize)]
+struct JobListItem<'s> {
+    name: &'s str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    aliases: &'s [String],
+    description: Option<&'s str>,
+    command: &'s [String],
+    analyzer: AnalyzerRef,
+    /// the key combinations launching the job
+    keybindings: Vec<String>,
+    default: bool,
+}
+
+/// Print the jobs as JSON, for launchers, completions, or editor plugins
+pub fn print_jobs_json(
+    w: &mut impl Write,
+    settings: &Settings,
+) -> Result<()> {
+    let reverse_map = settings.keybindings.build_reverse_map();
+    let mut names: Vec<&String> = visible_jobs(settings)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    let jobs: Vec<JobListItem> = names
+        .into_iter()
+        .map(|name| {
+            let job = &settings.jobs[name];
+            let mut merged = settings.all_jobs.clone();
+            merged.apply(job);
+            let action = Action::Job(JobRef::from_job_name(name.as_str()));
+            let mut keybindings: Vec<String> = reverse_map
+                .get(&action)
+                .map(|keys| keys.iter().map(|key| key.to_string()).collect())
+                .unwrap_or_default();
+            keybindings.sort();
+            JobListItem {
+                name,
+                aliases: job.aliases.as_deref().unwrap_or_default(),
+                description: job.description.as_deref(),
+                command: &job.command,
+                analyzer: merged.effective_analyzer(),
+                keybindings,
+                default: settings.default_job == ConcreteJobRef::from_job_name(name.as_str()),
+            }
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *w, &jobs)?;
+    writeln!(w)?;
+    Ok(())
+}
+
+/// Print the job names, one per line and sorted, followed by a tab and
+/// the description when there's one.
+///
+/// This format is stable, to be used in scripts and shell completions.
+pub fn print_jobs_porcelain(
+    w: &mut impl Write,
+    settings: &Settings,
+) -> Result<()> {
+    let mut jobs: Vec<_> = visible_jobs(settings);
+    jobs.sort_by_key(|(name, _)| name.to_string());
+    for (name, job) in jobs {
+        match &job.description {
+            Some(description) => {
+                let description = description.lines().next().unwrap_or_default();
+                writeln!(w, "{name}\t{description}")?;
+            }
+            None => writeln!(w, "{name}")?,
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_visible_jobs() {
+    let mut settings = Settings::default();
+    settings.jobs.insert(
+        "check".to_string(),
+        Job {
+            description: Some("Check the code\nwith the default features".to_string()),
+            ..Default::default()
+        },
+    );
+    settings.jobs.insert(
+        "helper".to_string(),
+        Job {
+            hidden: Some(true),
+            ..Default::default()
+        },
+    );
+    settings.jobs.insert("test".to_string(), Job::default());
+    let mut names: Vec<&String> = visible_jobs(&settings)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    assert_eq!(names, ["check", "test"]);
+    let mut porcelain = Vec::new();
+    print_jobs_porcelain(&mut porcelain, &settings).unwrap();
+    assert_eq!(
+        String::from_utf8(porcelain).unwrap(),
+        "check\tCheck the code\ntest\n"
+    );
 }
@@ -1,11 +1,15 @@
 mod examples;
+mod explanation_page;
 mod help_line;
 mod help_page;
 mod list_jobs;
+mod print_config;
 
 pub use {
     examples::*,
+    explanation_page::*,
     help_line::*,
     help_page::*,
     list_jobs::*,
+    print_config::*,
 };
@@ -0,0 +1,184 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    std::{
+        io::Write,
+        path::PathBuf,
+    },
+};
+
+/// Print, as commented TOML, the settings once merged and what running
+/// the job would do: the exact command with its env, the analyzer, and
+/// the watched and ignored files.
+///
+/// Nothing is run.
+pub fn print_config(
+    w: &mut impl Write,
+    context: &Context,
+    settings: &Settings,
+) -> Result<()> {
+    let Some((concrete_job_ref, job)) = JobStack::default().pick_job(&JobRef::Initial, settings)?
+    else {
+        bail!("No job to print");
+    };
+    let mission = context.mission(concrete_job_ref, &job, settings)?;
+    let job = &mission.job;
+    // the command hook isn't run: nothing is
+    let command = mission.get_command()?;
+
+    writeln!(w, "# effective configuration, once all sources are merged")?;
+    writeln!(
+        w,
+        "# (the origin of each property is given by `bacon --show-config-origin`)"
+    )?;
+
+    writeln!(w, "\n[settings]")?;
+    writeln!(w, "config_files = {}", paths(&settings.config_files))?;
+    writeln!(w, "default_job = {}", string(&settings.default_job))?;
+    if let Some(profile) = &settings.profile {
+        writeln!(w, "profile = {}", string(profile))?;
+    }
+    writeln!(w, "summary = {}", settings.summary)?;
+    writeln!(w, "wrap = {}", settings.wrap)?;
+    writeln!(w, "reverse = {}", settings.reverse)?;
+    writeln!(w, "help_line = {}", settings.help_line)?;
+    if let Some(features) = &settings.features {
+        writeln!(w, "features = {}", string(features))?;
+    }
+    writeln!(w, "all_features = {}", settings.all_features)?;
+    writeln!(w, "no_default_features = {}", settings.no_default_features)?;
+    let mut jobs: Vec<&String> = settings.jobs.keys().collect();
+    jobs.sort();
+    writeln!(w, "jobs = {}", strings(jobs))?;
+
+    writeln!(w, "\n[job] # {}", mission.concrete_job_ref.badge_label())?;
+    if let Some(hook) = &job.hooks.command {
+        writeln!(
+            w,
+            "# the command hook {}, not run here, may change the command",
+            strings(hook)
+        )?;
+    }
+    writeln!(w, "command = {}", strings(command.tokens()))?;
+    if let Some(dir) = command.get_current_dir() {
+        writeln!(w, "execution_directory = {}", string(dir.to_string_lossy()))?;
+    }
+    let mut envs: Vec<(String, String)> = command
+        .get_envs()
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string_lossy().to_string(),
+                v.to_string_lossy().to_string(),
+            )
+        })
+        .collect();
+    envs.sort();
+    let envs: Vec<String> = envs
+        .iter()
+        .map(|(k, v)| format!("{} = {}", toml_key(k), string(v)))
+        .collect();
+    writeln!(w, "env = {{ {} }}", envs.join(", "))?;
+    let analyzer = toml::Value::try_from(mission.analyzer())?;
+    let options = mission.analyzer_options();
+    if options == AnalyzerOptions::default() {
+        writeln!(w, "analyzer = {analyzer}")?;
+    } else {
+        let options = toml::Value::try_from(options)?;
+        writeln!(w, "analyzer = {{ name = {analyzer}, options = {options} }}")?;
+    }
+    writeln!(w, "json_messages = {}", mission.uses_json_messages())?;
+    writeln!(w, "need_stdout = {}", mission.need_stdout())?;
+    writeln!(w, "pty = {}", mission.uses_pty())?;
+    writeln!(w, "allow_failures = {}", job.allow_failures())?;
+    writeln!(
+        w,
+        "allow_warnings = {}",
+        toml::Value::try_from(job.allow_warnings())?
+    )?;
+    writeln!(w, "background = {}", job.background())?;
+    writeln!(
+        w,
+        "on_change_strategy = {}",
+        toml::Value::try_from(job.on_change_strategy())?
+    )?;
+    if let Some(kill) = mission.kill_command() {
+        writeln!(w, "kill = {}", strings(kill))?;
+    }
+    if let Some(action) = &job.on_success {
+        writeln!(w, "on_success = {}", string(action))?;
+    }
+    if let Some(action) = &job.on_failure {
+        writeln!(w, "on_failure = {}", string(action))?;
+    }
+
+    writeln!(w, "\n[watch]")?;
+    writeln!(w, "paths = {}", paths(&mission.paths_to_watch))?;
+    writeln!(w, "apply_gitignore = {}", job.apply_gitignore())?;
+    writeln!(
+        w,
+        "apply_global_gitignore = {}",
+        job.apply_global_gitignore()
+    )?;
+    let mut dirs = vec![mission.package_directory.clone()];
+    dirs.extend(mission.workspace_directory.clone());
+    let ignore_files: Vec<PathBuf> = job
+        .ignore_files()
+        .iter()
+        .flat_map(|name| dirs.iter().map(move |dir| dir.join(name)))
+        .filter(|path| path.exists())
+        .collect();
+    writeln!(w, "ignore_files = {}", paths(&ignore_files))?;
+    writeln!(w, "ignore = {}", strings(&job.ignore))?;
+    for (root, patterns) in &mission.watch_root_ignores {
+        writeln!(
+            w,
+            "# ignored in {}: {}",
+            root.to_string_lossy(),
+            strings(patterns)
+        )?;
+    }
+    if let Some(watch_globs) = &job.watch_globs {
+        writeln!(w, "watch_globs = {}", strings(watch_globs))?;
+    }
+    if let Some(workspace_ignorer) = &mission.workspace_ignorer {
+        writeln!(
+            w,
+            "workspace_excluded = {}",
+            paths(workspace_ignorer.excluded_directories())
+        )?;
+    }
+    writeln!(
+        w,
+        "ignore_editor_temp_files = {}",
+        job.ignore_editor_temp_files()
+    )?;
+    writeln!(w, "watch_dep_info = {}", job.watch_dep_info())?;
+    Ok(())
+}
+
+fn string(s: impl ToString) -> toml::Value {
+    toml::Value::String(s.to_string())
+}
+
+fn strings<I: IntoIterator<Item = S>, S: ToString>(items: I) -> toml::Value {
+    toml::Value::Array(items.into_iter().map(string).collect())
+}
+
+fn paths(paths: &[PathBuf]) -> toml::Value {
+    strings(paths.iter().map(|path| path.to_string_lossy()))
+}
+
+fn toml_key(key: &str) -> String {
+    if key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        format!("{key:?}")
+    }
+}
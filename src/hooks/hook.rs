@@ -0,0 +1,354 @@
+use {
+    crate::*,
+    anyhow::{
+        Context as _,
+        Result,
+        bail,
+    },
+    rustc_hash::FxHashSet,
+    serde::Deserialize,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        collections::HashMap,
+        io::{
+            self,
+            Read,
+            Write,
+        },
+        path::Path,
+        process::{
+            Command,
+            Output,
+            Stdio,
+        },
+        thread,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+    termimad::crossbeam::channel::{
+        self,
+        Receiver,
+        Sender,
+    },
+};
+
+/// Interval between two checks of the end of a hook
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// An item of the report, as kept by the report hook, with
+/// the severity it should be given, if changed
+#[derive(Debug, Deserialize)]
+struct HookItem {
+    index: usize,
+    severity: Option<Severity>,
+}
+
+/// The hooks run at the end of an execution, in a background thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Report,
+    Success,
+}
+
+/// The outcome of a hook run in the background
+#[derive(Debug)]
+pub struct HookOutcome {
+    pub kind: HookKind,
+    /// the execution the hook was run for
+    pub execution: usize,
+    pub tokens: Vec<String>,
+    pub output: Result<Output>,
+}
+
+/// Run the hook in the execution directory of the mission, with
+/// the input written as JSON on its stdin.
+///
+/// The hook is killed if it doesn't end before the timeout
+fn run_hook(
+    tokens: &[String],
+    execution_directory: &Path,
+    input: &Value,
+    timeout: Duration,
+) -> Result<Output> {
+    let Some((exe, args)) = tokens.split_first() else {
+        bail!("empty hook");
+    };
+    let mut child = Command::new(exe)
+        .args(args)
+        .current_dir(execution_directory)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to launch hook {exe:?}"))?;
+    // the pipes are handled in their own threads so that a hook writing
+    // before having read all its input doesn't block
+    let stdin = child.stdin.take().map(|mut stdin| {
+        let input = input.to_string();
+        thread::spawn(move || match stdin.write_all(input.as_bytes()) {
+            // the hook doesn't have to read its input
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+            _ => Ok(()),
+        })
+    });
+    let stdout = child.stdout.take().map(read_in_thread);
+    let stderr = child.stderr.take().map(read_in_thread);
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("hook {exe:?} didn't end in {timeout:?} and was killed");
+        }
+        thread::sleep(HOOK_POLL_INTERVAL);
+    };
+    if let Some(stdin) = stdin {
+        stdin.join().expect("hook stdin thread panicked")?;
+    }
+    let join = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        reader
+            .map(|reader| reader.join().expect("hook reader thread panicked"))
+            .unwrap_or_default()
+    };
+    let output = Output {
+        status,
+        stdout: join(stdout),
+        stderr: join(stderr),
+    };
+    debug!("hook {exe:?} exited with {}", output.status);
+    Ok(output)
+}
+
+fn read_in_thread<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+/// Check the hook succeeded
+fn check_hook_output(
+    tokens: &[String],
+    output: Output,
+) -> Result<Output> {
+    if !output.status.success() {
+        bail!(
+            "hook {:?} failed ({}): {}",
+            tokens.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+    Ok(output)
+}
+
+/// Start the command hook of the job, if any, in a background thread,
+/// the command it computes from the one of the configuration being
+/// sent when it ends.
+///
+/// This hook runs once per mission, before the first execution
+pub fn spawn_command_hook(mission: &Mission) -> Option<Receiver<Result<Vec<String>>>> {
+    let tokens = mission.job.hooks.command.as_ref()?;
+    let tokens: Vec<String> = tokens.iter().map(|t| mission.expand(t)).collect();
+    let input = json!({
+        "job": mission.concrete_job_ref.badge_label(),
+        "command": mission.job_command(),
+        "package_directory": mission.package_directory,
+        "execution_directory": mission.execution_directory,
+    });
+    let execution_directory = mission.execution_directory.clone();
+    let timeout = mission.job.hooks.timeout();
+    let (sender, receiver) = channel::bounded(1);
+    thread::spawn(move || {
+        let command = run_hook(&tokens, &execution_directory, &input, timeout)
+            .and_then(|output| check_hook_output(&tokens, output))
+            .and_then(|output| {
+                serde_json::from_slice(&output.stdout)
+                    .context("the command hook must write a JSON array of strings")
+            });
+        let _ = sender.send(command);
+    });
+    Some(receiver)
+}
+
+/// Start the report hook, or the success hook, of the job in a background
+/// thread, the outcome being sent when the hook ends
+pub fn spawn_report_hook(
+    kind: HookKind,
+    execution: usize,
+    mission: &Mission,
+    report: &Report,
+    sender: Sender<HookOutcome>,
+) {
+    let hooks = &mission.job.hooks;
+    let tokens = match kind {
+        HookKind::Report => &hooks.report,
+        HookKind::Success => &hooks.success,
+    };
+    let Some(tokens) = tokens else {
+        return;
+    };
+    let tokens: Vec<String> = tokens.iter().map(|t| mission.expand(t)).collect();
+    let input = match kind {
+        HookKind::Report => json!({
+            "job": mission.concrete_job_ref.badge_label(),
+            "items": hook_items(report),
+        }),
+        HookKind::Success => json!({
+            "job": mission.concrete_job_ref.badge_label(),
+            "success": mission.is_success(report),
+            "counts": {
+                "errors": report.stats.errors,
+                "test_fails": report.stats.test_fails,
+                "warnings": report.stats.warnings,
+                "passed_tests": report.stats.passed_tests,
+            },
+            "items": hook_items(report),
+        }),
+    };
+    let execution_directory = mission.execution_directory.clone();
+    let timeout = hooks.timeout();
+    thread::spawn(move || {
+        let output = run_hook(&tokens, &execution_directory, &input, timeout);
+        let _ = sender.send(HookOutcome {
+            kind,
+            execution,
+            tokens,
+            output,
+        });
+    });
+}
+
+impl HookOutcome {
+    /// Apply the outcome of the hook to the report: filter the items and
+    /// change their severities for the report hook, set the success for
+    /// the success hook
+    pub fn apply(
+        self,
+        report: &mut Report,
+    ) -> Result<()> {
+        let output = self.output?;
+        match self.kind {
+            HookKind::Report => {
+                let output = check_hook_output(&self.tokens, output)?;
+                let kept: Vec<HookItem> = serde_json::from_slice(&output.stdout)
+                    .context("the report hook must write a JSON array of items")?;
+                let mut removed: FxHashSet<usize> = hook_items(report)
+                    .iter()
+                    .filter_map(|item| item["index"].as_u64())
+                    .map(|index| index as usize)
+                    .collect();
+                let mut severities = HashMap::new();
+                for item in kept {
+                    removed.remove(&item.index);
+                    if let Some(severity) = item.severity {
+                        severities.insert(item.index, severity);
+                    }
+                }
+                report.remove_items(&removed);
+                report.apply_item_severities(&severities);
+            }
+            HookKind::Success => {
+                report.hook_success = match output.status.code() {
+                    Some(0) => Some(true),
+                    Some(1) => Some(false),
+                    _ => bail!(
+                        "success hook {:?} failed ({}): {}",
+                        self.tokens.join(" "),
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim(),
+                    ),
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The items of the report, as given to the hooks: their index,
+/// kind, title, code, and first location
+fn hook_items(report: &Report) -> Vec<Value> {
+    let mut items: Vec<Value> = Vec::new();
+    for (line_idx, line) in report.lines.iter().enumerate() {
+        let kind = match line.line_type {
+            LineType::Title(Kind::Error) => "error",
+            LineType::Title(Kind::Warning | Kind::External) => "warning",
+            LineType::Title(Kind::Info) => "info",
+            LineType::Title(Kind::TestFail | Kind::DoctestFail) => "test",
+            _ => {
+                if let Some(location) = line.location() {
+                    if let Some(item) = items.last_mut() {
+                        if item["index"] == line.item_idx && item["location"].is_null() {
+                            item["location"] = location.into();
+                        }
+                    }
+                }
+                continue;
+            }
+        };
+        items.push(json!({
+            "index": line.item_idx,
+            "kind": kind,
+            "external": line.line_type == LineType::Title(Kind::External),
+            "title": line.title_message().unwrap_or_default(),
+            "code": report.item_code(line_idx),
+            "location": null,
+        }));
+    }
+    items
+}
+
+#[test]
+fn test_hook_items() {
+    let mut lines = Vec::new();
+    let mut add_item = |item_idx, kind, label: &str, message: &str, location: Option<&str>| {
+        lines.push(Line::title(item_idx, kind, label, message));
+        if let Some(location) = location {
+            lines.push(Line::from_raw(
+                item_idx,
+                LineType::Location,
+                &format!("  --> {location}"),
+            ));
+        }
+    };
+    add_item(
+        1,
+        Kind::Warning,
+        "warning",
+        "unused variable",
+        Some("src/main.rs:3:9"),
+    );
+    add_item(2, Kind::TestFail, "test", "tests::it_works", None);
+    let report = Report::from_lines(lines);
+    let items = hook_items(&report);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["index"], 1);
+    assert_eq!(items[0]["kind"], "warning");
+    assert_eq!(items[0]["title"], "unused variable");
+    assert_eq!(items[0]["location"], "src/main.rs:3:9");
+    assert_eq!(items[1]["kind"], "test");
+    assert!(items[1]["location"].is_null());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hook_timeout() {
+    let input = serde_json::json!({});
+    let tokens = vec!["sleep".to_string(), "5".to_string()];
+    let start = Instant::now();
+    let result = run_hook(&tokens, Path::new("."), &input, Duration::from_millis(100));
+    assert!(result.is_err());
+    assert!(start.elapsed() < Duration::from_secs(2));
+    let tokens = vec!["cat".to_string()];
+    let output = run_hook(&tokens, Path::new("."), &input, Duration::from_secs(5)).unwrap();
+    assert_eq!(output.stdout, b"{}");
+}
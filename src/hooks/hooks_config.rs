@@ -0,0 +1,49 @@
+use {
+    crate::*,
+    serde::Deserialize,
+    std::time::Duration,
+};
+
+/// The default time a hook may run before being killed
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `hooks` part of a job: external programs, written in any
+/// language, receiving JSON on stdin, for the needs the declarative
+/// configuration doesn't cover
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct HooksConfig {
+    /// Computes the command of the job, written as a JSON array
+    /// of strings on stdout, from the one of the configuration
+    pub command: Option<Vec<String>>,
+    /// Filters the items of the report: writes on stdout the JSON
+    /// array of the items to keep, optionally with a new severity
+    pub report: Option<Vec<String>>,
+    /// Decides whether the execution is a success, with its exit code
+    pub success: Option<Vec<String>>,
+    /// How long a hook may run before being killed
+    pub timeout: Option<Period>,
+}
+
+impl HooksConfig {
+    pub fn apply(
+        &mut self,
+        hc: &HooksConfig,
+    ) {
+        if hc.command.is_some() {
+            self.command.clone_from(&hc.command);
+        }
+        if hc.report.is_some() {
+            self.report.clone_from(&hc.report);
+        }
+        if hc.success.is_some() {
+            self.success.clone_from(&hc.success);
+        }
+        if let Some(timeout) = hc.timeout {
+            self.timeout = Some(timeout);
+        }
+    }
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+            .map_or(DEFAULT_HOOK_TIMEOUT, |period| period.duration)
+    }
+}
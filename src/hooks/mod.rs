@@ -0,0 +1,7 @@
+mod hook;
+mod hooks_config;
+
+pub use {
+    hook::*,
+    hooks_config::*,
+};
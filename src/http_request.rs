@@ -0,0 +1,206 @@
+use std::{
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        Read,
+        Write,
+    },
+    net::{
+        IpAddr,
+        TcpStream,
+    },
+    time::Duration,
+};
+
+/// The max size of the request line and headers of a request
+const MAX_HEAD_SIZE: u64 = 8 * 1024;
+
+/// How long a client may take to send its request, or to read
+/// the response
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The request line and the headers of a request received by one
+/// of the small HTTP servers of bacon (triggers and dashboard)
+#[derive(Debug, Default)]
+pub struct HttpRequestHead {
+    pub request_line: String,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpRequestHead {
+    /// Read the head of the request, setting timeouts on the stream.
+    ///
+    /// Return the status of the error response when the request
+    /// can't be read
+    pub fn read(stream: &TcpStream) -> Result<Self, &'static str> {
+        let with_timeouts = stream
+            .set_read_timeout(Some(TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)));
+        if with_timeouts.is_err() {
+            return Err("500 Internal Server Error");
+        }
+        Self::read_from(stream)
+    }
+    fn read_from(reader: impl Read) -> Result<Self, &'static str> {
+        let mut reader = BufReader::new(reader.take(MAX_HEAD_SIZE));
+        let mut head = Self::default();
+        let read_line = |reader: &mut BufReader<_>, line: &mut String| match reader.read_line(line)
+        {
+            Ok(n) => Ok(n),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Err("408 Request Timeout")
+            }
+            Err(_) => Err("400 Bad Request"),
+        };
+        read_line(&mut reader, &mut head.request_line)?;
+        loop {
+            let mut header = String::new();
+            if read_line(&mut reader, &mut header)? == 0 {
+                // the client ended the request early, or it's too long
+                return if reader.get_ref().limit() == 0 {
+                    Err("431 Request Header Fields Too Large")
+                } else {
+                    Err("400 Bad Request")
+                };
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                return Ok(head);
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                head.headers
+                    .push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+    }
+    /// Return the value of the header, whose name must be lowercase
+    pub fn header(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+    /// Return the value of the cookie, if the request has it
+    pub fn cookie(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        self.header("cookie")?
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+    /// Return the token of the `Authorization: Bearer` header, if any
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.header("authorization")?
+            .strip_prefix("Bearer ")
+            .map(str::trim)
+    }
+    /// Tell whether the request is addressed to a loopback host,
+    /// which isn't the case of a DNS rebinding attack
+    pub fn has_loopback_host(&self) -> bool {
+        self.header("host").map_or(true, is_loopback_host)
+    }
+    /// Tell whether the request was sent by a browser, from a page
+    /// which isn't served by a loopback host
+    pub fn is_cross_origin(&self) -> bool {
+        if self.header("sec-fetch-site") == Some("cross-site") {
+            return true;
+        }
+        self.header("origin").is_some_and(|origin| {
+            let host = origin
+                .split_once("://")
+                .map_or("", |(_, rest)| rest.split('/').next().unwrap_or_default());
+            !is_loopback_host(host)
+        })
+    }
+}
+
+/// Write a response with no body
+pub fn write_empty_response(
+    mut stream: &TcpStream,
+    status: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    )
+}
+
+/// Compare the secrets in a time not depending on the position
+/// of the first difference
+pub fn secrets_match(
+    a: &str,
+    b: &str,
+) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Tell whether the host, with an optional port, is `localhost` or
+/// a loopback IP address
+fn is_loopback_host(host: &str) -> bool {
+    let name = if let Some(bracketed) = host.strip_prefix('[') {
+        bracketed.split(']').next().unwrap_or_default()
+    } else {
+        host.split(':').next().unwrap_or_default()
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+#[test]
+fn test_http_request_head() {
+    let head = HttpRequestHead::read_from(
+        "POST /job/clippy HTTP/1.1\r\nHost: localhost:8787\r\nOrigin: https://example.com\r\n\r\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(head.request_line.trim(), "POST /job/clippy HTTP/1.1");
+    assert!(head.has_loopback_host());
+    assert!(head.is_cross_origin());
+    let head =
+        HttpRequestHead::read_from("POST / HTTP/1.1\r\nHost: bacon.example.com\r\n\r\n".as_bytes())
+            .unwrap();
+    assert!(!head.has_loopback_host());
+    assert!(!head.is_cross_origin());
+    let head = HttpRequestHead::read_from(
+        "GET / HTTP/1.1\r\nHost: [::1]:8080\r\nOrigin: http://127.0.0.1:8080\r\n\r\n".as_bytes(),
+    )
+    .unwrap();
+    assert!(head.has_loopback_host());
+    assert!(!head.is_cross_origin());
+    let head = HttpRequestHead::read_from(
+        "GET / HTTP/1.1\r\nCookie: theme=dark; bacon-token=s3cr3t\r\nAuthorization: Bearer abc\r\n\r\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(head.cookie("bacon-token"), Some("s3cr3t"));
+    assert_eq!(head.cookie("other"), None);
+    assert_eq!(head.bearer_token(), Some("abc"));
+    assert!(secrets_match("s3cr3t", "s3cr3t"));
+    assert!(!secrets_match("s3cr3t", "s3cr3x"));
+    assert!(!secrets_match("s3cr3t", "s3c"));
+    let huge = format!("GET / HTTP/1.1\r\nX-Big: {}\r\n\r\n", "a".repeat(10_000));
+    assert_eq!(
+        HttpRequestHead::read_from(huge.as_bytes()).unwrap_err(),
+        "431 Request Header Fields Too Large",
+    );
+    assert_eq!(
+        HttpRequestHead::read_from("GET / HTTP/1.1\r\n".as_bytes()).unwrap_err(),
+        "400 Bad Request",
+    );
+}
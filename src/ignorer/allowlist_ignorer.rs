@@ -0,0 +1,35 @@
+use {
+    crate::*,
+    anyhow::Result,
+    std::path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// An ignorer excluding all paths but the listed ones, eg to watch
+/// given files in a directory
+pub struct AllowlistIgnorer {
+    paths: Vec<PathBuf>,
+}
+
+impl AllowlistIgnorer {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self { paths }
+    }
+}
+
+impl Ignorer for AllowlistIgnorer {
+    fn excludes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        Ok(!self.paths.iter().any(|allowed| allowed == path))
+    }
+    fn excludes_directory(
+        &mut self,
+        _dir: &Path,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+}
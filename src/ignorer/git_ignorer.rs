@@ -36,6 +36,24 @@ impl Ignorer for GitIgnorer {
     ) -> Result<bool> {
         self.excludes_all_paths(&[paths])
     }
+    fn excludes_directory(
+        &mut self,
+        dir: &Path,
+    ) -> Result<bool> {
+        let Some(work_dir) = self.repo.work_dir() else {
+            return Ok(false);
+        };
+        let Ok(relative_path) = dir.strip_prefix(work_dir) else {
+            return Ok(false);
+        };
+        if relative_path.as_os_str().is_empty() {
+            return Ok(false);
+        }
+        let worktree = self.repo.worktree().context("a worktree should exist")?;
+        let mut cache = worktree.excludes(None)?;
+        let platform = cache.at_path(relative_path, Some(gix::index::entry::Mode::DIR))?;
+        Ok(platform.is_excluded())
+    }
 }
 impl GitIgnorer {
     fn excludes_all_paths(
@@ -1,45 +1,194 @@
 use {
     crate::*,
     anyhow::Result,
-    std::path::Path,
+    std::path::{
+        Path,
+        PathBuf,
+    },
 };
 
+/// Patterns of the temporary, swap, and backup files written by
+/// common editors, which shouldn't trigger jobs
+pub static EDITOR_TEMP_FILES: &[&str] = &[
+    "*.sw[a-p]", // vim swap files
+    "4913",      // file written by vim to test write permissions
+    "*~",        // vim, emacs, and many others backup files
+    ".#*",       // emacs lock files
+    "#*#",       // emacs auto-save files
+    "*___jb_tmp___",
+    "*___jb_old___",
+    "*.kate-swp",
+    "*.tmp",
+    "*.crswap",
+];
+
+/// An ignorer based on glob patterns.
+///
+/// As in gitignore files, a pattern starting with `!` re-includes
+/// paths excluded by a previous pattern, and the last matching
+/// pattern wins.
 #[derive(Default)]
 pub struct GlobIgnorer {
+    rules: Vec<GlobRule>,
+    /// if set, the rules only apply to the paths in this directory
+    scope: Option<PathBuf>,
+}
+
+struct GlobRule {
     globs: Vec<glob::Pattern>,
+    negative: bool,
 }
 
 impl GlobIgnorer {
+    /// Build an ignorer excluding the temporary files of common editors
+    pub fn editor_temp_files() -> Self {
+        let mut ignorer = Self::default();
+        for pattern in EDITOR_TEMP_FILES {
+            if let Err(e) = ignorer.add(pattern, Path::new("/")) {
+                warn!("invalid editor temp file pattern {pattern}: {e}");
+            }
+        }
+        ignorer
+    }
+    /// Build an ignorer whose rules only apply inside the given directory
+    pub fn scoped(scope: PathBuf) -> Self {
+        Self {
+            rules: Vec::new(),
+            scope: Some(scope),
+        }
+    }
     pub fn add(
         &mut self,
         pattern: &str,
         root: &Path,
     ) -> Result<()> {
+        let (pattern, negative) = match pattern.strip_prefix('!') {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+        let mut globs = Vec::new();
         if pattern.starts_with('/') {
-            self.globs.push(glob::Pattern::new(pattern)?);
+            globs.push(glob::Pattern::new(pattern)?);
             // it's probably a path relative to the root of the package
-            let pattern = root.join(pattern);
+            let pattern = root.join(pattern.trim_start_matches('/'));
             let pattern = pattern.to_string_lossy();
-            self.globs.push(glob::Pattern::new(&pattern)?);
+            globs.push(glob::Pattern::new(&pattern)?);
         } else {
             // as glob doesn't work with non absolute paths, we make it absolute
-            self.globs
-                .push(glob::Pattern::new(&format!("/**/{}", pattern))?);
+            globs.push(glob::Pattern::new(&format!("/**/{}", pattern))?);
         }
+        self.rules.push(GlobRule { globs, negative });
         Ok(())
     }
 }
 
+impl GlobIgnorer {
+    /// Return the last rule matching the path, if any
+    fn last_matching_rule(
+        &self,
+        path: &Path,
+    ) -> Option<&GlobRule> {
+        if self
+            .scope
+            .as_ref()
+            .is_some_and(|scope| !path.starts_with(scope))
+        {
+            return None;
+        }
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.globs.iter().any(|glob| glob.matches_path(path)))
+    }
+}
+
 impl Ignorer for GlobIgnorer {
     fn excludes(
         &mut self,
         paths: &Path,
     ) -> Result<bool> {
-        for glob in &self.globs {
-            if glob.matches_path(paths) {
-                return Ok(true);
-            }
-        }
-        Ok(false)
+        Ok(self
+            .last_matching_rule(paths)
+            .is_some_and(|rule| !rule.negative))
+    }
+    fn includes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        Ok(self
+            .last_matching_rule(path)
+            .is_some_and(|rule| rule.negative))
+    }
+}
+
+#[test]
+fn test_negative_glob_patterns() {
+    let root = Path::new("/proj");
+    let mut ignorer = GlobIgnorer::default();
+    ignorer.add("target/**", root).unwrap();
+    ignorer.add("!target/generated/schema.rs", root).unwrap();
+    ignorer.add("*.tmp", root).unwrap();
+    assert!(
+        ignorer
+            .excludes(Path::new("/proj/target/debug/a.rs"))
+            .unwrap()
+    );
+    assert!(
+        !ignorer
+            .excludes(Path::new("/proj/target/generated/schema.rs"))
+            .unwrap()
+    );
+    assert!(
+        ignorer
+            .excludes(Path::new("/proj/target/generated/schema.tmp"))
+            .unwrap()
+    );
+    assert!(!ignorer.excludes(Path::new("/proj/src/main.rs")).unwrap());
+    assert!(
+        ignorer
+            .includes(Path::new("/proj/target/generated/schema.rs"))
+            .unwrap()
+    );
+    assert!(!ignorer.includes(Path::new("/proj/src/main.rs")).unwrap());
+}
+
+#[test]
+fn test_scoped_glob_ignorer() {
+    let root = Path::new("/shared-protos");
+    let mut ignorer = GlobIgnorer::scoped(root.to_path_buf());
+    ignorer.add("*.bin", root).unwrap();
+    ignorer.add("/build/**", root).unwrap();
+    assert!(
+        ignorer
+            .excludes(Path::new("/shared-protos/a/b.bin"))
+            .unwrap()
+    );
+    assert!(
+        ignorer
+            .excludes(Path::new("/shared-protos/build/x.proto"))
+            .unwrap()
+    );
+    assert!(
+        !ignorer
+            .excludes(Path::new("/shared-protos/a.proto"))
+            .unwrap()
+    );
+    assert!(!ignorer.excludes(Path::new("/pack/data.bin")).unwrap());
+}
+
+#[test]
+fn test_editor_temp_files() {
+    let mut ignorer = GlobIgnorer::editor_temp_files();
+    for path in [
+        "/pack/src/.main.rs.swp",
+        "/pack/src/main.rs~",
+        "/pack/src/.#main.rs",
+        "/pack/src/#main.rs#",
+        "/pack/src/4913",
+        "/pack/src/main.rs___jb_tmp___",
+    ] {
+        assert!(ignorer.excludes(Path::new(path)).unwrap(), "{path}");
     }
+    assert!(!ignorer.excludes(Path::new("/pack/src/main.rs")).unwrap());
+    assert!(!ignorer.excludes(Path::new("/pack/src/swap.rs")).unwrap());
 }
@@ -0,0 +1,174 @@
+use {
+    crate::*,
+    anyhow::Result,
+    gix::{
+        self as git,
+        bstr::BStr,
+        glob::pattern::Case,
+        ignore::Search,
+    },
+    std::{
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// An ignorer reading gitignore-syntax files which aren't necessarily
+/// managed by git, like the `.ignore` files of ripgrep and similar tools,
+/// `.bacon-ignore` files, or the global git excludes file.
+///
+/// Each file's patterns are relative to a base directory, usually the
+/// directory containing the file.
+#[derive(Default)]
+pub struct IgnoreFileIgnorer {
+    lists: Vec<IgnoreList>,
+}
+
+struct IgnoreList {
+    base: PathBuf,
+    search: Search,
+}
+
+impl IgnoreFileIgnorer {
+    /// Add the patterns of the file at the given path, ignoring it if it
+    /// doesn't exist.
+    ///
+    /// Return whether a file was read.
+    pub fn add_file(
+        &mut self,
+        path: &Path,
+        base: &Path,
+    ) -> Result<bool> {
+        if !path.is_file() {
+            return Ok(false);
+        }
+        let bytes = fs::read(path)?;
+        let mut search = Search::default();
+        search.add_patterns_buffer(&bytes, path, None);
+        debug!("ignore file {:?} read", path);
+        self.lists.push(IgnoreList {
+            base: base.to_path_buf(),
+            search,
+        });
+        Ok(true)
+    }
+    /// Add the ignore files with the given names found in the given directory
+    pub fn add_files_in_dir(
+        &mut self,
+        dir: &Path,
+        file_names: &[String],
+    ) -> Result<()> {
+        for file_name in file_names {
+            self.add_file(&dir.join(file_name), dir)?;
+        }
+        Ok(())
+    }
+    /// Add the global git excludes file (the one defined by `core.excludesFile`
+    /// in the global git config, or `$XDG_CONFIG_HOME/git/ignore` by default)
+    /// with patterns relative to the given base.
+    pub fn add_global_git_excludes(
+        &mut self,
+        base: &Path,
+    ) -> Result<bool> {
+        let Some(path) = global_git_excludes_path() else {
+            return Ok(false);
+        };
+        self.add_file(&path, base)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty()
+    }
+}
+
+impl Ignorer for IgnoreFileIgnorer {
+    fn excludes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        if path.is_dir() {
+            return Ok(false);
+        }
+        // the last added lists are the most specific ones, so they're checked first
+        for list in self.lists.iter().rev() {
+            let Ok(relative_path) = path.strip_prefix(&list.base) else {
+                continue;
+            };
+            if relative_path.as_os_str().is_empty() {
+                continue;
+            }
+            // as in git, a file can't be re-included when one of its parent
+            // directories is excluded
+            let mut parents: Vec<&Path> = relative_path.ancestors().skip(1).collect();
+            parents.reverse();
+            for parent in parents {
+                if parent.as_os_str().is_empty() {
+                    continue;
+                }
+                if list.matches(parent, true) == Some(true) {
+                    return Ok(true);
+                }
+            }
+            if let Some(excluded) = list.matches(relative_path, false) {
+                return Ok(excluded);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl IgnoreList {
+    /// Return `Some(true)` when the last matching pattern excludes the path,
+    /// `Some(false)` when it re-includes it, and `None` when no pattern matches.
+    fn matches(
+        &self,
+        relative_path: &Path,
+        is_dir: bool,
+    ) -> Option<bool> {
+        let relative_path =
+            git::path::to_unix_separators_on_windows(git::path::into_bstr(relative_path));
+        let relative_path: &BStr = relative_path.as_ref();
+        self.search
+            .pattern_matching_relative_path(relative_path, Some(is_dir), Case::Sensitive)
+            .map(|m| !m.pattern.is_negative())
+    }
+}
+
+fn global_git_excludes_path() -> Option<PathBuf> {
+    let configured = git::config::File::from_globals().ok().and_then(|config| {
+        let path = config.path("core.excludesFile")?;
+        let home_dir = git::path::env::home_dir();
+        let context = git::config::path::interpolate::Context {
+            home_dir: home_dir.as_deref(),
+            ..Default::default()
+        };
+        path.interpolate(context).ok().map(|p| p.into_owned())
+    });
+    configured.or_else(|| git::path::env::xdg_config("ignore", &mut git::path::env::var))
+}
+
+#[test]
+fn test_ignore_file_ignorer() {
+    let dir = std::env::temp_dir().join(format!("bacon-ignore-files-{}", std::process::id()));
+    fs::create_dir_all(dir.join("src/generated")).unwrap();
+    fs::write(
+        dir.join(".ignore"),
+        "generated/\n*.log\n!important.log\n/root-only.rs\n",
+    )
+    .unwrap();
+    let mut ignorer = IgnoreFileIgnorer::default();
+    ignorer
+        .add_files_in_dir(&dir, &[".ignore".to_string(), ".absent".to_string()])
+        .unwrap();
+    assert!(!ignorer.is_empty());
+    assert!(ignorer.excludes(&dir.join("build.log")).unwrap());
+    assert!(ignorer.excludes(&dir.join("src/build.log")).unwrap());
+    assert!(!ignorer.excludes(&dir.join("important.log")).unwrap());
+    assert!(ignorer.excludes(&dir.join("src/generated/a.rs")).unwrap());
+    assert!(ignorer.excludes(&dir.join("root-only.rs")).unwrap());
+    assert!(!ignorer.excludes(&dir.join("src/root-only.rs")).unwrap());
+    assert!(!ignorer.excludes(&dir.join("src/lib.rs")).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+}
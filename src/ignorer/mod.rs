@@ -6,12 +6,20 @@ use {
     },
 };
 
+mod allowlist_ignorer;
 mod git_ignorer;
 mod glob_ignorer;
+mod ignore_file_ignorer;
+mod watch_globs_ignorer;
+mod workspace_ignorer;
 
 pub use {
+    allowlist_ignorer::AllowlistIgnorer,
     git_ignorer::GitIgnorer,
     glob_ignorer::GlobIgnorer,
+    ignore_file_ignorer::IgnoreFileIgnorer,
+    watch_globs_ignorer::WatchGlobsIgnorer,
+    workspace_ignorer::WorkspaceIgnorer,
 };
 
 pub trait Ignorer {
@@ -24,6 +32,27 @@ pub trait Ignorer {
         &mut self,
         paths: &Path,
     ) -> Result<bool>;
+
+    /// Tell whether the path is explicitly re-included (eg by a `!pattern`),
+    /// which overrides the exclusions of the other ignorers of the set
+    fn includes(
+        &mut self,
+        _path: &Path,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Tell whether the directory is excluded with all its content, so
+    /// that it's not explored (eg to find symbolic links).
+    ///
+    /// The ignorers which only know about files, eg allowlists, don't
+    /// exclude directories.
+    fn excludes_directory(
+        &mut self,
+        dir: &Path,
+    ) -> Result<bool> {
+        self.excludes(dir)
+    }
 }
 
 /// A set of ignorers
@@ -38,6 +67,44 @@ impl IgnorerSet {
     ) {
         self.ignorers.push(ignorer);
     }
+    /// Tell whether the path is excluded by at least one ignorer
+    /// and not explicitly re-included by another one.
+    ///
+    /// A re-inclusion (eg a `!pattern` in the `ignore` of the job) isn't
+    /// scoped to its ignorer: it overrides all the exclusions of the set
+    /// (gitignore rules, ignore files, workspace exclusions, etc.)
+    pub fn excludes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        for ignorer in &mut self.ignorers {
+            if ignorer.includes(path)? {
+                return Ok(false);
+            }
+        }
+        for ignorer in &mut self.ignorers {
+            if ignorer.excludes(path)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    /// Tell whether the directory is excluded, with all its content,
+    /// by at least one ignorer
+    pub fn excludes_directory(
+        &mut self,
+        dir: &Path,
+    ) -> bool {
+        self.ignorers
+            .iter_mut()
+            .any(|ignorer| match ignorer.excludes_directory(dir) {
+                Ok(excluded) => excluded,
+                Err(e) => {
+                    warn!("exclusion check failed: {e}");
+                    false
+                }
+            })
+    }
     pub fn excludes_all_pathbufs(
         &mut self,
         paths: &[PathBuf],
@@ -46,17 +113,47 @@ impl IgnorerSet {
             return Ok(false);
         }
         for path in paths {
-            let mut excluded = false;
-            for ignorer in &mut self.ignorers {
-                if ignorer.excludes(path)? {
-                    excluded = true;
-                    break;
-                }
-            }
-            if !excluded {
+            if !self.excludes(path)? {
                 return Ok(false);
             }
         }
         Ok(true)
     }
+    /// Return the paths which aren't excluded
+    pub fn included_pathbufs(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> Vec<PathBuf> {
+        paths
+            .iter()
+            .filter(|path| match self.excludes(path) {
+                Ok(excluded) => !excluded,
+                Err(e) => {
+                    warn!("exclusion check failed: {e}");
+                    true
+                }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[test]
+fn test_reinclusion_overrides_other_ignorers() {
+    let root = Path::new("/proj");
+    // eg the exclusions of a gitignore file
+    let mut excluding = GlobIgnorer::default();
+    excluding.add("src/generated/**", root).unwrap();
+    // the `ignore` of the job
+    let mut job_ignorer = GlobIgnorer::default();
+    job_ignorer.add("*.bak", root).unwrap();
+    job_ignorer.add("!src/generated/schema.rs", root).unwrap();
+    let mut set = IgnorerSet::default();
+    set.add(Box::new(excluding));
+    set.add(Box::new(job_ignorer));
+    let excludes = |set: &mut IgnorerSet, path: &str| set.excludes(Path::new(path)).unwrap();
+    assert!(!excludes(&mut set, "/proj/src/generated/schema.rs"));
+    assert!(excludes(&mut set, "/proj/src/generated/other.rs"));
+    assert!(excludes(&mut set, "/proj/src/main.rs.bak"));
+    assert!(!excludes(&mut set, "/proj/src/main.rs"));
 }
@@ -0,0 +1,92 @@
+use {
+    crate::*,
+    anyhow::Result,
+    std::path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// An ignorer excluding all paths which don't match at least one
+/// of its globs, thus working as an allowlist.
+///
+/// Globs are relative to the base directories (usually the package
+/// and the workspace directories), eg `"**/*.rs"` or `"templates/**"`.
+pub struct WatchGlobsIgnorer {
+    bases: Vec<PathBuf>,
+    globs: Vec<glob::Pattern>,
+}
+
+static MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl WatchGlobsIgnorer {
+    pub fn new(bases: Vec<PathBuf>) -> Self {
+        Self {
+            bases,
+            globs: Vec::new(),
+        }
+    }
+    pub fn add(
+        &mut self,
+        pattern: &str,
+    ) -> Result<()> {
+        let pattern = pattern.trim_start_matches('/');
+        self.globs.push(glob::Pattern::new(pattern)?);
+        Ok(())
+    }
+    pub fn allows(
+        &self,
+        path: &Path,
+    ) -> bool {
+        self.bases
+            .iter()
+            .filter_map(|base| path.strip_prefix(base).ok())
+            .any(|relative_path| {
+                self.globs
+                    .iter()
+                    .any(|glob| glob.matches_path_with(relative_path, MATCH_OPTIONS))
+            })
+    }
+}
+
+impl Ignorer for WatchGlobsIgnorer {
+    fn excludes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        Ok(!self.allows(path))
+    }
+    fn excludes_directory(
+        &mut self,
+        _dir: &Path,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+#[test]
+fn test_watch_globs_ignorer() {
+    let mut ignorer = WatchGlobsIgnorer::new(vec![PathBuf::from("/ws/pack")]);
+    ignorer.add("**/*.rs").unwrap();
+    ignorer.add("templates/**").unwrap();
+    ignorer.add("/Cargo.toml").unwrap();
+    assert!(!ignorer.excludes(Path::new("/ws/pack/build.rs")).unwrap());
+    assert!(!ignorer.excludes(Path::new("/ws/pack/src/lib.rs")).unwrap());
+    assert!(
+        !ignorer
+            .excludes(Path::new("/ws/pack/templates/a/b.html"))
+            .unwrap()
+    );
+    assert!(!ignorer.excludes(Path::new("/ws/pack/Cargo.toml")).unwrap());
+    assert!(
+        ignorer
+            .excludes(Path::new("/ws/pack/sub/Cargo.toml"))
+            .unwrap()
+    );
+    assert!(ignorer.excludes(Path::new("/ws/pack/README.md")).unwrap());
+    assert!(ignorer.excludes(Path::new("/elsewhere/lib.rs")).unwrap());
+}
@@ -0,0 +1,165 @@
+use {
+    super::Ignorer,
+    anyhow::Result,
+    rustc_hash::FxHashMap,
+    std::{
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// An ignorer excluding the directories which are inside the workspace
+/// directory but aren't part of the workspace: the ones listed in the
+/// `exclude` array of the `[workspace]` section, and the nested workspaces.
+///
+/// The directories of the local packages (members and path dependencies)
+/// are never excluded, as their changes impact the build.
+#[derive(Debug, Clone)]
+pub struct WorkspaceIgnorer {
+    root: PathBuf,
+    excluded_directories: Vec<PathBuf>,
+    package_directories: Vec<PathBuf>,
+    /// whether directories are the roots of nested workspaces, filled
+    /// as paths are checked
+    nested_workspaces: FxHashMap<PathBuf, bool>,
+}
+
+impl WorkspaceIgnorer {
+    /// Build the ignorer of the workspace whose root manifest is given
+    pub fn new(
+        workspace_manifest: &Path,
+        package_directories: Vec<PathBuf>,
+    ) -> Self {
+        let root = workspace_manifest
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+        let excluded_directories = read_workspace_excludes(workspace_manifest)
+            .into_iter()
+            .map(|exclude| root.join(exclude))
+            .collect();
+        Self {
+            root,
+            excluded_directories,
+            package_directories,
+            nested_workspaces: Default::default(),
+        }
+    }
+    /// The directories listed in the `exclude` array of the workspace
+    pub fn excluded_directories(&self) -> &[PathBuf] {
+        &self.excluded_directories
+    }
+    fn is_nested_workspace(
+        &mut self,
+        dir: &Path,
+    ) -> bool {
+        if let Some(&nested) = self.nested_workspaces.get(dir) {
+            return nested;
+        }
+        let nested = fs::read_to_string(dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok())
+            .is_some_and(|manifest| manifest.contains_key("workspace"));
+        self.nested_workspaces.insert(dir.to_path_buf(), nested);
+        nested
+    }
+}
+
+impl Ignorer for WorkspaceIgnorer {
+    fn excludes(
+        &mut self,
+        path: &Path,
+    ) -> Result<bool> {
+        if !path.starts_with(&self.root) {
+            return Ok(false);
+        }
+        // the closest enclosing directory deciding wins
+        for dir in path.ancestors() {
+            if dir == self.root {
+                break;
+            }
+            if self.package_directories.iter().any(|p| p == dir) {
+                return Ok(false);
+            }
+            if self.excluded_directories.iter().any(|e| e == dir) {
+                return Ok(true);
+            }
+            if self.is_nested_workspace(dir) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Read the `exclude` array of the `[workspace]` section of the manifest
+fn read_workspace_excludes(workspace_manifest: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(workspace_manifest) else {
+        return Vec::new();
+    };
+    let manifest: toml::Table = match content.parse() {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            warn!("failed to parse {workspace_manifest:?}: {e}");
+            return Vec::new();
+        }
+    };
+    manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("exclude"))
+        .and_then(|exclude| exclude.as_array())
+        .map(|exclude| {
+            exclude
+                .iter()
+                .filter_map(|e| e.as_str())
+                .map(|e| e.trim_end_matches('/').to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_workspace_ignorer() {
+    let root = std::env::temp_dir().join(format!("bacon-test-workspace-{}", std::process::id()));
+    let write = |path: &str, content: &str| {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    };
+    write(
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"core\"]\nexclude = [\"sandbox/\", \"tools\"]\n",
+    );
+    write("core/Cargo.toml", "[package]\nname = \"core\"\n");
+    write("tools/gen/Cargo.toml", "[package]\nname = \"gen\"\n");
+    write("tools/dep/Cargo.toml", "[package]\nname = \"dep\"\n");
+    write("nested/Cargo.toml", "[workspace]\nmembers = [\"inner\"]\n");
+    let mut ignorer = WorkspaceIgnorer::new(
+        &root.join("Cargo.toml"),
+        vec![root.join("core"), root.join("tools/dep")],
+    );
+    for (path, excluded) in [
+        ("core/src/lib.rs", false),
+        ("src/main.rs", false),
+        ("sandbox/src/main.rs", true),
+        ("tools", true),
+        ("tools/gen/src/main.rs", true),
+        ("tools/dep/src/lib.rs", false), // a path dependency
+        ("nested/inner/src/lib.rs", true),
+        ("nested/Cargo.toml", true),
+    ] {
+        assert_eq!(
+            ignorer.excludes(&root.join(path)).unwrap(),
+            excluded,
+            "{path}"
+        );
+    }
+    assert!(
+        !ignorer
+            .excludes(Path::new("/elsewhere/src/lib.rs"))
+            .unwrap()
+    );
+    let _ = fs::remove_dir_all(&root);
+}
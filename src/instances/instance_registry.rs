@@ -0,0 +1,184 @@
+use {
+    crate::*,
+    anyhow::Result,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        env,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process,
+        sync::Mutex,
+    },
+};
+
+/// The directory, in the target directory, where the running
+/// instances are registered
+const REGISTRY_DIRECTORY: &str = "bacon-instances";
+
+/// What a running bacon tells the other instances about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceEntry {
+    pub pid: u32,
+    pub headless: bool,
+    /// the control socket of the instance, if it listens on one
+    pub control_socket: Option<PathBuf>,
+}
+
+/// The path of the registration of this instance, if it's registered
+static REGISTRATION: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Register this instance in the target directory of the context,
+/// if the settings ask for it
+pub fn register_instance(
+    context: &Context,
+    settings: &Settings,
+    headless: bool,
+) {
+    if !settings.instances.detect {
+        return;
+    }
+    let Some(target_directory) = context.target_directory() else {
+        return;
+    };
+    let control_socket = settings.control.socket.as_ref().map(|socket| {
+        // the other instances may run in other directories
+        env::current_dir()
+            .map(|dir| dir.join(socket))
+            .unwrap_or_else(|_| socket.clone())
+    });
+    let entry = InstanceEntry {
+        pid: process::id(),
+        headless,
+        control_socket,
+    };
+    match write_entry(target_directory, &entry) {
+        Ok(path) => {
+            if let Ok(mut registration) = REGISTRATION.lock() {
+                *registration = Some(path);
+            }
+        }
+        Err(e) => {
+            warn!("failed to register the instance: {e}");
+        }
+    }
+}
+
+/// Remove the registration of this instance, if any
+pub fn unregister_instance() {
+    let Some(path) = REGISTRATION.lock().ok().and_then(|mut r| r.take()) else {
+        return;
+    };
+    if let Err(e) = fs::remove_file(&path) {
+        debug!("failed to remove the registration: {e}");
+    }
+}
+
+/// Write the entry in the registry, return the path of its file
+fn write_entry(
+    target_directory: &Path,
+    entry: &InstanceEntry,
+) -> Result<PathBuf> {
+    let directory = target_directory.join(REGISTRY_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+    let path = directory.join(format!("{}.json", entry.pid));
+    fs::write(&path, serde_json::to_string(entry)?)?;
+    Ok(path)
+}
+
+/// Return the other live instances registered in the target directory,
+/// removing the entries of the dead ones
+pub fn other_instances(target_directory: &Path) -> Vec<InstanceEntry> {
+    let Ok(dir_entries) = fs::read_dir(target_directory.join(REGISTRY_DIRECTORY)) else {
+        return Vec::new();
+    };
+    let mut instances: Vec<InstanceEntry> = dir_entries
+        .flatten()
+        .filter_map(|dir_entry| {
+            let path = dir_entry.path();
+            let content = fs::read_to_string(&path).ok()?;
+            let entry: InstanceEntry = serde_json::from_str(&content).ok()?;
+            if entry.pid == process::id() {
+                return None;
+            }
+            if !is_alive(entry.pid) {
+                // the instance was killed without cleaning
+                debug!("removing the registration of dead instance {}", entry.pid);
+                let _ = fs::remove_file(&path);
+                return None;
+            }
+            Some(entry)
+        })
+        .collect();
+    instances.sort_by_key(|entry| entry.pid);
+    instances
+}
+
+/// Return a warning about the other instances running on the target
+/// directory of the context, if there are some and the settings ask
+/// for a detection
+pub fn other_instances_warning(
+    context: &Context,
+    settings: &Settings,
+) -> Option<String> {
+    if !settings.instances.detect {
+        return None;
+    }
+    let others = other_instances(context.target_directory()?).len();
+    if others == 0 {
+        return None;
+    }
+    Some(format!(
+        "{others} other bacon instance(s) use this target directory"
+    ))
+}
+
+/// Return the control socket of another instance running on the target
+/// directory, if there's one to which a TUI may attach
+pub fn executor_socket(target_directory: &Path) -> Option<PathBuf> {
+    other_instances(target_directory)
+        .into_iter()
+        .filter_map(|entry| entry.control_socket)
+        .find(|socket| socket.exists())
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: the signal 0 only checks that the process exists
+    let res = unsafe { libc::kill(pid, 0) };
+    // EPERM means that the process exists but belongs to another user
+    res == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(unix)]
+#[test]
+fn test_other_instances() {
+    let target_directory = env::temp_dir().join(format!("bacon-test-instances-{}", process::id()));
+    let registered = |pid| InstanceEntry {
+        pid,
+        headless: true,
+        control_socket: None,
+    };
+    // this process, and another live one (the parent of the test)
+    let own = registered(process::id());
+    let live = registered(std::os::unix::process::parent_id());
+    write_entry(&target_directory, &own).unwrap();
+    let live_path = write_entry(&target_directory, &live).unwrap();
+    assert_eq!(other_instances(&target_directory), vec![live.clone()]);
+    fs::remove_file(live_path).unwrap();
+    assert_eq!(other_instances(&target_directory), vec![]);
+    let _ = fs::remove_dir_all(&target_directory);
+}
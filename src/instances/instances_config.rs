@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+/// The `[instances]` part of a configuration: how bacon behaves when
+/// other instances work on the same target directory
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InstancesConfig {
+    /// Whether to register this instance and tell about the other ones
+    pub detect: Option<bool>,
+    /// Whether to wait for the jobs of the other instances to finish
+    /// before running one
+    pub exclusive_runs: Option<bool>,
+    /// Whether to attach to an instance already running with a control
+    /// socket, instead of running jobs
+    pub attach: Option<bool>,
+}
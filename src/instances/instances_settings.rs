@@ -0,0 +1,36 @@
+use crate::*;
+
+/// Settings of the coordination with the other bacon instances
+#[derive(Debug, Clone)]
+pub struct InstancesSettings {
+    pub detect: bool,
+    pub exclusive_runs: bool,
+    pub attach: bool,
+}
+
+impl Default for InstancesSettings {
+    fn default() -> Self {
+        Self {
+            detect: true,
+            exclusive_runs: false,
+            attach: false,
+        }
+    }
+}
+
+impl InstancesSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &InstancesConfig,
+    ) {
+        if let Some(b) = config.detect {
+            self.detect = b;
+        }
+        if let Some(b) = config.exclusive_runs {
+            self.exclusive_runs = b;
+        }
+        if let Some(b) = config.attach {
+            self.attach = b;
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod instance_registry;
+mod instances_config;
+mod instances_settings;
+mod run_lock;
+
+pub use {
+    instance_registry::*,
+    instances_config::*,
+    instances_settings::*,
+    run_lock::*,
+};
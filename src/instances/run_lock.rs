@@ -0,0 +1,47 @@
+use std::{
+    fs::{
+        self,
+        File,
+        OpenOptions,
+    },
+    io,
+    path::Path,
+};
+
+/// An exclusive lock on a file of the target directory, taken during
+/// the executions of jobs so that several bacon instances don't run
+/// cargo at the same time. It's released when dropped.
+pub struct RunLock {
+    _file: File,
+}
+
+impl RunLock {
+    /// Take the lock, if it's not held by another process
+    pub fn try_acquire(path: &Path) -> io::Result<Option<Self>> {
+        if let Some(directory) = path.parent() {
+            // the target directory may not have been created yet
+            fs::create_dir_all(directory)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: flock only works on the descriptor, which stays
+            // open as long as the lock
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() == io::ErrorKind::WouldBlock {
+                    return Ok(None);
+                }
+                return Err(e);
+            }
+        }
+        #[cfg(not(unix))]
+        warn!("exclusive runs are only supported on unix");
+        Ok(Some(Self { _file: file }))
+    }
+}
@@ -1,6 +1,7 @@
 use {
     crate::{
         PlaySoundCommand,
+        ReportFormat,
         ScrollCommand,
         Volume,
     },
@@ -22,26 +23,44 @@ use {
 /// to a key or ran after a successful job
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Internal {
+    ApplySuggestions,
     Back,       // leave help, clear search, go to previous job, leave, etc.
     BackOrQuit, // same as Back but quits if there is nothing to go back to
+    CaptureBaseline,
+    CopyReport(ReportFormat),
     CopyUnstyledOutput,
+    Explain,
     FocusSearch,
     FocusGoto,
     Help,
     NextMatch,
+    NextProfile,
+    NextRoot,
     NoOp, // no operation, can be used to clear a binding
+    OpenInEditor,
     Pause,
+    PickPackage,
+    PickTests,
     PlaySound(PlaySoundCommand),
     PreviousMatch,
+    PreviousRoot,
     Quit,
     ReRun,
     Refresh, // clear and rerun
     ReloadConfig,
+    SaveReport(String),
     ScopeToFailures,
+    ShowBuildTimings,
+    ShowFlakyTests,
     Scroll(ScrollCommand),
     ToggleBacktrace(&'static str),
+    ToggleCapturedOutput,
+    ToggleDiffScope,
+    ToggleFailedTestsFirst,
     TogglePause, // either pause or unpause
+    TogglePerfHud,
     ToggleRawOutput,
+    ToggleStdinForwarding,
     ToggleSummary,
     ToggleWrap,
     Unpause,
@@ -52,28 +71,46 @@ impl Internal {
     /// Return the action description to show in doc/help
     pub fn doc(&self) -> String {
         match self {
+            Self::ApplySuggestions => "preview then apply the suggested fixes".to_string(),
             Self::Back => "back to previous page or job".to_string(),
             Self::BackOrQuit => {
                 "back to previous page or job, quitting if there is none".to_string()
             }
+            Self::CaptureBaseline => "capture the current diagnostics as baseline".to_string(),
+            Self::CopyReport(format) => format!("copy the report ({format})"),
             Self::CopyUnstyledOutput => "copy current job's output".to_string(),
+            Self::Explain => "explain the error code or lint of the top item".to_string(),
             Self::FocusSearch => "focus search".to_string(),
             Self::FocusGoto => "focus goto".to_string(),
             Self::Help => "help".to_string(),
             Self::NextMatch => "next match".to_string(),
+            Self::NextProfile => "switch to the next configuration profile".to_string(),
+            Self::NextRoot => "switch to the next project root".to_string(),
             Self::NoOp => "no operation".to_string(),
+            Self::OpenInEditor => "open the location of the top item in the editor".to_string(),
             Self::Pause => "pause".to_string(),
+            Self::PickPackage => "pick the package to scope the jobs to".to_string(),
+            Self::PickTests => "pick the tests to run".to_string(),
             Self::PlaySound(_) => "play sound".to_string(),
             Self::PreviousMatch => "previous match".to_string(),
+            Self::PreviousRoot => "switch to the previous project root".to_string(),
             Self::Quit => "quit".to_string(),
             Self::ReRun => "run current job again".to_string(),
             Self::Refresh => "clear then run current job again".to_string(),
             Self::ReloadConfig => "reload configuration files".to_string(),
+            Self::SaveReport(path) => format!("save the report in {path}"),
             Self::ScopeToFailures => "scope to failures".to_string(),
+            Self::ShowBuildTimings => "show the build time of the crates".to_string(),
+            Self::ShowFlakyTests => "list the possibly flaky tests".to_string(),
             Self::Scroll(scroll_command) => scroll_command.doc(),
             Self::ToggleBacktrace(level) => format!("toggle backtrace ({level})"),
+            Self::ToggleCapturedOutput => "toggle captured output of the test".to_string(),
+            Self::ToggleDiffScope => "toggle scoping to changed lines".to_string(),
+            Self::ToggleFailedTestsFirst => "toggle running failed tests first".to_string(),
             Self::TogglePause => "toggle pause".to_string(),
+            Self::TogglePerfHud => "toggle the performance overlay".to_string(),
             Self::ToggleRawOutput => "toggle raw output".to_string(),
+            Self::ToggleStdinForwarding => "toggle typing lines for the command".to_string(),
             Self::ToggleSummary => "toggle summary".to_string(),
             Self::ToggleWrap => "toggle wrap".to_string(),
             Self::Unpause => "unpause".to_string(),
@@ -88,21 +125,36 @@ impl fmt::Display for Internal {
         f: &mut fmt::Formatter,
     ) -> fmt::Result {
         match self {
+            Self::ApplySuggestions => write!(f, "apply-suggestions"),
             Self::Back => write!(f, "back"),
             Self::BackOrQuit => write!(f, "back-or-quit"),
+            Self::CaptureBaseline => write!(f, "capture-baseline"),
+            Self::CopyReport(format) => write!(f, "copy-report({format})"),
             Self::CopyUnstyledOutput => write!(f, "copy-unstyled-output"),
+            Self::Explain => write!(f, "explain"),
             Self::Help => write!(f, "help"),
             Self::NoOp => write!(f, "no-op"),
+            Self::OpenInEditor => write!(f, "open-in-editor"),
             Self::Pause => write!(f, "pause"),
+            Self::PickPackage => write!(f, "pick-package"),
+            Self::PickTests => write!(f, "pick-tests"),
             Self::Quit => write!(f, "quit"),
             Self::ReRun => write!(f, "rerun"),
             Self::Refresh => write!(f, "refresh"),
             Self::ReloadConfig => write!(f, "reload-config"),
+            Self::SaveReport(path) => write!(f, "save-report({path})"),
             Self::ScopeToFailures => write!(f, "scope-to-failures"),
+            Self::ShowBuildTimings => write!(f, "show-build-timings"),
+            Self::ShowFlakyTests => write!(f, "show-flaky-tests"),
             Self::Scroll(scroll_command) => scroll_command.fmt(f),
             Self::ToggleBacktrace(level) => write!(f, "toggle-backtrace({level})"),
+            Self::ToggleCapturedOutput => write!(f, "toggle-captured-output"),
+            Self::ToggleDiffScope => write!(f, "toggle-diff-scope"),
+            Self::ToggleFailedTestsFirst => write!(f, "toggle-failed-tests-first"),
             Self::TogglePause => write!(f, "toggle-pause"),
+            Self::TogglePerfHud => write!(f, "toggle-perf-hud"),
             Self::ToggleRawOutput => write!(f, "toggle-raw-output"),
+            Self::ToggleStdinForwarding => write!(f, "toggle-stdin-forwarding"),
             Self::ToggleSummary => write!(f, "toggle-summary"),
             Self::ToggleWrap => write!(f, "toggle-wrap"),
             Self::Unpause => write!(f, "unpause"),
@@ -111,6 +163,9 @@ impl fmt::Display for Internal {
             Self::Validate => write!(f, "validate"),
             Self::NextMatch => write!(f, "next-match"),
             Self::PreviousMatch => write!(f, "previous-match"),
+            Self::NextProfile => write!(f, "next-profile"),
+            Self::NextRoot => write!(f, "next-root"),
+            Self::PreviousRoot => write!(f, "previous-root"),
             Self::PlaySound(PlaySoundCommand { name, volume }) => {
                 write!(f, "play-sound(")?;
                 if let Some(name) = name {
@@ -128,23 +183,36 @@ impl std::str::FromStr for Internal {
             return Ok(Self::Scroll(scroll_command));
         }
         match s {
+            "apply-suggestions" => Ok(Self::ApplySuggestions),
             "back" => Ok(Self::Back),
             "back-or-quit" => Ok(Self::BackOrQuit),
+            "capture-baseline" => Ok(Self::CaptureBaseline),
+            "explain" => Ok(Self::Explain),
             "help" => Ok(Self::Help),
             "quit" => Ok(Self::Quit),
             "refresh" => Ok(Self::Refresh),
             "reload-config" => Ok(Self::ReloadConfig),
             "rerun" => Ok(Self::ReRun),
             "scope-to-failures" => Ok(Self::ScopeToFailures),
+            "show-build-timings" => Ok(Self::ShowBuildTimings),
+            "show-flaky-tests" => Ok(Self::ShowFlakyTests),
             "toggle-raw-output" => Ok(Self::ToggleRawOutput),
+            "toggle-stdin-forwarding" => Ok(Self::ToggleStdinForwarding),
             "toggle-backtrace" => Ok(Self::ToggleBacktrace("1")),
             "toggle-backtrace(1)" => Ok(Self::ToggleBacktrace("1")),
             "toggle-backtrace(2)" => Ok(Self::ToggleBacktrace("2")),
             "toggle-backtrace(full)" => Ok(Self::ToggleBacktrace("full")),
+            "toggle-captured-output" => Ok(Self::ToggleCapturedOutput),
+            "toggle-diff-scope" => Ok(Self::ToggleDiffScope),
+            "toggle-failed-tests-first" => Ok(Self::ToggleFailedTestsFirst),
+            "toggle-perf-hud" => Ok(Self::TogglePerfHud),
             "toggle-summary" => Ok(Self::ToggleSummary),
             "toggle-wrap" => Ok(Self::ToggleWrap),
             "noop" | "no-op" | "no-operation" => Ok(Self::NoOp),
+            "open-in-editor" => Ok(Self::OpenInEditor),
             "pause" => Ok(Self::Pause),
+            "pick-package" => Ok(Self::PickPackage),
+            "pick-tests" => Ok(Self::PickTests),
             "unpause" => Ok(Self::Unpause),
             "toggle-pause" => Ok(Self::TogglePause),
             "focus-search" => Ok(Self::FocusSearch),
@@ -152,9 +220,19 @@ impl std::str::FromStr for Internal {
             "validate" => Ok(Self::Validate),
             "next-match" => Ok(Self::NextMatch),
             "previous-match" => Ok(Self::PreviousMatch),
+            "next-profile" => Ok(Self::NextProfile),
+            "next-root" => Ok(Self::NextRoot),
+            "previous-root" => Ok(Self::PreviousRoot),
             "copy-unstyled-output" => Ok(Self::CopyUnstyledOutput),
+            "copy-report" => Ok(Self::CopyReport(ReportFormat::default())),
             "play-sound" => Ok(Self::PlaySound(PlaySoundCommand::default())),
             _ => {
+                if let Some((_, format)) = regex_captures!(r"^copy-report\((.*)\)$", s) {
+                    return Ok(Self::CopyReport(format.trim().parse()?));
+                }
+                if let Some((_, path)) = regex_captures!(r"^save-report\((.+)\)$", s) {
+                    return Ok(Self::SaveReport(path.trim().to_string()));
+                }
                 if let Some((_, props)) = regex_captures!(r"^play[_-]sound\((.*)\)$", s) {
                     let iter = regex_captures_iter!(r"([^=,]+)=([^=,]+)", props);
                     let mut volume = Volume::default();
@@ -205,29 +283,47 @@ impl<'de> Deserialize<'de> for Internal {
 fn test_internal_string_round_trip() {
     use crate::Volume;
     let internals = [
+        Internal::ApplySuggestions,
         Internal::Back,
         Internal::BackOrQuit,
+        Internal::CaptureBaseline,
+        Internal::CopyReport(ReportFormat::Markdown),
+        Internal::CopyReport(ReportFormat::Text),
+        Internal::Explain,
         Internal::FocusSearch,
         Internal::Help,
         Internal::NoOp,
         Internal::Pause,
+        Internal::PickPackage,
+        Internal::PickTests,
         Internal::Quit,
         Internal::ReRun,
         Internal::ReloadConfig,
+        Internal::SaveReport("target/bacon-report.md".to_string()),
         Internal::ScopeToFailures,
+        Internal::ShowBuildTimings,
+        Internal::ShowFlakyTests,
         Internal::Scroll(ScrollCommand::MilliPages(-3000)),
         Internal::Scroll(ScrollCommand::MilliPages(-350)),
         Internal::Scroll(ScrollCommand::MilliPages(1561)),
         Internal::Scroll(ScrollCommand::Top),
         Internal::ToggleBacktrace("1"),
         Internal::ToggleBacktrace("full"),
+        Internal::ToggleCapturedOutput,
+        Internal::ToggleDiffScope,
+        Internal::ToggleFailedTestsFirst,
         Internal::TogglePause,
+        Internal::TogglePerfHud,
+        Internal::ToggleStdinForwarding,
         Internal::ToggleSummary,
         Internal::ToggleWrap,
         Internal::Unpause,
         Internal::Validate,
         Internal::NextMatch,
         Internal::PreviousMatch,
+        Internal::NextProfile,
+        Internal::NextRoot,
+        Internal::PreviousRoot,
         Internal::PlaySound(PlaySoundCommand::default()),
         Internal::PlaySound(PlaySoundCommand {
             name: None,
@@ -0,0 +1,42 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Whether warnings may be present in a successful report: either
+/// for all warnings, or only for the ones located in files matching
+/// some glob patterns, eg `["tests/**", "benches/**"]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AllowWarnings {
+    All(bool),
+    /// glob patterns, relative to the package or workspace directory
+    InPaths(Vec<String>),
+}
+
+impl Default for AllowWarnings {
+    fn default() -> Self {
+        Self::All(false)
+    }
+}
+
+impl From<bool> for AllowWarnings {
+    fn from(b: bool) -> Self {
+        Self::All(b)
+    }
+}
+
+#[test]
+fn test_allow_warnings_deserialization() {
+    #[derive(Deserialize)]
+    struct Conf {
+        allow_warnings: AllowWarnings,
+    }
+    let conf: Conf = toml::from_str("allow_warnings = true").unwrap();
+    assert_eq!(conf.allow_warnings, AllowWarnings::All(true));
+    let conf: Conf = toml::from_str(r#"allow_warnings = ["tests/**", "benches/**"]"#).unwrap();
+    assert_eq!(
+        conf.allow_warnings,
+        AllowWarnings::InPaths(vec!["tests/**".to_string(), "benches/**".to_string()]),
+    );
+}
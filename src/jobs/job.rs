@@ -8,6 +8,13 @@ use {
 /// One of the possible job that bacon can run
 #[derive(Debug, Default, Clone, Deserialize, PartialEq)]
 pub struct Job {
+    /// Other names of the job, eg `["c"]`, which can be used wherever
+    /// its name can (a job name takes precedence over an alias)
+    ///
+    /// `alias` is accepted as well
+    #[serde(alias = "alias")]
+    pub aliases: Option<Vec<String>>,
+
     /// Whether to consider that we can have a success
     /// when we have test failures
     pub allow_failures: Option<bool>,
@@ -15,15 +22,23 @@ pub struct Job {
     /// Whether to consider that we can have a success
     /// when we have warnings. This is especially useful
     /// for "cargo run" jobs
-    pub allow_warnings: Option<bool>,
+    ///
+    /// It may also be a list of glob patterns of the files
+    /// where warnings are allowed, eg `["tests/**"]`
+    pub allow_warnings: Option<AllowWarnings>,
 
-    /// The analyzer interpreting the output of the command, the
-    /// standard cargo dedicated one if not provided
-    pub analyzer: Option<AnalyzerRef>,
+    /// The analyzer interpreting the output of the command, with its
+    /// options, guessed from the command if not provided
+    pub analyzer: Option<AnalyzerConfig>,
 
     /// Whether gitignore rules must be applied
     pub apply_gitignore: Option<bool>,
 
+    /// Whether the global git excludes file must be applied, even
+    /// when not in a git repository or when gitignore rules aren't
+    /// applied
+    pub apply_global_gitignore: Option<bool>,
+
     /// Whether to wait for the computation to finish before
     /// to display it on screen
     ///
@@ -32,6 +47,10 @@ pub struct Job {
     /// the new one as soon as it starts.
     pub background: Option<bool>,
 
+    /// Path of the file storing the baselines of diagnostics, relative
+    /// to the workspace or package directory (".bacon-baseline.json" if not set)
+    pub baseline_file: Option<String>,
+
     /// The tokens making the command to execute (first one
     /// is the executable).
     #[serde(default)]
@@ -45,11 +64,23 @@ pub struct Job {
     /// `watch`.
     pub default_watch: Option<bool>,
 
+    /// A short description of the job, displayed in the job list
+    pub description: Option<String>,
+
+    /// Whether to start with the report scoped to the diagnostics
+    /// located on lines changed since `diff_base`
+    pub diff_scope: Option<bool>,
+
+    /// The git revision against which changed lines are computed
+    /// when the report is scoped to the diff ("HEAD" if not set)
+    pub diff_base: Option<String>,
+
     /// Env vars to set for this job execution
     #[serde(default)]
     pub env: HashMap<String, String>,
 
-    /// Whether to expand environment variables in the command
+    /// Whether to expand environment variables in the command, the
+    /// env values, and the kill command
     pub expand_env_vars: Option<bool>,
 
     /// Whether to insert extraneous arguments provided by bacon or end users
@@ -57,26 +88,125 @@ pub struct Job {
     /// Eg: --all-features or anything after -- in bacon incantation
     pub extraneous_args: Option<bool>,
 
+    /// Whether the job isn't listed, eg because it's only a helper
+    /// of other jobs (it can still be run)
+    pub hidden: Option<bool>,
+
     /// A list of glob patterns to ignore
     #[serde(default)]
     pub ignore: Vec<String>,
 
+    /// Names of gitignore-syntax files to read in the package and
+    /// workspace directories, `[".bacon-ignore"]` if not set
+    pub ignore_files: Option<Vec<String>>,
+
+    /// Whether to ignore the temporary, swap, and backup files
+    /// of common editors (true if not set)
+    pub ignore_editor_temp_files: Option<bool>,
+
     /// Patterns of lines which should be ignored. Patterns of
     /// the prefs or bacon.toml can be overridden at the job
     pub ignored_lines: Option<Vec<LinePattern>>,
 
+    /// Whether to run cargo with `--message-format json-diagnostic-rendered-ansi`
+    /// and build the report from the structured messages (this switches the
+    /// standard analyzer to the cargo_json one)
+    pub json_messages: Option<bool>,
+
     /// A kill command. If not provided, SIGKILL is used.
     pub kill: Option<Vec<String>>,
 
+    /// A signal sent to the command to let it terminate before
+    /// it's killed, when there's no kill command
+    pub kill_signal: Option<KillSignal>,
+
+    /// How long to wait for the command to terminate after the
+    /// kill signal, before killing it
+    pub kill_timeout: Option<Period>,
+
+    /// Whether to display the items found in the output while the
+    /// job is still running, instead of the previous report
+    pub live_report: Option<bool>,
+
+    /// Whether to mark as external, and not count, the warnings
+    /// located in dependencies or outside of the workspace
+    pub mark_external_diagnostics: Option<bool>,
+
+    /// The maximal number of lines of raw output kept per execution
+    /// (0, the default, for no limit). Items of the report are kept but, past the
+    /// limit, only with their title and locations
+    pub max_output_lines: Option<usize>,
+
+    /// The maximal size of the address space of each process
+    /// of the command (unix only)
+    pub memory_limit: Option<MemorySize>,
+
     /// Whether we need to capture stdout too (stderr is
     /// always captured)
     pub need_stdout: Option<bool>,
 
+    /// When to send a desktop notification at the end of
+    /// an execution
+    pub notify_on: Option<NotifyOn>,
+
+    /// How to send the notifications: with the tool of the system,
+    /// or with an escape sequence for the terminal
+    pub notify_with: Option<NotifyWith>,
+
+    /// Whether to set the title of the terminal (or of the tmux pane)
+    /// to the job name and state
+    pub pane_title: Option<bool>,
+
+    /// Whether the lines of large outputs are analyzed by several
+    /// threads. Disabling it may help debugging analyzers
+    pub parallel_analysis: Option<bool>,
+
+    /// The maximal number of lines of raw output kept with each
+    /// retained report (0 for no limit)
+    pub retained_output_lines: Option<usize>,
+
+    /// How many of the last reports of the job are kept in memory, the
+    /// last one being displayed when coming back to the job (0 for none)
+    pub retained_reports: Option<usize>,
+
+    /// The name of a tmux pane option (eg `@bacon`) to set to the
+    /// job name and state, when running in tmux
+    pub tmux_option: Option<String>,
+
+    /// The niceness of the command, from -20 to 19 (unix only)
+    pub nice: Option<i32>,
+
+    /// The IO priority of the command (linux only)
+    pub io_priority: Option<IoPriority>,
+
+    /// Whether to run the command in a pseudo-terminal, so that it
+    /// behaves as in a terminal (by default only with the passthrough
+    /// analyzer)
+    pub pty: Option<bool>,
+
+    /// The shell, with its arguments, running the command when
+    /// `use_shell` is true, eg `["bash", "-c"]`
+    pub shell: Option<Vec<String>>,
+
+    /// Whether the command's stdin is open to receive the lines typed
+    /// after the `toggle-stdin-forwarding` internal
+    pub stdin_forwarding: Option<bool>,
+
+    /// Whether to run the command, its tokens joined with spaces, through
+    /// a shell, so that pipes, `&&`, and redirections can be used.
+    /// Defaults to true when a `shell` is given
+    pub use_shell: Option<bool>,
+
     /// How to handle changes: either immediately kill the current job
     /// then restart it, or wait for the current job to finish before
     /// restarting it.
     pub on_change_strategy: Option<OnChangeStrategy>,
 
+    /// What to do with the changes received while the job runs:
+    /// queue a re-run, kill and restart, or ignore them.
+    /// Supersedes `on_change_strategy`.
+    pub defer_events: Option<DeferEvents>,
+
     /// The optional action to run when there's no
     /// error, warning or test failures
     /// (depending on whether allow_warnings is true or false)
@@ -89,6 +219,15 @@ pub struct Job {
 
     pub grace_period: Option<Period>,
 
+    /// Whether to start the command as soon as a change is received,
+    /// instead of after the grace period, and to restart it if other
+    /// changes arrive during the grace period
+    pub speculative_start: Option<bool>,
+
+    /// Minimal duration between the starts of two executions
+    /// triggered by changes
+    pub min_interval: Option<Period>,
+
     /// The optional action to run when it's not a success
     #[serde(default)]
     pub on_failure: Option<Action>,
@@ -97,16 +236,56 @@ pub struct Job {
     /// is run on a package.
     /// src, examples, tests, and benches are implicitly included
     /// unless you `set default_watch` to false.
-    pub watch: Option<Vec<String>>,
+    pub watch: Option<Vec<WatchEntry>>,
+
+    /// If set, only changes to files matching at least one of those
+    /// glob patterns (relative to the package or workspace directory)
+    /// trigger the job
+    pub watch_globs: Option<Vec<String>>,
 
     pub show_changes_count: Option<bool>,
 
+    /// Severities overriding the ones of the diagnostics, by error
+    /// code or clippy lint, eg `"clippy::unwrap_used" = "error"`
+    #[serde(default)]
+    pub severity: HashMap<String, Severity>,
+
+    /// Whether to display the paths of the file changes which
+    /// triggered the current execution
+    pub show_trigger_paths: Option<bool>,
+
+    /// Whether to detect the job re-triggering itself by modifying
+    /// watched files, and then to ignore those files for a while
+    pub loop_detection: Option<bool>,
+
+    /// Whether to also watch the files listed in the dep-info files of cargo,
+    /// and to ignore the rust files which aren't dependencies of the build
+    pub watch_dep_info: Option<bool>,
+
     #[serde(default)]
     pub sound: SoundConfig,
+
+    /// How to send push notifications, eg to a phone, when the job finishes
+    #[serde(default)]
+    pub push: PushConfig,
+
+    /// External programs post-processing the report, deciding the
+    /// success, or computing the command
+    #[serde(default)]
+    pub hooks: HooksConfig,
 }
 
 static DEFAULT_ARGS: &[&str] = &["--color", "always"];
 
+#[cfg(not(windows))]
+static DEFAULT_SHELL: &[&str] = &["sh", "-c"];
+#[cfg(windows)]
+static DEFAULT_SHELL: &[&str] = &["cmd", "/C"];
+
+/// The `.ignore` files of ripgrep and similar tools aren't read by
+/// default, as they're about searching, not building
+static DEFAULT_IGNORE_FILES: &[&str] = &[".bacon-ignore"];
+
 impl Job {
     /// Build a `Job` for a cargo alias
     pub fn from_alias(
@@ -131,8 +310,22 @@ impl Job {
     pub fn allow_failures(&self) -> bool {
         self.allow_failures.unwrap_or(false)
     }
-    pub fn allow_warnings(&self) -> bool {
-        self.allow_warnings.unwrap_or(false)
+    pub fn allow_warnings(&self) -> AllowWarnings {
+        self.allow_warnings.clone().unwrap_or_default()
+    }
+    pub fn apply_gitignore(&self) -> bool {
+        self.apply_gitignore.unwrap_or(true)
+    }
+    pub fn apply_global_gitignore(&self) -> bool {
+        self.apply_global_gitignore.unwrap_or(false)
+    }
+    pub fn ignore_editor_temp_files(&self) -> bool {
+        self.ignore_editor_temp_files.unwrap_or(true)
+    }
+    pub fn ignore_files(&self) -> Vec<String> {
+        self.ignore_files
+            .clone()
+            .unwrap_or_else(|| DEFAULT_IGNORE_FILES.iter().map(|s| s.to_string()).collect())
     }
     pub fn background(&self) -> bool {
         self.background.unwrap_or(true)
@@ -140,42 +333,179 @@ impl Job {
     pub fn default_watch(&self) -> bool {
         self.default_watch.unwrap_or(true)
     }
+    pub fn diff_scope(&self) -> bool {
+        self.diff_scope.unwrap_or(false)
+    }
+    pub fn diff_base(&self) -> &str {
+        self.diff_base.as_deref().unwrap_or("HEAD")
+    }
     pub fn expand_env_vars(&self) -> bool {
         self.expand_env_vars.unwrap_or(true)
     }
+    /// Whether the job is a cargo one run with the JSON message format
+    /// instead of the human one, see the `json_messages` job setting
+    pub fn uses_json_messages(&self) -> bool {
+        self.json_messages()
+            && self.selected_analyzer() == AnalyzerRef::Standard
+            && self.command.first().is_some_and(|exe| exe == "cargo")
+    }
+    /// The analyzer guessed from the command, when none is set
+    pub fn detected_analyzer(&self) -> Option<AnalyzerRef> {
+        if self.analyzer.is_some() {
+            return None;
+        }
+        detect_analyzer(&self.command)
+    }
+    /// The analyzer set in the job, or else the detected one
+    fn selected_analyzer(&self) -> AnalyzerRef {
+        self.analyzer
+            .as_ref()
+            .map(AnalyzerConfig::analyzer_ref)
+            .or_else(|| self.detected_analyzer())
+            .unwrap_or_default()
+    }
+    /// The options of the analyzer, when given with it
+    pub fn analyzer_options(&self) -> AnalyzerOptions {
+        self.analyzer
+            .as_ref()
+            .and_then(AnalyzerConfig::options)
+            .cloned()
+            .unwrap_or_default()
+    }
+    /// The analyzer of the output, taking the JSON message format into account
+    pub fn effective_analyzer(&self) -> AnalyzerRef {
+        if self.uses_json_messages() {
+            return AnalyzerRef::CargoJson;
+        }
+        self.selected_analyzer()
+    }
+    pub fn json_messages(&self) -> bool {
+        self.json_messages.unwrap_or(false)
+    }
+    pub fn kill_timeout(&self) -> Period {
+        self.kill_timeout
+            .unwrap_or(std::time::Duration::from_secs(5).into())
+    }
+    pub fn live_report(&self) -> bool {
+        self.live_report.unwrap_or(false)
+    }
+    pub fn mark_external_diagnostics(&self) -> bool {
+        self.mark_external_diagnostics.unwrap_or(false)
+    }
+    pub fn max_output_lines(&self) -> usize {
+        self.max_output_lines.unwrap_or(0)
+    }
     pub fn need_stdout(&self) -> bool {
         self.need_stdout.unwrap_or(false)
     }
+    pub fn stdin_forwarding(&self) -> bool {
+        self.stdin_forwarding.unwrap_or(false)
+    }
+    pub fn notify_on(&self) -> NotifyOn {
+        self.notify_on.unwrap_or_default()
+    }
+    pub fn notify_with(&self) -> NotifyWith {
+        self.notify_with.unwrap_or_default()
+    }
+    pub fn pane_title(&self) -> bool {
+        self.pane_title.unwrap_or(false)
+    }
+    pub fn parallel_analysis(&self) -> bool {
+        self.parallel_analysis.unwrap_or(true)
+    }
+    pub fn retained_output_lines(&self) -> usize {
+        self.retained_output_lines.unwrap_or(10_000)
+    }
+    pub fn retained_reports(&self) -> usize {
+        self.retained_reports.unwrap_or(1)
+    }
+    pub fn tmux_option(&self) -> Option<&str> {
+        self.tmux_option.as_deref()
+    }
+    /// The shell the command must be run through, if any
+    pub fn shell(&self) -> Option<Vec<String>> {
+        match (self.use_shell, &self.shell) {
+            (Some(false), _) | (None, None) => None,
+            (_, Some(shell)) => Some(shell.clone()),
+            (Some(true), None) => Some(
+                DEFAULT_SHELL
+                    .iter()
+                    .map(|token| token.to_string())
+                    .collect(),
+            ),
+        }
+    }
+    pub fn is_hidden(&self) -> bool {
+        self.hidden.unwrap_or(false)
+    }
+    /// Tell whether the given name is one of the aliases of the job
+    pub fn has_alias(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.aliases
+            .as_ref()
+            .is_some_and(|aliases| aliases.iter().any(|alias| alias == name))
+    }
     pub fn extraneous_args(&self) -> bool {
         self.extraneous_args.unwrap_or(true)
     }
     pub fn show_changes_count(&self) -> bool {
         self.show_changes_count.unwrap_or(false)
     }
+    pub fn show_trigger_paths(&self) -> bool {
+        self.show_trigger_paths.unwrap_or(false)
+    }
+    pub fn loop_detection(&self) -> bool {
+        self.loop_detection.unwrap_or(true)
+    }
+    pub fn watch_dep_info(&self) -> bool {
+        self.watch_dep_info.unwrap_or(false)
+    }
     pub fn grace_period(&self) -> Period {
         self.grace_period
             .unwrap_or(std::time::Duration::from_millis(15).into())
     }
+    pub fn speculative_start(&self) -> bool {
+        self.speculative_start.unwrap_or(false)
+    }
+    pub fn min_interval(&self) -> Period {
+        self.min_interval
+            .unwrap_or(std::time::Duration::ZERO.into())
+    }
     pub fn on_change_strategy(&self) -> OnChangeStrategy {
         self.on_change_strategy
             .unwrap_or(OnChangeStrategy::WaitThenRestart)
     }
+    pub fn defer_events(&self) -> DeferEvents {
+        self.defer_events
+            .unwrap_or_else(|| self.on_change_strategy().into())
+    }
     pub fn apply(
         &mut self,
         job: &Job,
     ) {
+        if job.aliases.is_some() {
+            self.aliases.clone_from(&job.aliases);
+        }
         if let Some(b) = job.allow_failures {
             self.allow_failures = Some(b);
         }
-        if let Some(b) = job.allow_warnings {
-            self.allow_warnings = Some(b);
+        if let Some(v) = job.allow_warnings.as_ref() {
+            self.allow_warnings = Some(v.clone());
         }
-        if let Some(v) = job.analyzer {
-            self.analyzer = Some(v);
+        if let Some(v) = job.analyzer.as_ref() {
+            self.analyzer = Some(v.clone());
         }
         if let Some(b) = job.apply_gitignore {
             self.apply_gitignore = Some(b);
         }
+        if let Some(b) = job.apply_global_gitignore {
+            self.apply_global_gitignore = Some(b);
+        }
+        if let Some(v) = job.baseline_file.as_ref() {
+            self.baseline_file = Some(v.clone());
+        }
         if let Some(b) = job.background {
             self.background = Some(b);
         }
@@ -185,6 +515,15 @@ impl Job {
         if let Some(b) = job.default_watch {
             self.default_watch = Some(b);
         }
+        if let Some(v) = job.description.as_ref() {
+            self.description = Some(v.clone());
+        }
+        if let Some(b) = job.diff_scope {
+            self.diff_scope = Some(b);
+        }
+        if let Some(v) = job.diff_base.as_ref() {
+            self.diff_base = Some(v.clone());
+        }
         for (k, v) in &job.env {
             self.env.insert(k.clone(), v.clone());
         }
@@ -194,39 +533,136 @@ impl Job {
         if let Some(b) = job.extraneous_args {
             self.extraneous_args = Some(b);
         }
+        if let Some(b) = job.hidden {
+            self.hidden = Some(b);
+        }
         for v in &job.ignore {
             if !self.ignore.contains(v) {
                 self.ignore.push(v.clone());
             }
         }
+        if let Some(b) = job.ignore_editor_temp_files {
+            self.ignore_editor_temp_files = Some(b);
+        }
+        if let Some(v) = job.ignore_files.as_ref() {
+            self.ignore_files = Some(v.clone());
+        }
+        if let Some(b) = job.json_messages {
+            self.json_messages = Some(b);
+        }
         if let Some(v) = job.ignored_lines.as_ref() {
             self.ignored_lines = Some(v.clone());
         }
         if let Some(v) = job.kill.as_ref() {
             self.kill = Some(v.clone());
         }
+        if let Some(v) = job.kill_signal {
+            self.kill_signal = Some(v);
+        }
+        if let Some(v) = job.kill_timeout {
+            self.kill_timeout = Some(v);
+        }
+        if let Some(b) = job.live_report {
+            self.live_report = Some(b);
+        }
+        if let Some(b) = job.mark_external_diagnostics {
+            self.mark_external_diagnostics = Some(b);
+        }
+        if let Some(v) = job.max_output_lines {
+            self.max_output_lines = Some(v);
+        }
+        if let Some(v) = job.memory_limit {
+            self.memory_limit = Some(v);
+        }
         if let Some(b) = job.need_stdout {
             self.need_stdout = Some(b);
         }
+        if let Some(v) = job.notify_on {
+            self.notify_on = Some(v);
+        }
+        if let Some(v) = job.notify_with {
+            self.notify_with = Some(v);
+        }
+        if let Some(b) = job.pane_title {
+            self.pane_title = Some(b);
+        }
+        if let Some(b) = job.parallel_analysis {
+            self.parallel_analysis = Some(b);
+        }
+        if let Some(v) = job.retained_output_lines {
+            self.retained_output_lines = Some(v);
+        }
+        if let Some(v) = job.retained_reports {
+            self.retained_reports = Some(v);
+        }
+        if job.tmux_option.is_some() {
+            self.tmux_option.clone_from(&job.tmux_option);
+        }
+        if let Some(v) = job.nice {
+            self.nice = Some(v);
+        }
+        if let Some(v) = job.io_priority {
+            self.io_priority = Some(v);
+        }
+        if let Some(b) = job.pty {
+            self.pty = Some(b);
+        }
+        if let Some(b) = job.stdin_forwarding {
+            self.stdin_forwarding = Some(b);
+        }
+        if job.shell.is_some() {
+            self.shell.clone_from(&job.shell);
+        }
+        if let Some(b) = job.use_shell {
+            self.use_shell = Some(b);
+        }
         if let Some(v) = job.on_change_strategy {
             self.on_change_strategy = Some(v);
+            // the most specific setting must win
+            self.defer_events = None;
+        }
+        if let Some(v) = job.defer_events {
+            self.defer_events = Some(v);
         }
         if let Some(v) = job.on_success.as_ref() {
             self.on_success = Some(v.clone());
         }
+        if let Some(v) = job.min_interval {
+            self.min_interval = Some(v);
+        }
         if let Some(v) = job.grace_period {
             self.grace_period = Some(v);
         }
+        if let Some(b) = job.speculative_start {
+            self.speculative_start = Some(b);
+        }
         if let Some(v) = job.on_failure.as_ref() {
             self.on_failure = Some(v.clone());
         }
         if let Some(v) = job.watch.as_ref() {
             self.watch = Some(v.clone());
         }
+        if let Some(v) = job.watch_globs.as_ref() {
+            self.watch_globs = Some(v.clone());
+        }
         if let Some(b) = job.show_changes_count {
             self.show_changes_count = Some(b);
         }
+        for (k, v) in &job.severity {
+            self.severity.insert(k.clone(), *v);
+        }
+        if let Some(b) = job.show_trigger_paths {
+            self.show_trigger_paths = Some(b);
+        }
+        if let Some(b) = job.loop_detection {
+            self.loop_detection = Some(b);
+        }
+        if let Some(b) = job.watch_dep_info {
+            self.watch_dep_info = Some(b);
+        }
         self.sound.apply(&job.sound);
+        self.push.apply(&job.push);
+        self.hooks.apply(&job.hooks);
     }
 }
 
@@ -235,32 +671,94 @@ fn test_job_apply() {
     use std::str::FromStr;
     let mut base_job = Job::default();
     let job_to_apply = Job {
+        aliases: Some(vec!["t".to_string()]),
         allow_failures: Some(true),
-        allow_warnings: Some(false),
-        analyzer: Some(AnalyzerRef::Nextest),
+        allow_warnings: Some(AllowWarnings::InPaths(vec!["tests/**".to_string()])),
+        analyzer: Some(AnalyzerConfig::WithOptions(AnalyzerWithOptions {
+            name: AnalyzerRef::Nextest,
+            options: toml::from_str("leaks_as_failures = true").unwrap(),
+        })),
         apply_gitignore: Some(false),
+        apply_global_gitignore: Some(true),
         background: Some(false),
+        baseline_file: Some("baselines/clippy.json".to_string()),
         command: vec!["cargo".to_string(), "test".to_string()],
         default_watch: Some(false),
+        description: Some("all the tests".to_string()),
+        diff_scope: Some(true),
+        diff_base: Some("main".to_string()),
         env: vec![("RUST_LOG".to_string(), "debug".to_string())]
             .into_iter()
             .collect(),
         expand_env_vars: Some(false),
         extraneous_args: Some(false),
+        hidden: Some(true),
         ignore: vec!["special-target".to_string(), "generated".to_string()],
+        ignore_editor_temp_files: Some(false),
+        ignore_files: Some(vec![".my-ignore".to_string()]),
         ignored_lines: Some(vec![LinePattern::from_str("half-error.*").unwrap()]),
+        json_messages: Some(true),
         kill: Some(vec!["die".to_string()]),
+        kill_signal: Some(KillSignal::Term),
+        kill_timeout: Some(Period::from_str("2s").unwrap()),
+        live_report: Some(true),
+        mark_external_diagnostics: Some(true),
+        max_output_lines: Some(5_000),
+        memory_limit: Some(MemorySize::from_str("4G").unwrap()),
         need_stdout: Some(true),
+        notify_on: Some(NotifyOn::FailThenSuccess),
+        notify_with: Some(NotifyWith::Osc777),
+        nice: Some(10),
+        io_priority: Some(IoPriority::Idle),
+        pane_title: Some(true),
+        parallel_analysis: Some(false),
+        retained_output_lines: Some(500),
+        retained_reports: Some(3),
+        pty: Some(false),
+        shell: Some(vec!["bash".to_string(), "-c".to_string()]),
+        stdin_forwarding: Some(true),
+        use_shell: Some(true),
         grace_period: Some(Period::from_str("20ms").unwrap()),
+        speculative_start: Some(true),
+        min_interval: Some(Period::from_str("5s").unwrap()),
         on_change_strategy: Some(OnChangeStrategy::KillThenRestart),
+        defer_events: Some(DeferEvents::Ignore),
         on_success: Some(Action::from_str("refresh").unwrap()),
         on_failure: Some(Action::from_str("play-sound(name=car-horn)").unwrap()),
-        watch: Some(vec!["src".to_string(), "tests".to_string()]),
+        watch: Some(vec![
+            "src".into(),
+            WatchEntry::Root {
+                path: "../protos".to_string(),
+                ignore: vec!["*.bin".to_string()],
+            },
+        ]),
+        watch_globs: Some(vec!["**/*.md".to_string()]),
         show_changes_count: Some(true),
+        tmux_option: Some("@bacon".to_string()),
+        severity: vec![("clippy::unwrap_used".to_string(), Severity::Error)]
+            .into_iter()
+            .collect(),
+        show_trigger_paths: Some(true),
+        loop_detection: Some(false),
+        watch_dep_info: Some(true),
         sound: SoundConfig {
             enabled: Some(true),
             base_volume: Some(Volume::from_str("50").unwrap()),
         },
+        push: PushConfig {
+            url: Some("https://ntfy.sh/bacon".to_string()),
+            service: Some(PushService::Ntfy),
+            token: None,
+            user: None,
+            on: Some(NotifyOn::Failure),
+            min_duration: Some(std::time::Duration::from_secs(60).into()),
+        },
+        hooks: HooksConfig {
+            command: None,
+            report: Some(vec!["python3".to_string(), "hooks/report.py".to_string()]),
+            success: Some(vec!["./check-success".to_string()]),
+            timeout: Some(Period::from_str("3s").unwrap()),
+        },
     };
     base_job.apply(&job_to_apply);
     dbg!(&base_job);
@@ -23,7 +23,7 @@ impl JobStack {
         settings: &Settings,
     ) -> Result<Option<(ConcreteJobRef, Job)>> {
         debug!("picking job {job_ref:?}");
-        let concrete = match job_ref {
+        let mut concrete = match job_ref {
             JobRef::Default => settings.default_job.clone(),
             JobRef::Initial => settings
                 .arg_job
@@ -67,6 +67,14 @@ impl JobStack {
                 }
             },
         };
+        // an alias of the job is replaced with its name
+        if let NameOrAlias::Name(name) = &concrete.name_or_alias {
+            if let Some(job_name) = settings.job_name(name) {
+                if job_name != name {
+                    concrete.name_or_alias = NameOrAlias::Name(job_name.to_string());
+                }
+            }
+        }
         let job = match &concrete.name_or_alias {
             NameOrAlias::Alias(alias) => Job::from_alias(alias, settings),
             NameOrAlias::Name(name) => settings
@@ -1,13 +1,17 @@
+mod allow_warnings;
 mod concrete_job_ref;
 mod job;
 mod job_ref;
 mod job_stack;
 mod scope;
+mod watch_entry;
 
 pub use {
+    allow_warnings::*,
     concrete_job_ref::*,
     job::*,
     job_ref::*,
     job_stack::*,
     scope::*,
+    watch_entry::*,
 };
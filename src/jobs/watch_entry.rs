@@ -0,0 +1,58 @@
+use serde::Deserialize;
+
+/// An entry of the `watch` list of a job: either a path, or a
+/// path with its own ignore patterns, eg
+/// `{ path = "../shared-protos", ignore = ["*.bin"] }`.
+///
+/// Paths are relative to the package (and to the workspace and the
+/// other local packages).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum WatchEntry {
+    Path(String),
+    Root {
+        path: String,
+        #[serde(default)]
+        ignore: Vec<String>,
+    },
+}
+
+impl WatchEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Path(path) => path,
+            Self::Root { path, .. } => path,
+        }
+    }
+    /// The ignore patterns, relative to the watched path
+    pub fn ignore(&self) -> &[String] {
+        match self {
+            Self::Path(_) => &[],
+            Self::Root { ignore, .. } => ignore,
+        }
+    }
+}
+
+impl From<&str> for WatchEntry {
+    fn from(path: &str) -> Self {
+        Self::Path(path.to_string())
+    }
+}
+
+#[test]
+fn test_watch_entry_deserialization() {
+    #[derive(Deserialize)]
+    struct Conf {
+        watch: Vec<WatchEntry>,
+    }
+    let conf: Conf = toml::from_str(
+        r#"
+        watch = ["src", { path = "../shared-protos", ignore = ["*.bin"] }]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(conf.watch[0], WatchEntry::from("src"));
+    assert!(conf.watch[0].ignore().is_empty());
+    assert_eq!(conf.watch[1].path(), "../shared-protos");
+    assert_eq!(conf.watch[1].ignore(), &["*.bin".to_string()]);
+}
@@ -4,19 +4,40 @@ mod cli;
 mod conf;
 mod context;
 mod context_nature;
+mod control;
+mod dashboard;
+mod dep_info;
+mod doctor;
+mod editor;
 mod exec;
 mod export;
+mod github;
 mod help;
+mod hooks;
+mod http_request;
 mod ignorer;
+mod instances;
 mod internal;
 mod jobs;
+mod linked_dirs;
+mod mcp;
+mod metadata_cache;
 mod mission;
+mod notifications;
+mod nvim;
+mod path_normalization;
+mod placeholders;
 mod result;
+mod run_log;
 mod search;
+mod session;
 mod sound;
+mod systemd;
+mod triggers;
 mod tty;
 mod tui;
 mod watcher;
+mod webhook;
 
 pub use {
     analysis::*,
@@ -24,19 +45,40 @@ pub use {
     conf::*,
     context::*,
     context_nature::*,
+    control::*,
+    dashboard::*,
+    dep_info::*,
+    doctor::*,
+    editor::*,
     exec::*,
     export::*,
+    github::*,
     help::*,
+    hooks::*,
+    http_request::*,
     ignorer::*,
+    instances::*,
     internal::*,
     jobs::*,
+    linked_dirs::*,
+    mcp::*,
+    metadata_cache::*,
     mission::*,
+    notifications::*,
+    nvim::*,
+    path_normalization::*,
+    placeholders::*,
     result::*,
+    run_log::*,
     search::*,
+    session::*,
     sound::*,
+    systemd::*,
+    triggers::*,
     tty::*,
     tui::*,
     watcher::*,
+    webhook::*,
 };
 
 #[macro_use]
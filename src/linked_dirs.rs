@@ -0,0 +1,148 @@
+use {
+    crate::*,
+    std::{
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// How deep, below the watched directories, symbolic links are looked for
+const MAX_DEPTH: usize = 16;
+
+/// The directories reached through symbolic links from the watched
+/// directories, eg shared crates symlinked in a workspace.
+///
+/// Some notification systems don't follow symlinks, so those directories
+/// must be watched with their real paths, and the paths of the events
+/// translated back so that the ignorers, whose rules are relative to the
+/// package, see the paths as they appear in the package.
+#[derive(Debug, Default)]
+pub struct LinkedDirs {
+    /// (real path, path through the link)
+    links: Vec<(PathBuf, PathBuf)>,
+}
+
+impl LinkedDirs {
+    /// Find the symlinked directories in the given watched paths,
+    /// following links of links but never visiting a directory twice.
+    ///
+    /// The excluded directories (eg the gitignored `target` or
+    /// `node_modules`) aren't explored, and neither are the ones deeper
+    /// than [`MAX_DEPTH`].
+    pub fn find(
+        watched_paths: &[PathBuf],
+        ignorer: &mut IgnorerSet,
+    ) -> Self {
+        let mut visited: Vec<PathBuf> = watched_paths
+            .iter()
+            .filter_map(|p| fs::canonicalize(p).ok())
+            .collect();
+        let mut links = Vec::new();
+        let mut dirs: Vec<(PathBuf, usize)> = watched_paths
+            .iter()
+            .filter(|p| p.is_dir())
+            .map(|p| (p.clone(), 0))
+            .collect();
+        while let Some((dir, depth)) = dirs.pop() {
+            if depth >= MAX_DEPTH {
+                debug!("not looking for links in {dir:?}, too deep");
+                continue;
+            }
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let path = entry.path();
+                let is_link_to_dir = file_type.is_symlink() && path.is_dir();
+                if !(file_type.is_dir() || is_link_to_dir) {
+                    continue;
+                }
+                if ignorer.excludes_directory(&path) {
+                    continue;
+                }
+                if file_type.is_dir() {
+                    dirs.push((path, depth + 1));
+                    continue;
+                }
+                let Ok(real_path) = fs::canonicalize(&path) else {
+                    continue;
+                };
+                // cycle protection, and no need to watch twice a directory
+                // which is already inside a watched one
+                if visited.iter().any(|v| real_path.starts_with(v)) {
+                    debug!("not following already visited link {:?}", path);
+                    continue;
+                }
+                debug!("following link {:?} to {:?}", path, real_path);
+                visited.push(real_path.clone());
+                links.push((real_path, path.clone()));
+                dirs.push((path, depth + 1));
+            }
+        }
+        Self { links }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+    /// The real paths of the linked directories
+    pub fn real_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.links.iter().map(|(real_path, _)| real_path)
+    }
+    /// Translate a real path into the path through the link, if
+    /// it's in a linked directory
+    pub fn to_link_path(
+        &self,
+        path: &Path,
+    ) -> PathBuf {
+        for (real_path, link_path) in &self.links {
+            if let Ok(relative_path) = path.strip_prefix(real_path) {
+                return link_path.join(relative_path);
+            }
+        }
+        path.to_path_buf()
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_linked_dirs() {
+    use std::os::unix::fs::symlink;
+    let dir = std::env::temp_dir().join(format!("bacon-linked-dirs-{}", std::process::id()));
+    let dir = {
+        fs::create_dir_all(&dir).unwrap();
+        fs::canonicalize(&dir).unwrap()
+    };
+    let pack = dir.join("pack");
+    let shared = dir.join("shared");
+    fs::create_dir_all(pack.join("src")).unwrap();
+    fs::create_dir_all(shared.join("src")).unwrap();
+    symlink(&shared, pack.join("src/shared")).unwrap();
+    // a cycle
+    symlink(&shared, shared.join("src/again")).unwrap();
+    // links in excluded directories aren't followed
+    let other = dir.join("other");
+    fs::create_dir_all(pack.join("src/ignored")).unwrap();
+    fs::create_dir_all(&other).unwrap();
+    symlink(&other, pack.join("src/ignored/other")).unwrap();
+    let mut ignorer = IgnorerSet::default();
+    let mut glob_ignorer = GlobIgnorer::default();
+    glob_ignorer.add("ignored", &pack).unwrap();
+    ignorer.add(Box::new(glob_ignorer));
+    let linked_dirs = LinkedDirs::find(&[pack.join("src")], &mut ignorer);
+    assert_eq!(linked_dirs.real_paths().collect::<Vec<_>>(), vec![&shared]);
+    assert_eq!(
+        linked_dirs.to_link_path(&shared.join("src/lib.rs")),
+        pack.join("src/shared/src/lib.rs"),
+    );
+    assert_eq!(
+        linked_dirs.to_link_path(&pack.join("src/main.rs")),
+        pack.join("src/main.rs"),
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
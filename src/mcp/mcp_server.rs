@@ -0,0 +1,86 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        anyhow,
+    },
+    serde_json::{
+        Value,
+        json,
+    },
+    std::io::{
+        self,
+        BufRead,
+        Write,
+    },
+};
+
+/// The protocol version answered when the client doesn't ask for one
+const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Run a Model Context Protocol server on stdin and stdout, giving
+/// AI agents access to the analysis of the bacon listening on the
+/// control socket
+pub fn run_mcp_server(settings: &Settings) -> Result<()> {
+    let socket = settings.control.socket.as_ref().ok_or_else(|| {
+        anyhow!(
+            "The MCP server needs a control socket: set it in [control] or with --control-socket"
+        )
+    })?;
+    let client = ControlClient::new(socket);
+    info!("MCP server started, using control socket {:?}", socket);
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle_mcp_request(&client, &request),
+            Err(_) => Some(rpc_response(Value::Null, Err(RpcError::parse_error()))),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Return the response to the request, or None if it's a notification
+fn handle_mcp_request(
+    client: &ControlClient,
+    request: &Value,
+) -> Option<Value> {
+    let id = request.get("id").cloned()?;
+    let params = &request["params"];
+    let result = match request["method"].as_str() {
+        Some("initialize") => Ok(json!({
+            "protocolVersion": params["protocolVersion"]
+                .as_str()
+                .unwrap_or(MCP_PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": "bacon",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })),
+        Some("ping") => Ok(json!({})),
+        Some("tools/list") => Ok(json!({ "tools": mcp_tools() })),
+        Some("tools/call") => {
+            let name = params["name"].as_str().unwrap_or_default();
+            // tool errors are results, so that the agent can read them
+            let (text, is_error) = match call_mcp_tool(client, name, &params["arguments"]) {
+                Ok(text) => (text, false),
+                Err(e) => (e.to_string(), true),
+            };
+            Ok(json!({
+                "content": [{ "type": "text", "text": text }],
+                "isError": is_error,
+            }))
+        }
+        Some(_) => Err(RpcError::method_not_found()),
+        None => Err(RpcError::invalid_request()),
+    };
+    Some(rpc_response(id, result))
+}
@@ -0,0 +1,158 @@
+use {
+    crate::*,
+    anyhow::Result,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        fmt::Write,
+        time::Duration,
+    },
+};
+
+/// How long `run_job` waits, by default, for the end of the execution
+const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The description of the tools, as returned by `tools/list`
+pub fn mcp_tools() -> Value {
+    json!([
+        {
+            "name": "get_diagnostics",
+            "description": "Get the errors, warnings, and test failures found by bacon in the last execution of its current job (eg cargo check or clippy), with their locations",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "run_job",
+            "description": "Run a bacon job (the current one if no job is given), wait for its end, and return its diagnostics",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "job": {
+                        "type": "string",
+                        "description": "name of the job, eg check, clippy, test",
+                    },
+                    "timeout_secs": {
+                        "type": "integer",
+                        "description": "maximal duration to wait for the end of the execution",
+                    },
+                },
+            },
+        },
+        {
+            "name": "get_failed_tests",
+            "description": "Get the names of the tests which failed in the last execution",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "list_jobs",
+            "description": "List the jobs bacon can run, and the current one",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
+
+/// Call the tool, using the control API of the running bacon, and
+/// return the text of the result
+pub fn call_mcp_tool(
+    client: &ControlClient,
+    name: &str,
+    arguments: &Value,
+) -> Result<String> {
+    match name {
+        "get_diagnostics" => Ok(diagnostics_text(
+            &client.call("get_diagnostics", Value::Null)?,
+        )),
+        "run_job" => {
+            let job = arguments["job"].as_str();
+            let timeout = arguments["timeout_secs"]
+                .as_u64()
+                .map_or(DEFAULT_RUN_TIMEOUT, Duration::from_secs);
+            client.run_and_wait(job, timeout)?;
+            Ok(diagnostics_text(
+                &client.call("get_diagnostics", Value::Null)?,
+            ))
+        }
+        "get_failed_tests" => {
+            let tests = client.call("get_failed_tests", Value::Null)?;
+            let names: Vec<&str> = tests
+                .as_array()
+                .map(|tests| tests.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            if names.is_empty() {
+                Ok("No failed test".to_string())
+            } else {
+                Ok(names.join("\n"))
+            }
+        }
+        "list_jobs" => {
+            let jobs = client.call("list_jobs", Value::Null)?;
+            Ok(format!(
+                "current job: {}\njobs: {}",
+                jobs["current"].as_str().unwrap_or_default(),
+                jobs["jobs"]
+                    .as_array()
+                    .map(|jobs| {
+                        jobs.iter()
+                            .filter_map(Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                    .unwrap_or_default(),
+            ))
+        }
+        _ => anyhow::bail!("Unknown tool: {name}"),
+    }
+}
+
+/// Build a compact text, one line per item, from the result of
+/// the `get_diagnostics` control method
+fn diagnostics_text(diagnostics: &Value) -> String {
+    if diagnostics.is_null() {
+        return "No report: the job is running or didn't run yet".to_string();
+    }
+    let status = &diagnostics["status"];
+    let mut text = format!(
+        "{}: {} ({} error(s), {} test failure(s), {} warning(s))\n",
+        status["job"].as_str().unwrap_or_default(),
+        status["state"].as_str().unwrap_or_default(),
+        status["errors"],
+        status["test_fails"],
+        status["warnings"],
+    );
+    if let Some(locations) = diagnostics["locations"].as_array() {
+        for item in locations {
+            let _ = writeln!(
+                text,
+                "{} {} {}",
+                item["kind"].as_str().unwrap_or_default(),
+                item["location"].as_str().unwrap_or_default(),
+                item["message"].as_str().unwrap_or_default(),
+            );
+        }
+    }
+    text
+}
+
+#[test]
+fn test_diagnostics_text() {
+    let diagnostics = json!({
+        "status": {
+            "job": "clippy",
+            "state": "failure",
+            "errors": 1,
+            "test_fails": 0,
+            "warnings": 1,
+        },
+        "locations": [
+            { "kind": "error", "location": "src/lib.rs:8:5", "message": "mismatched types" },
+            { "kind": "warning", "location": "src/main.rs:3:9", "message": "unused variable" },
+        ],
+    });
+    assert_eq!(
+        diagnostics_text(&diagnostics),
+        "clippy: failure (1 error(s), 0 test failure(s), 1 warning(s))\n\
+        error src/lib.rs:8:5 mismatched types\n\
+        warning src/main.rs:3:9 unused variable\n",
+    );
+}
@@ -0,0 +1,7 @@
+mod mcp_server;
+mod mcp_tools;
+
+pub use {
+    mcp_server::*,
+    mcp_tools::*,
+};
@@ -0,0 +1,280 @@
+use {
+    anyhow::Result,
+    cargo_metadata::{
+        Metadata,
+        MetadataCommand,
+    },
+    rustc_hash::FxHasher,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        env,
+        fs,
+        hash::{
+            Hash,
+            Hasher,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// The files whose change invalidates the cached metadata, with the
+/// hash of their content (None when they don't exist)
+type CacheInputs = Vec<(PathBuf, Option<u64>)>;
+
+/// The result of `cargo metadata`, as cached between launches
+#[derive(Serialize, Deserialize)]
+struct CachedMetadata {
+    bacon_version: String,
+    inputs: CacheInputs,
+    /// the manifests of the packages matched by the member globs of the
+    /// workspace, which change when a package is added or removed
+    #[serde(default)]
+    glob_members: Vec<PathBuf>,
+    metadata: Metadata,
+}
+
+/// Return the metadata of the cargo package, from the cache when none
+/// of the manifests (or the lock file, or the cargo configs) changed
+/// and no package was added to the workspace since it was cached, so
+/// that bacon starts fast in big workspaces
+pub fn load_metadata(
+    package_directory: &Path,
+    offline: bool,
+    use_cache: bool,
+) -> Result<Metadata> {
+    let cache_path = use_cache
+        .then(|| cache_path(package_directory, offline))
+        .flatten();
+    if let Some(cache_path) = &cache_path {
+        if let Some(metadata) = read_cache(cache_path) {
+            debug!("metadata read from cache {cache_path:?}");
+            return Ok(metadata);
+        }
+    }
+    let metadata = run_cargo_metadata(package_directory, offline)?;
+    if let Some(cache_path) = &cache_path {
+        // the inputs are read after cargo, which may have written the lock file
+        let inputs = hash_inputs(input_paths(package_directory, &metadata));
+        let glob_members = glob_members(metadata.workspace_root.as_std_path());
+        if let Err(e) = write_cache(cache_path, inputs, glob_members, &metadata) {
+            warn!("failed to cache the metadata: {e}");
+        }
+    }
+    Ok(metadata)
+}
+
+fn run_cargo_metadata(
+    package_directory: &Path,
+    offline: bool,
+) -> Result<Metadata> {
+    let metadata = if offline {
+        MetadataCommand::new()
+            .current_dir(package_directory)
+            .no_deps()
+            .other_options(["--frozen".to_string(), "--offline".to_string()])
+            .exec()
+    } else {
+        MetadataCommand::new().current_dir(package_directory).exec()
+    };
+    Ok(metadata?)
+}
+
+/// Return the path of the cache file for this package, if the system
+/// has a cache directory
+fn cache_path(
+    package_directory: &Path,
+    offline: bool,
+) -> Option<PathBuf> {
+    let project_dirs = directories_next::ProjectDirs::from("org", "dystroy", "bacon")?;
+    let mut hasher = FxHasher::default();
+    package_directory.hash(&mut hasher);
+    offline.hash(&mut hasher);
+    // the target directory of the metadata depends on it
+    env::var_os("CARGO_TARGET_DIR").hash(&mut hasher);
+    Some(
+        project_dirs
+            .cache_dir()
+            .join("metadata")
+            .join(format!("{:016x}.json", hasher.finish())),
+    )
+}
+
+fn read_cache(cache_path: &Path) -> Option<Metadata> {
+    let content = fs::read_to_string(cache_path).ok()?;
+    let cached: CachedMetadata = serde_json::from_str(&content).ok()?;
+    if cached.bacon_version != env!("CARGO_PKG_VERSION") {
+        return None;
+    }
+    if hash_inputs(cached.inputs.iter().map(|(path, _)| path.clone()).collect()) != cached.inputs {
+        debug!("cached metadata outdated");
+        return None;
+    }
+    if glob_members(cached.metadata.workspace_root.as_std_path()) != cached.glob_members {
+        debug!("workspace members changed since the metadata was cached");
+        return None;
+    }
+    Some(cached.metadata)
+}
+
+fn write_cache(
+    cache_path: &Path,
+    inputs: CacheInputs,
+    glob_members: Vec<PathBuf>,
+    metadata: &Metadata,
+) -> Result<()> {
+    if let Some(directory) = cache_path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let cached = CachedMetadata {
+        bacon_version: env!("CARGO_PKG_VERSION").to_string(),
+        inputs,
+        glob_members,
+        metadata: metadata.clone(),
+    };
+    fs::write(cache_path, serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
+/// Return the paths of the files which, when changed, may change
+/// the metadata
+fn input_paths(
+    package_directory: &Path,
+    metadata: &Metadata,
+) -> Vec<PathBuf> {
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let mut paths = vec![
+        package_directory.join("Cargo.toml"),
+        workspace_root.join("Cargo.lock"),
+    ];
+    for package in &metadata.packages {
+        // the local packages, ie the members and the path dependencies
+        if package.source.is_none() {
+            paths.push(package.manifest_path.as_std_path().to_path_buf());
+        }
+    }
+    // cargo reads the configuration files of all the parent directories,
+    // and rustup looks for the toolchain file in them too
+    for directory in package_directory
+        .ancestors()
+        .chain(workspace_root.ancestors())
+    {
+        paths.push(directory.join(".cargo/config.toml"));
+        paths.push(directory.join(".cargo/config"));
+        paths.push(directory.join("rust-toolchain.toml"));
+        paths.push(directory.join("rust-toolchain"));
+    }
+    if let Some(cargo_home) = cargo_home() {
+        paths.push(cargo_home.join("config.toml"));
+        paths.push(cargo_home.join("config"));
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| directories_next::BaseDirs::new().map(|d| d.home_dir().join(".cargo")))
+}
+
+/// Return the sorted manifests of the packages matched by the globs among
+/// the members of the workspace (eg `members = ["crates/*"]`), as the
+/// workspace manifest doesn't change when such a package is added
+fn glob_members(workspace_root: &Path) -> Vec<PathBuf> {
+    let Ok(content) = fs::read_to_string(workspace_root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let Ok(manifest) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let Some(members) = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+    else {
+        return Vec::new();
+    };
+    let mut manifests: Vec<PathBuf> = members
+        .iter()
+        .filter_map(|member| member.as_str())
+        .filter(|member| member.contains(['*', '?', '[']))
+        .filter_map(|member| {
+            let pattern = workspace_root.join(member).join("Cargo.toml");
+            glob::glob(&pattern.to_string_lossy()).ok()
+        })
+        .flat_map(|paths| paths.filter_map(Result::ok))
+        .collect();
+    manifests.sort();
+    manifests.dedup();
+    manifests
+}
+
+fn hash_inputs(paths: Vec<PathBuf>) -> CacheInputs {
+    paths
+        .into_iter()
+        .map(|path| {
+            let hash = fs::read(&path).ok().map(|content| {
+                let mut hasher = FxHasher::default();
+                content.hash(&mut hasher);
+                hasher.finish()
+            });
+            (path, hash)
+        })
+        .collect()
+}
+
+#[test]
+fn test_hash_inputs() {
+    let directory = env::temp_dir().join(format!("bacon-test-metadata-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let manifest = directory.join("Cargo.toml");
+    let lock_file = directory.join("Cargo.lock");
+    fs::write(&manifest, "[package]\nname = \"a\"\n").unwrap();
+    let paths = vec![manifest.clone(), lock_file.clone()];
+    let inputs = hash_inputs(paths.clone());
+    assert!(inputs[0].1.is_some());
+    assert_eq!(inputs[1].1, None);
+    assert_eq!(hash_inputs(paths.clone()), inputs);
+    // a change of content, or a new file, invalidates the cache
+    fs::write(&manifest, "[package]\nname = \"b\"\n").unwrap();
+    assert_ne!(hash_inputs(paths.clone()), inputs);
+    let inputs = hash_inputs(paths.clone());
+    fs::write(&lock_file, "version = 4\n").unwrap();
+    assert_ne!(hash_inputs(paths), inputs);
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_glob_members() {
+    let directory = tempfile::tempdir().unwrap();
+    let root = directory.path();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"app\", \"crates/*\"]\n",
+    )
+    .unwrap();
+    let add_package = |dir: &str| {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(root.join(dir).join("Cargo.toml"), "[package]\n").unwrap();
+    };
+    add_package("app");
+    add_package("crates/a");
+    fs::create_dir_all(root.join("crates/not-a-package")).unwrap();
+    assert_eq!(glob_members(root), vec![root.join("crates/a/Cargo.toml")]);
+    // a new package, matched by the glob, invalidates the cache
+    add_package("crates/b");
+    assert_eq!(
+        glob_members(root),
+        vec![
+            root.join("crates/a/Cargo.toml"),
+            root.join("crates/b/Cargo.toml")
+        ],
+    );
+}
@@ -1,10 +1,12 @@
 use {
     crate::*,
-    lazy_regex::regex_replace_all,
     rustc_hash::FxHashSet,
     std::{
         collections::HashMap,
-        path::PathBuf,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
 };
 
@@ -17,16 +19,37 @@ pub struct Mission<'s> {
     pub execution_directory: PathBuf,
     pub package_directory: PathBuf,
     pub workspace_directory: Option<PathBuf>,
+    /// target directory of cargo, if it's a cargo project
+    pub target_directory: Option<PathBuf>,
     pub job: Job,
     pub paths_to_watch: Vec<PathBuf>,
+    /// ignore patterns specific to some watched paths (which are
+    /// the roots of those patterns)
+    pub watch_root_ignores: Vec<(PathBuf, Vec<String>)>,
+    /// excludes the changes of the directories which aren't part
+    /// of the workspace (excluded members, nested workspaces)
+    pub workspace_ignorer: Option<WorkspaceIgnorer>,
+    /// directories of the local packages which aren't workspace members
+    pub foreign_package_directories: Vec<PathBuf>,
+    /// names of the members of the workspace
+    pub workspace_members: Vec<String>,
+    /// the package the cargo commands are scoped to, if any
+    pub package: Option<String>,
+    /// name of the cargo package of the package directory
+    pub package_name: Option<String>,
+    /// the command computed by the command hook of the job, once done
+    pub hooked_command: Option<Vec<String>>,
     pub settings: &'s Settings,
 }
 
+/// Default name of the baseline file, in the workspace (or package) directory
+pub const DEFAULT_BASELINE_FILE: &str = ".bacon-baseline.json";
+
 impl Mission<'_> {
     /// Return an Ignorer according to the job's settings
     pub fn ignorer(&self) -> IgnorerSet {
         let mut set = IgnorerSet::default();
-        if self.job.apply_gitignore != Some(false) {
+        if self.job.apply_gitignore() {
             match GitIgnorer::new(&self.package_directory) {
                 Ok(git_ignorer) => {
                     set.add(Box::new(git_ignorer));
@@ -37,6 +60,32 @@ impl Mission<'_> {
                 }
             }
         }
+        let mut ignore_file_ignorer = IgnoreFileIgnorer::default();
+        if self.job.apply_global_gitignore() {
+            if let Err(e) = ignore_file_ignorer.add_global_git_excludes(&self.package_directory) {
+                warn!("Failed to read global git excludes: {e}");
+            }
+        }
+        let ignore_files = self.job.ignore_files();
+        let mut dirs = vec![&self.package_directory];
+        if let Some(workspace_directory) = &self.workspace_directory {
+            // workspace level files are less specific so they're added first
+            dirs.insert(0, workspace_directory);
+        }
+        for dir in dirs {
+            if let Err(e) = ignore_file_ignorer.add_files_in_dir(dir, &ignore_files) {
+                warn!("Failed to read ignore files in {dir:?}: {e}");
+            }
+        }
+        if !ignore_file_ignorer.is_empty() {
+            set.add(Box::new(ignore_file_ignorer));
+        }
+        if let Some(workspace_ignorer) = &self.workspace_ignorer {
+            set.add(Box::new(workspace_ignorer.clone()));
+        }
+        if self.job.ignore_editor_temp_files() {
+            set.add(Box::new(GlobIgnorer::editor_temp_files()));
+        }
         if !self.job.ignore.is_empty() {
             let mut glob_ignorer = GlobIgnorer::default();
             for pattern in &self.job.ignore {
@@ -46,6 +95,28 @@ impl Mission<'_> {
             }
             set.add(Box::new(glob_ignorer));
         }
+        for (root, patterns) in &self.watch_root_ignores {
+            let mut glob_ignorer = GlobIgnorer::scoped(root.clone());
+            for pattern in patterns {
+                if let Err(e) = glob_ignorer.add(pattern, root) {
+                    warn!("Failed to add ignore pattern {pattern}: {e}");
+                }
+            }
+            set.add(Box::new(glob_ignorer));
+        }
+        if let Some(watch_globs) = &self.job.watch_globs {
+            let mut bases = vec![self.package_directory.clone()];
+            if let Some(workspace_directory) = &self.workspace_directory {
+                bases.push(workspace_directory.clone());
+            }
+            let mut watch_globs_ignorer = WatchGlobsIgnorer::new(bases);
+            for pattern in watch_globs {
+                if let Err(e) = watch_globs_ignorer.add(pattern) {
+                    warn!("Failed to add watch glob {pattern}: {e}");
+                }
+            }
+            set.add(Box::new(watch_globs_ignorer));
+        }
         set
     }
 
@@ -53,13 +124,40 @@ impl Mission<'_> {
         &self,
         report: &Report,
     ) -> bool {
-        report.is_success(self.job.allow_warnings(), self.job.allow_failures())
+        if let Some(success) = report.hook_success {
+            return success;
+        }
+        let allow_failures = self.job.allow_failures();
+        match self.job.allow_warnings() {
+            AllowWarnings::All(allow_warnings) => report.is_success(allow_warnings, allow_failures),
+            AllowWarnings::InPaths(patterns) => {
+                if !report.is_success(true, allow_failures) {
+                    return false;
+                }
+                let mut bases = vec![self.package_directory.clone()];
+                if let Some(workspace_directory) = &self.workspace_directory {
+                    bases.push(workspace_directory.clone());
+                }
+                let mut globs = WatchGlobsIgnorer::new(bases);
+                for pattern in &patterns {
+                    if let Err(e) = globs.add(pattern) {
+                        warn!("Failed to add allow_warnings pattern {pattern}: {e}");
+                    }
+                }
+                // warnings without location aren't allowed
+                report
+                    .warning_paths(self)
+                    .iter()
+                    .all(|path| path.as_ref().is_some_and(|path| globs.allows(path)))
+            }
+        }
     }
 
     pub fn make_absolute(
         &self,
         path: PathBuf,
     ) -> PathBuf {
+        let path = normalize_path(&path);
         if path.is_absolute() {
             return path;
         }
@@ -72,31 +170,108 @@ impl Mission<'_> {
                 return workspace_joined;
             }
         }
-        self.package_directory.join(&path)
+        let package_joined = self.package_directory.join(&path);
+        if package_joined.exists() {
+            return package_joined;
+        }
+        // Cargo makes the paths of the packages which aren't members of the
+        // workspace (eg path dependencies in excluded directories) relative
+        // to their own directory
+        for directory in &self.foreign_package_directories {
+            let joined = directory.join(&path);
+            if joined.exists() {
+                return joined;
+            }
+        }
+        package_joined
+    }
+
+    /// Return the tracker of the dependencies of the build, according to the
+    /// dep-info files of cargo, if the job asks for it
+    pub fn dep_info(&self) -> Option<DepInfo> {
+        if !self.job.watch_dep_info() {
+            return None;
+        }
+        let Some(target_directory) = &self.target_directory else {
+            warn!("no target directory: dep-info files can't be watched");
+            return None;
+        };
+        let root_directory = self
+            .workspace_directory
+            .as_ref()
+            .unwrap_or(&self.package_directory);
+        Some(DepInfo::new(
+            target_directory.clone(),
+            root_directory.clone(),
+        ))
+    }
+
+    /// Return the path of the file locked during the executions, when
+    /// they must be exclusive with the ones of other bacon instances
+    pub fn run_lock_path(&self) -> Option<PathBuf> {
+        if !self.settings.instances.exclusive_runs {
+            return None;
+        }
+        let Some(target_directory) = &self.target_directory else {
+            warn!("no target directory: runs can't be exclusive");
+            return None;
+        };
+        Some(target_directory.join("bacon.lock"))
+    }
+
+    /// Return the path relative to the package directory, or to the workspace
+    /// directory, if possible (the path is returned unchanged otherwise)
+    pub fn make_relative<'p>(
+        &self,
+        path: &'p Path,
+    ) -> &'p Path {
+        path.strip_prefix(&self.package_directory)
+            .ok()
+            .or_else(|| {
+                self.workspace_directory
+                    .as_ref()
+                    .and_then(|w| path.strip_prefix(w).ok())
+            })
+            .unwrap_or(path)
     }
 
-    /// build (and doesn't call) the external cargo command
+    /// Return the path of the baseline file
+    pub fn baseline_path(&self) -> PathBuf {
+        let dir = self
+            .workspace_directory
+            .as_ref()
+            .unwrap_or(&self.package_directory);
+        match &self.job.baseline_file {
+            Some(path) => dir.join(path),
+            None => dir.join(DEFAULT_BASELINE_FILE),
+        }
+    }
+    /// Return the key under which the baseline of the job is stored
+    pub fn baseline_job_key(&self) -> String {
+        match &self.concrete_job_ref.name_or_alias {
+            NameOrAlias::Name(name) => name.clone(),
+            NameOrAlias::Alias(alias) => format!("alias:{alias}"),
+        }
+    }
+
+    /// The tokens of the command of the job, as configured
+    pub fn job_command(&self) -> Vec<String> {
+        self.job.command.iter().map(|t| self.expand(t)).collect()
+    }
+    /// Whether the command can't be built before the end of the
+    /// command hook of the job
+    pub fn waits_for_command_hook(&self) -> bool {
+        self.job.hooks.command.is_some() && self.hooked_command.is_none()
+    }
+    /// build (and doesn't call) the external cargo command, from the
+    /// command computed by the command hook if it's done
     pub fn get_command(&self) -> anyhow::Result<CommandBuilder> {
-        let mut command = if self.job.expand_env_vars() {
-            self.job
-                .command
-                .iter()
-                .map(|token| {
-                    regex_replace_all!(r"\$([A-Z0-9a-z_]+)", token, |whole: &str, name| {
-                        match std::env::var(name) {
-                            Ok(value) => value,
-                            Err(_) => {
-                                warn!("variable {whole} not found in env");
-                                whole.to_string()
-                            }
-                        }
-                    })
-                    .to_string()
-                })
-                .collect()
-        } else {
-            self.job.command.clone()
+        let mut command = match &self.hooked_command {
+            Some(command) => command.clone(),
+            None => self.job_command(),
         };
+        // the tokens of the job, which aren't quoted when run by a shell
+        let job_tokens = command.clone();
 
         if command.is_empty() {
             anyhow::bail!(
@@ -106,37 +281,63 @@ impl Mission<'_> {
         }
 
         let scope = &self.concrete_job_ref.scope;
-        if scope.has_tests() && command.len() > 2 {
-            let tests = if command[0] == "cargo" && command[1] == "test" {
-                // Here we're going around a limitation of the vanilla cargo test:
-                // it can only be scoped to one test
-                &scope.tests[..1]
-            } else {
-                &scope.tests
-            };
-            for test in tests {
+        if scope.has_tests() && command.len() > 1 {
+            if command[0] == "cargo"
+                && command[1] == "test"
+                && !command.iter().any(|arg| arg == "--")
+            {
+                // cargo test takes only one test name, but the test harness,
+                // after the `--`, takes any number of filters
+                command.push("--".to_string());
+            }
+            for test in &scope.tests {
                 command.push(test.to_string());
             }
         }
 
+        if let Some(package) = &self.package {
+            if command[0] == "cargo" && command.len() > 1 {
+                scope_command_to_package(&mut command, package);
+            }
+        }
+
+        if self.uses_json_messages()
+            && !command
+                .iter()
+                .any(|arg| arg.starts_with("--message-format"))
+        {
+            let idx = command
+                .iter()
+                .position(|arg| arg == "--")
+                .unwrap_or(command.len());
+            command.insert(idx, "--message-format".to_string());
+            command.insert(idx + 1, "json-diagnostic-rendered-ansi".to_string());
+        }
+
         let mut tokens = command.iter();
         let mut command = CommandBuilder::new(
             tokens.next().unwrap(), // implies a check in the job
         );
-        command.with_stdout(self.job.need_stdout());
-        let envs: HashMap<&String, &String> = self
+        // with json messages, the diagnostics come on stdout
+        command.with_stdout(self.job.need_stdout() || self.uses_json_messages());
+        command.with_pty(self.uses_pty());
+        command.with_stdin(self.job.stdin_forwarding());
+        command.with_limits(ResourceLimits {
+            nice: self.job.nice,
+            io_priority: self.job.io_priority,
+            memory_limit: self.job.memory_limit,
+        });
+        let envs: HashMap<&String, String> = self
             .settings
             .all_jobs
             .env
             .iter()
             .chain(self.job.env.iter())
+            .map(|(key, value)| (key, self.expand(value)))
             .collect();
         if !self.job.extraneous_args() {
             command.args(tokens);
-            command.current_dir(&self.execution_directory);
-            command.envs(envs);
-            debug!("command: {:#?}", &command);
-            return Ok(command);
+            return Ok(self.finish_command(command, envs, &job_tokens));
         }
 
         let mut no_default_features_done = false;
@@ -212,14 +413,42 @@ impl Mission<'_> {
                 command.arg(arg);
             }
         }
+        Ok(self.finish_command(command, envs, &job_tokens))
+    }
+
+    fn finish_command(
+        &self,
+        mut command: CommandBuilder,
+        envs: HashMap<&String, String>,
+        job_tokens: &[String],
+    ) -> CommandBuilder {
+        if let Some(shell) = self.job.shell() {
+            command.wrap_in_shell(&shell, job_tokens);
+        }
         command.current_dir(&self.execution_directory);
         command.envs(envs);
         debug!("command builder: {:#?}", &command);
-        Ok(command)
+        command
+    }
+
+    /// Expand the environment variables (if the job allows it) and the
+    /// placeholders of a token of a command, or of an env value
+    pub fn expand(
+        &self,
+        token: &str,
+    ) -> String {
+        if self.job.expand_env_vars() {
+            expand_placeholders(&expand_env_vars(token), self)
+        } else {
+            expand_placeholders(token, self)
+        }
     }
 
     pub fn kill_command(&self) -> Option<Vec<String>> {
-        self.job.kill.clone()
+        self.job
+            .kill
+            .as_ref()
+            .map(|kill| kill.iter().map(|token| self.expand(token)).collect())
     }
 
     /// whether we need stdout and not just stderr
@@ -231,7 +460,38 @@ impl Mission<'_> {
     }
 
     pub fn analyzer(&self) -> AnalyzerRef {
-        self.job.analyzer.unwrap_or_default()
+        self.job.effective_analyzer()
+    }
+
+    /// The options of the analyzer, set in the job with the
+    /// `analyzer = { name = "...", options = {...} }` form
+    pub fn analyzer_options(&self) -> AnalyzerOptions {
+        self.job.analyzer_options()
+    }
+
+    /// Whether the job runs tests with cargo test or nextest
+    pub fn is_test_job(&self) -> bool {
+        let command = &self.job.command;
+        command.first().is_some_and(|exe| exe == "cargo")
+            && command
+                .get(1)
+                .is_some_and(|sub| sub == "test" || sub == "nextest")
+    }
+
+    /// Whether the job is a cargo one run with the JSON message format
+    /// instead of the human one, see the `json_messages` job setting
+    pub fn uses_json_messages(&self) -> bool {
+        self.job.uses_json_messages()
+    }
+
+    /// Whether the command runs in a pseudo-terminal, which is the
+    /// default only with the passthrough analyzer, and possible only on unix
+    pub fn uses_pty(&self) -> bool {
+        cfg!(unix)
+            && self
+                .job
+                .pty
+                .unwrap_or(self.analyzer() == AnalyzerRef::Passthrough)
     }
 
     pub fn ignored_lines_patterns(&self) -> Option<&Vec<LinePattern>> {
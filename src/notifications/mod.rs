@@ -0,0 +1,11 @@
+mod notification;
+mod notify_on;
+mod notify_with;
+mod push_config;
+
+pub use {
+    notification::*,
+    notify_on::*,
+    notify_with::*,
+    push_config::*,
+};
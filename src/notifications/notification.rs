@@ -0,0 +1,114 @@
+use {
+    crate::*,
+    std::{
+        io::Write,
+        process::{
+            Command,
+            Stdio,
+        },
+        thread,
+    },
+};
+
+/// A notification, either displayed with the tool of the system
+/// (`notify-send` on linux and BSDs, `osascript` on mac, a
+/// PowerShell balloon tip on windows), or raised by the terminal
+/// on an escape sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+impl Notification {
+    /// Send the notification, writing on the terminal if it's done
+    /// with an escape sequence
+    pub fn send(
+        self,
+        with: NotifyWith,
+        w: &mut W,
+    ) -> anyhow::Result<()> {
+        let title = sanitize(&self.title);
+        let body = sanitize(&self.body);
+        match with {
+            NotifyWith::Desktop => {
+                self.send_to_desktop();
+                return Ok(());
+            }
+            NotifyWith::Osc9 => write!(w, "\u{1b}]9;{title}: {body}\u{1b}\\")?,
+            NotifyWith::Osc777 => write!(
+                w,
+                "\u{1b}]777;notify;{};{body}\u{1b}\\",
+                title.replace(';', ","),
+            )?,
+            NotifyWith::Bell => write!(w, "\u{7}")?,
+        }
+        w.flush()?;
+        Ok(())
+    }
+    /// Display the notification, without waiting for the tool to finish
+    fn send_to_desktop(self) {
+        thread::spawn(move || {
+            let mut command = self.command();
+            command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            match command.status() {
+                Ok(status) if !status.success() => {
+                    warn!("notification command failed: {status}");
+                }
+                Err(e) => {
+                    warn!("notification command couldn't be launched: {e}");
+                }
+                _ => {}
+            }
+        });
+    }
+    #[cfg(target_os = "macos")]
+    fn command(&self) -> Command {
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            &self.title,
+            &self.body,
+        ]);
+        command
+    }
+    #[cfg(windows)]
+    fn command(&self) -> Command {
+        let mut command = Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Add-Type -AssemblyName System.Windows.Forms; \
+                $n = New-Object System.Windows.Forms.NotifyIcon; \
+                $n.Icon = [System.Drawing.SystemIcons]::Information; \
+                $n.Visible = $true; \
+                $n.ShowBalloonTip(5000, $env:BACON_NOTIFICATION_TITLE, $env:BACON_NOTIFICATION_BODY, 'Info'); \
+                Start-Sleep -Seconds 6; \
+                $n.Dispose()",
+            ])
+            .env("BACON_NOTIFICATION_TITLE", &self.title)
+            .env("BACON_NOTIFICATION_BODY", &self.body);
+        command
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn command(&self) -> Command {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=bacon", &self.title, &self.body]);
+        command
+    }
+}
+
+/// Remove the control chars which would end the escape sequence
+fn sanitize(s: &str) -> String {
+    s.chars().filter(|c| !c.is_control()).collect()
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+/// When to send a desktop notification at the end of an execution
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotifyOn {
+    /// Never notify
+    #[default]
+    Never,
+    /// Notify at the end of every execution
+    Always,
+    /// Notify when the result flips from a success to a failure,
+    /// or from a failure to a success
+    Change,
+    /// Notify on every failure
+    Failure,
+    /// Notify when a success follows a failure
+    FailThenSuccess,
+}
+
+impl NotifyOn {
+    /// Tell whether a notification must be sent, given the success
+    /// of the previous execution (if any) and of the new one
+    pub fn must_notify(
+        self,
+        previous_success: Option<bool>,
+        success: bool,
+    ) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Always => true,
+            Self::Change => previous_success.is_some_and(|previous| previous != success),
+            Self::Failure => !success,
+            Self::FailThenSuccess => previous_success == Some(false) && success,
+        }
+    }
+}
+
+#[test]
+fn test_must_notify() {
+    assert!(!NotifyOn::Change.must_notify(None, false));
+    assert!(NotifyOn::Change.must_notify(Some(true), false));
+    assert!(!NotifyOn::Change.must_notify(Some(true), true));
+    assert!(NotifyOn::FailThenSuccess.must_notify(Some(false), true));
+    assert!(!NotifyOn::FailThenSuccess.must_notify(None, true));
+    assert!(!NotifyOn::FailThenSuccess.must_notify(Some(true), true));
+    assert!(NotifyOn::Failure.must_notify(Some(false), false));
+    assert!(!NotifyOn::Never.must_notify(Some(false), true));
+}
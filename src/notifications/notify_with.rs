@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+/// How notifications are sent
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyWith {
+    /// With the notification tool of the system
+    #[default]
+    Desktop,
+    /// With the OSC 9 escape sequence, understood by eg WezTerm,
+    /// iTerm2, kitty, and Windows Terminal
+    Osc9,
+    /// With the OSC 777 escape sequence, understood by eg foot,
+    /// WezTerm, and rxvt-unicode
+    Osc777,
+    /// With the bell character, which most terminals turn into an
+    /// urgency hint or a badge
+    Bell,
+}
@@ -0,0 +1,221 @@
+use {
+    crate::*,
+    serde::Deserialize,
+    std::{
+        io::Write,
+        process::{
+            Command,
+            Stdio,
+        },
+        thread,
+        time::Duration,
+    },
+};
+
+/// The endpoint of pushover's API
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+
+/// The `push` part of a job: how to send push notifications, eg to
+/// a phone, when the job finishes
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct PushConfig {
+    /// URL of the endpoint, eg `https://ntfy.sh/my-topic`
+    /// (not needed for pushover)
+    pub url: Option<String>,
+    pub service: Option<PushService>,
+    /// the application token, for pushover
+    pub token: Option<String>,
+    /// the user key, for pushover
+    pub user: Option<String>,
+    /// when to push, `always` by default
+    pub on: Option<NotifyOn>,
+    /// the minimal duration of the executions to push about,
+    /// so that only the long ones are notified
+    pub min_duration: Option<Period>,
+}
+
+/// The kind of HTTP endpoint receiving the push notifications
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PushService {
+    /// An endpoint receiving the message as body and the title
+    /// in a header, as ntfy.sh
+    #[default]
+    Ntfy,
+    /// The pushover API
+    Pushover,
+}
+
+impl PushConfig {
+    pub fn apply(
+        &mut self,
+        pc: &PushConfig,
+    ) {
+        if pc.url.is_some() {
+            self.url.clone_from(&pc.url);
+        }
+        if let Some(service) = pc.service {
+            self.service = Some(service);
+        }
+        if pc.token.is_some() {
+            self.token.clone_from(&pc.token);
+        }
+        if pc.user.is_some() {
+            self.user.clone_from(&pc.user);
+        }
+        if let Some(on) = pc.on {
+            self.on = Some(on);
+        }
+        if let Some(min_duration) = pc.min_duration {
+            self.min_duration = Some(min_duration);
+        }
+    }
+    fn service(&self) -> PushService {
+        self.service.unwrap_or_default()
+    }
+    fn url(&self) -> Option<&str> {
+        match (self.url.as_deref(), self.service()) {
+            (None, PushService::Pushover) => Some(PUSHOVER_URL),
+            (url, _) => url,
+        }
+    }
+    /// Tell whether the end of an execution, which lasted the given
+    /// duration, must be pushed
+    pub fn must_push(
+        &self,
+        previous_success: Option<bool>,
+        success: bool,
+        duration: Duration,
+    ) -> bool {
+        self.url().is_some()
+            && self
+                .min_duration
+                .map_or(true, |min| duration >= min.duration)
+            && self
+                .on
+                .unwrap_or(NotifyOn::Always)
+                .must_notify(previous_success, success)
+    }
+    /// Push the notification (with `curl`), without waiting for the
+    /// endpoint's answer
+    pub fn push(
+        &self,
+        notification: Notification,
+    ) {
+        let Some(url) = self.url() else {
+            return;
+        };
+        let mut curl = Command::new("curl");
+        curl.args(["--silent", "--show-error", "--fail", "--max-time", "10"]);
+        // options read by curl on its stdin, to keep secrets out
+        // of the visible command line
+        let mut config = String::new();
+        match self.service() {
+            PushService::Ntfy => {
+                curl.arg("--header")
+                    .arg(format!("Title: {}", header_value(&notification.title)))
+                    .arg("--data-binary")
+                    .arg(&notification.body);
+            }
+            PushService::Pushover => {
+                curl.args(["--config", "-"]);
+                for (name, value) in [
+                    ("token", self.token.as_deref().unwrap_or_default()),
+                    ("user", self.user.as_deref().unwrap_or_default()),
+                    ("title", &notification.title),
+                    ("message", &notification.body),
+                ] {
+                    config.push_str(&curl_config_line("form-string", &format!("{name}={value}")));
+                }
+            }
+        }
+        curl.arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        thread::spawn(move || match send(curl, &config) {
+            Ok(output) if !output.status.success() => {
+                warn!(
+                    "push failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Err(e) => {
+                warn!("push command couldn't be launched: {e}");
+            }
+            _ => {}
+        });
+    }
+}
+
+fn send(
+    mut curl: Command,
+    config: &str,
+) -> std::io::Result<std::process::Output> {
+    let mut child = curl.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    child.wait_with_output()
+}
+
+/// Make the text usable as the value of an HTTP header, which
+/// can't contain line breaks
+fn header_value(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// Build a line of a curl config file, setting the option to the
+/// value, quoted and escaped
+fn curl_config_line(
+    option: &str,
+    value: &str,
+) -> String {
+    let mut line = format!("{option} = \"");
+    for c in value.chars() {
+        match c {
+            '\\' => line.push_str("\\\\"),
+            '"' => line.push_str("\\\""),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c => line.push(c),
+        }
+    }
+    line.push_str("\"\n");
+    line
+}
+
+#[test]
+fn test_push_escaping() {
+    assert_eq!(
+        header_value("bacon: check\r\nX-Injected: 1"),
+        "bacon: check  X-Injected: 1",
+    );
+    assert_eq!(
+        curl_config_line("form-string", "message=1 \"error\"\nC:\\dev"),
+        "form-string = \"message=1 \\\"error\\\"\\nC:\\\\dev\"\n",
+    );
+}
+
+#[test]
+fn test_must_push() {
+    let secs = Duration::from_secs;
+    let mut push = PushConfig {
+        min_duration: Some(secs(60).into()),
+        ..Default::default()
+    };
+    assert!(!push.must_push(None, true, secs(100)));
+    push.url = Some("https://ntfy.sh/bacon".to_string());
+    assert!(push.must_push(None, true, secs(100)));
+    assert!(!push.must_push(None, true, secs(10)));
+    push.on = Some(NotifyOn::Failure);
+    assert!(!push.must_push(None, true, secs(100)));
+    let pushover = PushConfig {
+        service: Some(PushService::Pushover),
+        ..Default::default()
+    };
+    assert_eq!(pushover.url(), Some(PUSHOVER_URL));
+}
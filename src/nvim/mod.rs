@@ -0,0 +1,9 @@
+mod msgpack;
+mod nvim_config;
+mod nvim_settings;
+
+pub use {
+    msgpack::*,
+    nvim_config::*,
+    nvim_settings::*,
+};
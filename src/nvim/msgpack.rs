@@ -0,0 +1,86 @@
+//! The small part of MessagePack needed to send a request
+//! to neovim
+
+/// A value to encode as MessagePack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Msgpack<'a> {
+    Uint(u32),
+    Str(&'a str),
+    Array(Vec<Msgpack<'a>>),
+}
+
+impl Msgpack<'_> {
+    /// Build a msgpack-RPC request: `[0, msgid, method, params]`
+    pub fn rpc_request<'a>(
+        msgid: u32,
+        method: &'a str,
+        params: Vec<Msgpack<'a>>,
+    ) -> Msgpack<'a> {
+        Msgpack::Array(vec![
+            Msgpack::Uint(0),
+            Msgpack::Uint(msgid),
+            Msgpack::Str(method),
+            Msgpack::Array(params),
+        ])
+    }
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write(&mut buf);
+        buf
+    }
+    fn write(
+        &self,
+        buf: &mut Vec<u8>,
+    ) {
+        match self {
+            Self::Uint(n) if *n < 128 => {
+                buf.push(*n as u8);
+            }
+            Self::Uint(n) => {
+                buf.push(0xce);
+                buf.extend_from_slice(&n.to_be_bytes());
+            }
+            Self::Str(s) => {
+                let len = s.len();
+                if len < 32 {
+                    buf.push(0xa0 | len as u8);
+                } else if len <= u8::MAX as usize {
+                    buf.push(0xd9);
+                    buf.push(len as u8);
+                } else if len <= u16::MAX as usize {
+                    buf.push(0xda);
+                    buf.extend_from_slice(&(len as u16).to_be_bytes());
+                } else {
+                    buf.push(0xdb);
+                    buf.extend_from_slice(&(len as u32).to_be_bytes());
+                }
+                buf.extend_from_slice(s.as_bytes());
+            }
+            Self::Array(values) => {
+                let len = values.len();
+                if len < 16 {
+                    buf.push(0x90 | len as u8);
+                } else if len <= u16::MAX as usize {
+                    buf.push(0xdc);
+                    buf.extend_from_slice(&(len as u16).to_be_bytes());
+                } else {
+                    buf.push(0xdd);
+                    buf.extend_from_slice(&(len as u32).to_be_bytes());
+                }
+                for value in values {
+                    value.write(buf);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_msgpack_rpc_request() {
+    let request = Msgpack::rpc_request(1, "nvim_eval", vec![Msgpack::Str("1+1")]);
+    assert_eq!(request.encode(), b"\x94\x00\x01\xa9nvim_eval\x91\xa31+1");
+    let long = "x".repeat(300);
+    let request = Msgpack::rpc_request(200, "m", vec![Msgpack::Str(&long)]).encode();
+    assert_eq!(&request[..8], b"\x94\x00\xce\x00\x00\x00\xc8\xa1");
+    assert_eq!(&request[8..13], b"m\x91\xda\x01\x2c");
+}
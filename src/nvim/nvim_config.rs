@@ -0,0 +1,43 @@
+use {
+    serde::Deserialize,
+    std::collections::HashMap,
+};
+
+/// The `[nvim]` part of a configuration: how to publish the
+/// diagnostics to a running neovim
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NvimConfig {
+    /// Whether to publish the diagnostics after each execution
+    pub publish_diagnostics: Option<bool>,
+    /// Address of the neovim server (by default the `NVIM` env var,
+    /// which is set in the terminals of neovim)
+    pub server: Option<String>,
+    /// Name of the `vim.diagnostic` namespace
+    pub namespace: Option<String>,
+    /// Severities of the diagnostics, per kind of item (`error`,
+    /// `warning`, `test`, or `info`)
+    #[serde(default)]
+    pub severities: HashMap<String, DiagnosticSeverity>,
+}
+
+/// The severity of a `vim.diagnostic`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warn,
+    Info,
+    Hint,
+}
+
+impl DiagnosticSeverity {
+    /// The name of the severity in `vim.diagnostic.severity`
+    pub fn lua_name(self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Hint => "HINT",
+        }
+    }
+}
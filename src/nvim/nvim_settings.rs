@@ -0,0 +1,203 @@
+use {
+    crate::*,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        collections::HashMap,
+        io::{
+            Read,
+            Write,
+        },
+        net::{
+            SocketAddr,
+            TcpStream,
+        },
+        path::PathBuf,
+        thread,
+        time::Duration,
+    },
+};
+
+/// The lua code run by neovim, receiving the namespace name and the
+/// diagnostics (as JSON), replacing the diagnostics of the namespace
+const PUBLISH_LUA: &str = r#"
+local name, json = ...
+local ns = vim.api.nvim_create_namespace(name)
+vim.diagnostic.reset(ns)
+local by_buf = {}
+for _, d in ipairs(vim.json.decode(json)) do
+    local buf = vim.fn.bufadd(d.path)
+    by_buf[buf] = by_buf[buf] or {}
+    table.insert(by_buf[buf], {
+        lnum = d.line - 1,
+        col = d.column - 1,
+        severity = vim.diagnostic.severity[d.severity],
+        message = d.message,
+        source = "bacon",
+    })
+end
+for buf, diagnostics in pairs(by_buf) do
+    vim.diagnostic.set(ns, buf, diagnostics)
+end
+"#;
+
+/// Settings of the publication of diagnostics to neovim
+#[derive(Debug, Clone)]
+pub struct NvimSettings {
+    pub publish_diagnostics: bool,
+    pub server: Option<String>,
+    pub namespace: String,
+    pub severities: HashMap<String, DiagnosticSeverity>,
+}
+
+impl Default for NvimSettings {
+    fn default() -> Self {
+        let severities = [
+            ("error", DiagnosticSeverity::Error),
+            ("warning", DiagnosticSeverity::Warn),
+            ("test", DiagnosticSeverity::Error),
+            ("info", DiagnosticSeverity::Info),
+        ]
+        .into_iter()
+        .map(|(kind, severity)| (kind.to_string(), severity))
+        .collect();
+        Self {
+            publish_diagnostics: false,
+            server: None,
+            namespace: "bacon".to_string(),
+            severities,
+        }
+    }
+}
+
+impl NvimSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &NvimConfig,
+    ) {
+        if let Some(b) = config.publish_diagnostics {
+            self.publish_diagnostics = b;
+        }
+        if config.server.is_some() {
+            self.server.clone_from(&config.server);
+        }
+        if let Some(namespace) = &config.namespace {
+            self.namespace.clone_from(namespace);
+        }
+        for (kind, severity) in &config.severities {
+            self.severities.insert(kind.clone(), *severity);
+        }
+    }
+    fn server(&self) -> Option<String> {
+        self.server
+            .clone()
+            .or_else(|| std::env::var("NVIM").ok())
+            .filter(|server| !server.is_empty())
+    }
+    /// Send the diagnostics of the report to neovim, in a background
+    /// thread, if it's configured
+    pub fn publish(
+        &self,
+        state: &AppState<'_>,
+    ) {
+        if !self.publish_diagnostics {
+            return;
+        }
+        let Some(server) = self.server() else {
+            warn!("no neovim server to publish the diagnostics to");
+            return;
+        };
+        let diagnostics = match state.cmd_result.report() {
+            Some(report) => self.diagnostics(report, &state.mission),
+            None => Vec::new(),
+        };
+        let json = Value::from(diagnostics).to_string();
+        let request = Msgpack::rpc_request(
+            1,
+            "nvim_exec_lua",
+            vec![
+                Msgpack::Str(PUBLISH_LUA),
+                Msgpack::Array(vec![Msgpack::Str(&self.namespace), Msgpack::Str(&json)]),
+            ],
+        )
+        .encode();
+        thread::spawn(move || match call_nvim(&server, &request) {
+            Ok(()) => debug!("diagnostics published to neovim"),
+            Err(e) => warn!("failed to publish diagnostics to neovim: {e}"),
+        });
+    }
+    /// Build the diagnostics, from the first location of each item
+    fn diagnostics(
+        &self,
+        report: &Report,
+        mission: &Mission,
+    ) -> Vec<Value> {
+        report_locations(report)
+            .into_iter()
+            .filter_map(|item| {
+                let kind = item["kind"].as_str()?;
+                let severity = self.severities.get(kind)?;
+                let location = item["location"].as_str()?;
+                let (path, line, column) = split_location(location);
+                let path = mission.make_absolute(PathBuf::from(path));
+                Some(json!({
+                    "path": path.to_string_lossy(),
+                    "line": line.parse::<usize>().ok()?,
+                    "column": column.parse::<usize>().unwrap_or(1),
+                    "severity": severity.lua_name(),
+                    "message": item["message"],
+                }))
+            })
+            .collect()
+    }
+}
+
+trait NvimStream: Read + Write {}
+impl<T: Read + Write> NvimStream for T {}
+
+/// Connect to the neovim server, which may be a TCP address, an unix
+/// socket, or a named pipe on windows
+fn connect(server: &str) -> anyhow::Result<Box<dyn NvimStream>> {
+    let timeout = Some(Duration::from_secs(5));
+    if let Ok(addr) = server.parse::<SocketAddr>() {
+        let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(5))?;
+        stream.set_read_timeout(timeout)?;
+        return Ok(Box::new(stream));
+    }
+    #[cfg(unix)]
+    {
+        let stream = std::os::unix::net::UnixStream::connect(server)?;
+        stream.set_read_timeout(timeout)?;
+        Ok(Box::new(stream))
+    }
+    #[cfg(windows)]
+    {
+        let pipe = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(server)?;
+        Ok(Box::new(pipe))
+    }
+    #[cfg(not(any(unix, windows)))]
+    anyhow::bail!("unsupported neovim server address: {server:?}")
+}
+
+/// Send the request and check neovim's answer, which starts with
+/// `[1, msgid, error, ...]`, the error being nil on success
+fn call_nvim(
+    server: &str,
+    request: &[u8],
+) -> anyhow::Result<()> {
+    let mut stream = connect(server)?;
+    stream.write_all(request)?;
+    let mut head = [0; 4];
+    stream.read_exact(&mut head)?;
+    if head[3] != 0xc0 {
+        let mut error = vec![0; 1024];
+        let n = stream.read(&mut error)?;
+        anyhow::bail!("neovim error: {}", String::from_utf8_lossy(&error[..n]));
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use {
+    lazy_regex::*,
+    std::path::{
+        Path,
+        PathBuf,
+    },
+    unicode_normalization::{
+        UnicodeNormalization,
+        is_nfc,
+    },
+};
+
+/// Normalize a path coming from a tool's output or from the watcher,
+/// so that it can be compared with the other paths.
+///
+/// On windows, the verbatim prefix (`\\?\`) is removed and the separators
+/// are all made backslashes. On macOS, where FSEvents may give decomposed
+/// (NFD) names while tools print composed (NFC) ones, the path is composed.
+/// Paths are returned unchanged on other systems.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        if let Some(s) = path.to_str() {
+            return PathBuf::from(normalize_windows_path(s));
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(s) = path.to_str() {
+            return PathBuf::from(compose_unicode(s));
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Make the path in the NFC form, eg with `é` as one char instead of
+/// an `e` followed by a combining accent
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn compose_unicode(path: &str) -> String {
+    if is_nfc(path) {
+        path.to_string()
+    } else {
+        path.nfc().collect()
+    }
+}
+
+/// Remove the verbatim prefix of a windows path (turning `\\?\UNC\server`
+/// into `\\server`), and replace the slashes with backslashes
+#[cfg_attr(not(windows), allow(dead_code))]
+fn normalize_windows_path(path: &str) -> String {
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{unc}")
+    } else if let Some(local) = path.strip_prefix(r"\\?\") {
+        local.to_string()
+    } else {
+        path
+    }
+}
+
+/// Split a location, eg `src/main.rs:15:3`, into its path, line, and
+/// column (the last two being empty when absent).
+///
+/// The drive and verbatim prefixes of windows paths, eg in
+/// `C:\dev\main.rs:15:3`, are kept in the path.
+pub fn split_location(location: &str) -> (&str, &str, &str) {
+    regex_captures!(
+        r#"^((?:\\\\\?\\)?(?:[a-zA-Z]:)?[^:\s]+)(?::(\d+)(?::(\d+))?)?$"#,
+        location
+    )
+    .map_or((location, "", ""), |(_, path, line, column)| {
+        (path, line, column)
+    })
+}
+
+#[test]
+fn test_normalize_windows_path() {
+    assert_eq!(
+        normalize_windows_path(r"\\?\C:\dev\bacon\src/main.rs"),
+        r"C:\dev\bacon\src\main.rs"
+    );
+    assert_eq!(
+        normalize_windows_path(r"\\?\UNC\server\share\src\lib.rs"),
+        r"\\server\share\src\lib.rs"
+    );
+    assert_eq!(normalize_windows_path("src/lib.rs"), r"src\lib.rs");
+}
+
+#[test]
+fn test_compose_unicode() {
+    assert_eq!(
+        compose_unicode("tests/fixtures/re\u{301}sume\u{301}.txt"),
+        "tests/fixtures/r\u{e9}sum\u{e9}.txt"
+    );
+    assert_eq!(compose_unicode("src/main.rs"), "src/main.rs");
+}
+
+#[test]
+fn test_split_location() {
+    assert_eq!(
+        split_location("src/main.rs:15:3"),
+        ("src/main.rs", "15", "3")
+    );
+    assert_eq!(split_location("src/main.rs:15"), ("src/main.rs", "15", ""));
+    assert_eq!(split_location("src/main.rs"), ("src/main.rs", "", ""));
+    assert_eq!(
+        split_location(r"C:\dev\bacon\src\main.rs:15:3"),
+        (r"C:\dev\bacon\src\main.rs", "15", "3")
+    );
+    assert_eq!(
+        split_location(r"\\?\C:\dev\bacon\src\main.rs:7:1"),
+        (r"\\?\C:\dev\bacon\src\main.rs", "7", "1")
+    );
+}
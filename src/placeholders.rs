@@ -0,0 +1,190 @@
+use {
+    crate::*,
+    lazy_regex::regex_replace_all,
+    std::{
+        env,
+        path::Path,
+        process::Command,
+        sync::OnceLock,
+    },
+};
+
+/// The names of the placeholders expanded by `expand_placeholders`
+const PLACEHOLDERS: &[&str] = &["package", "git_branch", "target_triple", "workspace_root"];
+
+/// Replace, in a token of a job command or in an env value, the
+/// placeholders describing the mission:
+///
+/// - `{package}`: the package the jobs are scoped to, or the package
+///   of the current directory
+/// - `{git_branch}`: the current git branch
+/// - `{target_triple}`: the target of the build (`CARGO_BUILD_TARGET`
+///   or the host triple)
+/// - `{workspace_root}`: the root of the workspace, or the package directory
+///
+/// Other placeholders (eg `{changed_files}`) are kept as is. Doubling the
+/// braces of one of the placeholders above, eg `{{package}}`, gives it
+/// unexpanded (`{package}`).
+pub fn expand_placeholders(
+    token: &str,
+    mission: &Mission,
+) -> String {
+    regex_replace_all!(
+        r"\{(\{[a-z_]+\})\}|\{([a-z_]+)\}",
+        token,
+        |whole: &str, escaped: &str, name: &str| {
+            if !escaped.is_empty() {
+                let known = PLACEHOLDERS.contains(&&escaped[1..escaped.len() - 1]);
+                return if known { escaped } else { whole }.to_string();
+            }
+            let value = match name {
+                "package" => mission
+                    .package
+                    .clone()
+                    .or_else(|| mission.package_name.clone()),
+                "git_branch" => git_branch(&mission.package_directory),
+                "target_triple" => target_triple(),
+                "workspace_root" => Some(
+                    mission
+                        .workspace_directory
+                        .as_ref()
+                        .unwrap_or(&mission.package_directory)
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                _ => return whole.to_string(),
+            };
+            value.unwrap_or_else(|| {
+                warn!("no value for placeholder {whole}");
+                whole.to_string()
+            })
+        }
+    )
+    .to_string()
+}
+
+/// Replace the references to environment variables:
+///
+/// - `$NAME` and `${NAME}`: the value of the variable (unchanged when
+///   the variable isn't set)
+/// - `${NAME:-default}`: the value of the variable, or the default when
+///   it's not set or empty
+/// - `$$`: a literal `$`
+pub fn expand_env_vars(token: &str) -> String {
+    expand_env_vars_with(token, |name| env::var(name).ok())
+}
+
+/// Replace the references to variables, as [expand_env_vars] does,
+/// with the values given by the `var` function
+pub fn expand_env_vars_with(
+    token: &str,
+    var: impl Fn(&str) -> Option<String>,
+) -> String {
+    regex_replace_all!(
+        r"\$(?:\$|\{([A-Za-z0-9_]+)(:-[^}]*)?\}|([A-Za-z0-9_]+))",
+        token,
+        |whole: &str, braced: &str, default: &str, bare: &str| {
+            if whole == "$$" {
+                return "$".to_string();
+            }
+            let name = if braced.is_empty() { bare } else { braced };
+            match (var(name), default.strip_prefix(":-")) {
+                (Some(value), Some(default)) if value.is_empty() => default.to_string(),
+                (Some(value), _) => value,
+                (None, Some(default)) => default.to_string(),
+                (None, None) => {
+                    warn!("variable {whole} not found in env");
+                    whole.to_string()
+                }
+            }
+        }
+    )
+    .to_string()
+}
+
+fn git_branch(directory: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(directory)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+/// The target of the builds: the one configured in env, or the host
+/// triple given by rustc, which is read only once
+fn target_triple() -> Option<String> {
+    if let Ok(target) = env::var("CARGO_BUILD_TARGET") {
+        return Some(target);
+    }
+    static HOST_TRIPLE: OnceLock<Option<String>> = OnceLock::new();
+    HOST_TRIPLE
+        .get_or_init(|| {
+            let output = Command::new("rustc").arg("-vV").output().ok()?;
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix("host: "))
+                .map(|host| host.trim().to_string())
+        })
+        .clone()
+}
+
+#[test]
+fn test_expand_env_vars() {
+    let var = |name: &str| match name {
+        "BACON_TEST_EXPAND" => Some("value".to_string()),
+        "BACON_TEST_EXPAND_EMPTY" => Some(String::new()),
+        _ => None,
+    };
+    for (token, expanded) in [
+        ("$BACON_TEST_EXPAND/bin", "value/bin"),
+        ("${BACON_TEST_EXPAND}_x", "value_x"),
+        ("${BACON_TEST_EXPAND:-other}", "value"),
+        ("${BACON_TEST_EXPAND_EMPTY:-other}", "other"),
+        ("${BACON_TEST_EXPAND_UNSET:-}", ""),
+        ("${BACON_TEST_EXPAND_UNSET:-a b}", "a b"),
+        ("$BACON_TEST_EXPAND_UNSET", "$BACON_TEST_EXPAND_UNSET"),
+        ("$$BACON_TEST_EXPAND", "$BACON_TEST_EXPAND"),
+        ("cost: 5$$", "cost: 5$"),
+    ] {
+        assert_eq!(expand_env_vars_with(token, var), expanded, "{token}");
+    }
+}
+
+#[test]
+fn test_expand_placeholders() {
+    use clap::Parser;
+    let dir = tempfile::tempdir().unwrap();
+    let args = Args::parse_from(["bacon", "--project", &dir.path().to_string_lossy()]);
+    let context = Context::new(&args).unwrap();
+    let settings = Settings::default();
+    let mut mission = context
+        .mission(ConcreteJobRef::default(), &Job::default(), &settings)
+        .unwrap();
+    mission.package = Some("core".to_string());
+    let root = mission
+        .workspace_directory
+        .as_ref()
+        .unwrap_or(&mission.package_directory)
+        .to_string_lossy()
+        .to_string();
+    for (token, expanded) in [
+        ("--package={package}", "--package=core".to_string()),
+        ("{workspace_root}/target", format!("{root}/target")),
+        ("{package}-{package}", "core-core".to_string()),
+        ("{package}{workspace_root}", format!("core{root}")),
+        // unknown placeholders are kept for a later expansion
+        ("{changed_files}", "{changed_files}".to_string()),
+        ("{Package} {}", "{Package} {}".to_string()),
+        // doubled braces escape the known placeholders only
+        ("{{package}}={package}", "{package}=core".to_string()),
+        ("{{changed_files}}", "{{changed_files}}".to_string()),
+        ("awk '{{print}}'", "awk '{{print}}'".to_string()),
+    ] {
+        assert_eq!(expand_placeholders(token, &mission), expanded, "{token}");
+    }
+}
@@ -0,0 +1,133 @@
+use {
+    crate::*,
+    anyhow::Result,
+    lazy_regex::regex_captures,
+    rustc_hash::FxHashSet,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::Path,
+    },
+};
+
+/// The content of a baseline file: the fingerprints of the
+/// accepted diagnostics, by job
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BaselineFile {
+    pub jobs: BTreeMap<String, Vec<String>>,
+}
+
+/// The set of diagnostics which were present when the baseline was
+/// captured, and which are thus not displayed anymore
+#[derive(Debug, Default)]
+pub struct Baseline {
+    fingerprints: FxHashSet<String>,
+}
+
+impl Baseline {
+    pub fn len(&self) -> usize {
+        self.fingerprints.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+    /// Read the baseline of the job, if the file exists and has one
+    pub fn load(
+        path: &Path,
+        job_key: &str,
+    ) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let mut file: BaselineFile = serde_json::from_str(&content)?;
+        Ok(file.jobs.remove(job_key).map(|fingerprints| Self {
+            fingerprints: fingerprints.into_iter().collect(),
+        }))
+    }
+    /// Make the baseline of all the items of the report
+    pub fn capture(
+        report: &Report,
+        mission: &Mission,
+    ) -> Self {
+        let fingerprints = report
+            .item_fingerprints(mission)
+            .into_iter()
+            .map(|(_, fingerprint)| fingerprint)
+            .collect();
+        Self { fingerprints }
+    }
+    /// Write the baseline of the job in the file, keeping the ones of other jobs
+    pub fn save(
+        &self,
+        path: &Path,
+        job_key: &str,
+    ) -> Result<()> {
+        let mut file: BaselineFile = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            BaselineFile::default()
+        };
+        let mut fingerprints: Vec<String> = self.fingerprints.iter().cloned().collect();
+        fingerprints.sort();
+        file.jobs.insert(job_key.to_string(), fingerprints);
+        fs::write(path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+    /// Remove from the report the items present in the baseline,
+    /// and return the number of removed items
+    pub fn suppress(
+        &self,
+        report: &mut Report,
+        mission: &Mission,
+    ) -> usize {
+        let suppressed_items: FxHashSet<usize> = report
+            .item_fingerprints(mission)
+            .into_iter()
+            .filter(|(_, fingerprint)| self.fingerprints.contains(fingerprint))
+            .map(|(item_idx, _)| item_idx)
+            .collect();
+        report.remove_items(&suppressed_items);
+        suppressed_items.len()
+    }
+}
+
+/// Build the fingerprint of a diagnostic, independent of its line and
+/// column so that it survives edits elsewhere in the file
+pub fn diagnostic_fingerprint(
+    title: &str,
+    code: Option<&str>,
+    path: Option<&Path>,
+) -> String {
+    let path = path.map(|p| p.to_string_lossy()).unwrap_or_default();
+    format!("{}|{}|{}", code.unwrap_or(""), path, title.trim())
+}
+
+/// Find the lint code in a line of a diagnostic, eg `clippy::needless_return`
+/// in the link to the lint documentation.
+///
+/// The "`#[warn(..)]` on by default" notes aren't used as rustc only
+/// displays them for the first occurrence of a lint. Error codes are
+/// part of the title (eg `error[E0308]`).
+pub fn find_lint_code(line: &str) -> Option<String> {
+    regex_captures!(r"rust-clippy/[^#\s]+#(\w+)", line).map(|(_, code)| format!("clippy::{code}"))
+}
+
+#[test]
+fn test_find_lint_code() {
+    assert_eq!(
+        find_lint_code("= note: `#[warn(unused_variables)]` on by default"),
+        None,
+    );
+    assert_eq!(
+        find_lint_code(
+            "= help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_return"
+        ),
+        Some("clippy::needless_return".to_string()),
+    );
+    assert_eq!(find_lint_code("let x = 3;"), None);
+}
@@ -0,0 +1,128 @@
+use {
+    lazy_regex::{
+        regex_captures,
+        regex_is_match,
+    },
+    std::time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// The crates built by cargo during an execution, with when their
+/// build started, to show where the build time goes
+#[derive(Debug, Default)]
+pub struct BuildTimings {
+    /// when the execution started
+    start: Option<Instant>,
+    /// the crates (eg `serde v1.0.219`), with the action (eg "Compiling")
+    /// and when it was announced by cargo
+    units: Vec<(String, String, Instant)>,
+    /// when cargo announced the end of the build
+    end: Option<Instant>,
+}
+
+impl BuildTimings {
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start: Some(start),
+            ..Default::default()
+        }
+    }
+    /// Read a line of output, to detect the progress of the build
+    pub fn receive_line(
+        &mut self,
+        raw: &str,
+        at: Instant,
+    ) {
+        if self.end.is_some() {
+            return;
+        }
+        if let Some((_, action, unit)) =
+            regex_captures!(r"^\s*(Compiling|Checking|Documenting)\s+(\S+ v\S+)", raw)
+        {
+            self.units.push((unit.to_string(), action.to_string(), at));
+        } else if regex_is_match!(r"^\s*Finished\s", raw) {
+            self.end = Some(at);
+        }
+    }
+    /// Mark the end of the build, if cargo didn't announce it before
+    pub fn finish(
+        &mut self,
+        at: Instant,
+    ) {
+        if !self.units.is_empty() && self.end.is_none() {
+            self.end = Some(at);
+        }
+    }
+    /// Return the crates, with how long each one was the last one
+    /// started (the slowest first)
+    fn durations(&self) -> Vec<(&str, &str, Duration)> {
+        let Some(end) = self.end else {
+            return Vec::new();
+        };
+        let mut durations: Vec<_> = self
+            .units
+            .iter()
+            .enumerate()
+            .map(|(idx, (unit, action, start))| {
+                let next = self.units.get(idx + 1).map_or(end, |(_, _, at)| *at);
+                (unit.as_str(), action.as_str(), next.duration_since(*start))
+            })
+            .collect();
+        durations.sort_by_key(|&(_, _, duration)| std::cmp::Reverse(duration));
+        durations
+    }
+    /// Build the markdown of the timings of the crates of the last build
+    pub fn markdown(&self) -> String {
+        let mut md = "# Build timings\n\n".to_string();
+        if self.units.is_empty() {
+            md.push_str("No crate was built in the last execution.");
+            return md;
+        }
+        let (Some(start), Some(end)) = (self.start, self.end) else {
+            md.push_str("The build is still running.");
+            return md;
+        };
+        md.push_str(&format!(
+            "{} crate(s) built in {:.1}s.\n\
+            As cargo builds crates in parallel, the time of a crate is the time until the next one started, \
+            which is exact only when it's built alone, as are usually the slowest crates at the end of the build.\n\n\
+            |:-|:-|-:\n|**crate**|**action**|**time**\n|:-|:-|-:\n",
+            self.units.len(),
+            end.duration_since(start).as_secs_f64(),
+        ));
+        for (unit, action, duration) in self.durations() {
+            md.push_str(&format!(
+                "|{}|{}|{:.1}s\n",
+                unit,
+                action,
+                duration.as_secs_f64()
+            ));
+        }
+        md.push_str("|-\n");
+        md
+    }
+}
+
+#[test]
+fn test_build_timings() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut timings = BuildTimings::new(start);
+    timings.receive_line("   Compiling libc v0.2.172", at(10));
+    timings.receive_line("   Compiling syn v2.0.101", at(20));
+    timings.receive_line("warning: unused variable: `x`", at(500));
+    timings.receive_line("    Checking bacon v3.12.0 (/home/dys/bacon)", at(3000));
+    timings.receive_line(
+        "    Finished `dev` profile [unoptimized] target(s)",
+        at(4000),
+    );
+    let durations = timings.durations();
+    assert_eq!(durations.len(), 3);
+    assert_eq!(durations[0].0, "syn v2.0.101");
+    assert_eq!(durations[0].2, Duration::from_millis(2980));
+    assert_eq!(durations[1].0, "bacon v3.12.0");
+    assert_eq!(durations[1].1, "Checking");
+    assert_eq!(durations[2].2, Duration::from_millis(10));
+}
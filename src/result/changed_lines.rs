@@ -0,0 +1,147 @@
+use {
+    anyhow::{
+        Result,
+        bail,
+    },
+    lazy_regex::regex_captures,
+    rustc_hash::FxHashMap,
+    std::{
+        ops::RangeInclusive,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::Command,
+    },
+};
+
+/// The lines changed since a base git revision, according
+/// to `git diff --unified=0 <base>`.
+///
+/// Untracked files (which git diff ignores) are considered as
+/// entirely changed.
+#[derive(Debug, Default)]
+pub struct ChangedLines {
+    /// line ranges by absolute path, `None` when the whole file is new
+    files: FxHashMap<PathBuf, Option<Vec<RangeInclusive<usize>>>>,
+}
+
+impl ChangedLines {
+    /// Compute the changed lines of the git repository containing `dir`
+    pub fn compute(
+        dir: &Path,
+        base: &str,
+    ) -> Result<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let root = PathBuf::from(root.trim());
+        let diff = git(
+            &root,
+            &["diff", "--unified=0", "--no-color", "--no-ext-diff", base],
+        )?;
+        let mut changed_lines = Self::from_diff(&root, &diff);
+        let untracked = git(&root, &["ls-files", "--others", "--exclude-standard"])?;
+        for path in untracked.lines() {
+            changed_lines.files.insert(root.join(path), None);
+        }
+        Ok(changed_lines)
+    }
+    /// Parse the output of `git diff --unified=0`, whose paths
+    /// are relative to `root`
+    pub fn from_diff(
+        root: &Path,
+        diff: &str,
+    ) -> Self {
+        let mut files = FxHashMap::default();
+        let mut current: Option<&mut Option<Vec<RangeInclusive<usize>>>> = None;
+        for line in diff.lines() {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                current = path
+                    .strip_prefix("b/")
+                    .map(|path| files.entry(root.join(path)).or_insert(Some(Vec::new())));
+                continue;
+            }
+            let Some(ranges) = current.as_mut().and_then(|ranges| ranges.as_mut()) else {
+                continue;
+            };
+            let Some((_, start, count)) = regex_captures!(r"^@@ -\S+ \+(\d+)(?:,(\d+))? @@", line)
+            else {
+                continue;
+            };
+            let start: usize = start.parse().unwrap_or(0);
+            let count: usize = if count.is_empty() {
+                1
+            } else {
+                count.parse().unwrap_or(0)
+            };
+            if count > 0 {
+                // pure deletions have no line in the new version
+                ranges.push(start..=start + count - 1);
+            }
+        }
+        Self { files }
+    }
+    /// Tell whether the given line (1-based) of the file was changed
+    pub fn contains(
+        &self,
+        path: &Path,
+        line: usize,
+    ) -> bool {
+        match self.files.get(path) {
+            Some(Some(ranges)) => ranges.iter().any(|range| range.contains(&line)),
+            Some(None) => true, // new file
+            None => false,
+        }
+    }
+}
+
+/// Run a git command in the given directory and return its output
+pub fn git(
+    dir: &Path,
+    args: &[&str],
+) -> Result<String> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[test]
+fn test_changed_lines_from_diff() {
+    let diff = r#"diff --git a/src/lib.rs b/src/lib.rs
+index 1234567..89abcde 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3 +3 @@ use std::path::Path;
+-fn a() {}
++fn b() {}
+@@ -10,0 +11,3 @@ fn c() {
++    let x = 1;
++    let y = 2;
++    let z = 3;
+@@ -20,2 +23,0 @@ fn d() {
+-    old();
+-    older();
+diff --git a/src/old.rs b/src/old.rs
+deleted file mode 100644
+--- a/src/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn e() {}
+"#;
+    let changed_lines = ChangedLines::from_diff(Path::new("/ws"), diff);
+    let lib = Path::new("/ws/src/lib.rs");
+    assert!(!changed_lines.contains(lib, 2));
+    assert!(changed_lines.contains(lib, 3));
+    assert!(!changed_lines.contains(lib, 10));
+    assert!(changed_lines.contains(lib, 11));
+    assert!(changed_lines.contains(lib, 13));
+    assert!(!changed_lines.contains(lib, 14));
+    assert!(!changed_lines.contains(lib, 23));
+    assert!(!changed_lines.contains(Path::new("/ws/src/old.rs"), 1));
+    assert!(!changed_lines.contains(Path::new("/ws/src/main.rs"), 1));
+}
@@ -31,6 +31,10 @@ pub struct CommandOutputLine {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CommandOutput {
     pub lines: Vec<Line>,
+    /// number of the oldest lines which were dropped, in which case the
+    /// first line tells it
+    #[serde(default)]
+    pub dropped: usize,
 }
 
 /// a piece of information about the execution of a command
@@ -59,9 +63,73 @@ impl CommandOutput {
     ) {
         self.lines.push(line.into());
     }
+    /// Drop the oldest lines so that at most `max_lines` of them are
+    /// kept (0 meaning no limit), and tell it in a first line.
+    ///
+    /// Lines are dropped by chunks so that the cost stays low.
+    pub fn limit(
+        &mut self,
+        max_lines: usize,
+    ) {
+        let start = usize::from(self.dropped > 0);
+        let count = self.lines.len() - start;
+        if max_lines == 0 || count <= max_lines {
+            return;
+        }
+        let dropped = (count - max_lines + max_lines / 10).min(count);
+        self.lines.drain(start..start + dropped);
+        self.dropped += dropped;
+        let marker = dropped_lines_marker(self.dropped);
+        if start == 0 {
+            self.lines.insert(0, marker);
+        } else {
+            self.lines[0] = marker;
+        }
+    }
+    /// Copy the most recent lines, at most `max_lines` of them (0
+    /// meaning no limit), without copying the other ones
+    pub fn limited_copy(
+        &self,
+        max_lines: usize,
+    ) -> Self {
+        let start = usize::from(self.dropped > 0);
+        let count = self.lines.len() - start;
+        if max_lines == 0 || count <= max_lines {
+            return self.clone();
+        }
+        let skipped = count - max_lines;
+        let dropped = self.dropped + skipped;
+        let mut lines = Vec::with_capacity(max_lines + 1);
+        lines.push(dropped_lines_marker(dropped));
+        lines.extend_from_slice(&self.lines[start + skipped..]);
+        Self { lines, dropped }
+    }
     pub fn len(&self) -> usize {
         self.lines.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+/// The first line of an output whose oldest lines were dropped
+fn dropped_lines_marker(dropped: usize) -> Line {
+    Line {
+        item_idx: 0,
+        line_type: LineType::Normal,
+        content: TLine::italic(format!("... {dropped} older lines dropped")),
+    }
+}
+
+impl CommandOutputLine {
+    /// Build the line of what a terminal would display, keeping the
+    /// styles but applying the sequences of progress bars
+    pub fn emulated(raw: RawCommandOutputLine) -> Self {
+        CommandOutputLine {
+            content: TLine::from_tty_emulated(&raw.content),
+            origin: raw.origin,
+        }
+    }
 }
 
 impl From<RawCommandOutputLine> for CommandOutputLine {
@@ -72,3 +140,28 @@ impl From<RawCommandOutputLine> for CommandOutputLine {
         }
     }
 }
+
+#[test]
+fn test_limit_output() {
+    let mut output = CommandOutput::default();
+    for i in 0..25 {
+        output.push(CommandOutputLine {
+            content: TLine::from_raw(format!("line {i}")),
+            origin: CommandStream::StdOut,
+        });
+        output.limit(10);
+    }
+    assert!(output.lines.len() <= 11);
+    assert_eq!(
+        output.lines[0].content.to_raw(),
+        format!("... {} older lines dropped", output.dropped),
+    );
+    assert_eq!(output.dropped + output.lines.len() - 1, 25);
+    assert_eq!(output.lines.last().unwrap().content.to_raw(), "line 24");
+    let copy = output.limited_copy(3);
+    assert_eq!(copy.lines.len(), 4);
+    assert_eq!(copy.dropped, 22);
+    assert_eq!(copy.lines[0].content.to_raw(), "... 22 older lines dropped");
+    assert_eq!(copy.lines[1].content.to_raw(), "line 22");
+    assert_eq!(output.limited_copy(0).lines.len(), output.lines.len());
+}
@@ -12,7 +12,7 @@ use {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CommandResult {
     /// a trustable report with errors and warnings computed
-    Report(Report),
+    Report(Box<Report>),
     /// we don't have a proper report
     Failure(Failure),
     /// not yet computed
@@ -44,7 +44,7 @@ impl CommandResult {
         }
         report.output = output;
         // report looks valid
-        Ok(Self::Report(report))
+        Ok(Self::Report(Box::new(report)))
     }
 
     pub fn output(&self) -> Option<&CommandOutput> {
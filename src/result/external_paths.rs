@@ -0,0 +1,90 @@
+use {
+    crate::*,
+    std::{
+        env,
+        path::{
+            Component,
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// Tells whether the location of a diagnostic is external, that is
+/// in the cargo home (where the sources of the dependencies are) or
+/// outside of the workspace
+#[derive(Debug, Clone)]
+pub struct ExternalPaths {
+    /// the root of the workspace, or of the package when there's no workspace
+    root: PathBuf,
+    cargo_home: Option<PathBuf>,
+}
+
+impl ExternalPaths {
+    pub fn new(
+        root: PathBuf,
+        cargo_home: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            root: normalize(&root),
+            cargo_home: cargo_home.map(|dir| normalize(&dir)),
+        }
+    }
+    pub fn for_mission(mission: &Mission) -> Self {
+        let root = mission
+            .workspace_directory
+            .as_ref()
+            .unwrap_or(&mission.package_directory);
+        let cargo_home = env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| directories_next::BaseDirs::new().map(|d| d.home_dir().join(".cargo")));
+        Self::new(root.clone(), cargo_home)
+    }
+    /// Tell whether the path, absolute or relative to the root,
+    /// is external
+    pub fn is_external(
+        &self,
+        path: &Path,
+    ) -> bool {
+        let path = normalize(&self.root.join(path));
+        if let Some(cargo_home) = &self.cargo_home {
+            if path.starts_with(cargo_home) {
+                return true;
+            }
+        }
+        !path.starts_with(&self.root)
+    }
+}
+
+/// Remove the `.` and `..` components of the path, without
+/// reading the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in normalize_path(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[test]
+fn test_external_paths() {
+    let external_paths = ExternalPaths::new(
+        PathBuf::from("/home/dev/project"),
+        Some(PathBuf::from("/home/dev/.cargo")),
+    );
+    let is_external = |path: &str| external_paths.is_external(Path::new(path));
+    assert!(!is_external("src/main.rs"));
+    assert!(!is_external("/home/dev/project/sub/src/lib.rs"));
+    assert!(is_external(
+        "/home/dev/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0/src/de.rs"
+    ));
+    assert!(is_external("../other/src/lib.rs"));
+    assert!(is_external("/rustc/abc/library/core/src/option.rs"));
+    assert!(!is_external("sub/../src/lib.rs"));
+}
@@ -90,3 +90,36 @@ impl From<CommandOutputLine> for Line {
         }
     }
 }
+
+#[cfg(test)]
+impl Line {
+    /// Build a line of unstyled content
+    pub fn from_raw(
+        item_idx: usize,
+        line_type: LineType,
+        raw: &str,
+    ) -> Self {
+        Self {
+            item_idx,
+            line_type,
+            content: TLine::from_raw(raw.to_string()),
+        }
+    }
+    /// Build the title of an item, styled as by cargo,
+    /// eg `warning: unused variable`
+    pub fn title(
+        item_idx: usize,
+        kind: Kind,
+        label: &str,
+        message: &str,
+    ) -> Self {
+        let mut content = TLine::default();
+        content.add_tstring(CSI_BOLD_YELLOW, label);
+        content.add_tstring(CSI_BOLD, format!(": {message}"));
+        Self {
+            item_idx,
+            line_type: LineType::Title(kind),
+            content,
+        }
+    }
+}
@@ -1,19 +1,39 @@
+mod baseline;
+mod build_timings;
+mod changed_lines;
 mod command_output;
 mod command_result;
+mod external_paths;
 mod failure;
 mod line;
+mod persisted_report;
 mod report;
+mod report_format;
+mod report_history;
+mod report_html;
 mod report_maker;
+mod suggestion;
+mod test_history;
 mod wrapped_command_output;
 mod wrapped_report;
 
 pub use {
+    baseline::*,
+    build_timings::*,
+    changed_lines::*,
     command_output::*,
     command_result::*,
+    external_paths::*,
     failure::*,
     line::*,
+    persisted_report::*,
     report::*,
+    report_format::*,
+    report_history::*,
+    report_html::*,
     report_maker::*,
+    suggestion::*,
+    test_history::*,
     wrapped_command_output::*,
     wrapped_report::*,
 };
@@ -0,0 +1,125 @@
+use {
+    crate::*,
+    anyhow::Result,
+    rustc_hash::FxHasher,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        fs,
+        hash::{
+            Hash,
+            Hasher,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+        sync::Once,
+        thread,
+        time::Duration,
+    },
+};
+
+/// How many lines of the raw output of a report are persisted
+const PERSISTED_OUTPUT_LINES: usize = 1_000;
+
+/// How long a persisted report is kept without being written again
+const PERSISTED_REPORT_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// The last report of a job, as kept between launches
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedReport {
+    bacon_version: String,
+    report: Report,
+}
+
+/// Return the path of the file keeping the last report of the job of the
+/// mission, if the system has a state directory
+fn persisted_report_path(mission: &Mission) -> Option<PathBuf> {
+    let mut hasher = FxHasher::default();
+    mission.package_directory.hash(&mut hasher);
+    mission.concrete_job_ref.badge_label().hash(&mut hasher);
+    Some(
+        bacon_state_dir()?
+            .join("reports")
+            .join(format!("{:016x}.json", hasher.finish())),
+    )
+}
+
+/// Load the report of the last execution of the job of the mission,
+/// possibly done in a previous session of bacon
+pub fn load_persisted_report(mission: &Mission) -> Option<Report> {
+    let path = persisted_report_path(mission)?;
+    let content = fs::read_to_string(&path).ok()?;
+    let persisted: PersistedReport = match serde_json::from_str(&content) {
+        Ok(persisted) => persisted,
+        Err(e) => {
+            warn!("invalid persisted report {path:?}: {e}");
+            return None;
+        }
+    };
+    if persisted.bacon_version != env!("CARGO_PKG_VERSION") {
+        debug!("persisted report written by another version of bacon");
+        return None;
+    }
+    Some(persisted.report)
+}
+
+/// Write, in a background thread, the report as the last one of the
+/// job of the mission, with only the end of its raw output
+pub fn persist_report(
+    mission: &Mission,
+    report: &Report,
+) {
+    let Some(path) = persisted_report_path(mission) else {
+        return;
+    };
+    let persisted = PersistedReport {
+        bacon_version: env!("CARGO_PKG_VERSION").to_string(),
+        report: report.clone_with_output_limit(PERSISTED_OUTPUT_LINES),
+    };
+    thread::spawn(move || {
+        if let Err(e) = write_persisted_report(&path, &persisted) {
+            warn!("failed to persist report: {e}");
+        }
+        static PRUNING: Once = Once::new();
+        PRUNING.call_once(|| {
+            if let Some(directory) = path.parent() {
+                prune_persisted_reports(directory);
+            }
+        });
+    });
+}
+
+fn write_persisted_report(
+    path: &Path,
+    persisted: &PersistedReport,
+) -> Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    write_atomically(path, serde_json::to_string(persisted)?)?;
+    Ok(())
+}
+
+/// Remove the persisted reports which weren't written for a long time,
+/// eg those of removed projects
+fn prune_persisted_reports(directory: &Path) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > PERSISTED_REPORT_MAX_AGE);
+        if stale {
+            debug!("removing stale persisted report {:?}", entry.path());
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
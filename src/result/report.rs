@@ -2,6 +2,7 @@ use {
     crate::*,
     anyhow::Result,
     lazy_regex::*,
+    rustc_hash::FxHashSet,
     serde::{
         Deserialize,
         Serialize,
@@ -9,7 +10,10 @@ use {
     std::{
         collections::HashMap,
         io,
-        path::PathBuf,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
 };
 
@@ -22,17 +26,423 @@ pub struct Report {
     pub suggest_backtrace: bool,
     pub output: CommandOutput,
     pub failure_keys: Vec<String>,
+    /// the keys of the tests which passed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub passed_keys: Vec<String>,
     /// the exports that the analyzers have done, by name
     pub analyzer_exports: HashMap<String, String>,
+    /// the paths of the file changes which triggered the execution
+    /// (empty when it wasn't triggered by a file change)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trigger_paths: Vec<PathBuf>,
+    /// the machine-applicable suggestions, by location of the
+    /// diagnostic (only known when cargo's output is in JSON)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub suggestions: HashMap<String, Vec<Suggestion>>,
+    /// the success as decided by the success hook of the job, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_success: Option<bool>,
 }
 
 impl Report {
+    /// Copy the report, keeping only the most recent lines of its raw
+    /// output, at most `max_output_lines` of them (0 meaning no limit),
+    /// without copying the other ones
+    pub fn clone_with_output_limit(
+        &self,
+        max_output_lines: usize,
+    ) -> Self {
+        let Self {
+            lines,
+            stats,
+            suggest_backtrace,
+            output,
+            failure_keys,
+            passed_keys,
+            analyzer_exports,
+            trigger_paths,
+            suggestions,
+            hook_success,
+        } = self;
+        Self {
+            lines: lines.clone(),
+            stats: stats.clone(),
+            suggest_backtrace: *suggest_backtrace,
+            output: output.limited_copy(max_output_lines),
+            failure_keys: failure_keys.clone(),
+            passed_keys: passed_keys.clone(),
+            analyzer_exports: analyzer_exports.clone(),
+            trigger_paths: trigger_paths.clone(),
+            suggestions: suggestions.clone(),
+            hook_success: *hook_success,
+        }
+    }
     /// change the order of the lines so that items are in reverse order
     /// (but keep the order of lines of a given item)
     pub fn reverse(&mut self) {
         self.lines
             .sort_by_key(|line| std::cmp::Reverse(line.item_idx));
     }
+    /// Remove the items whose location isn't on a changed line, and
+    /// return the number of removed items.
+    ///
+    /// Items without location (eg test failures) are kept.
+    pub fn scope_to_changed_lines(
+        &mut self,
+        changed_lines: &ChangedLines,
+        mission: &Mission,
+    ) -> usize {
+        let mut removed_items = FxHashSet::default();
+        let mut located_items = FxHashSet::default();
+        for line in &self.lines {
+            let Some(location) = line.location() else {
+                continue;
+            };
+            if !located_items.insert(line.item_idx) {
+                continue; // only the first location of an item matters
+            }
+            let (path, file_line, _) = split_location(location);
+            let path = mission.make_absolute(PathBuf::from(path));
+            let file_line = file_line.parse().unwrap_or(0);
+            if !changed_lines.contains(&path, file_line) {
+                removed_items.insert(line.item_idx);
+            }
+        }
+        self.remove_items(&removed_items);
+        removed_items.len()
+    }
+    /// Remove the warnings about the use of deprecated items of the
+    /// given crates, and return the number of removed items
+    pub fn remove_deprecation_warnings(
+        &mut self,
+        crates: &[String],
+    ) -> usize {
+        if crates.is_empty() {
+            return 0;
+        }
+        let removed_items: FxHashSet<usize> = self
+            .lines
+            .iter()
+            .filter(|line| line.line_type == LineType::Title(Kind::Warning))
+            .filter(|line| {
+                let title = line.content.to_raw();
+                regex_captures!(
+                    r"^warning: use of deprecated [\w ]+ `(?:::)?(\w+)::",
+                    &title
+                )
+                .is_some_and(|(_, krate)| crates.iter().any(|c| c.replace('-', "_") == krate))
+            })
+            .map(|line| line.item_idx)
+            .collect();
+        self.remove_items(&removed_items);
+        removed_items.len()
+    }
+    /// Mark as external the warnings whose first location is external,
+    /// so that they don't count in the stats, and return the number of
+    /// marked items.
+    ///
+    /// Errors are never marked: an error in a dependency still means
+    /// the build failed.
+    ///
+    /// Their lines other than the title and locations are marked as
+    /// captured, so that they're only displayed when the item is expanded
+    pub fn mark_external_items(
+        &mut self,
+        external_paths: &ExternalPaths,
+    ) -> usize {
+        let mut marked = 0;
+        for title_idx in 0..self.lines.len() {
+            let title_line = &self.lines[title_idx];
+            if title_line.line_type != LineType::Title(Kind::Warning) {
+                continue;
+            }
+            let is_external = self.lines[title_idx + 1..]
+                .iter()
+                .take_while(|line| line.item_idx == title_line.item_idx)
+                .find_map(|line| line.location())
+                .is_some_and(|location| {
+                    let (path, _, _) = split_location(location);
+                    external_paths.is_external(Path::new(path))
+                });
+            if !is_external {
+                continue;
+            }
+            let item_idx = title_line.item_idx;
+            let title_line = &mut self.lines[title_idx];
+            title_line.line_type = LineType::Title(Kind::External);
+            for tstring in &mut title_line.content.strings {
+                tstring.csi = CSI_GRAY.to_string();
+            }
+            for line in self.lines[title_idx + 1..]
+                .iter_mut()
+                .take_while(|line| line.item_idx == item_idx)
+                .filter(|line| line.line_type == LineType::Normal)
+            {
+                line.line_type = LineType::Captured;
+            }
+            marked += 1;
+        }
+        if marked > 0 {
+            self.update_stats();
+        }
+        marked
+    }
+    /// Return the absolute paths of the first locations of
+    /// the warnings, if they have one
+    pub fn warning_paths(
+        &self,
+        mission: &Mission,
+    ) -> Vec<Option<PathBuf>> {
+        let mut paths = Vec::new();
+        for (idx, title_line) in self.lines.iter().enumerate() {
+            if title_line.line_type != LineType::Title(Kind::Warning) {
+                continue;
+            }
+            let path = self.lines[idx + 1..]
+                .iter()
+                .take_while(|line| line.item_idx == title_line.item_idx)
+                .find_map(|line| line.location())
+                .map(|location| {
+                    let (path, _, _) = split_location(location);
+                    mission.make_absolute(PathBuf::from(path))
+                });
+            paths.push(path);
+        }
+        paths
+    }
+    /// Merge the diagnostics which are repeated (same title, code, and
+    /// location), eg because several members of a workspace compile the
+    /// same file, into one item with an occurrence count
+    pub fn merge_duplicate_items(&mut self) {
+        // for each kept item: key -> (index of its title line in new_lines, occurrences)
+        let mut kept: HashMap<(String, Option<String>, String), (usize, usize)> = HashMap::new();
+        let mut new_lines: Vec<Line> = Vec::with_capacity(self.lines.len());
+        let mut merged = false;
+        let mut idx = 0;
+        while idx < self.lines.len() {
+            let item_idx = self.lines[idx].item_idx;
+            let end = self.lines[idx..]
+                .iter()
+                .position(|line| line.item_idx != item_idx)
+                .map_or(self.lines.len(), |len| idx + len);
+            let title_line = &self.lines[idx];
+            if matches!(
+                title_line.line_type,
+                LineType::Title(Kind::Error | Kind::Warning | Kind::Info | Kind::External)
+            ) {
+                let location = self.lines[idx..end]
+                    .iter()
+                    .find_map(|line| line.location())
+                    .map(|location| location.to_string());
+                if let Some(location) = location {
+                    let key = (title_line.content.to_raw(), self.item_code(idx), location);
+                    if let Some((_, occurrences)) = kept.get_mut(&key) {
+                        *occurrences += 1;
+                        merged = true;
+                        idx = end;
+                        continue;
+                    }
+                    kept.insert(key, (new_lines.len(), 1));
+                }
+            }
+            new_lines.extend_from_slice(&self.lines[idx..end]);
+            idx = end;
+        }
+        if !merged {
+            return;
+        }
+        for (title_idx, occurrences) in kept.into_values() {
+            if occurrences > 1 {
+                new_lines[title_idx]
+                    .content
+                    .add_tstring(CSI_ITALIC, format!(" ({occurrences} occurrences)"));
+            }
+        }
+        // items are renumbered so that there's no hole
+        let mut item_idx = 0;
+        let mut last_old_item_idx = None;
+        for line in &mut new_lines {
+            if last_old_item_idx != Some(line.item_idx) {
+                last_old_item_idx = Some(line.item_idx);
+                item_idx += 1;
+            }
+            line.item_idx = item_idx;
+        }
+        self.lines = new_lines;
+        self.update_stats();
+    }
+    /// Find the code of the diagnostic whose title is at the given index:
+    /// either an error code (eg `E0308`) or a clippy lint (eg `clippy::unwrap_used`).
+    ///
+    /// Both are in the human readable output, so the json messages aren't
+    /// needed. Rustc lints aren't found (see `find_lint_code`).
+    pub fn item_code(
+        &self,
+        title_idx: usize,
+    ) -> Option<String> {
+        let title_line = &self.lines[title_idx];
+        let title = title_line.content.to_raw();
+        if let Some((_, code)) = regex_captures!(r"^(?:error|warning)\[(\w+)\]", &title) {
+            return Some(code.to_string());
+        }
+        self.lines[title_idx + 1..]
+            .iter()
+            .take_while(|line| line.item_idx == title_line.item_idx)
+            .find_map(|line| find_lint_code(&line.content.to_raw()))
+    }
+    /// Add a "possibly flaky" marker to the titles of the test
+    /// failures known as flaky in the history
+    pub fn mark_flaky_tests(
+        &mut self,
+        job_ref: &ConcreteJobRef,
+        history: &TestHistory,
+    ) {
+        for key in &self.failure_keys {
+            if !history.is_flaky(job_ref, key) {
+                continue;
+            }
+            let title = failure_lines(key).swap_remove(0);
+            for line in &mut self.lines {
+                if line.line_type == title.line_type && line.content == title.content {
+                    line.content.add_tstring("", " ");
+                    line.content.add_tstring(CSI_ITALIC, "(possibly flaky)");
+                }
+            }
+        }
+    }
+    /// Change the kind of the diagnostics whose code has a severity
+    /// override, and update the stats
+    pub fn apply_severities(
+        &mut self,
+        severities: &HashMap<String, Severity>,
+    ) {
+        if severities.is_empty() {
+            return;
+        }
+        let mut changed = false;
+        for title_idx in 0..self.lines.len() {
+            if !matches!(
+                self.lines[title_idx].line_type,
+                LineType::Title(Kind::Error | Kind::Warning | Kind::Info)
+            ) {
+                continue;
+            }
+            let Some(severity) = self
+                .item_code(title_idx)
+                .and_then(|code| severities.get(&code))
+            else {
+                continue;
+            };
+            changed |= self.set_title_severity(title_idx, *severity);
+        }
+        if changed {
+            self.update_stats();
+        }
+    }
+    /// Change the kind of the diagnostics of the given items, and
+    /// update the stats
+    pub fn apply_item_severities(
+        &mut self,
+        severities: &HashMap<usize, Severity>,
+    ) {
+        let mut changed = false;
+        for title_idx in 0..self.lines.len() {
+            if !matches!(
+                self.lines[title_idx].line_type,
+                LineType::Title(Kind::Error | Kind::Warning | Kind::Info)
+            ) {
+                continue;
+            }
+            let Some(severity) = severities.get(&self.lines[title_idx].item_idx) else {
+                continue;
+            };
+            changed |= self.set_title_severity(title_idx, *severity);
+        }
+        if changed {
+            self.update_stats();
+        }
+    }
+    /// Change the kind of the title at the given index, without
+    /// updating the stats, and return whether it changed
+    fn set_title_severity(
+        &mut self,
+        title_idx: usize,
+        severity: Severity,
+    ) -> bool {
+        let title_line = &mut self.lines[title_idx];
+        let kind = severity.kind();
+        if title_line.line_type == LineType::Title(kind) {
+            return false;
+        }
+        title_line.line_type = LineType::Title(kind);
+        // the start of the title tells the kind, it must be changed too
+        if let Some(label) = title_line.content.strings.first_mut() {
+            if let Some((_, suffix)) =
+                regex_captures!(r"^(?:error|warning|info)(\[\w+\])?$", &label.raw)
+            {
+                let (csi, kind_label) = severity.title_label();
+                let raw = format!("{kind_label}{suffix}");
+                *label = TString::new(csi, raw);
+            }
+        }
+        true
+    }
+    /// Recompute the stats after a change of the lines (the count of
+    /// passed tests, which isn't in the lines, is kept)
+    fn update_stats(&mut self) {
+        let passed_tests = self.stats.passed_tests;
+        self.stats = Stats::from(&self.lines);
+        self.stats.passed_tests = passed_tests;
+    }
+    /// Remove all lines of the given items, and update the stats
+    pub fn remove_items(
+        &mut self,
+        items: &FxHashSet<usize>,
+    ) {
+        if items.is_empty() {
+            return;
+        }
+        self.lines.retain(|line| !items.contains(&line.item_idx));
+        self.update_stats();
+    }
+    /// Compute the fingerprints of the diagnostic items (warnings, errors,
+    /// test failures), made of their lint code, file, and title
+    pub fn item_fingerprints(
+        &self,
+        mission: &Mission,
+    ) -> Vec<(usize, String)> {
+        let mut fingerprints = Vec::new();
+        let mut i = 0;
+        while i < self.lines.len() {
+            let title_line = &self.lines[i];
+            i += 1;
+            if !matches!(
+                title_line.line_type,
+                LineType::Title(Kind::Error | Kind::Warning | Kind::TestFail | Kind::DoctestFail)
+            ) {
+                continue;
+            }
+            let mut path = None;
+            for line in self.lines[i..]
+                .iter()
+                .take_while(|line| line.item_idx == title_line.item_idx)
+            {
+                if let Some(location) = line.location() {
+                    let (location_path, _, _) = split_location(location);
+                    let location_path = mission.make_absolute(PathBuf::from(location_path));
+                    path = Some(mission.make_relative(&location_path).to_path_buf());
+                    break;
+                }
+            }
+            let code = self.item_code(i - 1);
+            // the count of merged occurrences isn't part of the diagnostic identity
+            let title = title_line.content.to_raw();
+            let title = regex_replace!(r" \(\d+ occurrences\)$", &title, "");
+            let fingerprint = diagnostic_fingerprint(&title, code.as_deref(), path.as_deref());
+            fingerprints.push((title_line.item_idx, fingerprint));
+        }
+        fingerprints
+    }
     /// A successful report is one with nothing to tell: no warning,
     /// no error, no test failure
     pub fn is_success(
@@ -80,7 +490,7 @@ impl Report {
                     last_kind = "error";
                     message = line.title_message();
                 }
-                LineType::Title(Kind::TestFail) => {
+                LineType::Title(Kind::TestFail | Kind::DoctestFail) => {
                     last_kind = "test";
                     message = line.title_message();
                 }
@@ -89,9 +499,7 @@ impl Report {
             let Some(location) = line.location() else {
                 continue;
             };
-            let (_, path, file_line, mut file_column) =
-                regex_captures!(r#"^([^:\s]+):(\d+)(?:\:(\d+))?$"#, location)
-                    .unwrap_or(("", location, "", ""));
+            let (path, file_line, mut file_column) = split_location(location);
             // we need to make sure the path is absolute
             let path_buf = PathBuf::from(path);
             let path_buf = mission.make_absolute(path_buf);
@@ -126,3 +534,152 @@ impl Report {
         Ok(())
     }
 }
+
+#[cfg(test)]
+impl Report {
+    /// Build a report of the given lines, with their stats
+    pub fn from_lines(lines: Vec<Line>) -> Self {
+        Self {
+            stats: Stats::from(&lines),
+            lines,
+            suggest_backtrace: false,
+            output: CommandOutput::default(),
+            failure_keys: Vec::new(),
+            passed_keys: Vec::new(),
+            analyzer_exports: HashMap::new(),
+            trigger_paths: Vec::new(),
+            suggestions: HashMap::new(),
+            hook_success: None,
+        }
+    }
+}
+
+#[test]
+fn test_apply_severities() {
+    let mut lines = Vec::new();
+    let mut add_item = |item_idx: usize, kind: Kind, label: &str, lint: &str| {
+        lines.push(Line::title(item_idx, kind, label, "some problem"));
+        lines.push(Line::from_raw(
+            item_idx,
+            LineType::Normal,
+            &format!(
+                "= help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#{lint}"
+            ),
+        ));
+    };
+    add_item(1, Kind::Warning, "warning", "unwrap_used");
+    add_item(2, Kind::Warning, "warning", "needless_return");
+    add_item(3, Kind::Warning, "warning", "too_many_lines");
+    let mut report = Report::from_lines(lines);
+    assert_eq!(report.stats.warnings, 3);
+    let severities = vec![
+        ("clippy::unwrap_used".to_string(), Severity::Error),
+        ("clippy::needless_return".to_string(), Severity::Info),
+    ]
+    .into_iter()
+    .collect();
+    report.apply_severities(&severities);
+    assert_eq!(report.stats.errors, 1);
+    assert_eq!(report.stats.warnings, 1);
+    assert_eq!(report.lines[0].line_type, LineType::Title(Kind::Error));
+    assert_eq!(report.lines[0].content.strings[0].raw, "error");
+    assert_eq!(report.lines[2].line_type, LineType::Title(Kind::Info));
+    assert_eq!(report.lines[2].content.strings[0].raw, "info");
+}
+
+#[test]
+fn test_merge_duplicate_items() {
+    let mut lines = Vec::new();
+    let mut add_item = |item_idx: usize, message: &str, location: &str| {
+        lines.push(Line::title(item_idx, Kind::Warning, "warning", message));
+        lines.push(Line::from_raw(
+            item_idx,
+            LineType::Location,
+            &format!("  --> {location}"),
+        ));
+    };
+    add_item(1, "unused variable: `a`", "shared/src/lib.rs:3:9");
+    add_item(2, "unused variable: `b`", "shared/src/lib.rs:4:9");
+    add_item(3, "unused variable: `a`", "shared/src/lib.rs:3:9");
+    add_item(4, "unused variable: `a`", "other/src/lib.rs:3:9");
+    add_item(5, "unused variable: `a`", "shared/src/lib.rs:3:9");
+    let mut report = Report::from_lines(lines);
+    report.merge_duplicate_items();
+    assert_eq!(report.stats.warnings, 3);
+    assert_eq!(report.lines.len(), 6);
+    assert_eq!(
+        report.lines[0].content.to_raw(),
+        "warning: unused variable: `a` (3 occurrences)"
+    );
+    assert_eq!(
+        report.lines[2].content.to_raw(),
+        "warning: unused variable: `b`"
+    );
+    assert_eq!(report.lines[4].item_idx, 3);
+    assert_eq!(report.lines[5].location(), Some("other/src/lib.rs:3:9"));
+}
+
+#[test]
+fn test_remove_deprecation_warnings() {
+    let lines: Vec<Line> = [
+        "warning: use of deprecated function `old_crate::parse`: use `parse2`",
+        "warning: use of deprecated method `other::Cfg::load`",
+        "warning: unused variable: `old_crate`",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(idx, title)| Line::from_raw(idx + 1, LineType::Title(Kind::Warning), title))
+    .collect();
+    let mut report = Report::from_lines(lines);
+    assert_eq!(
+        report.remove_deprecation_warnings(&["old-crate".to_string()]),
+        1
+    );
+    assert_eq!(report.stats.warnings, 2);
+    assert_eq!(report.lines[0].item_idx, 2);
+}
+
+#[test]
+fn test_mark_external_items() {
+    let line = Line::from_raw;
+    let lines = vec![
+        line(1, LineType::Title(Kind::Warning), "warning: unused import"),
+        line(1, LineType::Location, "  --> src/main.rs:3:5"),
+        line(1, LineType::Normal, "   | use std::fs;"),
+        line(
+            2,
+            LineType::Title(Kind::Warning),
+            "warning: elided lifetime",
+        ),
+        line(
+            2,
+            LineType::Location,
+            "  --> /home/dev/.cargo/registry/src/index/dep-1.0.0/src/lib.rs:12:5",
+        ),
+        line(2, LineType::Normal, "   | fn get(&self) -> Ref {"),
+        line(
+            3,
+            LineType::Title(Kind::Error),
+            "error: cannot find derive macro",
+        ),
+        line(
+            3,
+            LineType::Location,
+            "  --> /home/dev/.cargo/registry/src/index/dep-1.0.0/src/de.rs:4:10",
+        ),
+    ];
+    let mut report = Report::from_lines(lines);
+    let external_paths = ExternalPaths::new(
+        PathBuf::from("/home/dev/project"),
+        Some(PathBuf::from("/home/dev/.cargo")),
+    );
+    assert_eq!(report.mark_external_items(&external_paths), 1);
+    assert_eq!(report.stats.warnings, 1);
+    assert_eq!(report.lines[3].line_type, LineType::Title(Kind::External));
+    assert_eq!(report.lines[4].line_type, LineType::Location);
+    assert_eq!(report.lines[5].line_type, LineType::Captured);
+    assert_eq!(report.lines[2].line_type, LineType::Normal);
+    // an error in a dependency still fails the build
+    assert_eq!(report.lines[6].line_type, LineType::Title(Kind::Error));
+    assert_eq!(report.stats.errors, 1);
+}
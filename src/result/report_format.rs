@@ -0,0 +1,229 @@
+use {
+    crate::*,
+    anyhow::Result,
+    std::{
+        fmt,
+        path::Path,
+        str::FromStr,
+    },
+};
+
+/// The format in which a report is copied or saved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReportFormat {
+    /// The lines of the report, without style
+    #[default]
+    Text,
+    /// Github-flavored markdown, with a summary table and the
+    /// items grouped by file
+    Markdown,
+    /// The report as analyzed by bacon, as in the `json-report` export
+    Json,
+    /// A self-contained HTML page, with collapsible files and a filter
+    Html,
+}
+
+impl ReportFormat {
+    /// The format matching the extension of the path,
+    /// defaulting to text
+    pub fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("md" | "markdown") => Self::Markdown,
+            Some("json") => Self::Json,
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Text,
+        }
+    }
+    pub fn render(
+        self,
+        report: &Report,
+    ) -> Result<String> {
+        Ok(match self {
+            Self::Text => report_text(report),
+            Self::Markdown => report_markdown(report),
+            Self::Json => serde_json::to_string_pretty(report)?,
+            Self::Html => report_html(report),
+        })
+    }
+}
+
+fn report_text(report: &Report) -> String {
+    let mut text = String::new();
+    for line in &report.lines {
+        text.push_str(&line.content.to_raw());
+        text.push('\n');
+    }
+    text
+}
+
+/// An item of the report, as rendered in markdown or HTML
+pub struct RenderedItem {
+    pub kind: Option<Kind>,
+    pub title: Option<String>,
+    pub file: Option<String>,
+    pub lines: Vec<String>,
+}
+
+/// The items of the report, without style, grouped by the file of their
+/// first location, in order of appearance, the items without location
+/// being last
+pub fn items_by_file(report: &Report) -> Vec<(Option<String>, Vec<RenderedItem>)> {
+    let mut items: Vec<RenderedItem> = Vec::new();
+    let mut item_idx = None;
+    for line in &report.lines {
+        let kind = match line.line_type {
+            LineType::Title(kind) => Some(kind),
+            _ => None,
+        };
+        if kind.is_some() || item_idx != Some(line.item_idx) {
+            items.push(RenderedItem {
+                kind,
+                title: None,
+                file: None,
+                lines: Vec::new(),
+            });
+            item_idx = Some(line.item_idx);
+        }
+        let Some(item) = items.last_mut() else {
+            continue;
+        };
+        if kind.is_some() {
+            item.title = Some(line.content.to_raw().trim().to_string());
+            continue;
+        }
+        if item.file.is_none() {
+            if let Some(location) = line.location() {
+                let (path, _, _) = split_location(location);
+                item.file = Some(path.to_string());
+            }
+        }
+        item.lines.push(line.content.to_raw());
+    }
+    let mut groups: Vec<(Option<String>, Vec<RenderedItem>)> = Vec::new();
+    for item in items {
+        match groups.iter_mut().find(|(file, _)| *file == item.file) {
+            Some((_, group)) => group.push(item),
+            None => groups.push((item.file.clone(), vec![item])),
+        }
+    }
+    groups.sort_by_key(|(file, _)| file.is_none());
+    groups
+}
+
+/// Render the report as GitHub-flavored markdown: a summary table, then
+/// the items grouped by file, their lines in code blocks
+fn report_markdown(report: &Report) -> String {
+    let stats = &report.stats;
+    let mut md = String::new();
+    md.push_str("| | count |\n|:-|-:|\n");
+    md.push_str(&format!("| errors | {} |\n", stats.errors));
+    md.push_str(&format!("| test failures | {} |\n", stats.test_fails));
+    md.push_str(&format!("| warnings | {} |\n", stats.warnings));
+    md.push_str(&format!("| passed tests | {} |\n", stats.passed_tests));
+    for (file, group) in items_by_file(report) {
+        match file {
+            Some(file) => md.push_str(&format!("\n### `{file}`\n")),
+            None => md.push_str("\n### Other\n"),
+        }
+        for item in group {
+            if let Some(title) = &item.title {
+                md.push_str(&format!("\n#### {title}\n"));
+            }
+            if item.lines.is_empty() {
+                continue;
+            }
+            let fence = code_fence(&item.lines);
+            md.push_str(&format!("\n{fence}text\n"));
+            for line in &item.lines {
+                md.push_str(line);
+                md.push('\n');
+            }
+            md.push_str(&format!("{fence}\n"));
+        }
+    }
+    md
+}
+
+/// A fence of backticks longer than the ones in the lines, so that
+/// the code block isn't closed by its content
+fn code_fence(lines: &[String]) -> String {
+    let longest_run = lines
+        .iter()
+        .flat_map(|line| line.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Markdown => write!(f, "markdown"),
+            Self::Json => write!(f, "json"),
+            Self::Html => write!(f, "html"),
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" | "plain" => Ok(Self::Text),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            "html" => Ok(Self::Html),
+            _ => Err(format!("invalid report format: {s:?}")),
+        }
+    }
+}
+
+#[test]
+fn test_report_markdown() {
+    let mut lines = Vec::new();
+    let mut add_item = |item_idx, label: &str, message: &str, location: Option<&str>| {
+        lines.push(Line::title(item_idx, Kind::Warning, label, message));
+        if let Some(location) = location {
+            lines.push(Line::from_raw(
+                item_idx,
+                LineType::Location,
+                &format!(" --> {location}"),
+            ));
+        }
+    };
+    add_item(
+        1,
+        "warning",
+        "unused variable: `x`",
+        Some("src/main.rs:2:9"),
+    );
+    add_item(2, "warning", "crate-level warning", None);
+    add_item(3, "warning", "unused import", Some("src/lib.rs:1:5"));
+    add_item(
+        4,
+        "warning",
+        "unused variable: `y`",
+        Some("src/main.rs:5:9"),
+    );
+    // the code block of this item must not be closed by a doc comment
+    lines.insert(5, Line::from_raw(3, LineType::Normal, "  | /// ```"));
+    let report = Report::from_lines(lines);
+    let md = ReportFormat::Markdown.render(&report).unwrap();
+    assert!(md.starts_with("| | count |\n|:-|-:|\n| errors | 0 |\n"));
+    assert!(md.contains("| warnings | 4 |\n"));
+    let main = md.find("### `src/main.rs`").unwrap();
+    let x = md.find("#### warning: unused variable: `x`").unwrap();
+    let y = md.find("#### warning: unused variable: `y`").unwrap();
+    let lib = md.find("### `src/lib.rs`").unwrap();
+    let other = md
+        .find("### Other\n\n#### warning: crate-level warning")
+        .unwrap();
+    assert!(main < x && x < y && y < lib && lib < other);
+    assert!(md.contains("\n```text\n --> src/main.rs:5:9\n```\n"));
+    assert!(md.contains("\n````text\n --> src/lib.rs:1:5\n  | /// ```\n````\n"));
+}
@@ -0,0 +1,105 @@
+use {
+    crate::*,
+    rustc_hash::FxHashMap,
+    std::collections::VecDeque,
+};
+
+/// The last reports of the jobs, kept during the session and displayed
+/// when coming back to a job, within the limits set by the
+/// `retained_reports` and `retained_output_lines` job settings
+#[derive(Debug, Default)]
+pub struct ReportHistory {
+    /// the reports of each job, the oldest first
+    reports: FxHashMap<ConcreteJobRef, VecDeque<Report>>,
+}
+
+impl ReportHistory {
+    /// Keep a copy of the report, with only the last `max_output_lines`
+    /// of its raw output, evicting the oldest reports of the job so that
+    /// at most `max_reports` are kept
+    pub fn record(
+        &mut self,
+        job_ref: &ConcreteJobRef,
+        report: &Report,
+        max_reports: usize,
+        max_output_lines: usize,
+    ) {
+        if max_reports == 0 {
+            self.reports.remove(job_ref);
+            return;
+        }
+        // the output is limited before the copy, which may be much
+        // smaller than the report
+        let report = report.clone_with_output_limit(max_output_lines);
+        let reports = self.reports.entry(job_ref.clone()).or_default();
+        reports.push_back(report);
+        while reports.len() > max_reports {
+            reports.pop_front();
+        }
+    }
+    /// The last report of the job
+    pub fn last(
+        &self,
+        job_ref: &ConcreteJobRef,
+    ) -> Option<&Report> {
+        self.reports.get(job_ref).and_then(|reports| reports.back())
+    }
+    /// The number of retained reports, all jobs included
+    pub fn len(&self) -> usize {
+        self.reports.values().map(VecDeque::len).sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Estimate the memory used by the retained reports
+    pub fn size(&self) -> usize {
+        self.reports.values().flatten().map(report_size).sum()
+    }
+}
+
+#[test]
+fn test_report_history() {
+    let report = |lines: usize| {
+        let mut output = CommandOutput::default();
+        for i in 0..lines {
+            output.push(CommandOutputLine {
+                content: TLine::from_raw(format!("line {i}")),
+                origin: CommandStream::StdOut,
+            });
+        }
+        Report {
+            output,
+            ..Report::from_lines(Vec::new())
+        }
+    };
+    let check = ConcreteJobRef::from_job_name("check");
+    let test = ConcreteJobRef::from_job_name("test");
+    let mut history = ReportHistory::default();
+    for lines in [10, 20, 30] {
+        history.record(&check, &report(lines), 1, 100);
+    }
+    history.record(&test, &report(1_000), 1, 100);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.last(&check).unwrap().output.len(), 30);
+    // the last 100 lines, after the marker of the dropped ones
+    assert_eq!(history.last(&test).unwrap().output.len(), 101);
+    history.record(&test, &report(1), 0, 100);
+    assert!(history.last(&test).is_none());
+    assert_eq!(history.len(), 1);
+
+    // the oldest reports are evicted at the cap
+    for lines in [1, 2, 3, 4, 5] {
+        history.record(&test, &report(lines), 3, 100);
+    }
+    assert_eq!(history.len(), 4);
+    let kept: Vec<usize> = history.reports[&test]
+        .iter()
+        .map(|report| report.output.len())
+        .collect();
+    assert_eq!(kept, [3, 4, 5]);
+    assert_eq!(history.last(&test).unwrap().output.len(), 5);
+    // lowering the cap evicts the excess on the next record
+    history.record(&test, &report(6), 2, 100);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.last(&test).unwrap().output.len(), 6);
+}
@@ -0,0 +1,143 @@
+use crate::*;
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>bacon report</title>
+<style>
+body { font-family: sans-serif; margin: 2em auto; max-width: 70em; padding: 0 1em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+td { border: 1px solid #ccc; padding: .2em .8em; }
+td.count { text-align: right; font-weight: bold; }
+#filters { position: sticky; top: 0; background: white; padding: .5em 0; border-bottom: 1px solid #ccc; }
+#filters input[type=search] { width: 20em; }
+#filters label { margin-left: 1em; }
+details.file { margin: .6em 0; }
+details.file > summary { font-size: 1.1em; cursor: pointer; }
+details.item { margin: .3em 0 .3em 1.5em; }
+details.item > summary { cursor: pointer; font-weight: bold; }
+.error > summary { color: #c00; }
+.warning > summary { color: #a60; }
+.test > summary { color: #a0a; }
+.info > summary { color: #06a; }
+pre { background: #f6f6f6; padding: .6em; overflow-x: auto; margin: .3em 0; }
+.hidden { display: none; }
+</style>
+</head>
+<body>
+<h1>bacon report</h1>
+"#;
+
+const HTML_TAIL: &str = r##"<script>
+const search = document.getElementById("search");
+const kinds = document.querySelectorAll("#filters input[type=checkbox]");
+function applyFilters() {
+    const query = search.value.toLowerCase();
+    const shown = new Set([...kinds].filter((c) => c.checked).map((c) => c.value));
+    for (const file of document.querySelectorAll("details.file")) {
+        let visible = 0;
+        for (const item of file.querySelectorAll("details.item")) {
+            const ok = shown.has(item.dataset.kind)
+                && item.textContent.toLowerCase().includes(query);
+            item.classList.toggle("hidden", !ok);
+            if (ok) visible++;
+        }
+        file.classList.toggle("hidden", visible === 0);
+    }
+}
+search.addEventListener("input", applyFilters);
+kinds.forEach((c) => c.addEventListener("change", applyFilters));
+</script>
+</body>
+</html>
+"##;
+
+/// The kinds of items which can be filtered in the page
+const HTML_KINDS: &[&str] = &["error", "test", "warning", "info", "other"];
+
+fn html_kind(kind: Option<Kind>) -> &'static str {
+    match kind {
+        Some(Kind::Error) => "error",
+        Some(Kind::TestFail | Kind::DoctestFail) => "test",
+        Some(Kind::Warning) => "warning",
+        Some(Kind::Info) => "info",
+        _ => "other",
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render the report as a self-contained HTML page: a summary table,
+/// a filter on the text and kind of the items, then the items
+/// grouped by file in collapsible sections
+pub fn report_html(report: &Report) -> String {
+    let stats = &report.stats;
+    let mut html = String::from(HTML_HEAD);
+    html.push_str("<table>\n");
+    for (name, count) in [
+        ("errors", stats.errors),
+        ("test failures", stats.test_fails),
+        ("warnings", stats.warnings),
+        ("passed tests", stats.passed_tests),
+    ] {
+        html.push_str(&format!(
+            "<tr><td>{name}</td><td class=\"count\">{count}</td></tr>\n"
+        ));
+    }
+    html.push_str("</table>\n<div id=\"filters\">\n");
+    html.push_str("<input type=\"search\" id=\"search\" placeholder=\"filter\">\n");
+    for kind in HTML_KINDS {
+        html.push_str(&format!(
+            "<label><input type=\"checkbox\" value=\"{kind}\" checked> {kind}</label>\n"
+        ));
+    }
+    html.push_str("</div>\n");
+    for (file, group) in items_by_file(report) {
+        let file = file.as_deref().map_or("Other".to_string(), escape_html);
+        html.push_str(&format!(
+            "<details class=\"file\" open>\n<summary><code>{file}</code> ({})</summary>\n",
+            group.len(),
+        ));
+        for item in group {
+            let kind = html_kind(item.kind);
+            let title = item.title.as_deref().map_or(String::new(), escape_html);
+            html.push_str(&format!(
+                "<details class=\"item {kind}\" data-kind=\"{kind}\" open>\n<summary>{title}</summary>\n"
+            ));
+            if !item.lines.is_empty() {
+                html.push_str("<pre>");
+                for line in &item.lines {
+                    html.push_str(&escape_html(line));
+                    html.push('\n');
+                }
+                html.push_str("</pre>\n");
+            }
+            html.push_str("</details>\n");
+        }
+        html.push_str("</details>\n");
+    }
+    html.push_str(HTML_TAIL);
+    html
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html(r#"expected `Vec<&str>`, found "x""#),
+        "expected `Vec&lt;&amp;str&gt;`, found &quot;x&quot;",
+    );
+}
@@ -1,14 +1,21 @@
 use {
     crate::*,
     anyhow::*,
-    std::process::ExitStatus,
+    std::{
+        collections::HashMap,
+        process::ExitStatus,
+    },
 };
 
 /// Dedicated to a mission, the report maker receives the command
 /// output lines and builds a report at end, complete with starts.
 pub struct ReportMaker {
     ignored_lines_patterns: Option<Vec<LinePattern>>,
+    severities: HashMap<String, Severity>,
+    /// set when the diagnostics of dependencies must be marked as external
+    external_paths: Option<ExternalPaths>,
     analyzer: Box<dyn Analyzer>,
+    max_output_lines: usize,
 }
 
 impl ReportMaker {
@@ -18,7 +25,13 @@ impl ReportMaker {
         let analyzer = analyzer_ref.create_analyzer();
         Self {
             ignored_lines_patterns,
+            severities: mission.job.severity.clone(),
+            external_paths: mission
+                .job
+                .mark_external_diagnostics()
+                .then(|| ExternalPaths::for_mission(mission)),
             analyzer,
+            max_output_lines: mission.job.max_output_lines(),
         }
     }
 
@@ -42,18 +55,47 @@ impl ReportMaker {
             }
         }
         self.analyzer.receive_line(cmd_line, command_output);
+        command_output.limit(self.max_output_lines);
     }
 
     pub fn build_report(&mut self) -> Result<Report> {
         self.analyzer.build_report()
     }
 
+    /// Build a report from the lines received so far, to be displayed
+    /// while the command runs (None if the analyzer can't do it)
+    pub fn build_partial_report(&mut self) -> Option<Report> {
+        let mut report = self
+            .analyzer
+            .build_partial_report()?
+            .inspect_err(|e| warn!("failed to build partial report: {e}"))
+            .ok()?;
+        self.finalize_report(&mut report);
+        Some(report)
+    }
+
+    /// Apply the job's settings to a report built by the analyzer
+    fn finalize_report(
+        &self,
+        report: &mut Report,
+    ) {
+        report.apply_severities(&self.severities);
+        if let Some(external_paths) = &self.external_paths {
+            let marked = report.mark_external_items(external_paths);
+            if marked > 0 {
+                debug!("{marked} external item(s)");
+            }
+        }
+        report.merge_duplicate_items();
+    }
+
     pub fn build_result(
         &mut self,
         output: CommandOutput,
         exit_status: Option<ExitStatus>,
     ) -> Result<CommandResult> {
-        let report = self.analyzer.build_report()?;
+        let mut report = self.analyzer.build_report()?;
+        self.finalize_report(&mut report);
         let result = CommandResult::build(output, exit_status, report)?;
         Ok(result)
     }
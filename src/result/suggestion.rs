@@ -0,0 +1,205 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    cargo_metadata::diagnostic::{
+        Applicability,
+        Diagnostic,
+        DiagnosticSpan,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// A replacement, in a file, of a range of bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replacement {
+    /// path of the file, as given by cargo (usually relative to the workspace)
+    pub file: String,
+    pub line: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub text: String,
+}
+
+/// A fix proposed by the compiler for a diagnostic, which can be
+/// applied without human intervention
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub message: String,
+    pub replacements: Vec<Replacement>,
+}
+
+impl Suggestion {
+    /// Return the machine-applicable suggestions of the diagnostic, which
+    /// are carried by the spans of its children (recursively)
+    pub fn from_diagnostic(diagnostic: &Diagnostic) -> Vec<Self> {
+        let mut suggestions = Vec::new();
+        for child in &diagnostic.children {
+            let replacements: Vec<Replacement> = child
+                .spans
+                .iter()
+                .filter_map(Replacement::from_span)
+                .collect();
+            if !replacements.is_empty() {
+                suggestions.push(Self {
+                    message: child.message.clone(),
+                    replacements,
+                });
+            }
+            suggestions.append(&mut Self::from_diagnostic(child));
+        }
+        suggestions
+    }
+}
+
+impl Replacement {
+    fn from_span(span: &DiagnosticSpan) -> Option<Self> {
+        if span.suggestion_applicability != Some(Applicability::MachineApplicable) {
+            return None;
+        }
+        let text = span.suggested_replacement.clone()?;
+        Some(Self {
+            file: span.file_name.clone(),
+            line: span.line_start,
+            byte_start: span.byte_start as usize,
+            byte_end: span.byte_end as usize,
+            text,
+        })
+    }
+}
+
+/// Return the key of a diagnostic in the suggestions of a report,
+/// which is the location of its primary span, as displayed
+/// by cargo (eg `src/main.rs:2:9`)
+pub fn suggestions_key(diagnostic: &Diagnostic) -> Option<String> {
+    diagnostic
+        .spans
+        .iter()
+        .find(|span| span.is_primary)
+        .map(|span| {
+            format!(
+                "{}:{}:{}",
+                span.file_name, span.line_start, span.column_start
+            )
+        })
+}
+
+/// A set of suggestions ready to be applied, with the paths of the files
+/// resolved and the replaced texts known
+pub struct SuggestionsPatch {
+    /// (absolute path, replacements sorted by decreasing position)
+    files: Vec<(PathBuf, Vec<Replacement>)>,
+}
+
+impl SuggestionsPatch {
+    pub fn new(
+        suggestions: &[Suggestion],
+        mission: &Mission,
+    ) -> Self {
+        let mut files: Vec<(PathBuf, Vec<Replacement>)> = Vec::new();
+        for replacement in suggestions.iter().flat_map(|s| &s.replacements) {
+            let path = mission.make_absolute(PathBuf::from(&replacement.file));
+            match files.iter_mut().find(|(p, _)| *p == path) {
+                Some((_, replacements)) => replacements.push(replacement.clone()),
+                None => files.push((path, vec![replacement.clone()])),
+            }
+        }
+        for (_, replacements) in &mut files {
+            replacements.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
+            replacements.dedup();
+        }
+        Self { files }
+    }
+    /// Build a markdown description of the changes
+    pub fn preview(&self) -> Result<String> {
+        let mut md = String::new();
+        for (path, replacements) in &self.files {
+            let content = fs::read_to_string(path)?;
+            for replacement in replacements.iter().rev() {
+                let Some(replaced) = content.get(replacement.byte_start..replacement.byte_end)
+                else {
+                    bail!("suggestion out of {:?}", path);
+                };
+                md.push_str(&format!(
+                    "\n**{}:{}**\n```\n- {}\n+ {}\n```\n",
+                    replacement.file,
+                    replacement.line,
+                    replaced.replace('\n', "\n- "),
+                    replacement.text.replace('\n', "\n+ "),
+                ));
+            }
+        }
+        Ok(md)
+    }
+    /// Write the changes in the files
+    pub fn apply(&self) -> Result<()> {
+        for (path, replacements) in &self.files {
+            let mut content = fs::read_to_string(path)?;
+            apply_replacements(&mut content, replacements, path)?;
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Apply replacements, which must be sorted by decreasing position
+fn apply_replacements(
+    content: &mut String,
+    replacements: &[Replacement],
+    path: &Path,
+) -> Result<()> {
+    let mut previous_start = usize::MAX;
+    for replacement in replacements {
+        if replacement.byte_end > previous_start {
+            bail!("overlapping suggestions in {:?}", path);
+        }
+        if replacement.byte_end > content.len()
+            || !content.is_char_boundary(replacement.byte_start)
+            || !content.is_char_boundary(replacement.byte_end)
+        {
+            bail!("suggestion out of {:?}: the file changed", path);
+        }
+        content.replace_range(
+            replacement.byte_start..replacement.byte_end,
+            &replacement.text,
+        );
+        previous_start = replacement.byte_start;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_apply_replacements() {
+    let mut content = "fn main() {\n    let x = 1;\n    return;\n}\n".to_string();
+    let mut replacements = vec![
+        Replacement {
+            file: "src/main.rs".to_string(),
+            line: 2,
+            byte_start: 20,
+            byte_end: 21,
+            text: "_x".to_string(),
+        },
+        Replacement {
+            file: "src/main.rs".to_string(),
+            line: 3,
+            byte_start: 31,
+            byte_end: 38,
+            text: "".to_string(),
+        },
+    ];
+    replacements.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
+    apply_replacements(&mut content, &replacements, Path::new("src/main.rs")).unwrap();
+    assert_eq!(content, "fn main() {\n    let _x = 1;\n    \n}\n");
+}
@@ -0,0 +1,163 @@
+use {
+    crate::*,
+    rustc_hash::FxHashMap,
+    std::collections::VecDeque,
+};
+
+/// How many outcomes are kept per test
+const MAX_OUTCOMES: usize = 20;
+
+/// The outcomes of the tests over the runs, to detect the tests whose
+/// result changes while the code doesn't.
+///
+/// Each job has its own history (the scoped runs of a job, eg on failed
+/// tests, counting as runs of the job), as a test may legitimately
+/// behave differently with another tool or other features.
+#[derive(Debug, Default)]
+pub struct TestHistory {
+    jobs: FxHashMap<ConcreteJobRef, JobTestHistory>,
+}
+
+/// The outcomes of the tests of a job
+#[derive(Debug, Default)]
+struct JobTestHistory {
+    /// incremented on every run triggered by file changes
+    code_version: usize,
+    tests: FxHashMap<String, TestOutcomes>,
+}
+
+#[derive(Debug, Default)]
+struct TestOutcomes {
+    /// the recent outcomes (true for a pass), with the code version
+    outcomes: VecDeque<(usize, bool)>,
+    /// whether the test passed and failed on the same code version
+    flaky: bool,
+}
+
+/// The key of the history of the job, which ignores the scope
+fn history_key(job_ref: &ConcreteJobRef) -> ConcreteJobRef {
+    ConcreteJobRef {
+        name_or_alias: job_ref.name_or_alias.clone(),
+        scope: Scope::default(),
+    }
+}
+
+impl TestHistory {
+    /// Record the outcomes of the tests of the report of a run of the job
+    pub fn record(
+        &mut self,
+        job_ref: &ConcreteJobRef,
+        report: &Report,
+    ) {
+        let outcomes = report
+            .passed_keys
+            .iter()
+            .map(|key| (key.as_str(), true))
+            .chain(report.failure_keys.iter().map(|key| (key.as_str(), false)));
+        self.jobs
+            .entry(history_key(job_ref))
+            .or_default()
+            .record_outcomes(!report.trigger_paths.is_empty(), outcomes);
+    }
+    pub fn is_flaky(
+        &self,
+        job_ref: &ConcreteJobRef,
+        key: &str,
+    ) -> bool {
+        self.jobs
+            .get(&history_key(job_ref))
+            .and_then(|job| job.tests.get(key))
+            .is_some_and(|test| test.flaky)
+    }
+    /// Build the markdown of the list of the possibly flaky tests of
+    /// the job, with their recent outcomes
+    pub fn flaky_tests_markdown(
+        &self,
+        job_ref: &ConcreteJobRef,
+    ) -> String {
+        let no_flaky_test =
+            "# Possibly flaky tests\n\nNo test had different results without code change.";
+        let Some(job) = self.jobs.get(&history_key(job_ref)) else {
+            return no_flaky_test.to_string();
+        };
+        let mut keys: Vec<&String> = job
+            .tests
+            .iter()
+            .filter(|(_, test)| test.flaky)
+            .map(|(key, _)| key)
+            .collect();
+        if keys.is_empty() {
+            return no_flaky_test.to_string();
+        }
+        keys.sort();
+        let mut md = "# Possibly flaky tests\n\nThose tests had different results without code change.\nRecent outcomes are listed (oldest first), `-` marking a code change.\n\n|:-|:-\n|**test**|**outcomes**\n|:-|:-\n".to_string();
+        for key in keys {
+            let mut outcomes = String::new();
+            let mut previous_version = None;
+            for &(version, passed) in &job.tests[key].outcomes {
+                if previous_version.is_some_and(|v| v != version) {
+                    outcomes.push_str(" - ");
+                }
+                outcomes.push_str(if passed { "pass " } else { "*fail* " });
+                previous_version = Some(version);
+            }
+            md.push_str(&format!("|{}|{}\n", key, outcomes.trim_end()));
+        }
+        md.push_str("|-\n");
+        md
+    }
+}
+
+impl JobTestHistory {
+    fn record_outcomes<'k>(
+        &mut self,
+        code_changed: bool,
+        outcomes: impl Iterator<Item = (&'k str, bool)>,
+    ) {
+        if code_changed {
+            self.code_version += 1;
+        }
+        for (key, passed) in outcomes {
+            let test = self.tests.entry(key.to_string()).or_default();
+            if test
+                .outcomes
+                .back()
+                .is_some_and(|&(version, last)| version == self.code_version && last != passed)
+            {
+                info!("test {key:?} is possibly flaky");
+                test.flaky = true;
+            }
+            test.outcomes.push_back((self.code_version, passed));
+            if test.outcomes.len() > MAX_OUTCOMES {
+                test.outcomes.pop_front();
+            }
+        }
+    }
+}
+
+#[test]
+fn test_flaky_detection() {
+    let test = ConcreteJobRef::from_job_name("test");
+    let nextest = ConcreteJobRef::from_job_name("nextest");
+    let mut history = TestHistory::default();
+    let mut record = |job: &ConcreteJobRef, code_changed, outcomes: &[(&str, bool)]| {
+        history
+            .jobs
+            .entry(history_key(job))
+            .or_default()
+            .record_outcomes(code_changed, outcomes.iter().copied());
+    };
+    record(&test, false, &[("a", true), ("b", false)]);
+    // b passes after a code change: it's a fix, not a flaky test
+    record(&test, true, &[("a", true), ("b", true)]);
+    // another job may have other results
+    record(&nextest, false, &[("a", false)]);
+    // a fails on a rerun without change, here scoped to the failing tests
+    let mut scoped = test.clone();
+    scoped.scope.tests = vec!["a".to_string()];
+    record(&scoped, false, &[("a", false)]);
+    assert!(history.is_flaky(&test, "a"));
+    assert!(!history.is_flaky(&test, "b"));
+    assert!(!history.is_flaky(&test, "c"));
+    assert!(!history.is_flaky(&nextest, "a"));
+}
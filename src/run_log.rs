@@ -0,0 +1,205 @@
+use {
+    crate::*,
+    anyhow::Result,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        fs::{
+            self,
+            OpenOptions,
+        },
+        io::Write,
+        path::{
+            Path,
+            PathBuf,
+        },
+        time::Duration,
+    },
+    termimad::{
+        MadSkin,
+        minimad::{
+            OwningTemplateExpander,
+            TextTemplate,
+        },
+    },
+};
+
+/// A finished execution, as recorded in the run log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunLogEntry {
+    /// seconds since the unix epoch, at the end of the execution
+    pub timestamp: u64,
+    pub project: PathBuf,
+    pub job: String,
+    pub duration_ms: u64,
+    /// `success` or `failure`
+    pub result: String,
+    pub errors: usize,
+    pub test_fails: usize,
+    pub warnings: usize,
+    pub passed_tests: usize,
+    /// the changed files which triggered the execution, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trigger_paths: Vec<PathBuf>,
+}
+
+/// Return the path of the run log, if the system has a state directory
+pub fn run_log_path() -> Option<PathBuf> {
+    Some(bacon_state_dir()?.join("runs.jsonl"))
+}
+
+impl RunLogEntry {
+    /// Build the entry of the execution which just finished, if any
+    pub fn of(
+        state: &AppState<'_>,
+        duration: Duration,
+    ) -> Option<Self> {
+        let status = JobStatus::of(state)?;
+        let mission = &state.mission;
+        let trigger_paths = state
+            .cmd_result
+            .report()
+            .map(|report| {
+                report
+                    .trigger_paths
+                    .iter()
+                    .map(|path| mission.make_relative(path).to_path_buf())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            timestamp: status.timestamp,
+            project: mission.package_directory.clone(),
+            job: status.job,
+            duration_ms: duration.as_millis() as u64,
+            result: status.state.name().to_string(),
+            errors: status.stats.errors,
+            test_fails: status.stats.test_fails,
+            warnings: status.stats.warnings,
+            passed_tests: status.stats.passed_tests,
+            trigger_paths,
+        })
+    }
+    /// Append the entry as a JSON line to the run log
+    pub fn append(&self) -> Result<()> {
+        let Some(path) = run_log_path() else {
+            return Ok(());
+        };
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Read the entries of the run log which are about the project
+fn read_run_log(project: &Path) -> Result<Vec<RunLogEntry>> {
+    let Some(path) = run_log_path() else {
+        return Ok(Vec::new());
+    };
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    let entries = content
+        .lines()
+        .filter_map(|line| match serde_json::from_str::<RunLogEntry>(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("invalid run log line {line:?}: {e}");
+                None
+            }
+        })
+        .filter(|entry| entry.project == project)
+        .collect();
+    Ok(entries)
+}
+
+/// Print the runs of the project, as JSON lines or in a table
+pub fn print_run_log(
+    project: &Path,
+    json: bool,
+) -> Result<()> {
+    let entries = read_run_log(project)?;
+    if json {
+        let mut stdout = std::io::stdout();
+        for entry in &entries {
+            writeln!(stdout, "{}", serde_json::to_string(entry)?)?;
+        }
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("No run recorded for this project (is `run_log = true` set?)");
+        return Ok(());
+    }
+    static MD: &str = r#"
+    |:-:|:-|-:|:-:|:-|:-|
+    |**time (UTC)**|**job**|**duration**|**result**|**counts**|**triggered by**|
+    |:-:|:-|-:|:-:|:-|:-|
+    ${runs
+    |${time}|${job}|${duration}|${result}|${counts}|${triggers}|
+    }
+    |-|-|-|-|-|-|
+    "#;
+    let mut expander = OwningTemplateExpander::new();
+    for entry in &entries {
+        let counts = format!(
+            "{} / {} / {}",
+            entry.errors, entry.test_fails, entry.warnings
+        );
+        let triggers: Vec<String> = entry
+            .trigger_paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        expander
+            .sub("runs")
+            .set("time", format_utc(entry.timestamp))
+            .set("job", &entry.job)
+            .set(
+                "duration",
+                format!("{:.1}s", entry.duration_ms as f64 / 1000.0),
+            )
+            .set("result", &entry.result)
+            .set("counts", counts)
+            .set("triggers", triggers.join(", "));
+    }
+    let skin = MadSkin::default();
+    skin.print_owning_expander(&expander, &TextTemplate::from(MD));
+    println!("counts: errors / failed tests / warnings");
+    Ok(())
+}
+
+/// Format a timestamp (seconds since the unix epoch) as an
+/// UTC date and time, eg `2026-10-14 08:03:21`
+fn format_utc(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // civil date from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+    )
+}
+
+#[test]
+fn test_format_utc() {
+    assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+    assert_eq!(format_utc(951782400), "2000-02-29 00:00:00");
+    assert_eq!(format_utc(1760000000), "2025-10-09 08:53:20");
+}
@@ -0,0 +1,9 @@
+mod recorder;
+mod replay;
+mod session_event;
+
+pub use {
+    recorder::*,
+    replay::*,
+    session_event::*,
+};
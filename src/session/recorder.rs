@@ -0,0 +1,100 @@
+use {
+    crate::*,
+    anyhow::{
+        Context as _,
+        Result,
+    },
+    std::{
+        fs::{
+            File,
+            OpenOptions,
+        },
+        io::{
+            LineWriter,
+            Write,
+        },
+        path::Path,
+        sync::{
+            Mutex,
+            atomic::{
+                AtomicBool,
+                Ordering,
+            },
+        },
+        time::Instant,
+    },
+};
+
+/// Whether a session is being recorded, checked before building
+/// the events to record
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+static RECORDER: Mutex<Option<SessionRecorder>> = Mutex::new(None);
+
+/// Writes the events of the session, as JSON lines, in the file given
+/// with `--record`
+struct SessionRecorder {
+    writer: LineWriter<File>,
+    start: Instant,
+}
+
+/// Start recording the session in the file at the given path,
+/// which is overwritten, and is only readable by the user
+pub fn start_session_recording(path: &Path) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options
+        .open(path)
+        .with_context(|| format!("failed to create {}", path.display()))?;
+    eprintln!(
+        "Recording the session in {}: it will contain the outputs of the commands, \
+        with the paths of your files, and the keys you hit",
+        path.display(),
+    );
+    let mut writer = LineWriter::new(file);
+    let (width, height) = termimad::terminal_size();
+    let header = SessionHeader {
+        bacon_version: env!("CARGO_PKG_VERSION").to_string(),
+        width,
+        height,
+    };
+    writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+    *RECORDER.lock().unwrap() = Some(SessionRecorder {
+        writer,
+        start: Instant::now(),
+    });
+    RECORDING.store(true, Ordering::SeqCst);
+    info!("recording the session in {path:?}");
+    Ok(())
+}
+
+/// Record an event of the session, if one is being recorded (the event
+/// is only built in that case)
+pub fn record_session_event(event: impl FnOnce() -> Option<SessionEvent>) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(event) = event() else {
+        return;
+    };
+    let mut recorder = RECORDER.lock().unwrap();
+    let Some(recorder) = recorder.as_mut() else {
+        return;
+    };
+    let event = TimedSessionEvent {
+        t: recorder.start.elapsed().as_millis() as u64,
+        outputs: received_outputs(),
+        event,
+    };
+    let written = serde_json::to_string(&event)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(writeln!(recorder.writer, "{json}")?));
+    if let Err(e) = written {
+        warn!("failed to record session event: {e}");
+    }
+}
@@ -0,0 +1,183 @@
+use {
+    crate::*,
+    anyhow::{
+        Context as _,
+        Result,
+        bail,
+    },
+    crokey::KeyCombination,
+    std::{
+        collections::VecDeque,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+        sync::{
+            Mutex,
+            OnceLock,
+        },
+        thread,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+    termimad::crossbeam::channel::{
+        self,
+        Receiver,
+        Sender,
+    },
+};
+
+/// How often the replay checks whether the next event is due
+const REPLAY_POLL_PERIOD: Duration = Duration::from_millis(10);
+
+static REPLAY: OnceLock<SessionReplay> = OnceLock::new();
+
+/// A session file, as written with `--record`
+#[derive(Debug)]
+pub struct SessionFile {
+    pub header: SessionHeader,
+    pub events: Vec<TimedSessionEvent>,
+}
+
+/// An execution of the recorded session, with its outputs and when
+/// they were received after the start
+#[derive(Debug, Default)]
+pub struct RecordedExecution {
+    pub outputs: Vec<(Duration, CommandExecInfo)>,
+}
+
+/// An event of the recorded session to replay in the event loop
+#[derive(Debug)]
+pub enum ReplayedEvent {
+    Key(KeyCombination),
+    Watch(Vec<PathBuf>),
+    /// There's no more event to replay
+    Finished,
+}
+
+struct SessionReplay {
+    executions: Mutex<VecDeque<RecordedExecution>>,
+    receiver: Receiver<ReplayedEvent>,
+    /// kept so that the channel stays open when all events are sent
+    _sender: Sender<ReplayedEvent>,
+}
+
+impl SessionFile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let Some(header) = lines.next() else {
+            bail!("empty session file {}", path.display());
+        };
+        let header: SessionHeader =
+            serde_json::from_str(header).context("invalid session file header")?;
+        let events = lines
+            .enumerate()
+            .map(|(idx, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("invalid session event at line {}", idx + 2))
+            })
+            .collect::<Result<Vec<TimedSessionEvent>>>()?;
+        Ok(Self { header, events })
+    }
+    /// The job of the first execution of the session
+    pub fn first_job(&self) -> Option<&str> {
+        self.events.iter().find_map(|event| match &event.event {
+            SessionEvent::Start { job } => Some(job.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Start replaying the session: the executions are replayed by the
+/// mission executors instead of running the commands, and the keys and
+/// file changes are sent, at the time and point of the outputs they
+/// were recorded, to the event loop
+pub fn start_session_replay(file: SessionFile) {
+    if file.header.bacon_version != env!("CARGO_PKG_VERSION") {
+        warn!(
+            "session recorded with bacon {}, replayed with bacon {}",
+            file.header.bacon_version,
+            env!("CARGO_PKG_VERSION"),
+        );
+    }
+    let mut executions = VecDeque::new();
+    let mut execution_start = 0;
+    let mut events = Vec::new();
+    for event in file.events {
+        match &event.event {
+            SessionEvent::Start { .. } => {
+                execution_start = event.t;
+                executions.push_back(RecordedExecution::default());
+            }
+            SessionEvent::Line { .. } | SessionEvent::End { .. } => {
+                let (Some(execution), Some(info)) =
+                    (executions.back_mut(), event.event.to_exec_info())
+                else {
+                    continue;
+                };
+                let delay = Duration::from_millis(event.t.saturating_sub(execution_start));
+                execution.outputs.push((delay, info));
+            }
+            SessionEvent::Key { key } => {
+                events.push((event.t, event.outputs, ReplayedEvent::Key(*key)));
+            }
+            SessionEvent::Watch { paths } => {
+                events.push((event.t, event.outputs, ReplayedEvent::Watch(paths.clone())));
+            }
+        }
+    }
+    info!(
+        "replaying {} executions and {} events",
+        executions.len(),
+        events.len()
+    );
+    let (sender, receiver) = channel::unbounded();
+    let events_sender = sender.clone();
+    let replay = SessionReplay {
+        executions: Mutex::new(executions),
+        receiver,
+        _sender: sender,
+    };
+    if REPLAY.set(replay).is_err() {
+        warn!("a session is already replayed");
+        return;
+    }
+    let start = Instant::now();
+    thread::spawn(move || {
+        for (t, outputs, event) in events {
+            let t = Duration::from_millis(t);
+            while start.elapsed() < t || received_outputs() < outputs {
+                thread::sleep(REPLAY_POLL_PERIOD);
+            }
+            if events_sender.send(event).is_err() {
+                return;
+            }
+        }
+        let _ = events_sender.send(ReplayedEvent::Finished);
+    });
+}
+
+pub fn is_replaying_session() -> bool {
+    REPLAY.get().is_some()
+}
+
+/// The receiver of the events to replay, if a session is replayed
+pub fn replayed_events() -> Option<Receiver<ReplayedEvent>> {
+    REPLAY.get().map(|replay| replay.receiver.clone())
+}
+
+/// Take the next recorded execution, to replay in place of starting
+/// the command, if a session is replayed
+pub fn next_replayed_execution() -> Option<RecordedExecution> {
+    let replay = REPLAY.get()?;
+    let execution = replay.executions.lock().unwrap().pop_front();
+    if execution.is_none() {
+        warn!("no more recorded execution to replay");
+    }
+    Some(execution.unwrap_or_default())
+}
@@ -0,0 +1,147 @@
+use {
+    crate::*,
+    crokey::KeyCombination,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        path::PathBuf,
+        process::ExitStatus,
+        sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+};
+
+/// The number of command outputs (lines and ends) received by the
+/// event loop since bacon started, used to replay the other events
+/// at the same point of the outputs
+static RECEIVED_OUTPUTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Count a command output received by the event loop
+pub fn count_received_output() {
+    RECEIVED_OUTPUTS.fetch_add(1, Ordering::SeqCst);
+}
+
+pub fn received_outputs() -> usize {
+    RECEIVED_OUTPUTS.load(Ordering::SeqCst)
+}
+
+/// The first line of a session file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHeader {
+    pub bacon_version: String,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// Something which happened during a recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SessionEvent {
+    /// The start of an execution of a job
+    Start {
+        job: String,
+    },
+    Line {
+        origin: CommandStream,
+        content: String,
+    },
+    /// The end of an execution, with the exit code of the command
+    End {
+        code: Option<i32>,
+    },
+    Key {
+        key: KeyCombination,
+    },
+    /// Changed files (none for a manual trigger)
+    Watch {
+        paths: Vec<PathBuf>,
+    },
+}
+
+/// An event of the session file, with when it happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedSessionEvent {
+    /// milliseconds since the start of the session
+    pub t: u64,
+    /// the number of command outputs received before the event
+    pub outputs: usize,
+    #[serde(flatten)]
+    pub event: SessionEvent,
+}
+
+impl SessionEvent {
+    /// Build the event recording a command output, if it's one
+    /// worth recording
+    pub fn of_exec_info(info: &CommandExecInfo) -> Option<Self> {
+        match info {
+            CommandExecInfo::Line(line) => Some(Self::Line {
+                origin: line.origin,
+                content: line.content.clone(),
+            }),
+            CommandExecInfo::End { status } => Some(Self::End {
+                code: status.and_then(|status| status.code()),
+            }),
+            _ => None,
+        }
+    }
+    /// Build the command output to send in place of the command, if
+    /// the event is one
+    pub fn to_exec_info(&self) -> Option<CommandExecInfo> {
+        match self {
+            Self::Line { origin, content } => Some(CommandExecInfo::Line(RawCommandOutputLine {
+                content: content.clone(),
+                origin: *origin,
+            })),
+            Self::End { code } => Some(CommandExecInfo::End {
+                status: code.map(exit_status),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+#[test]
+fn test_session_event_round_trip() {
+    let events = [
+        TimedSessionEvent {
+            t: 0,
+            outputs: 0,
+            event: SessionEvent::Start {
+                job: "check".to_string(),
+            },
+        },
+        TimedSessionEvent {
+            t: 1200,
+            outputs: 3,
+            event: SessionEvent::Key {
+                key: crokey::key!(ctrl - c),
+            },
+        },
+    ];
+    for event in events {
+        let json = serde_json::to_string(&event).unwrap();
+        let read: TimedSessionEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    }
+    let end = SessionEvent::End { code: Some(101) };
+    let Some(CommandExecInfo::End { status }) = end.to_exec_info() else {
+        panic!("not an end");
+    };
+    assert_eq!(status.and_then(|status| status.code()), Some(101));
+}
@@ -1,5 +1,12 @@
 use super::*;
 
+/// Tell why sounds can't be played
+pub fn check_sound_output() -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "bacon is compiled without the sound feature"
+    ))
+}
+
 /// A dummy sound player which does nothing
 pub struct SoundPlayer {}
 impl SoundPlayer {
@@ -123,6 +123,12 @@ impl fmt::Display for SoundError {
 
 impl std::error::Error for SoundError {}
 
+/// Check that an audio output can be opened
+pub fn check_sound_output() -> Result<(), SoundError> {
+    OutputStream::try_default()?;
+    Ok(())
+}
+
 /// Play the requested sound, sleeps for its duration (until interrupted)
 pub fn play_sound(
     psc: &PlaySoundCommand,
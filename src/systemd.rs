@@ -0,0 +1,56 @@
+use crate::*;
+
+/// Send a notification (eg `READY=1`) to systemd, when bacon runs
+/// as a `Type=notify` service, ie when `NOTIFY_SOCKET` is set
+pub fn sd_notify(message: &str) {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    if let Err(e) = send_notification(&socket, message) {
+        warn!("failed to notify systemd: {e}");
+    }
+}
+
+/// Update the status shown by `systemctl status`
+pub fn sd_notify_status(state: &AppState<'_>) {
+    if std::env::var_os("NOTIFY_SOCKET").is_none() {
+        return;
+    }
+    if let Some(status) = JobStatus::of(state) {
+        sd_notify(&format!("STATUS={}", status.summary_line()));
+    }
+}
+
+#[cfg(unix)]
+fn send_notification(
+    socket: &std::ffi::OsStr,
+    message: &str,
+) -> std::io::Result<()> {
+    use std::os::unix::{
+        ffi::OsStrExt,
+        net::UnixDatagram,
+    };
+    let datagram = UnixDatagram::unbound()?;
+    let socket = socket.as_bytes();
+    #[cfg(target_os = "linux")]
+    if let Some(name) = socket.strip_prefix(b"@") {
+        // a socket in the abstract namespace
+        use std::os::{
+            linux::net::SocketAddrExt,
+            unix::net::SocketAddr,
+        };
+        let addr = SocketAddr::from_abstract_name(name)?;
+        datagram.send_to_addr(message.as_bytes(), &addr)?;
+        return Ok(());
+    }
+    datagram.send_to(message.as_bytes(), std::ffi::OsStr::from_bytes(socket))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_notification(
+    _socket: &std::ffi::OsStr,
+    _message: &str,
+) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,304 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    std::{
+        io::{
+            self,
+            BufRead,
+            IsTerminal,
+        },
+        net::{
+            Ipv4Addr,
+            TcpListener,
+            TcpStream,
+        },
+        path::Path,
+        thread,
+        time::Duration,
+    },
+    termimad::crossbeam::channel::{
+        Receiver,
+        Sender,
+        unbounded,
+    },
+};
+
+/// How long a request to run a job waits for the app to check it
+const JOB_ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A trigger, received from another source than file changes
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    /// name of the source, eg "signal"
+    pub source: &'static str,
+    /// the job to run, if not the current one
+    pub job: Option<ConcreteJobRef>,
+}
+
+impl Trigger {
+    pub fn rerun(source: &'static str) -> Self {
+        Self { source, job: None }
+    }
+}
+
+/// The sources, other than file changes, of job triggers: signals,
+/// lines on stdin, lines on a FIFO, HTTP requests, and the control
+/// socket (which also answers queries, so has its own channel).
+///
+/// Each source is listened to in its own thread, and each trigger is
+/// sent as a message.
+pub struct ManualTriggers {
+    pub receiver: Receiver<Trigger>,
+    pub sender: Sender<Trigger>,
+    pub control: ControlServer,
+}
+
+impl ManualTriggers {
+    pub fn new(
+        settings: &TriggersSettings,
+        control_settings: &ControlSettings,
+        headless: bool,
+    ) -> Result<Self> {
+        let (sender, receiver) = unbounded();
+        if settings.signal {
+            listen_to_signal(sender.clone())?;
+        }
+        if settings.stdin {
+            if !headless && io::stdin().is_terminal() {
+                // reading the terminal would steal the key events of the TUI
+                warn!("stdin is a terminal: not listening to it for triggers");
+            } else {
+                listen_to_stdin(sender.clone());
+            }
+        }
+        if let Some(path) = &settings.fifo {
+            listen_to_fifo(path, sender.clone())?;
+        }
+        let control = ControlServer::new(control_settings)?;
+        if let Some(port) = settings.http_port {
+            listen_to_http(port, sender.clone(), control.sender())?;
+        }
+        Ok(Self {
+            receiver,
+            sender,
+            control,
+        })
+    }
+}
+
+#[cfg(unix)]
+fn listen_to_signal(sender: Sender<Trigger>) -> Result<()> {
+    use signal_hook::{
+        consts::SIGUSR1,
+        iterator::Signals,
+    };
+    let mut signals = Signals::new([SIGUSR1])?;
+    info!("listening to SIGUSR1 for triggers");
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            debug!("SIGUSR1 received");
+            if sender.send(Trigger::rerun("signal")).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen_to_signal(_sender: Sender<Trigger>) -> Result<()> {
+    bail!("Signal triggers are only available on unix systems");
+}
+
+fn listen_to_stdin(sender: Sender<Trigger>) {
+    info!("listening to stdin for triggers");
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if line.is_err() || sender.send(Trigger::rerun("stdin")).is_err() {
+                break;
+            }
+        }
+        info!("end of stdin");
+    });
+}
+
+#[cfg(unix)]
+fn listen_to_fifo(
+    path: &Path,
+    sender: Sender<Trigger>,
+) -> Result<()> {
+    use std::{
+        fs::File,
+        os::unix::fs::FileTypeExt,
+    };
+    let is_fifo = path
+        .metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_fifo());
+    if !is_fifo {
+        bail!(
+            "Trigger FIFO {:?} not found or not a FIFO (it can be created with mkfifo)",
+            path
+        );
+    }
+    info!("listening to FIFO {:?} for triggers", path);
+    let path = path.to_path_buf();
+    thread::spawn(move || {
+        loop {
+            // opening blocks until there's a writer, and reading ends
+            // when the last writer closes the FIFO, so we reopen it
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    warn!("error opening trigger FIFO {:?}: {}", path, e);
+                    break;
+                }
+            };
+            for line in io::BufReader::new(file).lines() {
+                if line.is_err() {
+                    break;
+                }
+                if sender.send(Trigger::rerun("fifo")).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen_to_fifo(
+    _path: &Path,
+    _sender: Sender<Trigger>,
+) -> Result<()> {
+    bail!("FIFO triggers are only available on unix systems");
+}
+
+/// Listen, on localhost only, to HTTP requests:
+/// - `POST /` runs the current job again
+/// - `POST /job/<name>` runs the named job
+///
+/// Requests coming from web pages (which any site could make the
+/// browser send to localhost) are rejected.
+///
+/// As the app must check that the named job exists, those requests
+/// are sent as control requests.
+fn listen_to_http(
+    port: u16,
+    sender: Sender<Trigger>,
+    control_sender: Sender<ControlRequest>,
+) -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    info!("listening to http://localhost:{} for triggers", port);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("error on trigger connection: {}", e);
+                    continue;
+                }
+            };
+            let sender = sender.clone();
+            let control_sender = control_sender.clone();
+            // each connection has its own thread, so that a slow
+            // client doesn't delay the others
+            thread::spawn(move || {
+                if let Err(e) = handle_http_request(&stream, &sender, &control_sender) {
+                    warn!("error handling trigger request: {}", e);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn handle_http_request(
+    stream: &TcpStream,
+    sender: &Sender<Trigger>,
+    control_sender: &Sender<ControlRequest>,
+) -> Result<()> {
+    let status = match HttpRequestHead::read(stream) {
+        Err(status) => status,
+        Ok(head) if !head.has_loopback_host() || head.is_cross_origin() => {
+            warn!("rejected a trigger request from a web page");
+            "403 Forbidden"
+        }
+        Ok(head) => match parse_http_request_line(&head.request_line) {
+            ("202 Accepted", Some(Trigger { job: Some(job), .. })) => run_job(control_sender, job),
+            (status, Some(trigger)) => {
+                sender.send(trigger)?;
+                status
+            }
+            (status, None) => status,
+        },
+    };
+    write_empty_response(stream, status)?;
+    Ok(())
+}
+
+/// Ask the app to run the job, and return the status of the response
+fn run_job(
+    control_sender: &Sender<ControlRequest>,
+    job: ConcreteJobRef,
+) -> &'static str {
+    let (reply, reply_receiver) = unbounded();
+    let request = ControlRequest {
+        command: ControlCommand::Run(Some(job)),
+        reply,
+    };
+    if control_sender.send(request).is_err() {
+        return "503 Service Unavailable";
+    }
+    match reply_receiver.recv_timeout(JOB_ANSWER_TIMEOUT) {
+        Ok(Ok(_)) => "202 Accepted",
+        Ok(Err(_)) => "404 Not Found", // unknown job
+        Err(_) => "503 Service Unavailable",
+    }
+}
+
+/// Return the response status and the trigger, if the request is valid
+fn parse_http_request_line(request_line: &str) -> (&'static str, Option<Trigger>) {
+    let mut tokens = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (tokens.next(), tokens.next()) else {
+        return ("400 Bad Request", None);
+    };
+    if method != "POST" {
+        return ("405 Method Not Allowed", None);
+    }
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        return ("202 Accepted", Some(Trigger::rerun("http")));
+    }
+    match path.strip_prefix("/job/") {
+        Some(name) if !name.is_empty() && !name.contains('/') => (
+            "202 Accepted",
+            Some(Trigger {
+                source: "http",
+                job: Some(ConcreteJobRef::from(name)),
+            }),
+        ),
+        _ => ("404 Not Found", None),
+    }
+}
+
+#[test]
+fn test_parse_http_request_line() {
+    let (status, trigger) = parse_http_request_line("POST / HTTP/1.1\r\n");
+    assert_eq!(status, "202 Accepted");
+    assert_eq!(trigger.unwrap().job, None);
+    let (status, trigger) = parse_http_request_line("POST /job/clippy HTTP/1.1\r\n");
+    assert_eq!(status, "202 Accepted");
+    assert_eq!(
+        trigger.unwrap().job,
+        Some(ConcreteJobRef::from_job_name("clippy"))
+    );
+    let (status, trigger) = parse_http_request_line("GET /job/clippy HTTP/1.1\r\n");
+    assert_eq!(status, "405 Method Not Allowed");
+    assert!(trigger.is_none());
+    let (status, _) = parse_http_request_line("POST /jobs HTTP/1.1\r\n");
+    assert_eq!(status, "404 Not Found");
+}
@@ -0,0 +1,9 @@
+mod manual_triggers;
+mod triggers_config;
+mod triggers_settings;
+
+pub use {
+    manual_triggers::*,
+    triggers_config::*,
+    triggers_settings::*,
+};
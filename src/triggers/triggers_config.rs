@@ -0,0 +1,22 @@
+use {
+    serde::Deserialize,
+    std::path::PathBuf,
+};
+
+/// The `[triggers]` part of a configuration: what, in addition
+/// to (or instead of) file changes, makes the job run again
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TriggersConfig {
+    /// Whether changes of watched files trigger the job
+    pub files: Option<bool>,
+    /// Whether receiving SIGUSR1 triggers the job (unix only)
+    pub signal: Option<bool>,
+    /// Whether a line received on stdin triggers the job
+    pub stdin: Option<bool>,
+    /// Path to a FIFO (named pipe) on which each received line
+    /// triggers the job
+    pub fifo: Option<PathBuf>,
+    /// Port on which to listen, on localhost, to HTTP POST
+    /// requests triggering the current job or a named one
+    pub http_port: Option<u16>,
+}
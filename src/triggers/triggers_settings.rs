@@ -0,0 +1,52 @@
+use {
+    crate::*,
+    std::path::PathBuf,
+};
+
+/// Settings of the sources of job triggers
+#[derive(Debug, Clone)]
+pub struct TriggersSettings {
+    pub files: bool,
+    pub signal: bool,
+    pub stdin: bool,
+    pub fifo: Option<PathBuf>,
+    pub http_port: Option<u16>,
+}
+
+impl Default for TriggersSettings {
+    fn default() -> Self {
+        Self {
+            files: true,
+            signal: false,
+            stdin: false,
+            fifo: None,
+            http_port: None,
+        }
+    }
+}
+
+impl TriggersSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &TriggersConfig,
+    ) {
+        if let Some(b) = config.files {
+            self.files = b;
+        }
+        if let Some(b) = config.signal {
+            self.signal = b;
+        }
+        if let Some(b) = config.stdin {
+            self.stdin = b;
+        }
+        if config.fifo.is_some() {
+            self.fifo.clone_from(&config.fifo);
+        }
+        if config.http_port.is_some() {
+            self.http_port = config.http_port;
+        }
+    }
+    pub fn has_manual_triggers(&self) -> bool {
+        self.signal || self.stdin || self.fifo.is_some() || self.http_port.is_some()
+    }
+}
@@ -1,5 +1,6 @@
 mod tline;
 mod tline_builder;
+mod tline_emulator;
 mod trange;
 mod tstring;
 
@@ -13,6 +14,9 @@ pub const CSI_RED: &str = "\u{1b}[31m";
 pub const CSI_BOLD_RED: &str = "\u{1b}[1m\u{1b}[38;5;9m";
 pub const CSI_BOLD_ORANGE: &str = "\u{1b}[1m\u{1b}[38;5;208m";
 
+/// Used for the diagnostics of dependencies
+pub const CSI_GRAY: &str = "\u{1b}[38;5;244m";
+
 /// Used for "Blocking"
 pub const CSI_BLUE: &str = "\u{1b}[1m\u{1b}[36m";
 
@@ -43,6 +47,7 @@ use {
 pub use {
     tline::*,
     tline_builder::*,
+    tline_emulator::*,
     trange::*,
     tstring::*,
 };
@@ -78,6 +78,14 @@ impl TLine {
         builder.read(tty);
         builder.build()
     }
+    /// Build the line as a terminal would display it, with carriage
+    /// returns and erasures applied, and without the sequences other
+    /// than styles
+    pub fn from_tty_emulated(tty: &str) -> Self {
+        let mut emulator = TLineEmulator::default();
+        emulator.read(tty);
+        emulator.build()
+    }
     pub fn from_raw(raw: String) -> Self {
         Self {
             strings: vec![TString {
@@ -176,7 +184,7 @@ impl TLine {
     pub fn if_unstyled(&self) -> Option<&str> {
         if self.strings.len() == 1 {
             self.strings
-                .get(0)
+                .first()
                 .filter(|s| s.csi.is_empty())
                 .map(|s| s.raw.as_str())
         } else {
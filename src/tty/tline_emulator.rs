@@ -0,0 +1,163 @@
+use super::*;
+
+/// A builder interpreting a line of TTY output as a terminal would,
+/// to build the TLine of what would be visible.
+///
+/// Carriage returns, backspaces, erasures and horizontal cursor moves
+/// (as used by progress bars) overwrite the previous content, style
+/// sequences are kept, and other sequences, which can't be honored in
+/// a single line, are dropped.
+#[derive(Debug, Default)]
+pub struct TLineEmulator {
+    /// the style sequences currently applying
+    csi: String,
+    /// the distinct styles of the cells
+    styles: Vec<String>,
+    /// the visible chars, with the index of their style
+    cells: Vec<(usize, char)>,
+    cursor: usize,
+}
+impl TLineEmulator {
+    pub fn read(
+        &mut self,
+        s: &str,
+    ) {
+        let mut parser = vte::Parser::new();
+        parser.advance(self, s.as_bytes());
+    }
+    pub fn build(self) -> TLine {
+        let mut strings: Vec<TString> = Vec::new();
+        let mut last_style = None;
+        for (style, c) in self.cells {
+            match strings.last_mut() {
+                Some(ts) if last_style == Some(style) => ts.raw.push(c),
+                _ => strings.push(TString::new(self.styles[style].clone(), c)),
+            }
+            last_style = Some(style);
+        }
+        TLine { strings }
+    }
+    fn style_idx(&mut self) -> usize {
+        match self.styles.iter().position(|s| *s == self.csi) {
+            Some(idx) => idx,
+            None => {
+                self.styles.push(self.csi.clone());
+                self.styles.len() - 1
+            }
+        }
+    }
+    fn write(
+        &mut self,
+        c: char,
+    ) {
+        let style = self.style_idx();
+        while self.cells.len() < self.cursor {
+            self.cells.push((style, ' '));
+        }
+        if self.cursor < self.cells.len() {
+            self.cells[self.cursor] = (style, c);
+        } else {
+            self.cells.push((style, c));
+        }
+        self.cursor += 1;
+    }
+}
+impl vte::Perform for TLineEmulator {
+    fn print(
+        &mut self,
+        c: char,
+    ) {
+        self.write(c);
+    }
+    fn execute(
+        &mut self,
+        byte: u8,
+    ) {
+        match byte {
+            b'\r' => self.cursor = 0,
+            0x08 => self.cursor = self.cursor.saturating_sub(1),
+            b'\t' => {
+                for c in TAB_REPLACEMENT.chars() {
+                    self.write(c);
+                }
+            }
+            _ => {}
+        }
+    }
+    fn csi_dispatch(
+        &mut self,
+        params: &vte::Params,
+        _intermediates: &[u8],
+        _ignore: bool,
+        action: char,
+    ) {
+        let first = params
+            .iter()
+            .next()
+            .and_then(|param| param.first().copied())
+            .unwrap_or(0) as usize;
+        match action {
+            'm' => {
+                if first == 0 && params.len() <= 1 {
+                    self.csi.clear();
+                } else {
+                    let mut ts = TString::default();
+                    ts.push_csi(params, action);
+                    self.csi.push_str(&ts.csi);
+                }
+            }
+            'K' => match first {
+                0 => self.cells.truncate(self.cursor),
+                1 => {
+                    let end = self.cursor.min(self.cells.len());
+                    for cell in &mut self.cells[..end] {
+                        cell.1 = ' ';
+                    }
+                }
+                _ => self.cells.clear(),
+            },
+            'G' => self.cursor = first.max(1) - 1,
+            'C' => self.cursor += first.max(1),
+            'D' => self.cursor = self.cursor.saturating_sub(first.max(1)),
+            _ => {}
+        }
+    }
+    fn hook(
+        &mut self,
+        _params: &vte::Params,
+        _intermediates: &[u8],
+        _ignore: bool,
+        _action: char,
+    ) {
+    }
+    fn put(
+        &mut self,
+        _byte: u8,
+    ) {
+    }
+    fn unhook(&mut self) {}
+    fn osc_dispatch(
+        &mut self,
+        _params: &[&[u8]],
+        _bell_terminated: bool,
+    ) {
+    }
+    fn esc_dispatch(
+        &mut self,
+        _intermediates: &[u8],
+        _ignore: bool,
+        _byte: u8,
+    ) {
+    }
+}
+
+#[test]
+fn test_emulate_progress_bar() {
+    let line = TLine::from_tty_emulated(
+        "[=>   ] 1/4\r[===> ] 3/4\r\u{1b}[32m[=====]\u{1b}[0m done\u{1b}[K",
+    );
+    assert_eq!(line.to_raw(), "[=====] done");
+    assert_eq!(line.strings[0].csi, "\u{1b}[32m");
+    let line = TLine::from_tty_emulated("abc\u{8}\u{8}XY\u{1b}[2Ddef");
+    assert_eq!(line.to_raw(), "adef");
+}
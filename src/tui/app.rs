@@ -4,13 +4,27 @@ use {
     crokey::*,
     std::{
         io::Write,
-        time::Duration,
+        path::{
+            Path,
+            PathBuf,
+        },
+        thread,
+        time::{
+            Duration,
+            Instant,
+        },
     },
     termimad::{
         EventSource,
         EventSourceOptions,
         Ticker,
-        crossbeam::channel::select,
+        crossbeam::channel::{
+            Receiver,
+            after,
+            bounded,
+            never,
+            select,
+        },
         crossterm::event::Event,
     },
 };
@@ -24,10 +38,26 @@ use {
     },
 };
 
+/// Minimal interval between the redraws caused by the output of the
+/// command, so that a fast output doesn't keep the CPU busy drawing
+const MIN_OUTPUT_DRAW_INTERVAL: Duration = Duration::from_millis(50);
+
 enum DoAfterMission {
     NextJob(JobRef),
-    ReloadConfig,
+    /// Use the settings read after a change of the configuration
+    ReloadConfig(Box<Settings>),
+    /// Reload the configuration with the next profile applied
+    NextProfile,
+    /// Use the new context, eg because workspace members changed
+    ReloadContext(Box<Context>),
+    /// Switch to another root of a multi-root session, the one coming
+    /// `delta` places after the current one
+    SwitchRoot(isize),
     Quit,
+    /// Quit after the single execution of the `--once` mode
+    Exit {
+        success: bool,
+    },
 }
 
 impl From<JobRef> for DoAfterMission {
@@ -36,66 +66,309 @@ impl From<JobRef> for DoAfterMission {
     }
 }
 
-/// Run the application until the user quits
+/// What's kept from one mission to the next
+#[derive(Default)]
+struct Continuity {
+    /// a message to display at the start of the next mission
+    message: Option<Message>,
+    /// whether runs are scoped to the failing tests until they pass
+    failed_tests_first: bool,
+    /// the outcomes of the tests in the previous missions
+    test_history: TestHistory,
+    /// the timings of the previous runs, and whether they're displayed
+    perf_hud: PerfHud,
+    /// the recent reports of the jobs
+    report_history: ReportHistory,
+    /// the state of the previous mission, when it's rebuilt
+    preserved: Option<PreservedState>,
+    /// the index of the root and the number of roots, in a
+    /// multi-root session
+    root_position: Option<(usize, usize)>,
+    /// the package the cargo jobs are scoped to, picked by the user
+    package: Option<String>,
+}
+
+/// A project root of the session, with the state of its missions.
+///
+/// Only the current root is live, with a watcher and a mission: the
+/// other ones just keep their state until the user switches to them.
+struct ProjectRoot {
+    /// the launch arguments, with this root as project
+    args: Args,
+    context: Context,
+    settings: Settings,
+    job_stack: JobStack,
+    next_job: JobRef,
+    continuity: Continuity,
+}
+
+impl ProjectRoot {
+    fn new(
+        args: Args,
+        context: Context,
+        settings: Settings,
+    ) -> Self {
+        Self {
+            args,
+            context,
+            settings,
+            job_stack: JobStack::default(),
+            next_job: JobRef::Initial,
+            continuity: Continuity::default(),
+        }
+    }
+    /// Start with the job which was running when bacon was last left
+    /// in this project, unless a job was given in arguments
+    fn restore_last_job(&mut self) {
+        if !self.settings.persist_ui_state || self.settings.arg_job.is_some() {
+            return;
+        }
+        let ui_state = UiState::load(&self.context.package_directory);
+        let Some(last_job) = ui_state.last_job(&self.settings.default_job) else {
+            return;
+        };
+        if self.settings.has_job(last_job) {
+            self.next_job = JobRef::Concrete(last_job.clone());
+        }
+    }
+    /// Open another root of a multi-root session
+    fn open(
+        args: &Args,
+        project: &str,
+    ) -> Result<Self> {
+        let mut args = args.clone();
+        args.project = Some(project.to_string());
+        let context = Context::new(&args)?;
+        let settings = Settings::read(&args, &context)?;
+        Ok(Self::new(args, context, settings))
+    }
+}
+
+/// Create the source of the user events of the TUI
+pub fn user_event_source() -> Result<EventSource> {
+    Ok(EventSource::with_options(EventSourceOptions {
+        combine_keys: false,
+        ..Default::default()
+    })?)
+}
+
+/// Run the application until the user quits, and return the success
+/// of the execution when in `--once` mode
+///
+/// The event source may be given when it was needed before, eg for
+/// the configuration error screen.
 pub fn run(
     w: &mut W,
-    mut settings: Settings,
+    settings: Settings,
     args: &Args,
     location: Context,
     headless: bool,
-) -> Result<()> {
+    event_source: Option<EventSource>,
+) -> Result<Option<bool>> {
     let event_source = if headless {
         // in headless mode, in some contexts, ctrl-c might not be enough to kill
-        // bacon so we add this handler
+        // bacon so we add this handler (which also handles SIGTERM, eg when
+        // systemd stops the service)
         ctrlc::set_handler(move || {
+            // the command runs in its own process group, which
+            // doesn't receive the interruption
+            sd_notify("STOPPING=1");
+            ProcessGroup::kill_all();
+            PaneStatus::unset_tmux_options();
+            unregister_instance();
             eprintln!("bye");
             std::process::exit(0);
         })
         .expect("Error setting Ctrl-C handler");
         None
     } else {
-        Some(EventSource::with_options(EventSourceOptions {
-            combine_keys: false,
-            ..Default::default()
-        })?)
+        match event_source {
+            Some(event_source) => Some(event_source),
+            None => Some(user_event_source()?),
+        }
     };
-    let mut job_stack = JobStack::default();
-    let mut next_job = JobRef::Initial;
-    let mut message = None;
+    // the servers are set up with the settings of the first root, and
+    // their requests go to the mission of the current root
+    let manual_triggers = ManualTriggers::new(&settings.triggers, &settings.control, headless)?;
+    serve_dashboard(&settings.dashboard, manual_triggers.control.sender())?;
+    let mut once_success = None;
+    let mut first_root = ProjectRoot::new(args.clone(), location, settings);
+    if let Some(warning) = other_instances_warning(&first_root.context, &first_root.settings) {
+        if headless {
+            eprintln!("{warning}");
+        }
+        first_root.continuity.message = Some(Message::short(warning));
+    }
+    if !first_root.settings.config_warnings.is_empty() {
+        if headless {
+            for warning in &first_root.settings.config_warnings {
+                eprintln!("Config warning: {warning}");
+            }
+        }
+        first_root.continuity.message = Some(config_message("Config loaded", &first_root.settings));
+    }
+    register_instance(&first_root.context, &first_root.settings, headless);
+    let mut roots = vec![first_root];
+    for project in args.root.iter().skip(1) {
+        roots.push(ProjectRoot::open(args, project)?);
+    }
+    if !headless {
+        for root in &mut roots {
+            root.restore_last_job();
+        }
+    }
+    let root_count = roots.len();
+    let mut current_root = 0;
+    sd_notify("READY=1");
     loop {
-        let Some((concrete_job_ref, job)) = job_stack.pick_job(&next_job, &settings)? else {
+        let root = &mut roots[current_root];
+        root.continuity.root_position = (root_count > 1).then_some((current_root, root_count));
+        let Some((concrete_job_ref, job)) =
+            root.job_stack.pick_job(&root.next_job, &root.settings)?
+        else {
             break;
         };
-        let mission = location.mission(concrete_job_ref, &job, &settings)?;
-        let do_after =
-            app::run_mission(w, mission, event_source.as_ref(), message.take(), headless)?;
+        let mut mission = root
+            .context
+            .mission(concrete_job_ref.clone(), &job, &root.settings)?;
+        mission.package.clone_from(&root.continuity.package);
+        // the manifests whose change may change the layout of the workspace
+        let mut manifests: Vec<PathBuf> = root
+            .context
+            .local_manifests()
+            .into_iter()
+            .map(Path::to_path_buf)
+            .collect();
+        manifests.push(root.context.workspace_manifest());
+        let config_candidates = config_paths(&root.context);
+        let reloaders = Reloaders {
+            context: &|paths| {
+                if paths.iter().any(|path| manifests.contains(path)) {
+                    Some(check_members(&root.context, &root.args))
+                } else {
+                    None
+                }
+            },
+            config_candidates: &config_candidates,
+            settings: &|| Settings::read(&root.args, &root.context),
+        };
+        let do_after = app::run_mission(
+            w,
+            mission,
+            &reloaders,
+            event_source.as_ref(),
+            &manual_triggers,
+            &mut root.continuity,
+            headless,
+        )?;
         match do_after {
             DoAfterMission::NextJob(job_ref) => {
-                next_job = job_ref;
+                root.next_job = job_ref;
             }
-            DoAfterMission::ReloadConfig => match Settings::read(args, &location) {
-                Ok(new_settings) => {
-                    settings = new_settings;
-                    message = Some(Message::short("Config reloaded"));
+            DoAfterMission::ReloadConfig(new_settings) => {
+                root.settings = *new_settings;
+                root.continuity.message = Some(config_message("Config reloaded", &root.settings));
+            }
+            DoAfterMission::NextProfile => {
+                let mut args = root.args.clone();
+                args.profile = root.settings.next_profile();
+                match Settings::read(&args, &root.context) {
+                    Ok(new_settings) => {
+                        let message = match &args.profile {
+                            Some(profile) => format!("Profile {profile:?} applied"),
+                            None => "No profile applied".to_string(),
+                        };
+                        root.args = args;
+                        root.settings = new_settings;
+                        root.continuity.message = Some(config_message(&message, &root.settings));
+                    }
+                    Err(e) => {
+                        root.continuity.message = Some(invalid_config_message(&e));
+                    }
                 }
-                Err(e) => {
-                    message = Some(Message::short(format!("Invalid config: {e}")));
+            }
+            DoAfterMission::ReloadContext(new_location) => {
+                root.context = *new_location;
+                root.next_job = concrete_job_ref.into();
+                // the configuration files depend on the context
+                match Settings::read(&root.args, &root.context) {
+                    Ok(new_settings) => {
+                        root.settings = new_settings;
+                        root.continuity.message =
+                            Some(config_message("Workspace members changed", &root.settings));
+                    }
+                    Err(e) => {
+                        root.continuity.message = Some(invalid_config_message(&e));
+                    }
                 }
-            },
+            }
+            DoAfterMission::SwitchRoot(delta) => {
+                // the job is resumed when coming back to this root
+                root.next_job = concrete_job_ref.into();
+                current_root =
+                    (current_root as isize + delta).rem_euclid(root_count as isize) as usize;
+            }
             DoAfterMission::Quit => {
                 break;
             }
+            DoAfterMission::Exit { success } => {
+                once_success = Some(success);
+                break;
+            }
         }
     }
-    Ok(())
+    sd_notify("STOPPING=1");
+    TaskExecutor::wait_for_ending_tasks();
+    PaneStatus::unset_tmux_options();
+    unregister_instance();
+    Ok(once_success)
+}
+
+/// The message telling the configuration was (re)loaded, with its
+/// warnings if any
+fn config_message(
+    message: &str,
+    settings: &Settings,
+) -> Message {
+    if settings.config_warnings.is_empty() {
+        Message::short(message)
+    } else {
+        let summary = problems_summary(&settings.config_warnings);
+        Message::short(format!("{message}, with warnings: {summary}"))
+    }
+}
+
+/// The message telling why the configuration couldn't be reloaded (the
+/// previous one being kept)
+fn invalid_config_message(e: &anyhow::Error) -> Message {
+    let description = match e.downcast_ref::<ConfigErrors>() {
+        Some(errors) => errors.summary(),
+        None => e.to_string(),
+    };
+    Message::short(format!("Invalid config: {description}"))
+}
+
+/// What a mission needs to tell how to be rebuilt, when the workspace
+/// or the configuration change
+struct Reloaders<'r> {
+    /// when the changed paths may modify the local packages, start computing
+    /// the context again, and return the receiver of the new context, which
+    /// is sent only if the local packages changed
+    context: &'r dyn Fn(&[PathBuf]) -> Option<Receiver<Context>>,
+    /// the paths where configuration files are looked for
+    config_candidates: &'r [PathBuf],
+    /// read the configuration again
+    settings: &'r dyn Fn() -> Result<Settings>,
 }
 
 /// Run the mission and return what to do afterwards
 fn run_mission(
     w: &mut W,
     mission: Mission,
+    reloaders: &Reloaders,
     event_source: Option<&EventSource>,
-    message: Option<Message>,
+    manual_triggers: &ManualTriggers,
+    continuity: &mut Continuity,
     headless: bool,
 ) -> Result<DoAfterMission> {
     let keybindings = mission.settings.keybindings.clone();
@@ -104,20 +377,76 @@ fn run_mission(
     let sound_player = mission.sound_player_if_needed();
 
     // build the watcher detecting and transmitting mission file changes
-    let ignorer = time!(Info, mission.ignorer());
-    let mission_watcher = Watcher::new(&mission.paths_to_watch, ignorer)?;
+    let mut ignorer = time!(Info, mission.ignorer());
+    let dep_info = mission.dep_info();
+    if let Some(dep_info) = &dep_info {
+        ignorer.add(Box::new(dep_info.ignorer()));
+    }
+    let paths_to_watch: &[PathBuf] = if mission.settings.triggers.files {
+        &mission.paths_to_watch
+    } else {
+        &[]
+    };
+    let mut mission_watcher = Watcher::new(paths_to_watch, ignorer)?;
+    if let Some(dep_info) = &dep_info {
+        // dependencies known from previous builds
+        mission_watcher.watch_files(&dep_info.update());
+    }
 
-    // create the watcher for config file changes
-    let config_watcher = Watcher::new(&mission.settings.config_files, IgnorerSet::default())?;
+    // create the watcher for config file changes: the loaded files, and the
+    // directories in which the other ones may be created
+    let config_files = &mission.settings.config_files;
+    let missing_config_files: Vec<PathBuf> = reloaders
+        .config_candidates
+        .iter()
+        .filter(|path| !path.exists())
+        .cloned()
+        .collect();
+    let mut watched_config_files = config_files.clone();
+    watched_config_files.extend(missing_config_files.iter().cloned());
+    let mut config_ignorer = IgnorerSet::default();
+    config_ignorer.add(Box::new(AllowlistIgnorer::new(watched_config_files)));
+    let mut config_watcher = Watcher::new(config_files, config_ignorer)?;
+    let mut config_directories: Vec<PathBuf> = missing_config_files
+        .iter()
+        .filter_map(|path| path.parent())
+        .filter(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+        .collect();
+    config_directories.sort();
+    config_directories.dedup();
+    config_watcher.watch_directory_contents(&config_directories);
 
-    // create the executor, mission, and state
+    // create the executor, mission, and state. The command hook, if
+    // any, runs in the background, the command starting once it's done
+    let mut command_hook = spawn_command_hook(&mission);
+    let no_command_hook = never();
     let mut executor = MissionExecutor::new(&mission)?;
-    let on_change_strategy = mission.job.on_change_strategy();
+    let defer_events = mission.job.defer_events();
+    let min_interval = mission.job.min_interval();
     let mut state = AppState::new(mission, headless)?;
-    if let Some(message) = message {
+    state.failed_tests_first = continuity.failed_tests_first;
+    state.test_history = std::mem::take(&mut continuity.test_history);
+    state.perf_hud = std::mem::take(&mut continuity.perf_hud);
+    state.report_history = std::mem::take(&mut continuity.report_history);
+    state.root_position = continuity.root_position;
+    if let Some(message) = continuity.message.take() {
+        state.messages.push(message);
+    }
+    if let Some(message) = mission_watcher.watch_limit_message() {
         state.messages.push(message);
     }
     state.computation_starts();
+    // restored after the start of the computation, so that the previous report
+    // stays displayed until the new one replaces it
+    if let Some(preserved) = continuity.preserved.take() {
+        state.restore(preserved);
+    }
+    if !headless {
+        state.restore_retained_report();
+        state.restore_persisted_report();
+        state.restore_ui_state();
+    }
     if !headless {
         state.draw(w)?;
     }
@@ -136,7 +465,22 @@ fn run_mission(
         _dummy_sender = sender;
         receiver
     };
+    // the timer of the re-run delayed because of the min_interval of the job
+    let mut deferred_rerun: Option<Receiver<Instant>> = None;
+    let no_deferred_rerun = never();
+    // the timer of the redraw delayed because the previous one was too recent
+    let mut deferred_draw: Option<Receiver<Instant>> = None;
+    let no_deferred_draw = never();
+    // the check of the workspace members, after a change of a manifest
+    let mut members_check: Option<Receiver<Context>> = None;
+    let no_members_check = never();
+    let mut last_draw = Instant::now();
     let mut mission_end = None;
+    let replayed_events = replayed_events().unwrap_or_else(never);
+    let passthrough = state.mission.analyzer() == AnalyzerRef::Passthrough;
+    let hook_outcomes = state.hook_outcomes();
+    let changed_lines_updates = state.changed_lines_updates();
+    let test_list_updates = state.test_list_updates();
     // loop on events
     #[allow(unused_mut)]
     loop {
@@ -144,67 +488,149 @@ fn run_mission(
         // While it's a vec, action execution will stop at the first one quitting the
         // mission or requesting a task execution, and the rest of the vec will be dropped.
         let mut actions: Vec<Action> = Vec::new();
+        let live_report_timer = state.live_report_delay().map_or_else(never, after);
+        // the event to handle, from the user or from the replayed session
+        let mut user_event = None;
+        let mut from_event_source = false;
+        // whether the redraw can be delayed
+        let mut throttled_draw = false;
+        // whether the task just ended, its report hooks included
+        let mut task_ended = false;
         select! {
             recv(ticker.tick_receiver) -> _ => {
                 // just redraw
             }
-            recv(mission_watcher.receiver) -> _ => {
+            recv(live_report_timer) -> _ => {
+                state.update_live_report();
+            }
+            recv(deferred_draw.as_ref().unwrap_or(&no_deferred_draw)) -> _ => {
+                deferred_draw = None;
+            }
+            recv(deferred_rerun.as_ref().unwrap_or(&no_deferred_rerun)) -> _ => {
+                debug!("deferred re-run");
+                deferred_rerun = None;
+                if state.changes_since_last_job_start > 0
+                    && state.auto_refresh.is_enabled()
+                    && (!state.is_computing() || defer_events == DeferEvents::Restart)
+                {
+                    actions.push(Action::Internal(Internal::ReRun));
+                }
+            }
+            recv(mission_watcher.receiver) -> paths => {
                 debug!("watch event received");
+                let paths = paths.unwrap_or_default();
+                if let Some(check) = (reloaders.context)(&paths) {
+                    // a previous check, if any, is superseded
+                    members_check = Some(check);
+                }
                 if task_executor.is_in_grace_period() {
-                    debug!("ignoring notify event in grace period");
-                    continue;
+                    if task_executor.is_speculative() && state.auto_refresh.is_enabled() {
+                        debug!("restarting the speculative execution");
+                        record_session_event(|| Some(SessionEvent::Watch { paths: paths.clone() }));
+                        if state.receive_speculative_watch_event(paths) {
+                            actions.push(Action::Internal(Internal::ReRun));
+                        }
+                    } else {
+                        debug!("ignoring notify event in grace period");
+                    }
+                } else if state.is_computing() && defer_events == DeferEvents::Ignore {
+                    debug!("ignoring notify event during computation");
+                } else {
+                    record_session_event(|| Some(SessionEvent::Watch { paths: paths.clone() }));
+                    if state.receive_watch_event(paths)
+                        && state.auto_refresh.is_enabled()
+                        && (!state.is_computing() || defer_events == DeferEvents::Restart)
+                        && rerun_allowed(&state, min_interval, &mut deferred_rerun)
+                    {
+                        actions.push(Action::Internal(Internal::ReRun));
+                    }
                 }
-                state.receive_watch_event();
-                if state.auto_refresh.is_enabled() {
-                    if !state.is_computing() || on_change_strategy == OnChangeStrategy::KillThenRestart {
+            }
+            recv(members_check.as_ref().unwrap_or(&no_members_check)) -> new_location => {
+                members_check = None;
+                if let Ok(new_location) = new_location {
+                    mission_end = Some(DoAfterMission::ReloadContext(Box::new(new_location)));
+                }
+            }
+            recv(manual_triggers.receiver) -> trigger => {
+                let Ok(trigger) = trigger else {
+                    continue;
+                };
+                info!("manual trigger received: {:?}", &trigger);
+                if let Some(job) = trigger.job {
+                    // a named job is always accepted as it's a change of mission
+                    if state.mission.settings.has_job(&job) {
+                        actions.push(Action::Job(job.into()));
+                    } else {
+                        state.messages.push(Message::short(format!("Job not found: *{job}*")));
+                    }
+                } else if state.is_computing() && defer_events == DeferEvents::Ignore {
+                    debug!("ignoring trigger during computation");
+                } else {
+                    record_session_event(|| Some(SessionEvent::Watch { paths: Vec::new() }));
+                    state.receive_watch_event(Vec::new());
+                    if state.auto_refresh.is_enabled()
+                        && (!state.is_computing() || defer_events == DeferEvents::Restart)
+                        && rerun_allowed(&state, min_interval, &mut deferred_rerun)
+                    {
                         actions.push(Action::Internal(Internal::ReRun));
                     }
                 }
             }
+            recv(manual_triggers.control.receiver) -> request => {
+                let Ok(request) = request else {
+                    continue;
+                };
+                debug!("control request received: {:?}", &request.command);
+                if let Some(trigger) = request.trigger(&state) {
+                    // handled as the other manual triggers
+                    let _ = manual_triggers.sender.send(trigger);
+                }
+                request.answer(&state);
+            }
             recv(config_watcher.receiver) -> _ => {
                 info!("config watch event received");
                 grace_period.sleep(); // Fix #310
                 actions.push(Action::Internal(Internal::ReloadConfig));
             }
+            recv(command_hook.as_ref().unwrap_or(&no_command_hook)) -> command => {
+                command_hook = None;
+                let command = command.unwrap_or_else(|e| Err(e.into()));
+                match command {
+                    Ok(command) => {
+                        debug!("command hook computed {command:?}");
+                        state.mission.hooked_command = Some(command);
+                        executor.update_command(&state.mission)?;
+                        task_executor.die();
+                        task_executor = state.start_computation(&mut executor)?;
+                    }
+                    Err(e) => {
+                        state.computation_stops();
+                        return Err(e.context(format!("command hook of job '{}' failed", state.mission.concrete_job_ref.badge_label())));
+                    }
+                }
+            }
             recv(executor.line_receiver) -> info => {
                 if let Ok(info) = info {
+                    record_session_event(|| SessionEvent::of_exec_info(&info));
+                    count_received_output();
                     match info {
                         CommandExecInfo::Line(line) => {
+                            throttled_draw = true;
                             if headless {
-                                match line.origin {
-                                    CommandStream::StdOut => print!("{}", line.content),
-                                    CommandStream::StdErr => eprint!("{}", line.content),
-                                }
+                                state.mission.settings.headless_output.print_output_line(&line);
                             }
-                            let line = line.into();
+                            let line = if passthrough {
+                                CommandOutputLine::emulated(line)
+                            } else {
+                                line.into()
+                            };
                             state.add_line(line);
                         }
                         CommandExecInfo::End { status } => {
                             // computation finished
                             info!("execution finished with status: {:?}", status);
-                            state.finish_task(status)?;
-                            if headless {
-                                for badge in state.job_badges() {
-                                    badge.draw(w)?;
-                                }
-                                writeln!(w)?;
-                                w.flush()?;
-                            }
-                            if state.is_success() {
-                                if let Some(action) = &state.mission.job.on_success {
-                                    actions.push(action.clone());
-                                }
-                            }
-                            if state.is_failure() {
-                                if let Some(action) = &state.mission.job.on_failure {
-                                    actions.push(action.clone());
-                                }
-                            }
-                            if state.changes_since_last_job_start > 0 && state.auto_refresh.is_enabled() {
-                                // will be ignored if a on_success or on_failures ends the mission
-                                // or does a rerun already
-                                actions.push(Action::Internal(Internal::ReRun))
-                            }
+                            task_ended = state.finish_task(status)?;
                         }
                         CommandExecInfo::Error(e) => {
                             state.computation_stops();
@@ -216,40 +642,179 @@ fn run_mission(
                     }
                 }
             }
-            recv(user_events) -> user_event => {
-                match user_event?.event {
-                    Event::Resize(mut width, mut height) => {
-                        state.resize(width, height);
-                    }
-                    Event::Key(key_event) => {
-                        let key_combination = KeyCombination::from(key_event);
-                        debug!("key combination pressed: {}", key_combination);
-                        if !state.apply_key_combination(key_combination) {
-                            let action = keybindings.get(key_combination);
-                            if let Some(action) = action {
-                                actions.push(action.clone());
-                            }
+            recv(changed_lines_updates) -> update => {
+                if let Ok(update) = update {
+                    state.receive_changed_lines(update);
+                }
+            }
+            recv(test_list_updates) -> update => {
+                if let Ok(update) = update {
+                    state.receive_test_list(update);
+                }
+            }
+            recv(hook_outcomes) -> outcome => {
+                if let Ok(outcome) = outcome {
+                    task_ended = state.receive_hook_outcome(outcome);
+                }
+            }
+            recv(user_events) -> timed_event => {
+                user_event = Some(timed_event?.event);
+                from_event_source = true;
+            }
+            recv(replayed_events) -> event => {
+                match event {
+                    Ok(ReplayedEvent::Key(key)) => {
+                        user_event = Some(Event::Key(key.into()));
+                    }
+                    Ok(ReplayedEvent::Watch(paths)) => {
+                        if state.receive_watch_event(paths)
+                            && state.auto_refresh.is_enabled()
+                            && (!state.is_computing() || defer_events == DeferEvents::Restart)
+                            && rerun_allowed(&state, min_interval, &mut deferred_rerun)
+                        {
+                            actions.push(Action::Internal(Internal::ReRun));
                         }
                     }
-                    #[cfg(windows)]
-                    Event::Mouse(MouseEvent { kind: MouseEventKind::ScrollDown, .. }) => {
-                        let action = keybindings.get(key!(down));
+                    Ok(ReplayedEvent::Finished) => {
+                        state.messages.push(Message::short("End of the replayed session"));
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+        if task_ended {
+            if state.mission.settings.webhook.url.is_some() {
+                let duration = state.computation_start.elapsed();
+                state
+                    .mission
+                    .settings
+                    .webhook
+                    .post(&webhook_payload(&state, duration));
+            }
+            state.mission.settings.nvim.publish(&state);
+            state.mission.settings.github.publish_review(&state);
+            let (previous_success, success) = state.record_success();
+            if let Some(notification) = state.end_notification(success) {
+                let job = &state.mission.job;
+                if job
+                    .push
+                    .must_push(previous_success, success, state.computation_start.elapsed())
+                {
+                    job.push.push(notification.clone());
+                }
+                if job.notify_on().must_notify(previous_success, success) {
+                    // a failed notification must not end the mission
+                    if let Err(e) = notification.send(job.notify_with(), w) {
+                        warn!("notification failed: {e}");
+                    }
+                }
+            }
+            if let Some(dep_info) = &dep_info {
+                mission_watcher.watch_files(&dep_info.update());
+            }
+            if headless {
+                state
+                    .mission
+                    .settings
+                    .headless_output
+                    .print_result(w, &state)?;
+                state.update_pane_status(w)?;
+                w.flush()?;
+            }
+            if state.mission.settings.once {
+                mission_end = Some(DoAfterMission::Exit {
+                    success: state.is_success(),
+                });
+            } else {
+                if state.failed_tests_now_pass() {
+                    // back to the unscoped job, following actions are dropped
+                    info!("failing tests now pass, running the full suite");
+                    actions.push(Action::Job(JobRef::Previous));
+                }
+                if state.is_success() {
+                    if let Some(action) = &state.mission.job.on_success {
+                        actions.push(action.clone());
+                    }
+                }
+                if state.is_failure() {
+                    if let Some(action) = &state.mission.job.on_failure {
+                        actions.push(action.clone());
+                    }
+                }
+                if state.changes_since_last_job_start > 0
+                    && state.auto_refresh.is_enabled()
+                    && rerun_allowed(&state, min_interval, &mut deferred_rerun)
+                {
+                    // will be ignored if a on_success or on_failures ends the mission
+                    // or does a rerun already
+                    actions.push(Action::Internal(Internal::ReRun))
+                }
+            }
+        }
+        if let Some(user_event) = user_event {
+            match user_event {
+                Event::Resize(mut width, mut height) => {
+                    state.resize(width, height);
+                    w.invalidate();
+                }
+                Event::Key(key_event) => {
+                    let key_combination = KeyCombination::from(key_event);
+                    debug!("key combination pressed: {}", key_combination);
+                    let was_forwarding_stdin = state.is_forwarding_stdin();
+                    if !state.apply_key_combination(key_combination) {
+                        let action = keybindings.get(key_combination);
                         if let Some(action) = action {
                             actions.push(action.clone());
                         }
                     }
-                    #[cfg(windows)]
-                    Event::Mouse(MouseEvent { kind: MouseEventKind::ScrollUp, .. }) => {
-                        let action = keybindings.get(key!(up));
-                        if let Some(action) = action {
-                            actions.push(action.clone());
+                    // the keys typed for the command, which may be secrets, aren't
+                    // recorded (but the ones opening and closing the input are)
+                    if !(was_forwarding_stdin && state.is_forwarding_stdin()) {
+                        record_session_event(|| {
+                            Some(SessionEvent::Key {
+                                key: key_combination,
+                            })
+                        });
+                    }
+                    if let Some(line) = state.take_stdin_line() {
+                        if !state.is_computing() || !task_executor.send_input(&line) {
+                            state
+                                .messages
+                                .push(Message::short("No running command to send the line to"));
                         }
                     }
-                    _ => {}
+                    if let Some(job_ref) = state.take_picked_job() {
+                        info!("running picked tests: {job_ref:?}");
+                        actions.push(Action::Job(job_ref));
+                    }
+                    if let Some(package) = state.take_picked_package() {
+                        info!("scoping to package {package:?}");
+                        continuity.package = package;
+                        // the mission is rebuilt with the package
+                        actions.push(Action::Job(state.mission.concrete_job_ref.clone().into()));
+                    }
                 }
-                if let Some(event_source) = event_source {
-                    event_source.unblock(false);
+                #[cfg(windows)]
+                Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::ScrollDown,
+                    ..
+                }) => {
+                    let action = keybindings.get(key!(down));
+                    if let Some(action) = action {
+                        actions.push(action.clone());
+                    }
+                }
+                #[cfg(windows)]
+                Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::ScrollUp,
+                    ..
+                }) => {
+                    let action = keybindings.get(key!(up));
+                    if let Some(action) = action {
+                        actions.push(action.clone());
+                    }
                 }
+                _ => {}
             }
         }
         for action in actions.drain(..) {
@@ -277,6 +842,19 @@ fn run_mission(
                             mission_end = Some(DoAfterMission::NextJob(JobRef::PreviousOrQuit));
                         }
                     }
+                    Internal::ApplySuggestions => {
+                        if state.apply_suggestions() {
+                            task_executor.die();
+                            task_executor = state.start_computation(&mut executor)?;
+                            break; // drop following actions
+                        }
+                    }
+                    Internal::CaptureBaseline => {
+                        state.capture_baseline();
+                    }
+                    Internal::CopyReport(format) => {
+                        state.copy_report(format);
+                    }
                     Internal::CopyUnstyledOutput => {
                         state.copy_unstyled_output();
                     }
@@ -292,13 +870,25 @@ fn run_mission(
                     Internal::FocusGoto => {
                         state.focus_goto();
                     }
+                    Internal::Explain => {
+                        state.explain_top_item();
+                    }
                     Internal::Help => {
                         state.toggle_help();
                     }
                     Internal::NoOp => {}
+                    Internal::OpenInEditor => {
+                        state.open_top_item_in_editor(w);
+                    }
                     Internal::Pause => {
                         state.auto_refresh = AutoRefresh::Paused;
                     }
+                    Internal::PickPackage => {
+                        state.toggle_package_picker();
+                    }
+                    Internal::PickTests => {
+                        state.toggle_test_picker();
+                    }
                     Internal::PlaySound(play_sound_command) => {
                         if let Some(sound_player) = &sound_player {
                             sound_player.play(play_sound_command.clone());
@@ -311,6 +901,11 @@ fn run_mission(
                         break;
                     }
                     Internal::ReRun => {
+                        if let Some(scope) = state.failed_tests_first_scope() {
+                            info!("running failed tests first: {scope:#?}");
+                            mission_end = Some(JobRef::from(scope).into());
+                            break;
+                        }
                         task_executor.die();
                         task_executor = state.start_computation(&mut executor)?;
                         break; // drop following actions
@@ -321,9 +916,41 @@ fn run_mission(
                         task_executor = state.start_computation(&mut executor)?;
                         break; // drop following actions
                     }
-                    Internal::ReloadConfig => {
-                        mission_end = Some(DoAfterMission::ReloadConfig);
-                        break;
+                    Internal::ReloadConfig => match (reloaders.settings)() {
+                        Ok(settings) => {
+                            mission_end = Some(DoAfterMission::ReloadConfig(Box::new(settings)));
+                            break;
+                        }
+                        Err(e) => {
+                            // the current mission goes on, with its report
+                            state.messages.push(invalid_config_message(&e));
+                        }
+                    },
+                    Internal::SaveReport(path) => {
+                        state.save_report(&path);
+                    }
+                    Internal::NextProfile => {
+                        if !state.mission.settings.profiles.is_empty() {
+                            mission_end = Some(DoAfterMission::NextProfile);
+                            break;
+                        }
+                        state.messages.push(Message::short(
+                            "No profile defined (see `[profile.<name>]` in config)",
+                        ));
+                    }
+                    Internal::NextRoot | Internal::PreviousRoot => {
+                        if continuity.root_position.is_some() {
+                            let delta = if internal == Internal::NextRoot {
+                                1
+                            } else {
+                                -1
+                            };
+                            mission_end = Some(DoAfterMission::SwitchRoot(delta));
+                            break;
+                        }
+                        state
+                            .messages
+                            .push(Message::short("Only one project root (see `--root`)"));
                     }
                     Internal::ScopeToFailures => {
                         if let Some(scope) = state.failures_scope() {
@@ -334,6 +961,12 @@ fn run_mission(
                             warn!("no available failures scope");
                         }
                     }
+                    Internal::ShowBuildTimings => {
+                        state.toggle_build_timings();
+                    }
+                    Internal::ShowFlakyTests => {
+                        state.toggle_flaky_tests();
+                    }
                     Internal::Scroll(scroll_command) => {
                         state.apply_scroll_command(scroll_command);
                     }
@@ -343,6 +976,12 @@ fn run_mission(
                         task_executor = state.start_computation(&mut executor)?;
                         break; // drop following actions
                     }
+                    Internal::ToggleCapturedOutput => {
+                        state.toggle_captured_output();
+                    }
+                    Internal::ToggleStdinForwarding => {
+                        state.toggle_stdin_forwarding();
+                    }
                     Internal::TogglePause => match state.auto_refresh {
                         AutoRefresh::Enabled => {
                             state.auto_refresh = AutoRefresh::Paused;
@@ -357,12 +996,22 @@ fn run_mission(
                             state.auto_refresh = AutoRefresh::Enabled;
                         }
                     },
+                    Internal::ToggleDiffScope => {
+                        state.toggle_diff_scope();
+                    }
+                    Internal::ToggleFailedTestsFirst => {
+                        state.toggle_failed_tests_first();
+                        continuity.failed_tests_first = state.failed_tests_first;
+                    }
                     Internal::ToggleRawOutput => {
                         state.toggle_raw_output();
                     }
                     Internal::ToggleSummary => {
                         state.toggle_summary_mode();
                     }
+                    Internal::TogglePerfHud => {
+                        state.toggle_perf_hud();
+                    }
                     Internal::ToggleWrap => {
                         state.toggle_wrap_mode();
                     }
@@ -385,12 +1034,99 @@ fn run_mission(
                 }
             }
         }
+        // the event source is unblocked only after the actions, so that it
+        // doesn't read the keys typed in an editor running in the terminal
+        if from_event_source {
+            if let Some(event_source) = event_source {
+                event_source.unblock(false);
+            }
+        }
         if !headless {
-            state.draw(w)?;
+            let since_last_draw = last_draw.elapsed();
+            if throttled_draw && since_last_draw < MIN_OUTPUT_DRAW_INTERVAL {
+                if deferred_draw.is_none() {
+                    deferred_draw = Some(after(MIN_OUTPUT_DRAW_INTERVAL - since_last_draw));
+                }
+            } else {
+                state.draw(w)?;
+                last_draw = Instant::now();
+                deferred_draw = None;
+            }
         }
         if let Some(mission_end) = mission_end {
             task_executor.die();
+            if !headless {
+                state.save_ui_state();
+            }
+            continuity.test_history = std::mem::take(&mut state.test_history);
+            continuity.perf_hud = std::mem::take(&mut state.perf_hud);
+            continuity.report_history = std::mem::take(&mut state.report_history);
+            if matches!(
+                mission_end,
+                DoAfterMission::ReloadConfig(_)
+                    | DoAfterMission::NextProfile
+                    | DoAfterMission::ReloadContext(_)
+                    | DoAfterMission::SwitchRoot(_)
+            ) {
+                continuity.preserved = Some(state.preserve());
+            }
             return Ok(mission_end);
         }
     }
 }
+
+/// Tell whether an automatic re-run can start now. If the minimal interval
+/// between runs of the job isn't elapsed, schedule a deferred re-run instead.
+fn rerun_allowed(
+    state: &AppState,
+    min_interval: Period,
+    deferred_rerun: &mut Option<Receiver<Instant>>,
+) -> bool {
+    let Some(remaining) = min_interval
+        .duration
+        .checked_sub(state.computation_start.elapsed())
+    else {
+        return true;
+    };
+    if remaining.is_zero() {
+        return true;
+    }
+    if deferred_rerun.is_none() {
+        debug!("re-run deferred by {:?}", remaining);
+        *deferred_rerun = Some(after(remaining));
+    }
+    false
+}
+
+/// Compute the context again in a thread, as `cargo metadata` may be slow,
+/// and send it if the local packages changed (eg a crate was added to the
+/// workspace)
+fn check_members(
+    location: &Context,
+    args: &Args,
+) -> Receiver<Context> {
+    let local_manifests: Vec<PathBuf> = location
+        .local_manifests()
+        .into_iter()
+        .map(Path::to_path_buf)
+        .collect();
+    let args = args.clone();
+    let (sender, receiver) = bounded(1);
+    thread::spawn(move || {
+        let new_location = match Context::new(&args) {
+            Ok(new_location) => new_location,
+            Err(e) => {
+                // probably a Cargo.toml being edited
+                warn!("failed to reload context: {e}");
+                return;
+            }
+        };
+        if new_location.local_manifests() == local_manifests {
+            return;
+        }
+        info!("local packages changed");
+        // the mission may have ended in the meantime
+        let _ = sender.send(new_location);
+    });
+    receiver
+}
@@ -1,16 +1,31 @@
 use {
     crate::*,
     anyhow::Result,
-    crokey::KeyCombination,
+    crokey::{
+        KeyCombination,
+        key,
+    },
+    rustc_hash::FxHashSet,
     std::{
         io::Write,
+        path::PathBuf,
         process::ExitStatus,
-        time::Instant,
+        thread,
+        time::{
+            Duration,
+            Instant,
+        },
     },
     termimad::{
         Area,
         CompoundStyle,
+        InputField,
         MadSkin,
+        crossbeam::channel::{
+            self,
+            Receiver,
+            Sender,
+        },
         crossterm::{
             cursor,
             execute,
@@ -27,6 +42,20 @@ use {
     },
 };
 
+/// Minimal duration between two builds of the live report
+const LIVE_REPORT_PERIOD: Duration = Duration::from_millis(300);
+
+/// How long the end of an execution waits for the changed lines
+const CHANGED_LINES_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The lines changed since the diff base, computed in the background
+/// for an execution
+pub type ChangedLinesUpdate = (usize, Result<ChangedLines>);
+
+/// The tests of the job, listed in the background for the test picker
+/// (with the number of the listing)
+pub type TestListUpdate = (usize, Result<Vec<String>>);
+
 /// Currently rendered state of the TUI application
 pub struct AppState<'s> {
     /// the mission to run, with settings
@@ -38,6 +67,19 @@ pub struct AppState<'s> {
     wrapped_output: Option<WrappedCommandOutput>,
     /// result of a command, hopefully a report
     pub cmd_result: CommandResult,
+    /// whether the report is the one of an execution done in a previous
+    /// session, not yet replaced
+    report_from_previous_session: bool,
+    /// whether the report is the last one of the job kept in the
+    /// history, not yet replaced
+    report_from_history: bool,
+    /// the report of the lines of the running computation, displayed
+    /// instead of the previous one when the job has `live_report`
+    live_report: Option<Report>,
+    /// when the live report was last built
+    live_report_built: Instant,
+    /// whether lines were received since the live report was built
+    live_report_stale: bool,
     /// a report wrapped for the size of the console
     wrapped_report: Option<WrappedReport>,
     /// screen width
@@ -46,6 +88,14 @@ pub struct AppState<'s> {
     height: u16,
     /// whether a computation is in progress
     computing: bool,
+    /// the number of the current execution, to recognize the
+    /// outcomes of the hooks of the previous ones
+    execution: usize,
+    /// the result waiting for the report hooks, with the duration
+    /// of the execution
+    hooked_result: Option<(CommandResult, Duration)>,
+    hook_sender: Sender<HookOutcome>,
+    hook_receiver: Receiver<HookOutcome>,
     /// whether the user wants wrapped lines
     pub wrap: bool,
     /// the optional RUST_BACKTRACE env var to set
@@ -60,10 +110,18 @@ pub struct AppState<'s> {
     scroll: usize,
     /// item_idx of the item which was on top on last draw
     top_item_idx: usize,
+    /// the item to bring on top when the report is received, as
+    /// it was when bacon was last left
+    restored_top_item_idx: Option<usize>,
     /// the tool building the help line
     help_line: Option<HelpLine>,
     /// the help page displayed over the rest, if any
     help_page: Option<HelpPage>,
+    /// the explanation of an error code or lint, or the preview of
+    /// suggested changes, displayed over the report
+    explanation_page: Option<ExplanationPage>,
+    /// the suggested changes being previewed, waiting for confirmation
+    pending_patch: Option<SuggestionsPatch>,
     /// display the raw output instead of the report
     raw_output: bool,
     /// whether auto-refresh is enabled
@@ -72,10 +130,95 @@ pub struct AppState<'s> {
     pub changes_since_last_job_start: usize,
     /// whether to display the count of changes
     pub show_changes_count: bool,
+    /// paths of the file changes received since last job start
+    changed_paths: Vec<PathBuf>,
+    /// paths of the file changes which triggered the current (or last) execution
+    trigger_paths: Vec<PathBuf>,
+    /// whether to display the paths of the changes which triggered the execution
+    pub show_trigger_paths: bool,
+    /// when the current (or last) computation started
+    pub computation_start: Instant,
+    /// whether bacon runs without TUI
+    headless: bool,
+    /// whether all changes since last job start were received during a computation
+    changed_during_computation: bool,
+    /// detects the job re-triggering itself, if enabled
+    loop_detector: Option<LoopDetector>,
+    /// whether the report is scoped to the diagnostics on changed lines
+    diff_scope: bool,
+    /// the lines changed since the diff base, computed in the background
+    /// at the start of the executions when the report is diff scoped
+    changed_lines: Option<ChangedLines>,
+    /// whether the changed lines are being computed
+    computing_changed_lines: bool,
+    changed_lines_sender: Sender<ChangedLinesUpdate>,
+    changed_lines_receiver: Receiver<ChangedLinesUpdate>,
+    /// the report before scoping to the changed lines and removing
+    /// the baseline items, if it was filtered
+    unfiltered_report: Option<Report>,
+    /// number of items removed from the report by the diff scoping
+    diff_scoped_out: usize,
+    /// the diagnostics not to display, if a baseline was captured
+    baseline: Option<Baseline>,
+    /// number of items removed from the report because they're in the baseline
+    baseline_suppressed: usize,
+    /// whether runs are scoped to the tests which failed, until they pass
+    pub failed_tests_first: bool,
+    /// the outcomes of the tests over the runs
+    pub test_history: TestHistory,
+    /// the recent reports of the jobs of the session
+    pub report_history: ReportHistory,
+    /// when the crates of the current (or last) build started
+    build_timings: BuildTimings,
+    /// the timings of the runs, and whether to display them
+    pub perf_hud: PerfHud,
+    /// the job status shown in the terminal title or in tmux
+    pane_status: PaneStatus,
+    /// whether the last finished execution was a success
+    previous_success: Option<bool>,
+    /// the items whose captured test output is displayed
+    expanded_outputs: FxHashSet<usize>,
+    /// the list of tests to pick from, when open
+    test_picker: Option<TestPicker>,
+    /// the number of the listing of the tests running for the test
+    /// picker, if any
+    test_listing: Option<usize>,
+    test_listings: usize,
+    test_list_sender: Sender<TestListUpdate>,
+    test_list_receiver: Receiver<TestListUpdate>,
+    /// the scoped job made with the test picker, to run
+    picked_job: Option<JobRef>,
+    /// the list of the workspace members to pick from, when open
+    package_picker: Option<PackagePicker>,
+    /// the package just picked (None meaning all packages)
+    picked_package: Option<Option<String>>,
+    /// the input of the lines to send to the command, when stdin
+    /// forwarding is on
+    stdin_input: Option<InputField>,
+    /// the line typed in the stdin input, to send to the command
+    stdin_line: Option<String>,
     /// messages to display to the user for a short duration
     pub messages: Vec<Message>,
     /// the search state
     pub search: SearchState,
+    /// the index of the root and the number of roots, in a
+    /// multi-root session
+    pub root_position: Option<(usize, usize)>,
+}
+
+/// The part of the state kept when the mission of the same job is
+/// rebuilt, eg because the configuration or the workspace changed,
+/// so that the user doesn't lose the report nor the display modes
+pub struct PreservedState {
+    concrete_job_ref: ConcreteJobRef,
+    cmd_result: CommandResult,
+    /// whether the result is reversed
+    reverse: bool,
+    previous_success: Option<bool>,
+    backtrace: Option<&'static str>,
+    raw_output: bool,
+    auto_refresh: AutoRefresh,
+    scroll: usize,
 }
 
 impl<'s> AppState<'s> {
@@ -94,35 +237,92 @@ impl<'s> AppState<'s> {
         } else {
             termimad::terminal_size()
         };
+        let baseline = match Baseline::load(&mission.baseline_path(), &mission.baseline_job_key()) {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                warn!("failed to load baseline: {e}");
+                None
+            }
+        };
         let help_line = mission
             .settings
             .help_line
             .then(|| HelpLine::new(mission.settings));
+        let (hook_sender, hook_receiver) = channel::unbounded();
+        let (changed_lines_sender, changed_lines_receiver) = channel::unbounded();
+        let (test_list_sender, test_list_receiver) = channel::unbounded();
         Ok(Self {
             report_maker,
             output: None,
             wrapped_output: None,
             cmd_result: CommandResult::None,
+            report_from_previous_session: false,
+            report_from_history: false,
             wrapped_report: None,
             width,
             height,
             computing: true,
+            execution: 0,
+            hooked_result: None,
+            hook_sender,
+            hook_receiver,
             summary: mission.settings.summary,
             wrap: mission.settings.wrap,
             backtrace: None,
             reverse: mission.settings.reverse,
             show_changes_count: mission.job.show_changes_count(),
+            changed_paths: Vec::new(),
+            trigger_paths: Vec::new(),
+            show_trigger_paths: mission.job.show_trigger_paths(),
+            computation_start: Instant::now(),
+            headless,
+            live_report: None,
+            live_report_built: Instant::now(),
+            live_report_stale: false,
+            changed_during_computation: true,
+            loop_detector: mission.job.loop_detection().then(LoopDetector::default),
+            diff_scope: mission.job.diff_scope(),
+            changed_lines: None,
+            computing_changed_lines: false,
+            changed_lines_sender,
+            changed_lines_receiver,
+            unfiltered_report: None,
+            diff_scoped_out: 0,
+            baseline,
+            baseline_suppressed: 0,
+            failed_tests_first: false,
+            test_history: TestHistory::default(),
+            report_history: ReportHistory::default(),
+            build_timings: BuildTimings::default(),
+            perf_hud: PerfHud::default(),
+            pane_status: PaneStatus::default(),
+            previous_success: None,
+            expanded_outputs: FxHashSet::default(),
+            test_picker: None,
+            test_listing: None,
+            test_listings: 0,
+            test_list_sender,
+            test_list_receiver,
+            picked_job: None,
+            package_picker: None,
+            picked_package: None,
+            stdin_input: None,
+            stdin_line: None,
             status_skin,
             scroll: 0,
             top_item_idx: 0,
+            restored_top_item_idx: None,
             help_line,
             help_page: None,
+            explanation_page: None,
+            pending_patch: None,
             mission,
             raw_output: false,
             auto_refresh: AutoRefresh::Enabled,
             changes_since_last_job_start: 0,
             messages: Vec::new(),
             search: Default::default(),
+            root_position: None,
         })
     }
     pub fn focus_search(&mut self) {
@@ -135,12 +335,25 @@ impl<'s> AppState<'s> {
     }
     // Handle the "back" operation, return true if it did (thus consuming the action)
     pub fn back(&mut self) -> bool {
-        if self.search.focused() {
+        if self.test_picker.is_some() {
+            self.test_picker = None;
+            true
+        } else if self.test_listing.is_some() {
+            self.cancel_test_listing();
+            true
+        } else if self.package_picker.is_some() {
+            self.package_picker = None;
+            true
+        } else if self.search.focused() {
             self.search.unfocus_and_clear();
             true
         } else if self.help_page.is_some() {
             self.help_page = None;
             true
+        } else if self.explanation_page.is_some() {
+            self.explanation_page = None;
+            self.pending_patch = None;
+            true
         } else if self.search.input_has_content() {
             self.search.clear();
             true
@@ -149,25 +362,73 @@ impl<'s> AppState<'s> {
         }
     }
     pub fn copy_unstyled_output(&mut self) {
+        let mut content = String::new();
+        for line in self.lines_to_draw() {
+            content.push_str(&line.content.to_raw());
+            content.push('\n');
+        }
+        self.copy_to_clipboard(content, "Output");
+    }
+    /// Copy the whole current report, not just the displayed lines
+    pub fn copy_report(
+        &mut self,
+        format: ReportFormat,
+    ) {
+        let Some(report) = self.cmd_result.report() else {
+            self.messages.push(Message::short("No report to copy"));
+            return;
+        };
+        match format.render(report) {
+            Ok(content) => self.copy_to_clipboard(content, "Report"),
+            Err(e) => {
+                error!("Failed to render report: {e}");
+                self.messages.push(Message::short(format!("*{e}*")));
+            }
+        }
+    }
+    fn copy_to_clipboard(
+        &mut self,
+        content: String,
+        what: &str,
+    ) {
+        #[cfg(feature = "clipboard")]
+        let message = match arboard::Clipboard::new() {
+            Ok(mut clipboard) => {
+                let _ = clipboard.set_text(content);
+                format!("{what} copied to clipboard")
+            }
+            Err(e) => {
+                error!("Failed to copy {what} to clipboard: {}", e);
+                "Clipboard error - nothing copied".to_string()
+            }
+        };
+        #[cfg(not(feature = "clipboard"))]
         let message = {
-            #[cfg(feature = "clipboard")]
-            match arboard::Clipboard::new() {
-                Ok(mut clipboard) => {
-                    let mut content = String::new();
-                    for line in self.lines_to_draw() {
-                        content.push_str(&line.content.to_raw());
-                        content.push('\n');
-                    }
-                    let _ = clipboard.set_text(content);
-                    "Output copied to clipboard"
-                }
-                Err(e) => {
-                    error!("Failed to copy output to clipboard: {}", e);
-                    "Clipboard error - nothing copied"
-                }
+            let _ = (content, what);
+            "clipboard feature not enabled : nothing copied".to_string()
+        };
+        self.messages.push(Message::short(message));
+    }
+    /// Write the current report in a file, in the format given by its
+    /// extension (`.md` for markdown, `.json` for JSON, text otherwise)
+    pub fn save_report(
+        &mut self,
+        path: &str,
+    ) {
+        let Some(report) = self.cmd_result.report() else {
+            self.messages.push(Message::short("No report to save"));
+            return;
+        };
+        let path = self.mission.package_directory.join(path);
+        let res = ReportFormat::of_path(&path)
+            .render(report)
+            .and_then(|content| Ok(std::fs::write(&path, content)?));
+        let message = match res {
+            Ok(()) => format!("Report saved in `{}`", path.display()),
+            Err(e) => {
+                error!("Failed to save report in {path:?}: {e}");
+                format!("*Report not saved: {e}*")
             }
-            #[cfg(not(feature = "clipboard"))]
-            "clipboard feature not enabled : nothing copied"
         };
         self.messages.push(Message::short(message));
     }
@@ -198,6 +459,57 @@ impl<'s> AppState<'s> {
         &mut self,
         key: KeyCombination,
     ) -> bool {
+        if let Some(test_picker) = self.test_picker.as_mut() {
+            match test_picker.apply_key_combination(key) {
+                TestPickerEffect::None => {}
+                TestPickerEffect::NotConsumed => {
+                    return false;
+                }
+                TestPickerEffect::Cancel => {
+                    self.test_picker = None;
+                }
+                TestPickerEffect::Pick(scope) => {
+                    self.test_picker = None;
+                    self.picked_job = Some(if self.mission.is_test_job() {
+                        JobRef::from(scope)
+                    } else {
+                        // the tests were listed with cargo test
+                        ConcreteJobRef::from_job_name("test")
+                            .with_scope(scope)
+                            .into()
+                    });
+                }
+            }
+            return true;
+        }
+        if let Some(package_picker) = self.package_picker.as_mut() {
+            match package_picker.apply_key_combination(key) {
+                PackagePickerEffect::None => {}
+                PackagePickerEffect::NotConsumed => {
+                    return false;
+                }
+                PackagePickerEffect::Cancel => {
+                    self.package_picker = None;
+                }
+                PackagePickerEffect::Pick(package) => {
+                    self.package_picker = None;
+                    self.picked_package = Some(package);
+                }
+            }
+            return true;
+        }
+        if let Some(input) = self.stdin_input.as_mut() {
+            if key == key!(esc) {
+                self.stdin_input = None;
+                return true;
+            }
+            if key == key!(enter) {
+                self.stdin_line = Some(input.get_content());
+                input.clear();
+                return true;
+            }
+            return input.apply_key_combination(key);
+        }
         if self.search.apply_key_combination(key) {
             self.update_search();
             self.show_selected_found();
@@ -238,10 +550,40 @@ impl<'s> AppState<'s> {
         &mut self,
         line: CommandOutputLine,
     ) {
+        let start = Instant::now();
+        self.receive_line(line);
+        self.perf_hud.add_parsing(start.elapsed());
+    }
+    fn receive_line(
+        &mut self,
+        line: CommandOutputLine,
+    ) {
+        self.live_report_stale = true;
+        if line.origin == CommandStream::StdErr {
+            self.build_timings
+                .receive_line(&line.content.to_raw(), Instant::now());
+        }
+        if self.live_report.is_some() {
+            // only the live report is displayed, not the lines
+            if let Some(output) = self.output.as_mut() {
+                self.report_maker.receive_line(line, output);
+            }
+            self.update_live_report();
+            return;
+        }
         let auto_scroll = self.is_scroll_at_bottom();
         let line_count_before = self.lines_to_draw_unfiltered().len();
+        let mut shifted = false;
         if let Some(output) = self.output.as_mut() {
+            let dropped_before = output.dropped;
             self.report_maker.receive_line(line, output);
+            if output.dropped != dropped_before {
+                // the oldest lines were dropped, so the wrapping and the
+                // search must be done again
+                shifted = true;
+                self.wrapped_output = None;
+                self.search.touch();
+            }
             if self.wrap {
                 self.update_wrap(self.width - 1);
             }
@@ -259,12 +601,56 @@ impl<'s> AppState<'s> {
             self.scroll = 0;
             self.fix_scroll();
         }
-        self.update_search_from_line(line_count_before);
+        if !shifted {
+            self.update_search_from_line(line_count_before);
+        }
+        self.update_live_report();
+    }
+    /// Return how long to wait before updating the live report with
+    /// the lines received since it was built, if any
+    pub fn live_report_delay(&self) -> Option<Duration> {
+        (self.computing && self.live_report_stale && self.mission.job.live_report())
+            .then(|| LIVE_REPORT_PERIOD.saturating_sub(self.live_report_built.elapsed()))
+    }
+    /// Rebuild the live report from the lines received so far, if the
+    /// job wants one and the last one isn't too recent
+    pub fn update_live_report(&mut self) {
+        if !self
+            .live_report_delay()
+            .is_some_and(|delay| delay.is_zero())
+        {
+            return;
+        }
+        self.live_report_built = Instant::now();
+        self.live_report_stale = false;
+        let Some(mut report) = self.report_maker.build_partial_report() else {
+            return;
+        };
+        if report.stats.items() == 0 {
+            // nothing worth replacing the previous report yet
+            return;
+        }
+        if self.reverse {
+            report.reverse();
+        }
+        let first = self.live_report.is_none();
+        self.live_report = Some(report);
+        self.wrapped_report = None;
+        self.search.touch();
+        if first {
+            self.reset_scroll();
+        }
+        if self.wrap {
+            self.update_wrap(self.width - 1);
+        }
+        self.fix_scroll();
     }
     pub fn new_task(&self) -> Task {
         Task {
             backtrace: self.backtrace,
             grace_period: self.mission.job.grace_period(),
+            speculative: self.mission.job.speculative_start(),
+            changed_files: self.trigger_paths.clone(),
         }
     }
     pub fn take_output(&mut self) -> Option<CommandOutput> {
@@ -298,14 +684,123 @@ impl<'s> AppState<'s> {
         }
         self.search.touch();
     }
+    /// Build the result of the execution which just ended, and set it
+    /// unless it must first go through the report hooks.
+    ///
+    /// Return whether the task ended (false when waiting for the hooks)
     pub fn finish_task(
         &mut self,
         exit_status: Option<ExitStatus>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
+        self.build_timings.finish(Instant::now());
+        let duration = self.computation_start.elapsed();
+        let parsing_start = Instant::now();
         let output = self.take_output().unwrap_or_default();
         let result = self.report_maker.build_result(output, exit_status)?;
+        self.perf_hud.add_parsing(parsing_start.elapsed());
+        self.perf_hud.run_ends(duration);
+        if let CommandResult::Report(report) = &result {
+            let hooks = &self.mission.job.hooks;
+            let first_hook = if hooks.report.is_some() {
+                Some(HookKind::Report)
+            } else if hooks.success.is_some() {
+                Some(HookKind::Success)
+            } else {
+                None
+            };
+            if let Some(kind) = first_hook {
+                // the end of the task waits for the hooks, which run in the background
+                self.spawn_report_hook(kind, report);
+                self.hooked_result = Some((result, duration));
+                return Ok(false);
+            }
+        }
+        self.end_task(result, duration);
+        Ok(true)
+    }
+    /// The receiver of the outcomes of the report hooks
+    pub fn hook_outcomes(&self) -> Receiver<HookOutcome> {
+        self.hook_receiver.clone()
+    }
+    fn spawn_report_hook(
+        &self,
+        kind: HookKind,
+        report: &Report,
+    ) {
+        spawn_report_hook(
+            kind,
+            self.execution,
+            &self.mission,
+            report,
+            self.hook_sender.clone(),
+        );
+    }
+    /// Apply the outcome of a report hook, telling the user when the hook
+    /// failed, then either start the success hook or end the task.
+    ///
+    /// Return whether the task ended.
+    pub fn receive_hook_outcome(
+        &mut self,
+        outcome: HookOutcome,
+    ) -> bool {
+        if outcome.execution != self.execution {
+            debug!("ignoring the outcome of a hook of a previous execution");
+            return false;
+        }
+        let Some((mut result, duration)) = self.hooked_result.take() else {
+            return false;
+        };
+        let kind = outcome.kind;
+        if let CommandResult::Report(report) = &mut result {
+            if let Err(e) = outcome.apply(report) {
+                let name = match kind {
+                    HookKind::Report => "Report",
+                    HookKind::Success => "Success",
+                };
+                warn!("{name} hook failed: {e:#}");
+                self.messages
+                    .push(Message::long(format!("{name} hook failed: *{e:#}*")));
+            }
+            if kind == HookKind::Report && self.mission.job.hooks.success.is_some() {
+                self.spawn_report_hook(HookKind::Success, report);
+                self.hooked_result = Some((result, duration));
+                return false;
+            }
+        }
+        self.end_task(result, duration);
+        true
+    }
+    /// Set the result of the execution, once the report hooks are done
+    fn end_task(
+        &mut self,
+        mut result: CommandResult,
+        duration: Duration,
+    ) {
+        if let CommandResult::Report(report) = &mut result {
+            report.trigger_paths.clone_from(&self.trigger_paths);
+            // recorded once per execution, not when the report is displayed again
+            self.test_history
+                .record(&self.mission.concrete_job_ref, report);
+            if self.mission.settings.persist_reports {
+                persist_report(&self.mission, report);
+            }
+            self.report_history.record(
+                &self.mission.concrete_job_ref,
+                report,
+                self.mission.job.retained_reports(),
+                self.mission.job.retained_output_lines(),
+            );
+        }
+        self.report_from_previous_session = false;
+        self.report_from_history = false;
         self.set_result(result);
-        Ok(())
+        if self.mission.settings.run_log {
+            if let Some(entry) = RunLogEntry::of(self, duration) {
+                if let Err(e) = entry.append() {
+                    warn!("failed to append to the run log: {e}");
+                }
+            }
+        }
     }
     fn set_result(
         &mut self,
@@ -337,10 +832,29 @@ impl<'s> AppState<'s> {
             {
                 report.lines.pop();
             }
+            report.mark_flaky_tests(&self.mission.concrete_job_ref, &self.test_history);
+        }
+        self.unfiltered_report = None;
+        self.expanded_outputs.clear();
+        self.diff_scoped_out = 0;
+        self.baseline_suppressed = 0;
+        if self.diff_scope {
+            // they're computed since the start of the execution, so
+            // are usually already there
+            self.wait_changed_lines();
+        }
+        if self.diff_scope || self.baseline.is_some() {
+            if let CommandResult::Report(report) = cmd_result {
+                cmd_result = CommandResult::Report(Box::new(self.filter_report(*report)));
+            }
         }
 
         // we keep the scroll when the number of lines didn't change
-        let reset_scroll = self.cmd_result.lines_len() != cmd_result.lines_len();
+        let displayed_lines = match self.live_report.take() {
+            Some(live_report) => live_report.lines.len(),
+            None => self.cmd_result.lines_len(),
+        };
+        let reset_scroll = displayed_lines != cmd_result.lines_len();
         self.wrapped_report = None;
         self.wrapped_output = None;
         self.cmd_result = cmd_result;
@@ -348,6 +862,10 @@ impl<'s> AppState<'s> {
         if reset_scroll {
             self.reset_scroll();
         }
+        if let Some(item_idx) = self.restored_top_item_idx.take() {
+            self.top_item_idx = item_idx;
+            self.try_scroll_to_last_top_item();
+        }
         self.raw_output = false;
         if self.wrap {
             self.update_wrap(self.width - 1);
@@ -355,6 +873,187 @@ impl<'s> AppState<'s> {
 
         // we do all exports which are set to auto
         self.mission.settings.exports.do_auto_exports(self);
+        ControlServer::broadcast_status(self);
+        sd_notify_status(self);
+    }
+    /// Keep the unfiltered report and return a report without the items
+    /// which aren't on changed lines (when diff scoped) or are in the baseline
+    fn filter_report(
+        &mut self,
+        report: Report,
+    ) -> Report {
+        let mut filtered_report = report.clone();
+        if self.diff_scope {
+            if let Some(changed_lines) = &self.changed_lines {
+                self.diff_scoped_out =
+                    filtered_report.scope_to_changed_lines(changed_lines, &self.mission);
+            }
+        }
+        if let Some(baseline) = &self.baseline {
+            self.baseline_suppressed = baseline.suppress(&mut filtered_report, &self.mission);
+        }
+        self.unfiltered_report = Some(report);
+        filtered_report
+    }
+    /// Filter again the current report, after a change of the filters
+    /// or of the changed lines
+    fn refilter(&mut self) {
+        let report = match self.unfiltered_report.take() {
+            Some(report) => report,
+            None => match &self.cmd_result {
+                CommandResult::Report(report) => (**report).clone(),
+                _ => return,
+            },
+        };
+        self.diff_scoped_out = 0;
+        self.baseline_suppressed = 0;
+        let report = if self.diff_scope || self.baseline.is_some() {
+            self.filter_report(report)
+        } else {
+            report
+        };
+        let reset_scroll = report.lines.len() != self.cmd_result.lines_len();
+        self.cmd_result = CommandResult::Report(Box::new(report));
+        self.wrapped_report = None;
+        if reset_scroll {
+            self.reset_scroll();
+        }
+        if self.wrap {
+            self.update_wrap(self.width - 1);
+        }
+        self.search.touch();
+    }
+    /// Start computing, in the background, the lines changed since
+    /// the diff base
+    fn start_computing_changed_lines(&mut self) {
+        self.changed_lines = None;
+        self.computing_changed_lines = true;
+        let execution = self.execution;
+        let directory = self.mission.package_directory.clone();
+        let base = self.mission.job.diff_base().to_string();
+        let sender = self.changed_lines_sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send((execution, ChangedLines::compute(&directory, &base)));
+        });
+    }
+    /// The receiver of the changed lines computed in the background
+    pub fn changed_lines_updates(&self) -> Receiver<ChangedLinesUpdate> {
+        self.changed_lines_receiver.clone()
+    }
+    /// Keep the changed lines computed in the background, and filter the
+    /// report again if it's diff scoped
+    pub fn receive_changed_lines(
+        &mut self,
+        update: ChangedLinesUpdate,
+    ) {
+        if self.set_changed_lines(update) && self.diff_scope && !self.computing {
+            self.refilter();
+        }
+    }
+    /// Keep the changed lines, unless they were computed for a previous
+    /// execution, and return whether they were kept
+    fn set_changed_lines(
+        &mut self,
+        (execution, changed_lines): ChangedLinesUpdate,
+    ) -> bool {
+        if execution != self.execution {
+            return false;
+        }
+        self.computing_changed_lines = false;
+        match changed_lines {
+            Ok(changed_lines) => {
+                self.changed_lines = Some(changed_lines);
+            }
+            Err(e) => {
+                warn!("failed to compute changed lines: {e}");
+                self.messages
+                    .push(Message::short(format!("Diff scoping failed: *{e}*")));
+            }
+        }
+        true
+    }
+    /// Wait for the end of the computation of the changed lines, if any
+    fn wait_changed_lines(&mut self) {
+        while self.computing_changed_lines {
+            match self
+                .changed_lines_receiver
+                .recv_timeout(CHANGED_LINES_TIMEOUT)
+            {
+                Ok(update) => {
+                    self.set_changed_lines(update);
+                }
+                Err(_) => {
+                    warn!("changed lines not computed in {CHANGED_LINES_TIMEOUT:?}");
+                    self.computing_changed_lines = false;
+                }
+            }
+        }
+    }
+    /// Save the diagnostics of the current report as the baseline of the
+    /// job, so that only new diagnostics are displayed from now on
+    pub fn capture_baseline(&mut self) {
+        let Some(report) = self.unfiltered_report.as_ref().or(self.cmd_result.report()) else {
+            self.messages
+                .push(Message::short("No report to capture as baseline"));
+            return;
+        };
+        let baseline = Baseline::capture(report, &self.mission);
+        let path = self.mission.baseline_path();
+        if let Err(e) = baseline.save(&path, &self.mission.baseline_job_key()) {
+            warn!("failed to save baseline: {e}");
+            self.messages
+                .push(Message::short(format!("Baseline capture failed: *{e}*")));
+            return;
+        }
+        self.messages.push(Message::short(format!(
+            "Baseline of {} diagnostics saved in *{}*",
+            baseline.len(),
+            path.to_string_lossy(),
+        )));
+        self.baseline = Some(baseline);
+        self.refilter();
+    }
+    pub fn toggle_diff_scope(&mut self) {
+        self.diff_scope ^= true;
+        if self.diff_scope && self.changed_lines.is_none() && !self.computing_changed_lines {
+            // the report is filtered when they're received
+            self.start_computing_changed_lines();
+        }
+        self.refilter();
+        if self.diff_scope {
+            let base = self.mission.job.diff_base();
+            self.messages.push(Message::short(format!(
+                "Only diagnostics on lines changed since *{base}* are displayed"
+            )));
+        }
+    }
+    pub fn toggle_failed_tests_first(&mut self) {
+        self.failed_tests_first ^= true;
+        self.messages
+            .push(Message::short(if self.failed_tests_first {
+                "Next runs will be scoped to the failing tests until they pass"
+            } else {
+                "Failed tests first mode disabled"
+            }));
+    }
+    /// In failed tests first mode, when the last (unscoped) run had test
+    /// failures, return the scope the next run should have
+    pub fn failed_tests_first_scope(&self) -> Option<Scope> {
+        if !self.failed_tests_first || self.mission.concrete_job_ref.scope.has_tests() {
+            return None;
+        }
+        self.failures_scope()
+    }
+    /// In failed tests first mode, tell whether the run, scoped to the
+    /// previously failing tests, passed so that the full suite should run
+    pub fn failed_tests_now_pass(&self) -> bool {
+        self.failed_tests_first
+            && self.mission.concrete_job_ref.scope.has_tests()
+            && self.cmd_result.report().is_some_and(|report| {
+                report.stats.errors == 0
+                    && report.stats.test_fails == 0
+                    && report.stats.passed_tests > 0
+            })
     }
     pub fn is_computing(&self) -> bool {
         self.computing
@@ -365,6 +1064,135 @@ impl<'s> AppState<'s> {
         self.cmd_result = CommandResult::None;
         self.search.touch();
     }
+    /// Take what should survive the rebuild of the mission
+    pub fn preserve(&mut self) -> PreservedState {
+        PreservedState {
+            concrete_job_ref: self.mission.concrete_job_ref.clone(),
+            cmd_result: std::mem::replace(&mut self.cmd_result, CommandResult::None),
+            reverse: self.reverse,
+            previous_success: self.previous_success,
+            backtrace: self.backtrace,
+            raw_output: self.raw_output,
+            auto_refresh: self.auto_refresh,
+            scroll: self.scroll,
+        }
+    }
+    /// Restore the state preserved from the previous mission, if it was
+    /// the same job (the display modes coming from the settings aren't
+    /// restored, as they may have been changed in the configuration)
+    pub fn restore(
+        &mut self,
+        preserved: PreservedState,
+    ) {
+        if preserved.concrete_job_ref != self.mission.concrete_job_ref {
+            return;
+        }
+        let mut cmd_result = preserved.cmd_result;
+        if preserved.reverse != self.reverse {
+            cmd_result.reverse();
+        }
+        self.cmd_result = cmd_result;
+        self.previous_success = preserved.previous_success;
+        self.backtrace = preserved.backtrace;
+        self.raw_output = preserved.raw_output;
+        self.auto_refresh = preserved.auto_refresh;
+        self.scroll = preserved.scroll;
+        self.wrapped_report = None;
+        self.search.touch();
+    }
+    /// Display the last report of the job kept in the history, if any,
+    /// until the current execution finishes
+    pub fn restore_retained_report(&mut self) {
+        if !matches!(self.cmd_result, CommandResult::None) {
+            return;
+        }
+        let Some(report) = self.report_history.last(&self.mission.concrete_job_ref) else {
+            return;
+        };
+        let mut cmd_result = CommandResult::Report(Box::new(report.clone()));
+        if self.reverse {
+            cmd_result.reverse();
+        }
+        self.cmd_result = cmd_result;
+        self.report_from_history = true;
+        self.wrapped_report = None;
+        self.search.touch();
+    }
+    /// Display the last report of the job, persisted by a previous
+    /// session, until the current execution finishes
+    pub fn restore_persisted_report(&mut self) {
+        if !self.mission.settings.persist_reports || !matches!(self.cmd_result, CommandResult::None)
+        {
+            return;
+        }
+        let Some(report) = load_persisted_report(&self.mission) else {
+            return;
+        };
+        let mut cmd_result = CommandResult::Report(Box::new(report));
+        if self.reverse {
+            cmd_result.reverse();
+        }
+        self.cmd_result = cmd_result;
+        self.report_from_previous_session = true;
+        self.wrapped_report = None;
+        self.search.touch();
+    }
+    /// The part of the UI state which is kept between launches
+    fn job_ui_state(&self) -> JobUiState {
+        JobUiState {
+            top_item_idx: self
+                .lines_to_draw()
+                .nth(self.scroll)
+                .map(|line| line.item_idx),
+            summary_toggle: ToggledSetting::new(self.summary, self.mission.settings.summary),
+            diff_scope_toggle: ToggledSetting::new(self.diff_scope, self.mission.job.diff_scope()),
+            search: self.search.pattern(),
+        }
+    }
+    /// Save the job and its UI state, so that they're restored
+    /// on next launch
+    pub fn save_ui_state(&self) {
+        if !self.mission.settings.persist_ui_state {
+            return;
+        }
+        let package_directory = &self.mission.package_directory;
+        let mut ui_state = UiState::load(package_directory);
+        ui_state.set_last_job(
+            self.mission.concrete_job_ref.clone(),
+            &self.mission.settings.default_job,
+        );
+        ui_state
+            .jobs
+            .insert(self.mission.baseline_job_key(), self.job_ui_state());
+        if let Err(e) = ui_state.save(package_directory) {
+            warn!("failed to save UI state: {e}");
+        }
+    }
+    /// Restore the UI state of the job as it was when bacon was last left
+    pub fn restore_ui_state(&mut self) {
+        if !self.mission.settings.persist_ui_state {
+            return;
+        }
+        let mut ui_state = UiState::load(&self.mission.package_directory);
+        let Some(job_ui_state) = ui_state.jobs.remove(&self.mission.baseline_job_key()) else {
+            return;
+        };
+        // the settings the user didn't toggle are the configured ones
+        self.summary =
+            ToggledSetting::resolve(job_ui_state.summary_toggle, self.mission.settings.summary);
+        self.diff_scope = ToggledSetting::resolve(
+            job_ui_state.diff_scope_toggle,
+            self.mission.job.diff_scope(),
+        );
+        if let Some(pattern) = &job_ui_state.search {
+            self.search.set_pattern(pattern);
+        }
+        if let Some(item_idx) = job_ui_state.top_item_idx {
+            self.top_item_idx = item_idx;
+            self.try_scroll_to_last_top_item();
+            self.restored_top_item_idx = Some(item_idx);
+        }
+    }
     /// Start a new task on the current mission
     pub fn start_computation(
         &mut self,
@@ -380,15 +1208,123 @@ impl<'s> AppState<'s> {
             self.clear();
         }
         self.report_maker.start(&self.mission);
+        self.live_report = None;
+        self.live_report_built = Instant::now();
+        self.live_report_stale = false;
         self.computing = true;
+        self.execution += 1;
+        self.hooked_result = None;
+        if self.diff_scope {
+            self.start_computing_changed_lines();
+        } else {
+            self.changed_lines = None;
+            self.computing_changed_lines = false;
+        }
+        self.computation_start = Instant::now();
+        let debounce = if self.mission.job.speculative_start() {
+            Duration::ZERO.into()
+        } else {
+            self.mission.job.grace_period()
+        };
+        self.perf_hud.run_starts(debounce);
+        self.build_timings = BuildTimings::new(self.computation_start);
         self.changes_since_last_job_start = 0;
+        self.trigger_paths = std::mem::take(&mut self.changed_paths);
+        if let Some(loop_detector) = self.loop_detector.as_mut() {
+            let loop_paths = loop_detector
+                .execution_starts(&self.trigger_paths, self.changed_during_computation);
+            if let Some(loop_paths) = loop_paths {
+                warn!("job loop detected on {:?}", &loop_paths);
+                let names: Vec<String> = loop_paths
+                    .iter()
+                    .map(|p| format!("*{}*", self.mission.make_relative(p).to_string_lossy()))
+                    .collect();
+                self.messages.push(Message::long(format!(
+                    "Loop detected: the job modifies {} - changes ignored for {}s",
+                    names.join(", "),
+                    LOOP_MUTE_DURATION.as_secs(),
+                )));
+            }
+        }
+        self.changed_during_computation = true;
         self.search.touch();
+        self.mission.settings.exports.do_start_exports(self);
+        ControlServer::broadcast_status(self);
+        sd_notify_status(self);
+        if self.headless {
+            self.mission.settings.headless_output.print_start(self);
+        }
     }
     pub fn computation_stops(&mut self) {
         self.computing = false;
     }
-    pub fn receive_watch_event(&mut self) {
+    /// Register a file change, unless only files muted by the loop
+    /// detector changed.
+    ///
+    /// Return whether the change was registered
+    pub fn receive_watch_event(
+        &mut self,
+        paths: Vec<PathBuf>,
+    ) -> bool {
+        let paths = match self.loop_detector.as_mut() {
+            Some(loop_detector) if !paths.is_empty() => {
+                let paths = loop_detector.filter(paths);
+                if paths.is_empty() {
+                    debug!("ignoring change of muted paths");
+                    return false;
+                }
+                paths
+            }
+            _ => paths,
+        };
         self.changes_since_last_job_start += 1;
+        self.perf_hud.change_received();
+        if !self.computing {
+            self.changed_during_computation = false;
+        }
+        for path in paths {
+            if !self.changed_paths.contains(&path) {
+                self.changed_paths.push(path);
+            }
+        }
+        true
+    }
+    /// Handle a change received during the grace period of a speculative
+    /// execution, and return whether it's to be restarted, in which case
+    /// the paths which triggered it trigger the next one too
+    pub fn receive_speculative_watch_event(
+        &mut self,
+        paths: Vec<PathBuf>,
+    ) -> bool {
+        if !self.receive_watch_event(paths) {
+            return false;
+        }
+        for path in std::mem::take(&mut self.trigger_paths) {
+            if !self.changed_paths.contains(&path) {
+                self.changed_paths.push(path);
+            }
+        }
+        true
+    }
+    /// Return a short description of the file changes which triggered
+    /// the current execution, eg "triggered by: src/lib.rs, Cargo.toml (+3)"
+    pub fn trigger_summary(&self) -> Option<String> {
+        const MAX_NAMED: usize = 2;
+        if self.trigger_paths.is_empty() {
+            return None;
+        }
+        let mut summary = "triggered by: ".to_string();
+        for (i, path) in self.trigger_paths.iter().take(MAX_NAMED).enumerate() {
+            if i > 0 {
+                summary.push_str(", ");
+            }
+            let path = self.mission.make_relative(path);
+            summary.push_str(&path.to_string_lossy());
+        }
+        if self.trigger_paths.len() > MAX_NAMED {
+            summary.push_str(&format!(" (+{})", self.trigger_paths.len() - MAX_NAMED));
+        }
+        Some(summary)
     }
     fn scroll_to_top(&mut self) {
         self.scroll = 0;
@@ -463,6 +1399,244 @@ impl<'s> AppState<'s> {
             Some(_) => None,
             None => Some(HelpPage::new(self.mission.settings)),
         };
+        self.explanation_page = None;
+        self.pending_patch = None;
+    }
+    /// Return the report and the index in its lines of the title of
+    /// the item on top of the screen
+    fn top_item_title(&self) -> Option<(&Report, usize)> {
+        let top_item_idx = self.lines_to_draw().nth(self.scroll)?.item_idx;
+        let report = self.report_to_draw()?;
+        let title_idx = report.lines.iter().position(|line| {
+            line.item_idx == top_item_idx && matches!(line.line_type, LineType::Title(_))
+        })?;
+        Some((report, title_idx))
+    }
+    /// Start listing the tests of the job, in the background, to open the
+    /// test picker, or close it
+    pub fn toggle_test_picker(&mut self) {
+        if self.test_picker.take().is_some() {
+            return;
+        }
+        if self.test_listing.is_some() {
+            self.cancel_test_listing();
+            return;
+        }
+        self.test_listings += 1;
+        let listing_number = self.test_listings;
+        self.test_listing = Some(listing_number);
+        let listing = TestListing::new(&self.mission);
+        let sender = self.test_list_sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send((listing_number, listing.run()));
+        });
+        self.messages.push(Message::short("Listing the tests..."));
+    }
+    /// Forget the listing of the tests, whose result will be ignored
+    fn cancel_test_listing(&mut self) {
+        self.test_listing = None;
+        self.messages.push(Message::short("Test listing cancelled"));
+    }
+    pub fn test_list_updates(&self) -> Receiver<TestListUpdate> {
+        self.test_list_receiver.clone()
+    }
+    /// Open the test picker with the listed tests, unless the
+    /// listing was cancelled
+    pub fn receive_test_list(
+        &mut self,
+        (listing_number, tests): TestListUpdate,
+    ) {
+        if self.test_listing != Some(listing_number) {
+            return;
+        }
+        self.test_listing = None;
+        match tests.and_then(TestPicker::new) {
+            Ok(test_picker) => {
+                self.help_page = None;
+                self.explanation_page = None;
+                self.package_picker = None;
+                self.test_picker = Some(test_picker);
+            }
+            Err(e) => {
+                self.messages.push(Message::short(format!("*{e}*")));
+            }
+        }
+    }
+    /// Open the list of the workspace members to pick the one the
+    /// cargo jobs are scoped to, or close it
+    pub fn toggle_package_picker(&mut self) {
+        if self.package_picker.take().is_some() {
+            return;
+        }
+        match PackagePicker::new(&self.mission) {
+            Ok(package_picker) => {
+                self.help_page = None;
+                self.explanation_page = None;
+                self.test_picker = None;
+                self.package_picker = Some(package_picker);
+            }
+            Err(e) => {
+                self.messages.push(Message::short(format!("*{e}*")));
+            }
+        }
+    }
+    /// Take the package picked with the package picker, if one was just
+    /// picked (None meaning the jobs aren't scoped to a package anymore)
+    pub fn take_picked_package(&mut self) -> Option<Option<String>> {
+        self.picked_package.take()
+    }
+    /// Open the list of the tests whose results changed without
+    /// code change, or close it
+    pub fn toggle_flaky_tests(&mut self) {
+        self.pending_patch = None;
+        if self.explanation_page.take().is_some() {
+            return;
+        }
+        self.help_page = None;
+        self.explanation_page = Some(ExplanationPage::from_markdown(
+            self.test_history
+                .flaky_tests_markdown(&self.mission.concrete_job_ref),
+        ));
+    }
+    /// Open the timings of the crates of the last build, or close them
+    pub fn toggle_build_timings(&mut self) {
+        self.pending_patch = None;
+        if self.explanation_page.take().is_some() {
+            return;
+        }
+        self.help_page = None;
+        self.explanation_page = Some(ExplanationPage::from_markdown(
+            self.build_timings.markdown(),
+        ));
+    }
+    pub fn toggle_perf_hud(&mut self) {
+        self.perf_hud.visible ^= true;
+    }
+    /// Estimate the memory used by the reports and outputs kept by
+    /// the state
+    fn retained_reports_size(&self) -> usize {
+        let mut size = 0;
+        match &self.cmd_result {
+            CommandResult::Report(report) => size += report_size(report),
+            CommandResult::Failure(failure) => size += lines_size(&failure.output.lines),
+            CommandResult::None => {}
+        }
+        size += self.unfiltered_report.as_ref().map_or(0, report_size);
+        size += self.live_report.as_ref().map_or(0, report_size);
+        size += self
+            .output
+            .as_ref()
+            .map_or(0, |output| lines_size(&output.lines));
+        size
+    }
+    /// Take the scoped job built with the test picker, if tests were just picked
+    pub fn take_picked_job(&mut self) -> Option<JobRef> {
+        self.picked_job.take()
+    }
+    /// Display the explanation of the error code or of the clippy
+    /// lint of the item on top of the screen
+    pub fn explain_top_item(&mut self) {
+        self.pending_patch = None;
+        if self.explanation_page.take().is_some() {
+            return;
+        }
+        let code = self
+            .top_item_title()
+            .and_then(|(report, title_idx)| report.item_code(title_idx));
+        let Some(code) = code else {
+            self.messages
+                .push(Message::short("No error code or lint to explain"));
+            return;
+        };
+        match ExplanationPage::new(&code) {
+            Ok(page) => {
+                self.help_page = None;
+                self.explanation_page = Some(page);
+            }
+            Err(e) => {
+                self.messages.push(Message::short(format!("*{e}*")));
+            }
+        }
+    }
+    /// Open the first location of the item on top of the screen in
+    /// the editor of the settings
+    pub fn open_top_item_in_editor(
+        &mut self,
+        w: &mut W,
+    ) {
+        let location = self.top_item_title().and_then(|(report, title_idx)| {
+            let item_idx = report.lines[title_idx].item_idx;
+            report.lines[title_idx..]
+                .iter()
+                .take_while(|line| line.item_idx == item_idx)
+                .find_map(|line| line.location())
+        });
+        let Some(location) = location else {
+            self.messages.push(Message::short("No location to open"));
+            return;
+        };
+        let location = EditorLocation::from_report_location(location, &self.mission);
+        let editor = &self.mission.settings.editor;
+        match editor.open(&location, &self.mission.execution_directory, w) {
+            Ok(command) => {
+                info!("editor launched: {command}");
+                self.messages
+                    .push(Message::short(format!("Opened with `{command}`")));
+            }
+            Err(e) => {
+                self.messages.push(Message::short(format!("*{e}*")));
+            }
+        }
+    }
+    /// Preview the machine-applicable suggestions of the item on top of
+    /// the screen, or apply them when they're already previewed.
+    ///
+    /// Return true when files were changed
+    pub fn apply_suggestions(&mut self) -> bool {
+        if let Some(patch) = self.pending_patch.take() {
+            self.explanation_page = None;
+            if let Err(e) = patch.apply() {
+                self.messages.push(Message::short(format!(
+                    "Failed to apply suggestions: *{e}*"
+                )));
+                return false;
+            }
+            self.messages.push(Message::short("Suggestions applied"));
+            return true;
+        }
+        let suggestions = self.top_item_title().and_then(|(report, title_idx)| {
+            let item_idx = report.lines[title_idx].item_idx;
+            report.lines[title_idx..]
+                .iter()
+                .take_while(|line| line.item_idx == item_idx)
+                .find_map(|line| line.location())
+                .and_then(|location| report.suggestions.get(location))
+        });
+        let Some(suggestions) = suggestions else {
+            self.messages.push(Message::short(
+                "No machine-applicable suggestion for this item (they come with the *cargo_json* analyzer)",
+            ));
+            return false;
+        };
+        let patch = SuggestionsPatch::new(suggestions, &self.mission);
+        let preview = match patch.preview() {
+            Ok(preview) => preview,
+            Err(e) => {
+                self.messages
+                    .push(Message::short(format!("Invalid suggestions: *{e}*")));
+                return false;
+            }
+        };
+        let messages: Vec<&str> = suggestions.iter().map(|s| s.message.as_str()).collect();
+        let markdown = format!(
+            "# Suggested changes\n\n{}\n{}\n\nRun *apply-suggestions* again to apply those changes, or *back* to cancel.",
+            messages.join("\n\n"),
+            preview,
+        );
+        self.help_page = None;
+        self.explanation_page = Some(ExplanationPage::from_markdown(markdown));
+        self.pending_patch = Some(patch);
+        false
     }
     pub fn toggle_summary_mode(&mut self) {
         self.summary ^= true;
@@ -471,6 +1645,56 @@ impl<'s> AppState<'s> {
         self.update_search();
         self.show_selected_found();
     }
+    /// Start or stop forwarding the typed lines to the command's stdin
+    pub fn toggle_stdin_forwarding(&mut self) {
+        if self.stdin_input.take().is_some() {
+            return;
+        }
+        if !self.mission.job.stdin_forwarding() {
+            self.messages.push(Message::short(
+                "Stdin forwarding needs `stdin_forwarding = true` in the job",
+            ));
+            return;
+        }
+        let mut input = InputField::default();
+        input.set_focus(true);
+        self.stdin_input = Some(input);
+    }
+    /// Whether the typed keys go to the input of the line for the
+    /// command's stdin
+    pub fn is_forwarding_stdin(&self) -> bool {
+        self.stdin_input.is_some()
+    }
+    /// Take the line typed for the command's stdin, if any
+    pub fn take_stdin_line(&mut self) -> Option<String> {
+        self.stdin_line.take()
+    }
+    /// Show or hide the captured output of the failed test on top
+    /// of the screen
+    pub fn toggle_captured_output(&mut self) {
+        let Some(item_idx) = self
+            .lines_to_draw()
+            .nth(self.scroll)
+            .map(|line| line.item_idx)
+        else {
+            return;
+        };
+        let has_captured_output = self
+            .lines_to_draw_unfiltered()
+            .iter()
+            .any(|line| line.item_idx == item_idx && line.line_type == LineType::Captured);
+        if !has_captured_output {
+            self.messages
+                .push(Message::short("No captured output for the item on top"));
+            return;
+        }
+        if !self.expanded_outputs.remove(&item_idx) {
+            self.expanded_outputs.insert(item_idx);
+        }
+        self.search.touch();
+        self.update_search();
+        self.fix_scroll();
+    }
     pub fn toggle_backtrace(
         &mut self,
         level: &'static str,
@@ -522,8 +1746,11 @@ impl<'s> AppState<'s> {
         &mut self,
         cmd: ScrollCommand,
     ) {
+        self.restored_top_item_idx = None;
         if let Some(help_page) = self.help_page.as_mut() {
             help_page.apply_scroll_command(cmd);
+        } else if let Some(explanation_page) = self.explanation_page.as_mut() {
+            explanation_page.apply_scroll_command(cmd);
         } else {
             debug!("content_height: {}", self.content_height());
             debug!("page_height: {}", self.page_height());
@@ -537,6 +1764,15 @@ impl<'s> AppState<'s> {
         y: u16,
     ) -> Result<()> {
         let mut help_start = 0;
+        // Stdin input
+        if let Some(input) = self.stdin_input.as_mut() {
+            let input_width = (self.width / 2).max(9);
+            goto_line(w, y)?;
+            draw(w, CSI_FOUND, ">")?;
+            input.change_area(1, y, input_width - 1);
+            input.display_on(w)?;
+            help_start += input_width;
+        }
         // Search input
         if self.search.must_be_drawn() {
             let search_width = (self.width / 4).clamp(9, 27);
@@ -564,23 +1800,18 @@ impl<'s> AppState<'s> {
 
     pub fn job_badges(&self) -> Vec<TString> {
         let mut badges = Vec::new();
-        let project_name = &self.mission.location_name;
-        badges.push(TString::badge(project_name, 255, 240));
-        let job_label = self.mission.concrete_job_ref.badge_label();
+        let project_name = match self.root_position {
+            Some((idx, count)) => format!("{} {}/{}", self.mission.location_name, idx + 1, count),
+            None => self.mission.location_name.clone(),
+        };
+        badges.push(TString::badge(&project_name, 255, 240));
+        let mut job_label = self.mission.concrete_job_ref.badge_label();
+        if let Some(package) = &self.mission.package {
+            job_label = format!("{job_label} -p {package}");
+        }
         badges.push(TString::badge(&job_label, 235, 204));
         if let CommandResult::Report(report) = &self.cmd_result {
-            let stats = &report.stats;
-            if stats.errors > 0 {
-                badges.push(TString::num_badge(stats.errors, "error", 235, 9));
-            }
-            if stats.test_fails > 0 {
-                badges.push(TString::num_badge(stats.test_fails, "fail", 235, 208));
-            } else if stats.passed_tests > 0 {
-                badges.push(TString::badge("pass!", 254, 2));
-            }
-            if stats.warnings > 0 {
-                badges.push(TString::num_badge(stats.warnings, "warning", 235, 11));
-            }
+            badges.extend(report.stats.badges());
         } else if let CommandResult::Failure(failure) = &self.cmd_result {
             badges.push(TString::badge(
                 &format!("Command error code: {}", failure.error_code),
@@ -591,6 +1822,21 @@ impl<'s> AppState<'s> {
         badges
     }
 
+    /// How the analyzer was selected, when it's not set in the job and
+    /// the command isn't a cargo one, for which the standard analyzer
+    /// is the obvious choice
+    fn analyzer_selection_label(&self) -> Option<String> {
+        let job = &self.mission.job;
+        if job.analyzer.is_some() || job.uses_json_messages() {
+            return None;
+        }
+        match job.detected_analyzer() {
+            Some(AnalyzerRef::Standard) => None,
+            Some(analyzer) => Some(format!("{} analyzer", analyzer.name())),
+            None => Some("standard analyzer (default)".to_string()),
+        }
+    }
+
     /// draw the line of colored badges, usually on top
     pub fn draw_badges(
         &mut self,
@@ -602,6 +1848,12 @@ impl<'s> AppState<'s> {
         for badge in self.job_badges() {
             t_line.add_badge(badge);
         }
+        if let Some(profile) = &self.mission.settings.profile {
+            t_line.add_badge(TString::badge(&format!("{profile} profile"), 235, 183));
+        }
+        if let Some(label) = self.analyzer_selection_label() {
+            t_line.add_badge(TString::badge(&label, 235, 250));
+        }
         if self.show_changes_count {
             t_line.add_badge(TString::num_badge(
                 self.changes_since_last_job_start,
@@ -610,6 +1862,25 @@ impl<'s> AppState<'s> {
                 6,
             ));
         }
+        if self.diff_scope {
+            let mut label = format!("diff {}", self.mission.job.diff_base());
+            if self.diff_scoped_out > 0 {
+                label.push_str(&format!(" ({} hidden)", self.diff_scoped_out));
+            }
+            t_line.add_badge(TString::badge(&label, 235, 153));
+        }
+        if self.baseline_suppressed > 0 {
+            let label = format!("{} suppressed", self.baseline_suppressed);
+            t_line.add_badge(TString::badge(&label, 235, 250));
+        }
+        if self.failed_tests_first {
+            t_line.add_badge(TString::badge("failed first", 235, 216));
+        }
+        if self.show_trigger_paths {
+            if let Some(summary) = self.trigger_summary() {
+                t_line.add_badge(TString::new(CSI_ITALIC, summary));
+            }
+        }
         self.search.add_summary_tstring(&mut t_line);
         let width = self.width as usize;
         let cols = t_line.draw_in(w, width)?;
@@ -624,11 +1895,31 @@ impl<'s> AppState<'s> {
     ) -> Result<()> {
         goto_line(w, y)?;
         let width = self.width as usize;
-        if self.computing {
+        if let Some(report) = self.live_report.as_ref() {
+            let items = report.stats.items();
+            let running = format!(
+                "running... {} item{} so far",
+                items,
+                if items > 1 { "s" } else { "" },
+            );
             write!(
                 w,
                 "\u{1b}[38;5;235m\u{1b}[48;5;204m{:^w$}\u{1b}[0m",
-                "computing...",
+                running,
+                w = width
+            )?;
+        } else if self.computing {
+            let computing = if self.report_from_previous_session {
+                "computing... (the report is the last one of a previous session)"
+            } else if self.report_from_history {
+                "computing... (the report is the one of the previous run)"
+            } else {
+                "computing..."
+            };
+            write!(
+                w,
+                "\u{1b}[38;5;235m\u{1b}[48;5;204m{:^w$}\u{1b}[0m",
+                computing,
                 w = width
             )?;
         } else {
@@ -669,6 +1960,53 @@ impl<'s> AppState<'s> {
             _ => false,
         }
     }
+    /// Record the success of the execution which just finished, and
+    /// return the success of the previous one, if any, with this one
+    pub fn record_success(&mut self) -> (Option<bool>, bool) {
+        let success = self.is_success();
+        let previous_success = self.previous_success.replace(success);
+        (previous_success, success)
+    }
+    /// Build the notification describing the execution which just
+    /// finished, if there's a result
+    pub fn end_notification(
+        &self,
+        success: bool,
+    ) -> Option<Notification> {
+        let body = match &self.cmd_result {
+            CommandResult::Report(report) => report.stats.summary(),
+            CommandResult::Failure(failure) => {
+                format!("command error code: {}", failure.error_code)
+            }
+            CommandResult::None => {
+                return None;
+            }
+        };
+        Some(Notification {
+            title: format!(
+                "{} {}: {}",
+                self.mission.location_name,
+                self.mission.concrete_job_ref.badge_label(),
+                if success { "success" } else { "failure" },
+            ),
+            body,
+        })
+    }
+    /// Update the terminal title or the tmux option, when the job
+    /// settings ask for it
+    pub fn update_pane_status(
+        &mut self,
+        w: &mut W,
+    ) -> Result<()> {
+        let job = &self.mission.job;
+        if !job.pane_title() && job.tmux_option().is_none() {
+            return Ok(());
+        }
+        if let Some(status) = JobStatus::of(self) {
+            self.pane_status.update(w, &status, &self.mission.job)?;
+        }
+        Ok(())
+    }
     pub fn is_failure(&self) -> bool {
         match &self.cmd_result {
             CommandResult::Report(report) => !self.mission.is_success(report),
@@ -707,14 +2045,27 @@ impl<'s> AppState<'s> {
         }
     }
     fn lines_to_draw(&self) -> impl Iterator<Item = &Line> {
-        self.lines_to_draw_unfiltered().iter().filter(|line| {
+        let lines = self.lines_to_draw_unfiltered();
+        lines.iter().enumerate().filter_map(move |(idx, line)| {
             // if this command failed, always show the output
-            matches!(self.cmd_result, CommandResult::Failure(..)) || line.matches(self.summary)
+            if matches!(self.cmd_result, CommandResult::Failure(..)) {
+                return Some(line);
+            }
+            if !line.matches(self.summary) {
+                return None;
+            }
+            if LineType::at_index_in(idx, lines) == Some(LineType::Captured)
+                && !self.expanded_outputs.contains(&line.item_idx)
+            {
+                return None;
+            }
+            Some(line)
         })
     }
     fn report_to_draw(&self) -> Option<&Report> {
-        self.cmd_result
-            .report()
+        self.live_report
+            .as_ref()
+            .or(self.cmd_result.report())
             .filter(|_| !self.raw_output)
             .filter(|report| !self.mission.is_success(report))
     }
@@ -739,6 +2090,19 @@ impl<'s> AppState<'s> {
             }
         }
     }
+    /// The URL of the hyperlink to put on the line, if hyperlinks
+    /// are enabled and the line is a location
+    fn hyperlink_url(
+        &self,
+        line: &Line,
+    ) -> Option<String> {
+        let settings = self.mission.settings;
+        if !settings.hyperlinks {
+            return None;
+        }
+        let location = EditorLocation::from_report_location(line.location()?, &self.mission);
+        Some(settings.hyperlink_scheme.url(&location))
+    }
     /// draw the report or the lines of the current computation, between
     /// y and self.page_height()
     pub fn draw_content(
@@ -832,7 +2196,14 @@ impl<'s> AppState<'s> {
                         }
                         tline = &modified;
                     }
+                    let url = self.hyperlink_url(line);
+                    if let Some(url) = &url {
+                        write!(w, "{}", osc8_start(url))?;
+                    }
                     tline.draw_in(w, width - 1 - line.line_type.cols())?;
+                    if url.is_some() {
+                        write!(w, "{OSC8_END}")?;
+                    }
                 }
             }
             clear_line(w)?;
@@ -842,34 +2213,106 @@ impl<'s> AppState<'s> {
         }
         Ok(())
     }
+    /// draw the performance HUD at line y, if it's displayed
+    fn draw_perf_hud(
+        &self,
+        w: &mut W,
+        y: u16,
+    ) -> Result<()> {
+        if !self.perf_hud.visible {
+            return Ok(());
+        }
+        self.perf_hud.draw(
+            w,
+            self.width,
+            y,
+            self.retained_reports_size(),
+            &self.report_history,
+        )
+    }
     /// draw the state on the whole terminal
     pub fn draw(
         &mut self,
         w: &mut W,
+    ) -> Result<()> {
+        let start = Instant::now();
+        w.begin_frame()?;
+        let drawn = self.draw_frame(w);
+        w.end_frame()?;
+        self.perf_hud.add_rendering(start.elapsed());
+        drawn
+    }
+    fn draw_frame(
+        &mut self,
+        w: &mut W,
     ) -> Result<()> {
         self.update_search();
         if self.reverse {
             self.draw_status_line(w, 0)?;
             if let Some(help_page) = self.help_page.as_mut() {
                 help_page.draw(w, Area::new(0, 1, self.width, self.height - 1))?;
+            } else if let Some(explanation_page) = self.explanation_page.as_mut() {
+                explanation_page.draw(w, Area::new(0, 1, self.width, self.height - 1))?;
+            } else if let Some(test_picker) = self.test_picker.as_mut() {
+                test_picker.draw(w, Area::new(0, 1, self.width, self.height - 1))?;
+            } else if let Some(package_picker) = self.package_picker.as_mut() {
+                package_picker.draw(w, Area::new(0, 1, self.width, self.height - 1))?;
             } else {
                 self.draw_content(w, 1)?;
                 self.draw_computing(w, self.height - 2)?;
                 self.draw_message(w, self.height - 2)?;
                 self.draw_badges(w, self.height - 1)?;
+                self.draw_perf_hud(w, 1)?;
             }
         } else {
             if let Some(help_page) = self.help_page.as_mut() {
                 help_page.draw(w, Area::new(0, 0, self.width, self.height - 1))?;
+            } else if let Some(explanation_page) = self.explanation_page.as_mut() {
+                explanation_page.draw(w, Area::new(0, 0, self.width, self.height - 1))?;
+            } else if let Some(test_picker) = self.test_picker.as_mut() {
+                test_picker.draw(w, Area::new(0, 0, self.width, self.height - 1))?;
+            } else if let Some(package_picker) = self.package_picker.as_mut() {
+                package_picker.draw(w, Area::new(0, 0, self.width, self.height - 1))?;
             } else {
                 self.draw_badges(w, 0)?;
                 self.draw_computing(w, 1)?;
                 self.draw_message(w, 1)?; // drawn over the "computing..." line
                 self.draw_content(w, 2)?;
+                self.draw_perf_hud(w, 2)?;
             }
             self.draw_status_line(w, self.height - 1)?;
         }
-        w.flush()?;
+        self.update_pane_status(w)?;
         Ok(())
     }
 }
+
+#[test]
+fn test_speculative_restart() {
+    use clap::Parser;
+    let dir = tempfile::tempdir().unwrap();
+    let args = Args::parse_from(["bacon", "--project", &dir.path().to_string_lossy()]);
+    let context = Context::new(&args).unwrap();
+    let settings = Settings::default();
+    let mission = context
+        .mission(ConcreteJobRef::default(), &Job::default(), &settings)
+        .unwrap();
+    let mut state = AppState::new(mission, true).unwrap();
+    let lib = dir.path().join("src/lib.rs");
+    let main = dir.path().join("src/main.rs");
+    state.receive_watch_event(vec![lib.clone()]);
+    state.computation_starts();
+    // a change in the grace period: the paths of both are the triggers
+    // of the restarted execution
+    assert!(state.receive_speculative_watch_event(vec![main.clone()]));
+    state.computation_starts();
+    assert_eq!(state.trigger_paths, vec![main.clone(), lib.clone()]);
+    // a change of muted paths doesn't restart the execution, which
+    // keeps its triggers
+    let loop_detector = state.loop_detector.as_mut().unwrap();
+    for _ in 0..3 {
+        loop_detector.execution_starts(std::slice::from_ref(&main), true);
+    }
+    assert!(!state.receive_speculative_watch_event(vec![main.clone()]));
+    assert_eq!(state.trigger_paths, vec![main, lib]);
+}
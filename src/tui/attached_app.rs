@@ -0,0 +1,261 @@
+use {
+    crate::*,
+    anyhow::Result,
+    crokey::*,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        io::Write,
+        path::Path,
+    },
+    termimad::{
+        EventSource,
+        crossbeam::channel::select,
+        crossterm::event::Event,
+    },
+};
+
+/// The state of a viewer attached to a bacon running elsewhere (eg a
+/// headless one, in another terminal or as a service), through its
+/// control socket.
+///
+/// The viewer owns nothing: it displays the report of the session and
+/// sends it the run and job requests. It's not a full bacon TUI: the
+/// other actions (search, display toggles, help, etc.) aren't available.
+struct AttachedState<'k> {
+    client: ControlClient,
+    keybindings: &'k KeyBindings,
+    status: Value,
+    report: Option<Report>,
+    jobs: Vec<String>,
+    scroll: usize,
+    width: u16,
+    height: u16,
+    message: Option<String>,
+}
+
+impl<'k> AttachedState<'k> {
+    fn new(
+        client: ControlClient,
+        keybindings: &'k KeyBindings,
+    ) -> Self {
+        let (width, height) = termimad::terminal_size();
+        Self {
+            client,
+            keybindings,
+            status: Value::Null,
+            report: None,
+            jobs: Vec::new(),
+            scroll: 0,
+            width,
+            height,
+            message: None,
+        }
+    }
+    /// Query the state of the session
+    fn refresh(&mut self) -> Result<()> {
+        self.status = self.client.call("get_status", Value::Null)?;
+        if self.status["state"] != "running" {
+            let report = self.client.call("get_report", Value::Null)?;
+            self.report = serde_json::from_value(report).ok();
+            self.scroll = self.scroll.min(self.max_scroll());
+        }
+        let jobs = self.client.call("list_jobs", Value::Null)?;
+        self.jobs = jobs["jobs"]
+            .as_array()
+            .map(|jobs| {
+                jobs.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.jobs.sort();
+        Ok(())
+    }
+    fn page_height(&self) -> usize {
+        self.height.saturating_sub(2) as usize
+    }
+    fn max_scroll(&self) -> usize {
+        let lines = self.report.as_ref().map_or(0, |report| report.lines.len());
+        lines.saturating_sub(self.page_height())
+    }
+    /// Ask the session to run the job, if it knows it
+    fn set_job(
+        &mut self,
+        job_ref: &JobRef,
+    ) -> Result<()> {
+        let name = match job_ref {
+            JobRef::Concrete(ConcreteJobRef {
+                name_or_alias: NameOrAlias::Name(name),
+                ..
+            }) => name,
+            _ => {
+                self.message = Some(format!("{job_ref} can't be run from an attached viewer"));
+                return Ok(());
+            }
+        };
+        if !self.jobs.contains(name) {
+            self.message = Some(format!("The session has no {name:?} job"));
+            return Ok(());
+        }
+        self.client.call("set_job", json!({ "job": name }))?;
+        Ok(())
+    }
+    /// Apply a key, with the keybindings of the user, return false
+    /// when the user wants to detach
+    fn apply_key(
+        &mut self,
+        key: KeyCombination,
+    ) -> Result<bool> {
+        self.message = None;
+        let Some(action) = self.keybindings.get(key).cloned() else {
+            return Ok(true);
+        };
+        match action {
+            Action::Internal(Internal::Quit | Internal::Back | Internal::BackOrQuit) => {
+                return Ok(false);
+            }
+            Action::Internal(Internal::ReRun | Internal::Refresh) => {
+                self.client.call("run", Value::Null)?;
+            }
+            Action::Internal(Internal::Scroll(scroll_command)) => {
+                let content_height = self.report.as_ref().map_or(0, |report| report.lines.len());
+                self.scroll = scroll_command.apply(self.scroll, content_height, self.page_height());
+            }
+            Action::Job(job_ref) => {
+                self.set_job(&job_ref)?;
+            }
+            action => {
+                self.message = Some(format!("{} isn't available when attached", action.doc()));
+            }
+        }
+        Ok(true)
+    }
+    /// The help line, with the keys of the available actions
+    fn help(&self) -> String {
+        let mut parts = Vec::new();
+        let rerun = self
+            .keybindings
+            .shortest_internal_key(Internal::ReRun)
+            .or_else(|| self.keybindings.shortest_internal_key(Internal::Refresh));
+        if let Some(key) = rerun {
+            parts.push(format!("{key}: run again"));
+        }
+        parts.push("job keys: run the job".to_string());
+        if let Some(key) = self.keybindings.shortest_internal_key(Internal::Quit) {
+            parts.push(format!("{key}: detach"));
+        }
+        parts.join(", ")
+    }
+    fn badges(&self) -> TLine {
+        let mut t_line = TLine::default();
+        t_line.add_badge(TString::badge("attached", 255, 240));
+        let job = self.status["job"].as_str().unwrap_or("-");
+        t_line.add_badge(TString::badge(job, 235, 204));
+        let count = |key: &str| self.status[key].as_u64().unwrap_or(0) as usize;
+        match self.status["state"].as_str() {
+            Some("running") => {
+                t_line.add_badge(TString::badge("running...", 235, 117));
+            }
+            Some(_) => {
+                let stats = Stats {
+                    errors: count("errors"),
+                    test_fails: count("test_fails"),
+                    passed_tests: count("passed_tests"),
+                    warnings: count("warnings"),
+                    ..Default::default()
+                };
+                for badge in stats.badges() {
+                    t_line.add_badge(badge);
+                }
+            }
+            None => {
+                t_line.add_badge(TString::badge("no execution yet", 235, 250));
+            }
+        }
+        t_line
+    }
+    fn draw(
+        &self,
+        w: &mut W,
+    ) -> Result<()> {
+        let width = self.width as usize;
+        goto_line(w, 0)?;
+        self.badges().draw_in(w, width)?;
+        clear_line(w)?;
+        let lines = self
+            .report
+            .as_ref()
+            .map_or(&[][..], |report| &report.lines[..]);
+        for y in 0..self.page_height() {
+            goto_line(w, y as u16 + 1)?;
+            if let Some(line) = lines.get(self.scroll + y) {
+                line.content.draw_in(w, width)?;
+            }
+            clear_line(w)?;
+        }
+        goto_line(w, self.height.saturating_sub(1))?;
+        let help = match &self.message {
+            Some(message) => TLine::from_raw(message.clone()),
+            None => TLine::italic(self.help()),
+        };
+        help.draw_in(w, width)?;
+        clear_line(w)?;
+        w.flush()?;
+        Ok(())
+    }
+}
+
+/// Run a viewer attached to the bacon session listening on the socket,
+/// until the user detaches (which doesn't stop the session)
+pub fn run_attached(
+    w: &mut W,
+    socket: &Path,
+    keybindings: &KeyBindings,
+) -> Result<()> {
+    let client = ControlClient::new(socket);
+    let statuses = client.subscribe()?;
+    let mut state = AttachedState::new(client, keybindings);
+    state.refresh()?;
+    let event_source = EventSource::new()?;
+    let user_events = event_source.receiver();
+    loop {
+        state.draw(w)?;
+        select! {
+            recv(statuses) -> status => {
+                if status.is_err() {
+                    anyhow::bail!("The bacon session ended");
+                }
+                if let Err(e) = state.refresh() {
+                    state.message = Some(e.to_string());
+                }
+            }
+            recv(user_events) -> user_event => {
+                match user_event?.event {
+                    Event::Resize(width, height) => {
+                        state.width = width;
+                        state.height = height;
+                        state.scroll = state.scroll.min(state.max_scroll());
+                    }
+                    Event::Key(key_event) => {
+                        match state.apply_key(KeyCombination::from(key_event)) {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                break;
+                            }
+                            Err(e) => {
+                                state.message = Some(e.to_string());
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                event_source.unblock(false);
+            }
+        }
+    }
+    Ok(())
+}
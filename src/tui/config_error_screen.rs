@@ -0,0 +1,104 @@
+use {
+    crate::*,
+    anyhow::Result,
+    crokey::*,
+    std::{
+        io::Write,
+        path::PathBuf,
+    },
+    termimad::{
+        Area,
+        EventSource,
+        crossbeam::channel::select,
+        crossterm::{
+            QueueableCommand,
+            event::Event,
+            terminal::{
+                Clear,
+                ClearType,
+            },
+        },
+    },
+};
+
+/// Display the problems of the configuration files until they're fixed,
+/// then return the settings, or None when the user quits.
+///
+/// The files are read again each time one of them is saved.
+pub fn run_config_error_screen(
+    w: &mut W,
+    args: &Args,
+    context: &Context,
+    event_source: &EventSource,
+    mut errors: ConfigErrors,
+) -> Result<Option<Settings>> {
+    let mut paths: Vec<PathBuf> = config_paths(context)
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect();
+    for problem in &errors.problems {
+        if !paths.contains(&problem.file) {
+            paths.push(problem.file.clone());
+        }
+    }
+    let watcher = Watcher::new(&paths, IgnorerSet::default())?;
+    let user_events = event_source.receiver();
+    let mut page = ExplanationPage::from_markdown(errors_markdown(&errors));
+    loop {
+        let (width, height) = termimad::terminal_size();
+        w.queue(Clear(ClearType::All))?;
+        page.draw(w, Area::new(0, 0, width, height))?;
+        w.flush()?;
+        select! {
+            recv(user_events) -> user_event => {
+                if let Event::Key(key_event) = user_event?.event {
+                    match KeyCombination::from(key_event) {
+                        key!(q) | key!(ctrl-c) | key!(ctrl-q) | key!(esc) => {
+                            return Ok(None);
+                        }
+                        key!(up) => page.apply_scroll_command(ScrollCommand::Lines(-1)),
+                        key!(down) => page.apply_scroll_command(ScrollCommand::Lines(1)),
+                        key!(pageup) => page.apply_scroll_command(ScrollCommand::pages(-1)),
+                        key!(pagedown) => page.apply_scroll_command(ScrollCommand::pages(1)),
+                        _ => {}
+                    }
+                }
+                event_source.unblock(false);
+            }
+            recv(watcher.receiver) -> _ => {
+                info!("config watch event received");
+                match Settings::read(args, context) {
+                    Ok(settings) => {
+                        return Ok(Some(settings));
+                    }
+                    Err(e) => {
+                        errors = e.downcast()?;
+                        page = ExplanationPage::from_markdown(errors_markdown(&errors));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn errors_markdown(errors: &ConfigErrors) -> String {
+    let mut md = String::from("# Invalid configuration\n\n");
+    md.push_str("Bacon starts as soon as the problems are fixed and the files saved.\n\n");
+    for problem in &errors.problems {
+        md.push_str(&format!("* **{}**", problem.file.to_string_lossy()));
+        if let Some(line) = problem.line {
+            md.push_str(&format!(" line {line}"));
+        }
+        md.push_str(": ");
+        if let Some(key_path) = &problem.key_path {
+            md.push_str(&format!("`{key_path}`: "));
+        }
+        md.push_str(&problem.message);
+        if let Some(suggestion) = &problem.suggestion {
+            md.push_str(&format!(" - *did you mean* `{suggestion}`?"));
+        }
+        md.push('\n');
+    }
+    md.push_str("\nHit *q* to quit\n");
+    md
+}
@@ -32,3 +32,20 @@ pub fn clear_line(w: &mut W) -> Result<()> {
     execute!(w, terminal::Clear(terminal::ClearType::UntilNewLine))?;
     Ok(())
 }
+
+/// Leave the TUI, giving the terminal back, to run the function (eg a
+/// terminal editor), then restore the TUI, which must then be redrawn.
+///
+/// The user events must not be read while the function runs.
+pub fn run_outside_tui<T, F: FnOnce() -> T>(
+    w: &mut W,
+    f: F,
+) -> Result<T> {
+    execute!(w, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    let value = f();
+    terminal::enable_raw_mode()?;
+    execute!(w, terminal::EnterAlternateScreen, cursor::Hide)?;
+    w.invalidate();
+    Ok(value)
+}
@@ -0,0 +1,246 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        anyhow,
+    },
+    serde_json::json,
+    std::{
+        io::Write,
+        str::FromStr,
+    },
+};
+
+/// What bacon prints in headless mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeadlessOutput {
+    /// The output of the command, as it comes, then the badges
+    #[default]
+    Raw,
+    /// A line per diagnostic, then a summary line, without style,
+    /// at the end of each execution
+    Plain,
+    /// Newline delimited JSON events, for other programs
+    JsonStream,
+}
+
+/// An item of the report, with its first location
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReportItem<'r> {
+    kind: &'static str,
+    /// whether the item is a warning marked as external
+    external: bool,
+    location: Option<&'r str>,
+    message: &'r str,
+}
+
+impl FromStr for HeadlessOutput {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "raw" => Ok(Self::Raw),
+            "plain" => Ok(Self::Plain),
+            "json-stream" => Ok(Self::JsonStream),
+            _ => Err(anyhow!(
+                "Invalid output: {s:?} (expected raw, plain, or json-stream)"
+            )),
+        }
+    }
+}
+
+impl HeadlessOutput {
+    /// Print what's needed when an execution starts
+    pub fn print_start(
+        self,
+        state: &AppState<'_>,
+    ) {
+        if self != Self::JsonStream {
+            return;
+        }
+        if let Some(status) = JobStatus::of(state) {
+            let event = json!({
+                "event": "run-started",
+                "job": status.job,
+                "timestamp": status.timestamp,
+            });
+            println!("{event}");
+        }
+    }
+    /// Print a line of the command's output, when it's received
+    pub fn print_output_line(
+        self,
+        line: &RawCommandOutputLine,
+    ) {
+        if self == Self::Raw {
+            match line.origin {
+                CommandStream::StdOut => print!("{}", line.content),
+                CommandStream::StdErr => eprint!("{}", line.content),
+            }
+        }
+    }
+    /// Print the result of the execution, which just finished
+    pub fn print_result(
+        self,
+        w: &mut W,
+        state: &AppState<'_>,
+    ) -> Result<()> {
+        match self {
+            Self::Raw => {
+                for badge in state.job_badges() {
+                    badge.draw(w)?;
+                }
+                writeln!(w)?;
+            }
+            Self::Plain => {
+                match &state.cmd_result {
+                    CommandResult::Report(report) => {
+                        for item in report_items(report) {
+                            writeln!(w, "{}", item.plain_line())?;
+                        }
+                    }
+                    CommandResult::Failure(failure) => {
+                        // there's no report, so the output is needed to understand
+                        for line in &failure.output.lines {
+                            writeln!(w, "{}", line.content.to_raw())?;
+                        }
+                    }
+                    CommandResult::None => {}
+                }
+                if let Some(status) = JobStatus::of(state) {
+                    writeln!(w, "{}", status.summary_line())?;
+                }
+            }
+            Self::JsonStream => {
+                if let CommandResult::Report(report) = &state.cmd_result {
+                    for item in report_items(report) {
+                        let mut event = json!({
+                            "event": "diagnostic",
+                            "kind": item.kind,
+                            "location": item.location,
+                            "message": item.message,
+                        });
+                        if item.external {
+                            event["external"] = true.into();
+                        }
+                        writeln!(w, "{event}")?;
+                    }
+                }
+                if let Some(status) = JobStatus::of(state) {
+                    let mut event = status.to_json();
+                    event["event"] = "run-finished".into();
+                    event["success"] = state.is_success().into();
+                    event["duration_ms"] =
+                        (state.computation_start.elapsed().as_millis() as u64).into();
+                    if let CommandResult::Failure(failure) = &state.cmd_result {
+                        event["error_code"] = failure.error_code.into();
+                    }
+                    writeln!(w, "{event}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReportItem<'_> {
+    fn plain_line(&self) -> String {
+        let suffix = if self.external { " (external)" } else { "" };
+        match self.location {
+            Some(location) => format!("{location}: {}: {}{suffix}", self.kind, self.message),
+            None => format!("{}: {}{suffix}", self.kind, self.message),
+        }
+    }
+}
+
+/// Return the items of the report, with the first location of each
+/// item when there's one
+fn report_items(report: &Report) -> Vec<ReportItem<'_>> {
+    let mut items = Vec::new();
+    // the item whose location is searched, with its index
+    let mut item: Option<(usize, ReportItem)> = None;
+    for line in &report.lines {
+        if let LineType::Title(kind) = line.line_type {
+            if let Some((_, item)) = item.take() {
+                items.push(item);
+            }
+            let kind_name = match kind {
+                Kind::Error => "error",
+                Kind::Warning | Kind::External => "warning",
+                Kind::TestFail | Kind::DoctestFail => "test",
+                Kind::Info => "info",
+                Kind::Sum => continue,
+            };
+            item = Some((
+                line.item_idx,
+                ReportItem {
+                    kind: kind_name,
+                    external: kind == Kind::External,
+                    location: None,
+                    message: line.title_message().unwrap_or_default(),
+                },
+            ));
+            continue;
+        }
+        if item.as_ref().map_or(true, |(idx, _)| *idx != line.item_idx) {
+            continue;
+        }
+        if let Some(location) = line.location() {
+            if let Some((_, mut found)) = item.take() {
+                found.location = Some(location);
+                items.push(found);
+            }
+        }
+    }
+    if let Some((_, item)) = item {
+        items.push(item);
+    }
+    items
+}
+
+#[test]
+fn test_report_items() {
+    let mut lines = Vec::new();
+    let mut add_item = |item_idx, kind, label: &str, message: &str, locations: &[&str]| {
+        lines.push(Line::title(item_idx, kind, label, message));
+        for location in locations {
+            lines.push(Line::from_raw(
+                item_idx,
+                LineType::Location,
+                &format!("  --> {location}"),
+            ));
+        }
+    };
+    add_item(
+        1,
+        Kind::Warning,
+        "warning",
+        "unused variable",
+        &["src/main.rs:3:9", "src/main.rs:1:1"],
+    );
+    add_item(2, Kind::TestFail, "test", "tests::it_works", &[]);
+    add_item(3, Kind::Sum, "warning", "1 warning emitted", &[]);
+    let report = Report::from_lines(lines);
+    let items = report_items(&report);
+    assert_eq!(
+        items,
+        vec![
+            ReportItem {
+                kind: "warning",
+                external: false,
+                location: Some("src/main.rs:3:9"),
+                message: "unused variable",
+            },
+            ReportItem {
+                kind: "test",
+                external: false,
+                location: None,
+                message: "tests::it_works",
+            },
+        ],
+    );
+    assert_eq!(
+        items[0].plain_line(),
+        "src/main.rs:3:9: warning: unused variable"
+    );
+    assert_eq!(items[1].plain_line(), "test: tests::it_works");
+}
@@ -0,0 +1,115 @@
+use std::{
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// Number of consecutive executions triggered by changes, made during
+/// the previous execution, of the same files, before we consider the
+/// job is triggering itself
+const LOOP_THRESHOLD: usize = 3;
+
+/// How long the changes of the files involved in a loop are ignored
+pub const LOOP_MUTE_DURATION: Duration = Duration::from_secs(60);
+
+/// Detects jobs whose command modifies watched files, thus re-triggering
+/// themselves forever (eg a codegen or a fmt job)
+#[derive(Debug, Default)]
+pub struct LoopDetector {
+    /// files whose changes, made during the previous execution,
+    /// triggered the current one
+    last_trigger_paths: Vec<PathBuf>,
+    /// how many consecutive executions were triggered by the same files
+    repeats: usize,
+    /// files whose changes are currently ignored
+    muted_paths: Vec<PathBuf>,
+    muted_until: Option<Instant>,
+}
+
+impl LoopDetector {
+    /// Register the start of an execution, given the files whose changes
+    /// triggered it, and whether all those changes were received while the
+    /// previous execution was running.
+    ///
+    /// Return the paths involved in a loop, if one is detected. Those paths
+    /// are then muted for [`LOOP_MUTE_DURATION`].
+    pub fn execution_starts(
+        &mut self,
+        trigger_paths: &[PathBuf],
+        changed_during_execution: bool,
+    ) -> Option<Vec<PathBuf>> {
+        if trigger_paths.is_empty() || !changed_during_execution {
+            self.last_trigger_paths.clear();
+            self.repeats = 0;
+            return None;
+        }
+        let same_paths = trigger_paths.len() == self.last_trigger_paths.len()
+            && trigger_paths
+                .iter()
+                .all(|path| self.last_trigger_paths.contains(path));
+        if same_paths {
+            self.repeats += 1;
+        } else {
+            self.last_trigger_paths = trigger_paths.to_vec();
+            self.repeats = 1;
+        }
+        if self.repeats < LOOP_THRESHOLD {
+            return None;
+        }
+        self.repeats = 0;
+        let loop_paths = std::mem::take(&mut self.last_trigger_paths);
+        self.muted_paths.clone_from(&loop_paths);
+        self.muted_until = Some(Instant::now() + LOOP_MUTE_DURATION);
+        Some(loop_paths)
+    }
+    fn is_muted(
+        &mut self,
+        path: &Path,
+    ) -> bool {
+        let Some(muted_until) = self.muted_until else {
+            return false;
+        };
+        if Instant::now() > muted_until {
+            self.muted_paths.clear();
+            self.muted_until = None;
+            return false;
+        }
+        self.muted_paths.iter().any(|p| p == path)
+    }
+    /// Remove the muted paths
+    pub fn filter(
+        &mut self,
+        mut paths: Vec<PathBuf>,
+    ) -> Vec<PathBuf> {
+        paths.retain(|path| !self.is_muted(path));
+        paths
+    }
+}
+
+#[test]
+fn test_loop_detection() {
+    let generated = vec![PathBuf::from("/pack/src/generated.rs")];
+    let lib = vec![PathBuf::from("/pack/src/lib.rs")];
+    let mut detector = LoopDetector::default();
+    // changes made by the user between executions aren't suspicious
+    for _ in 0..5 {
+        assert_eq!(detector.execution_starts(&lib, false), None);
+    }
+    assert_eq!(detector.execution_starts(&generated, true), None);
+    assert_eq!(detector.execution_starts(&generated, true), None);
+    assert_eq!(
+        detector.execution_starts(&generated, true),
+        Some(generated.clone())
+    );
+    assert!(detector.filter(generated.clone()).is_empty());
+    assert_eq!(detector.filter(lib.clone()), lib);
+    // a different set of paths resets the count
+    assert_eq!(detector.execution_starts(&generated, true), None);
+    assert_eq!(detector.execution_starts(&lib, true), None);
+    assert_eq!(detector.execution_starts(&generated, true), None);
+}
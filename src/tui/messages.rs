@@ -22,4 +22,13 @@ impl Message {
             display_duration: Duration::from_secs(5),
         }
     }
+    /// build a message which must stay visible long enough to be
+    /// noticed even when the user isn't looking at bacon
+    pub fn long<S: Into<String>>(markdown: S) -> Self {
+        Self {
+            markdown: markdown.into(),
+            display_start: None,
+            display_duration: Duration::from_secs(30),
+        }
+    }
 }
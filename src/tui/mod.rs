@@ -1,16 +1,36 @@
 pub mod app;
 mod app_state;
+mod attached_app;
+mod config_error_screen;
 mod drawing;
+mod headless_output;
+mod loop_detector;
 mod messages;
+mod package_picker;
+mod pane_status;
+mod perf_hud;
+mod screen_writer;
 mod scroll;
 mod search_state;
+mod test_picker;
+mod ui_state;
 mod wrap;
 
 pub use {
     app_state::*,
+    attached_app::*,
+    config_error_screen::*,
     drawing::*,
+    headless_output::*,
+    loop_detector::*,
     messages::*,
+    package_picker::*,
+    pane_status::*,
+    perf_hud::*,
+    screen_writer::*,
     scroll::*,
     search_state::*,
+    test_picker::*,
+    ui_state::*,
     wrap::*,
 };
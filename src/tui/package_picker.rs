@@ -0,0 +1,219 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    crokey::{
+        KeyCombination,
+        key,
+    },
+    termimad::{
+        Area,
+        InputField,
+    },
+};
+
+const CSI_PICKER_CURSOR: &str = "\u{1b}[1m\u{1b}[30m\u{1b}[48;5;250m";
+const CSI_PICKER_ACTIVE: &str = "\u{1b}[1m\u{1b}[38;5;208m";
+
+const ALL_PACKAGES: &str = "(all packages)";
+
+/// What the package picker wants done after a key combination
+pub enum PackagePickerEffect {
+    /// the key was handled by the picker, which stays open
+    None,
+    /// the key isn't one of the picker
+    NotConsumed,
+    /// the picker must be closed without changing the package
+    Cancel,
+    /// the jobs must be scoped to this package, or to none if None
+    Pick(Option<String>),
+}
+
+/// A list of the members of the workspace, filtered by a fuzzy pattern,
+/// to pick the one the cargo jobs are scoped to
+pub struct PackagePicker {
+    /// the choices, the first one (None) removing the scope
+    packages: Vec<Option<String>>,
+    active: Option<String>,
+    input: InputField,
+    /// indexes of the packages matching the pattern
+    matching: Vec<usize>,
+    /// index, among the matching packages, of the one under the cursor
+    cursor: usize,
+    scroll: usize,
+}
+
+impl PackagePicker {
+    pub fn new(mission: &Mission) -> Result<Self> {
+        if mission.workspace_members.len() < 2 {
+            bail!("not a workspace with several members");
+        }
+        Ok(Self::from_members(
+            &mission.workspace_members,
+            mission.package.clone(),
+        ))
+    }
+    pub fn from_members(
+        members: &[String],
+        active: Option<String>,
+    ) -> Self {
+        let mut input = InputField::default();
+        input.set_focus(true);
+        let mut packages = vec![None];
+        packages.extend(members.iter().cloned().map(Some));
+        let matching = (0..packages.len()).collect();
+        let cursor = packages.iter().position(|p| *p == active).unwrap_or(0);
+        Self {
+            packages,
+            active,
+            input,
+            matching,
+            cursor,
+            scroll: 0,
+        }
+    }
+    fn label(package: &Option<String>) -> &str {
+        package.as_deref().unwrap_or(ALL_PACKAGES)
+    }
+    fn update_matching(&mut self) {
+        let pattern = self.input.get_content();
+        self.matching = if pattern.is_empty() {
+            (0..self.packages.len()).collect()
+        } else {
+            // when filtering, only the packages are listed
+            (1..self.packages.len())
+                .filter(|&idx| fuzzy_match(&pattern, Self::label(&self.packages[idx])))
+                .collect()
+        };
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+    pub fn apply_key_combination(
+        &mut self,
+        key: KeyCombination,
+    ) -> PackagePickerEffect {
+        if key == key!(esc) {
+            return PackagePickerEffect::Cancel;
+        }
+        if key == key!(enter) {
+            return match self.matching.get(self.cursor) {
+                Some(&idx) => PackagePickerEffect::Pick(self.packages[idx].clone()),
+                None => PackagePickerEffect::None,
+            };
+        }
+        if key == key!(up) {
+            self.cursor = self.cursor.saturating_sub(1);
+        } else if key == key!(down) {
+            if self.cursor + 1 < self.matching.len() {
+                self.cursor += 1;
+            }
+        } else if self.input.apply_key_combination(key) {
+            self.update_matching();
+        } else {
+            return PackagePickerEffect::NotConsumed;
+        }
+        PackagePickerEffect::None
+    }
+    pub fn draw(
+        &mut self,
+        w: &mut W,
+        area: Area,
+    ) -> Result<()> {
+        if area.height < 3 {
+            return Ok(());
+        }
+        goto_line(w, area.top)?;
+        clear_line(w)?;
+        draw(
+            w,
+            CSI_BOLD,
+            &format!(
+                "{}/{} packages - enter: scope the jobs, esc: cancel",
+                self.matching.iter().filter(|&&idx| idx > 0).count(),
+                self.packages.len() - 1,
+            ),
+        )?;
+        goto_line(w, area.top + 1)?;
+        draw(w, CSI_FOUND, ">")?;
+        self.input
+            .change_area(area.left + 2, area.top + 1, area.width - 2);
+        self.input.display_on(w)?;
+        let list_height = (area.height - 2) as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + list_height {
+            self.scroll = self.cursor + 1 - list_height;
+        }
+        for row in 0..list_height {
+            goto_line(w, area.top + 2 + row as u16)?;
+            clear_line(w)?;
+            let line_idx = self.scroll + row;
+            let Some(&package_idx) = self.matching.get(line_idx) else {
+                continue;
+            };
+            let package = &self.packages[package_idx];
+            let label: String = Self::label(package)
+                .chars()
+                .take(area.width as usize)
+                .collect();
+            let csi = if line_idx == self.cursor {
+                CSI_PICKER_CURSOR
+            } else if *package == self.active {
+                CSI_PICKER_ACTIVE
+            } else {
+                ""
+            };
+            draw(w, csi, &label)?;
+        }
+        Ok(())
+    }
+}
+
+/// Make the cargo command apply to the package only, replacing the
+/// package selection arguments of the job (eg `--workspace`)
+pub fn scope_command_to_package(
+    command: &mut Vec<String>,
+    package: &str,
+) {
+    let end = command
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(command.len());
+    let trailing = command.split_off(end);
+    let mut args = std::mem::take(command).into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--workspace" | "--all" => {}
+            "-p" | "--package" | "--exclude" => {
+                args.next(); // the value
+            }
+            _ if arg.starts_with("--package=")
+                || arg.starts_with("--exclude=")
+                || (arg.starts_with("-p") && !arg.starts_with("--")) => {}
+            _ => command.push(arg),
+        }
+    }
+    command.push("-p".to_string());
+    command.push(package.to_string());
+    command.extend(trailing);
+}
+
+#[test]
+fn test_scope_command_to_package() {
+    let scoped = |command: &str| {
+        let mut command: Vec<String> = command.split(' ').map(str::to_string).collect();
+        scope_command_to_package(&mut command, "core");
+        command.join(" ")
+    };
+    assert_eq!(scoped("cargo check"), "cargo check -p core");
+    assert_eq!(
+        scoped("cargo clippy --workspace --all-targets -- -D warnings"),
+        "cargo clippy --all-targets -p core -- -D warnings",
+    );
+    assert_eq!(
+        scoped("cargo nextest run -p cli --package=ui -pgui --exclude x"),
+        "cargo nextest run -p core",
+    );
+}
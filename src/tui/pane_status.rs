@@ -0,0 +1,95 @@
+use {
+    crate::*,
+    std::{
+        io::Write,
+        process::Command,
+        sync::Mutex,
+    },
+};
+
+/// The tmux options which were set, to be unset when bacon quits
+static SET_TMUX_OPTIONS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Make the job name and state visible outside of bacon, in the title
+/// of the terminal (the title of the pane, in tmux) or in a tmux option
+#[derive(Debug, Default)]
+pub struct PaneStatus {
+    /// the last written status, to update only on change
+    written: Option<String>,
+}
+
+impl PaneStatus {
+    /// Write the status of the job, if it changed since the last call
+    pub fn update(
+        &mut self,
+        w: &mut W,
+        status: &JobStatus,
+        job: &Job,
+    ) -> anyhow::Result<()> {
+        let text = status.line("{job} {state}");
+        if self.written.as_ref() == Some(&text) {
+            return Ok(());
+        }
+        if job.pane_title() {
+            // OSC 2 sets the title of the window, or of the pane in tmux
+            write!(w, "\u{1b}]2;bacon {text}\u{1b}\\")?;
+        }
+        if let Some(name) = job.tmux_option() {
+            set_tmux_option(name, Some(&text));
+        }
+        self.written = Some(text);
+        Ok(())
+    }
+    /// Unset the tmux options set by bacon, before it quits
+    pub fn unset_tmux_options() {
+        let names = match SET_TMUX_OPTIONS.lock() {
+            Ok(mut names) => std::mem::take(&mut *names),
+            Err(_) => return,
+        };
+        for name in names {
+            set_tmux_option(&name, None);
+        }
+    }
+}
+
+/// Set (or unset when there's no value) an option of the tmux pane
+/// bacon runs in, if it runs in tmux
+fn set_tmux_option(
+    name: &str,
+    value: Option<&str>,
+) {
+    let Ok(pane) = std::env::var("TMUX_PANE") else {
+        debug!("not in tmux, option {name:?} not set");
+        return;
+    };
+    let mut tmux = Command::new("tmux");
+    tmux.args(["set-option", "-p", "-t", &pane]);
+    match value {
+        Some(value) => {
+            tmux.args([name, value]);
+        }
+        None => {
+            tmux.args(["-u", name]);
+        }
+    }
+    match tmux.output() {
+        Ok(output) if output.status.success() => {
+            if value.is_some() {
+                if let Ok(mut names) = SET_TMUX_OPTIONS.lock() {
+                    if !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        Ok(output) => {
+            warn!(
+                "tmux set-option failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Err(e) => {
+            warn!("tmux couldn't be launched: {e}");
+        }
+    }
+}
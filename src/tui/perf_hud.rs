@@ -0,0 +1,203 @@
+use {
+    crate::*,
+    anyhow::Result,
+    std::{
+        io::Write,
+        mem::size_of,
+        sync::atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// Width of the overlay, borders excluded
+const HUD_WIDTH: usize = 34;
+
+/// Time spent by the watcher threads filtering the file events,
+/// since the start of the last run
+static WATCH_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// Count time spent handling a file event
+pub fn add_watch_time(duration: Duration) {
+    WATCH_NANOS.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+}
+
+fn take_watch_time() -> Duration {
+    Duration::from_nanos(WATCH_NANOS.swap(0, Ordering::Relaxed))
+}
+
+/// How long the steps of a run took
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunTimings {
+    /// filtering the file events, since the previous run
+    pub watching: Duration,
+    /// from the first change to the start of the command, including
+    /// the grace period
+    pub debouncing: Duration,
+    pub execution: Duration,
+    /// analyzing the lines, and building the report
+    pub parsing: Duration,
+    /// drawing the screen, during the run
+    pub rendering: Duration,
+    pub draws: usize,
+}
+
+/// The timings of the runs, and the overlay displaying them, toggled
+/// with the `toggle-perf-hud` internal
+#[derive(Debug, Default)]
+pub struct PerfHud {
+    pub visible: bool,
+    current: RunTimings,
+    last: Option<RunTimings>,
+    first_change: Option<Instant>,
+    last_draw: Duration,
+}
+
+impl PerfHud {
+    pub fn change_received(&mut self) {
+        self.first_change.get_or_insert_with(Instant::now);
+    }
+    pub fn run_starts(
+        &mut self,
+        grace_period: Period,
+    ) {
+        let debouncing = self
+            .first_change
+            .take()
+            .map(|instant| instant.elapsed())
+            .unwrap_or_default();
+        self.current = RunTimings {
+            watching: take_watch_time(),
+            debouncing: debouncing + grace_period.duration,
+            ..Default::default()
+        };
+    }
+    pub fn add_parsing(
+        &mut self,
+        duration: Duration,
+    ) {
+        self.current.parsing += duration;
+    }
+    pub fn add_rendering(
+        &mut self,
+        duration: Duration,
+    ) {
+        self.current.rendering += duration;
+        self.current.draws += 1;
+        self.last_draw = duration;
+    }
+    pub fn run_ends(
+        &mut self,
+        execution: Duration,
+    ) {
+        self.current.execution = execution;
+        self.last = Some(self.current);
+    }
+    fn lines(
+        &self,
+        retained_bytes: usize,
+        history: &ReportHistory,
+    ) -> Vec<String> {
+        let mut lines = vec![" perf of the last run".to_string()];
+        match &self.last {
+            Some(t) => {
+                lines.push(format!(" watching    {}", format_duration(t.watching)));
+                lines.push(format!(" debouncing  {}", format_duration(t.debouncing)));
+                lines.push(format!(" execution   {}", format_duration(t.execution)));
+                lines.push(format!(" parsing     {}", format_duration(t.parsing)));
+                lines.push(format!(
+                    " rendering   {} ({} draws)",
+                    format_duration(t.rendering),
+                    t.draws
+                ));
+            }
+            None => lines.push(" no finished run yet".to_string()),
+        }
+        lines.push(format!(" last draw   {}", format_duration(self.last_draw)));
+        lines.push(format!(" reports     {}", format_bytes(retained_bytes)));
+        lines.push(format!(
+            " history     {} ({} reports)",
+            format_bytes(history.size()),
+            history.len()
+        ));
+        lines
+    }
+    /// Draw the overlay in the top right corner of the given area
+    pub fn draw(
+        &self,
+        w: &mut W,
+        width: u16,
+        y: u16,
+        retained_bytes: usize,
+        history: &ReportHistory,
+    ) -> Result<()> {
+        if (width as usize) < HUD_WIDTH + 2 {
+            return Ok(());
+        }
+        let x = width - HUD_WIDTH as u16 - 1;
+        for (i, line) in self.lines(retained_bytes, history).iter().enumerate() {
+            goto(w, x, y + i as u16)?;
+            write!(
+                w,
+                "\u{1b}[38;5;235m\u{1b}[48;5;250m{:<w$}\u{1b}[0m",
+                line,
+                w = HUD_WIDTH
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_millis(1) {
+        format!("{}µs", duration.as_micros())
+    } else if duration < Duration::from_secs(1) {
+        format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} kB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Estimate the memory used by lines: their strings and the vec
+pub fn lines_size(lines: &[Line]) -> usize {
+    lines
+        .iter()
+        .map(|line| {
+            size_of::<Line>()
+                + line
+                    .content
+                    .strings
+                    .iter()
+                    .map(|s| size_of::<TString>() + s.csi.capacity() + s.raw.capacity())
+                    .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Estimate the memory used by a report, with its output
+pub fn report_size(report: &Report) -> usize {
+    size_of::<Report>() + lines_size(&report.lines) + lines_size(&report.output.lines)
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(Duration::from_micros(250)), "250µs");
+    assert_eq!(format_duration(Duration::from_micros(12_340)), "12.3ms");
+    assert_eq!(format_duration(Duration::from_millis(1520)), "1.52s");
+    assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MB");
+}
@@ -0,0 +1,235 @@
+use {
+    rustc_hash::{
+        FxHashMap,
+        FxHashSet,
+    },
+    std::io::{
+        self,
+        Stdout,
+        Write,
+    },
+};
+
+/// Size over which the bytes written outside of a frame are sent
+/// to stdout without waiting for a flush
+const PASSTHROUGH_CAPACITY: usize = 8 * 1024;
+
+/// Reset of the styles, written before each redrawn row so that it
+/// doesn't depend on the style left by the previous one
+const CSI_RESET: &[u8] = b"\x1b[0m";
+
+/// The writer of the application, which, when drawing a frame, only
+/// sends to the terminal the rows which changed since they were last
+/// drawn.
+///
+/// The rows of a frame are found with the cursor moves starting them.
+/// A frame which can't be split this way (eg one clearing the screen
+/// or containing new lines) is sent whole. Everything written outside
+/// of a frame is sent when flushed, and makes the content of the
+/// screen unknown, so that the next frame is sent whole.
+pub struct ScreenWriter {
+    stdout: Stdout,
+    buffer: Vec<u8>,
+    in_frame: bool,
+    /// what was last drawn on each row of the screen, when known
+    rows: FxHashMap<u16, Vec<u8>>,
+}
+
+/// A frame split in rows
+#[derive(Debug, PartialEq)]
+struct SplitFrame<'f> {
+    /// what's written before the first cursor move
+    prefix: &'f [u8],
+    /// the bytes of each row, including the cursor moves, in the
+    /// order the rows are first drawn
+    rows: Vec<(u16, Vec<u8>)>,
+}
+
+impl ScreenWriter {
+    pub fn new() -> Self {
+        Self {
+            stdout: io::stdout(),
+            buffer: Vec::new(),
+            in_frame: false,
+            rows: FxHashMap::default(),
+        }
+    }
+    /// Start drawing a frame: flushes are ignored until the end
+    /// of the frame
+    pub fn begin_frame(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.in_frame = true;
+        Ok(())
+    }
+    /// Send the changed rows of the frame to the terminal
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        self.in_frame = false;
+        let frame = std::mem::take(&mut self.buffer);
+        let diff = self.diff(&frame);
+        self.stdout.write_all(&diff)?;
+        self.stdout.flush()
+    }
+    /// Forget what's on the screen, eg after a resize, so that the next
+    /// frame is sent whole
+    pub fn invalidate(&mut self) {
+        self.rows.clear();
+    }
+    fn diff(
+        &mut self,
+        frame: &[u8],
+    ) -> Vec<u8> {
+        let Some(split) = split_frame(frame) else {
+            self.rows.clear();
+            return frame.to_vec();
+        };
+        let changed: FxHashSet<u16> = split
+            .rows
+            .iter()
+            .filter(|(y, bytes)| self.rows.get(y) != Some(bytes))
+            .map(|(y, _)| *y)
+            .collect();
+        let mut diff = split.prefix.to_vec();
+        for (y, bytes) in split.rows {
+            // the row after a changed one is redrawn too, in case the
+            // changed one overflowed on it
+            if changed.contains(&y) || (y > 0 && changed.contains(&(y - 1))) {
+                diff.extend_from_slice(CSI_RESET);
+                diff.extend_from_slice(&bytes);
+                self.rows.insert(y, bytes);
+            }
+        }
+        diff
+    }
+    fn write_buffer(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.rows.clear();
+            self.stdout.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+impl Default for ScreenWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for ScreenWriter {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if !self.in_frame && self.buffer.len() > PASSTHROUGH_CAPACITY {
+            self.write_buffer()?;
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        if self.in_frame {
+            return Ok(());
+        }
+        self.write_buffer()?;
+        self.stdout.flush()
+    }
+}
+
+/// Parse a cursor move (`ESC[<row>;<col>H`) at the start of the bytes,
+/// returning the 0 based row and the length of the sequence
+fn parse_move_to(bytes: &[u8]) -> Option<(u16, usize)> {
+    let rest = bytes.strip_prefix(b"\x1b[")?;
+    let len = rest
+        .iter()
+        .position(|&b| !b.is_ascii_digit() && b != b';')?;
+    if rest[len] != b'H' {
+        return None;
+    }
+    let params = std::str::from_utf8(&rest[..len]).ok()?;
+    let (row, col) = params.split_once(';')?;
+    if col.is_empty() {
+        return None;
+    }
+    let row: u16 = row.parse().ok()?;
+    Some((row.checked_sub(1)?, len + 3))
+}
+
+/// Split the frame in rows, or return None if it does something
+/// else than drawing in rows
+fn split_frame(frame: &[u8]) -> Option<SplitFrame<'_>> {
+    // clearing the screen, or a part of it, isn't done by rows
+    let clears = [
+        b"\x1b[J".as_slice(),
+        b"\x1b[0J",
+        b"\x1b[1J",
+        b"\x1b[2J",
+        b"\x1b[3J",
+    ];
+    if frame.contains(&b'\n')
+        || clears
+            .iter()
+            .any(|clear| frame.windows(clear.len()).any(|w| w == *clear))
+    {
+        return None;
+    }
+    let mut prefix = frame;
+    let mut rows: Vec<(u16, Vec<u8>)> = Vec::new();
+    let mut current: Option<usize> = None; // index in rows
+    let mut segment_start = 0;
+    let mut i = 0;
+    while i < frame.len() {
+        if frame[i] == 0x1b {
+            if let Some((y, len)) = parse_move_to(&frame[i..]) {
+                match current {
+                    Some(idx) => rows[idx].1.extend_from_slice(&frame[segment_start..i]),
+                    None => prefix = &frame[..i],
+                }
+                let idx = match rows.iter().position(|(row, _)| *row == y) {
+                    Some(idx) => idx,
+                    None => {
+                        rows.push((y, Vec::new()));
+                        rows.len() - 1
+                    }
+                };
+                current = Some(idx);
+                segment_start = i;
+                i += len;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    if let Some(idx) = current {
+        rows[idx].1.extend_from_slice(&frame[segment_start..]);
+    }
+    Some(SplitFrame { prefix, rows })
+}
+
+#[test]
+fn test_split_frame() {
+    let frame = b"\x1b[?25l\x1b[1;1Habc\x1b[2;1Hdef\x1b[1;5Hg";
+    let split = split_frame(frame).unwrap();
+    assert_eq!(split.prefix, b"\x1b[?25l");
+    assert_eq!(
+        split.rows,
+        vec![
+            (0, b"\x1b[1;1Habc\x1b[1;5Hg".to_vec()),
+            (1, b"\x1b[2;1Hdef".to_vec()),
+        ]
+    );
+    assert_eq!(split_frame(b"\x1b[2J\x1b[1;1Habc"), None);
+    let mut writer = ScreenWriter::new();
+    assert_eq!(writer.diff(frame).len(), frame.len() + 2 * CSI_RESET.len());
+    // only the changed row, and the one after it, are sent again
+    let frame = b"\x1b[1;1Habc\x1b[1;5Hg\x1b[2;1Hxyz\x1b[3;1Hghi\x1b[9;1Hjkl";
+    let diff = writer.diff(frame);
+    assert_eq!(
+        diff,
+        b"\x1b[0m\x1b[2;1Hxyz\x1b[0m\x1b[3;1Hghi\x1b[0m\x1b[9;1Hjkl"
+    );
+    assert!(writer.diff(frame).is_empty());
+    // the row after a changed one is sent too
+    let diff = writer.diff(b"\x1b[1;1Habc\x1b[1;5Hh\x1b[2;1Hxyz\x1b[3;1Hghi\x1b[9;1Hjkl");
+    assert_eq!(diff, b"\x1b[0m\x1b[1;1Habc\x1b[1;5Hh\x1b[0m\x1b[2;1Hxyz");
+}
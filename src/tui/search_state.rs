@@ -97,14 +97,27 @@ impl SearchState {
         &mut self,
         key: KeyCombination,
     ) -> bool {
-        if self.input.focused() {
-            if self.input.apply_key_combination(key) {
-                self.up_to_date = false;
-                return true;
-            }
+        if self.input.focused() && self.input.apply_key_combination(key) {
+            self.up_to_date = false;
+            return true;
         }
         false
     }
+    /// The searched pattern, if any (an item index being
+    /// searched isn't a pattern)
+    pub fn pattern(&self) -> Option<String> {
+        (self.mode == SearchMode::Pattern && !self.input.is_empty())
+            .then(|| self.input.get_content())
+    }
+    pub fn set_pattern(
+        &mut self,
+        pattern: &str,
+    ) {
+        self.mode = SearchMode::Pattern;
+        self.input.set_str(pattern);
+        self.input.set_focus(false);
+        self.up_to_date = false;
+    }
     pub fn search(&self) -> Search {
         match self.mode {
             SearchMode::Pattern => Search::Pattern(Pattern {
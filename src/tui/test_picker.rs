@@ -0,0 +1,285 @@
+use {
+    crate::*,
+    anyhow::{
+        Result,
+        bail,
+    },
+    crokey::{
+        KeyCombination,
+        key,
+    },
+    std::{
+        path::PathBuf,
+        process::Command,
+    },
+    termimad::{
+        Area,
+        InputField,
+    },
+};
+
+const CSI_PICKER_CURSOR: &str = "\u{1b}[1m\u{1b}[30m\u{1b}[48;5;250m";
+const CSI_PICKER_SELECTED: &str = "\u{1b}[1m\u{1b}[38;5;208m";
+
+/// What the test picker wants done after a key combination
+pub enum TestPickerEffect {
+    /// the key was handled by the picker, which stays open
+    None,
+    /// the key isn't one of the picker
+    NotConsumed,
+    /// the picker must be closed without running anything
+    Cancel,
+    /// the job must run, scoped to the picked tests
+    Pick(Scope),
+}
+
+/// A list of the tests of the job, filtered by a fuzzy pattern,
+/// in which some of them can be selected to scope the job
+pub struct TestPicker {
+    tests: Vec<String>,
+    selected: Vec<bool>,
+    input: InputField,
+    /// indexes of the tests matching the pattern
+    matching: Vec<usize>,
+    /// index, among the matching tests, of the one under the cursor
+    cursor: usize,
+    scroll: usize,
+}
+
+impl TestPicker {
+    /// Build a picker with the tests listed by cargo test or nextest
+    pub fn new(tests: Vec<String>) -> Result<Self> {
+        if tests.is_empty() {
+            bail!("no test found");
+        }
+        Ok(Self::from_tests(tests))
+    }
+    pub fn from_tests(tests: Vec<String>) -> Self {
+        let mut input = InputField::default();
+        input.set_focus(true);
+        let matching = (0..tests.len()).collect();
+        Self {
+            selected: vec![false; tests.len()],
+            tests,
+            input,
+            matching,
+            cursor: 0,
+            scroll: 0,
+        }
+    }
+    fn update_matching(&mut self) {
+        let pattern = self.input.get_content();
+        self.matching = (0..self.tests.len())
+            .filter(|&idx| fuzzy_match(&pattern, &self.tests[idx]))
+            .collect();
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+    /// The tests to scope the job to: the selected ones or,
+    /// if none is selected, the one under the cursor
+    fn picked_tests(&self) -> Vec<String> {
+        let selected: Vec<String> = self
+            .tests
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(test, _)| test.clone())
+            .collect();
+        if !selected.is_empty() {
+            return selected;
+        }
+        self.matching
+            .get(self.cursor)
+            .map(|&idx| vec![self.tests[idx].clone()])
+            .unwrap_or_default()
+    }
+    pub fn apply_key_combination(
+        &mut self,
+        key: KeyCombination,
+    ) -> TestPickerEffect {
+        if key == key!(esc) {
+            return TestPickerEffect::Cancel;
+        }
+        if key == key!(enter) {
+            let tests = self.picked_tests();
+            if tests.is_empty() {
+                return TestPickerEffect::None;
+            }
+            return TestPickerEffect::Pick(Scope { tests });
+        }
+        if key == key!(up) {
+            self.cursor = self.cursor.saturating_sub(1);
+        } else if key == key!(down) {
+            if self.cursor + 1 < self.matching.len() {
+                self.cursor += 1;
+            }
+        } else if key == key!(space) {
+            if let Some(&idx) = self.matching.get(self.cursor) {
+                self.selected[idx] ^= true;
+            }
+        } else if self.input.apply_key_combination(key) {
+            self.update_matching();
+        } else {
+            return TestPickerEffect::NotConsumed;
+        }
+        TestPickerEffect::None
+    }
+    pub fn draw(
+        &mut self,
+        w: &mut W,
+        area: Area,
+    ) -> Result<()> {
+        if area.height < 3 {
+            return Ok(());
+        }
+        goto_line(w, area.top)?;
+        clear_line(w)?;
+        let selected_count = self.selected.iter().filter(|s| **s).count();
+        draw(
+            w,
+            CSI_BOLD,
+            &format!(
+                "{}/{} tests, {} selected - space: select, enter: run, esc: cancel",
+                self.matching.len(),
+                self.tests.len(),
+                selected_count,
+            ),
+        )?;
+        goto_line(w, area.top + 1)?;
+        draw(w, CSI_FOUND, ">")?;
+        self.input
+            .change_area(area.left + 2, area.top + 1, area.width - 2);
+        self.input.display_on(w)?;
+        let list_height = (area.height - 2) as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + list_height {
+            self.scroll = self.cursor + 1 - list_height;
+        }
+        for row in 0..list_height {
+            goto_line(w, area.top + 2 + row as u16)?;
+            clear_line(w)?;
+            let line_idx = self.scroll + row;
+            let Some(&test_idx) = self.matching.get(line_idx) else {
+                continue;
+            };
+            let mark = if self.selected[test_idx] {
+                "[x] "
+            } else {
+                "[ ] "
+            };
+            let label: String = format!("{mark}{}", self.tests[test_idx])
+                .chars()
+                .take(area.width as usize)
+                .collect();
+            let csi = if line_idx == self.cursor {
+                CSI_PICKER_CURSOR
+            } else if self.selected[test_idx] {
+                CSI_PICKER_SELECTED
+            } else {
+                ""
+            };
+            draw(w, csi, &label)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tell whether all the chars of the pattern are found, in order,
+/// in the candidate (ignoring case)
+pub fn fuzzy_match(
+    pattern: &str,
+    candidate: &str,
+) -> bool {
+    let mut candidate_chars = candidate.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|pc| candidate_chars.any(|cc| cc == pc))
+}
+
+/// The listing of the tests of the job, with `cargo nextest list` if
+/// it's a nextest job, with `cargo test -- --list` otherwise, which
+/// may be run in another thread
+pub struct TestListing {
+    args: Vec<String>,
+    is_nextest: bool,
+    directory: PathBuf,
+}
+
+impl TestListing {
+    pub fn new(mission: &Mission) -> Self {
+        let command = &mission.job.command;
+        let is_nextest = mission.is_test_job() && command[1] == "nextest";
+        let mut args: Vec<&str> = if is_nextest {
+            vec!["nextest", "list", "--message-format", "oneline"]
+        } else {
+            vec!["test"]
+        };
+        if mission.is_test_job() {
+            // we keep the arguments of the job (eg features, packages)
+            let first_arg = if is_nextest { 3 } else { 2 };
+            args.extend(
+                command
+                    .iter()
+                    .skip(first_arg)
+                    .take_while(|arg| *arg != "--")
+                    .map(String::as_str),
+            );
+        }
+        if !is_nextest {
+            args.extend(["--", "--list", "--format", "terse"]);
+        }
+        Self {
+            args: args.into_iter().map(str::to_string).collect(),
+            is_nextest,
+            directory: mission.execution_directory.clone(),
+        }
+    }
+    /// Run the listing, which may take a while as the tests are built
+    pub fn run(&self) -> Result<Vec<String>> {
+        let Self {
+            args,
+            is_nextest,
+            directory,
+        } = self;
+        info!("listing tests with cargo {args:?}");
+        let output = Command::new("cargo")
+            .args(args)
+            .current_dir(directory)
+            .output()?;
+        if !output.status.success() {
+            bail!(
+                "listing tests failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut tests: Vec<String> = if *is_nextest {
+            // lines are like "my-crate::bin/my-bin module::test_name"
+            stdout
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .map(|(_, test)| test.trim().to_string())
+                .collect()
+        } else {
+            // lines are like "module::test_name: test"
+            stdout
+                .lines()
+                .filter_map(|line| line.strip_suffix(": test"))
+                .map(str::to_string)
+                .collect()
+        };
+        tests.sort();
+        tests.dedup();
+        Ok(tests)
+    }
+}
+
+#[test]
+fn test_fuzzy_match() {
+    assert!(fuzzy_match("", "analysis::test_parse"));
+    assert!(fuzzy_match("tpars", "analysis::test_parse"));
+    assert!(fuzzy_match("ANAparse", "analysis::test_parse"));
+    assert!(!fuzzy_match("parsetest", "analysis::test_parse"));
+}
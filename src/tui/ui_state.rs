@@ -0,0 +1,179 @@
+use {
+    crate::*,
+    anyhow::Result,
+    rustc_hash::FxHasher,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        hash::{
+            Hash,
+            Hasher,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// The state of the UI in a project, kept between launches
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UiState {
+    /// the job which was running when bacon was left, when it
+    /// wasn't the default one
+    last_job: Option<ConcreteJobRef>,
+    /// the default job when the last job was saved
+    #[serde(default)]
+    default_job: Option<ConcreteJobRef>,
+    /// the state of the UI of the jobs, by name
+    #[serde(default)]
+    pub jobs: HashMap<String, JobUiState>,
+}
+
+/// The state of the UI of a job, kept between launches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobUiState {
+    /// the item on top of the screen
+    pub top_item_idx: Option<usize>,
+    #[serde(default)]
+    pub summary_toggle: Option<ToggledSetting>,
+    #[serde(default)]
+    pub diff_scope_toggle: Option<ToggledSetting>,
+    /// the pattern searched, if any
+    pub search: Option<String>,
+}
+
+/// A display setting toggled by the user, kept between launches as
+/// long as the configuration of the setting doesn't change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToggledSetting {
+    pub value: bool,
+    /// the configured value when the user toggled it
+    pub configured: bool,
+}
+
+impl ToggledSetting {
+    /// The toggle to keep, if the value isn't the configured one
+    pub fn new(
+        value: bool,
+        configured: bool,
+    ) -> Option<Self> {
+        (value != configured).then_some(Self { value, configured })
+    }
+    /// The value to apply: the toggled one, unless the configuration
+    /// changed since it was toggled
+    pub fn resolve(
+        toggle: Option<Self>,
+        configured: bool,
+    ) -> bool {
+        match toggle {
+            Some(toggle) if toggle.configured == configured => toggle.value,
+            _ => configured,
+        }
+    }
+}
+
+impl UiState {
+    /// Keep the job to restore on next launch, if the user switched
+    /// from the default one
+    pub fn set_last_job(
+        &mut self,
+        mut job: ConcreteJobRef,
+        default_job: &ConcreteJobRef,
+    ) {
+        job.scope = Scope::default();
+        self.last_job = (job != *default_job).then_some(job);
+        self.default_job = Some(default_job.clone());
+    }
+    /// The job to restore, unless the default job changed since
+    /// it was saved
+    pub fn last_job(
+        &self,
+        default_job: &ConcreteJobRef,
+    ) -> Option<&ConcreteJobRef> {
+        if self.default_job.as_ref() != Some(default_job) {
+            return None;
+        }
+        self.last_job.as_ref()
+    }
+    /// Return the path of the file keeping the UI state of the project,
+    /// if the system has a state directory
+    fn path(package_directory: &Path) -> Option<PathBuf> {
+        let mut hasher = FxHasher::default();
+        package_directory.hash(&mut hasher);
+        Some(
+            bacon_state_dir()?
+                .join("ui")
+                .join(format!("{:016x}.json", hasher.finish())),
+        )
+    }
+    /// Load the UI state of the project, or a default one when
+    /// none was saved
+    pub fn load(package_directory: &Path) -> Self {
+        let Some(path) = Self::path(package_directory) else {
+            return Self::default();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("invalid UI state {path:?}: {e}");
+            Self::default()
+        })
+    }
+    pub fn save(
+        &self,
+        package_directory: &Path,
+    ) -> Result<()> {
+        let Some(path) = Self::path(package_directory) else {
+            return Ok(());
+        };
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        write_atomically(&path, &serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_toggled_setting() {
+    // not toggled: the configuration applies
+    assert_eq!(ToggledSetting::new(true, true), None);
+    assert!(ToggledSetting::resolve(None, true));
+    assert!(!ToggledSetting::resolve(None, false));
+    // toggled: kept while the configuration doesn't change
+    let toggle = ToggledSetting::new(true, false);
+    assert!(toggle.is_some());
+    assert!(ToggledSetting::resolve(toggle, false));
+    // the configuration changed since: it wins
+    assert!(!ToggledSetting::resolve(
+        ToggledSetting::new(false, true),
+        false
+    ));
+}
+
+#[test]
+fn test_last_job() {
+    let check = ConcreteJobRef::from_job_name("check");
+    let clippy = ConcreteJobRef::from_job_name("clippy");
+    let test = ConcreteJobRef::from_job_name("test");
+    let mut ui_state = UiState::default();
+    // leaving on the default job: nothing to restore
+    ui_state.set_last_job(check.clone(), &check);
+    assert_eq!(ui_state.last_job(&check), None);
+    // leaving on another job: restored while the default job is the same
+    ui_state.set_last_job(clippy.clone(), &check);
+    assert_eq!(ui_state.last_job(&check), Some(&clippy));
+    // the default job changed, eg in the config or the directory defaults
+    assert_eq!(ui_state.last_job(&test), None);
+    // the scope isn't kept
+    let mut scoped = test.clone();
+    scoped.scope.tests.push("my_test".to_string());
+    ui_state.set_last_job(scoped, &check);
+    assert_eq!(ui_state.last_job(&check), Some(&test));
+}
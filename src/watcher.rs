@@ -2,6 +2,8 @@ use {
     crate::*,
     anyhow::Result,
     notify::{
+        EventHandler,
+        PollWatcher,
         RecommendedWatcher,
         RecursiveMode,
         Watcher as NotifyWatcher,
@@ -13,17 +15,106 @@ use {
             ModifyKind,
         },
     },
-    std::path::PathBuf,
+    std::{
+        path::{
+            Path,
+            PathBuf,
+        },
+        sync::{
+            Arc,
+            Mutex,
+        },
+        time::{
+            Duration,
+            Instant,
+        },
+    },
     termimad::crossbeam::channel::{
         Receiver,
+        Sender,
         bounded,
     },
 };
 
-/// A file watcher, providing a channel to receive notifications
+/// Interval between two scans of the paths which must be polled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A file watcher, providing a channel to receive notifications,
+/// each one with the (not excluded) paths of the event
 pub struct Watcher {
-    pub receiver: Receiver<()>,
-    _notify_watcher: RecommendedWatcher,
+    pub receiver: Receiver<Vec<PathBuf>>,
+    /// Paths which couldn't be watched with the notification mechanism
+    /// of the system (because of its limits) and are polled instead
+    pub polled_paths: Vec<PathBuf>,
+    /// all watched paths
+    watched_paths: Vec<PathBuf>,
+    notify_watcher: RecommendedWatcher,
+    _poll_watcher: Option<PollWatcher>,
+}
+
+/// Filters notify events and sends the paths of the relevant ones
+#[derive(Clone)]
+struct WatchEventHandler {
+    sender: Sender<Vec<PathBuf>>,
+    ignorer: Arc<Mutex<IgnorerSet>>,
+    linked_dirs: Arc<LinkedDirs>,
+    /// whether events come from a poll watcher, which reports
+    /// modifications in a less precise way
+    polling: bool,
+}
+
+impl EventHandler for WatchEventHandler {
+    fn handle_event(
+        &mut self,
+        res: notify::Result<notify::Event>,
+    ) {
+        let we = match res {
+            Ok(we) => we,
+            Err(e) => {
+                warn!("watch error: {:?}", e);
+                return;
+            }
+        };
+        match we.kind {
+            EventKind::Modify(ModifyKind::Metadata(_)) if !self.polling => {
+                debug!("ignoring metadata change");
+                return; // useless event
+            }
+            EventKind::Modify(ModifyKind::Data(DataChange::Any)) if !self.polling => {
+                debug!("ignoring 'any' data change");
+                return; // probably useless event with no real change
+            }
+            EventKind::Access(AccessKind::Close(AccessMode::Write)) => {
+                info!("close write event: {we:?}");
+            }
+            EventKind::Access(_) => {
+                debug!("ignoring access event: {we:?}");
+                return; // probably useless event
+            }
+            _ => {
+                info!("notify event: {we:?}");
+            }
+        }
+        let start = Instant::now();
+        let paths: Vec<PathBuf> = we
+            .paths
+            .iter()
+            .map(|path| normalize_path(&self.linked_dirs.to_link_path(path)))
+            .collect();
+        let paths = {
+            let mut ignorer = self.ignorer.lock().unwrap();
+            time!(Info, ignorer.included_pathbufs(&paths))
+        };
+        add_watch_time(start.elapsed());
+        if paths.is_empty() && !we.paths.is_empty() {
+            debug!("all excluded");
+            return;
+        }
+        debug!("included paths: {paths:?}");
+        if let Err(e) = self.sender.send(paths) {
+            debug!("error when notifying on notify event: {}", e);
+        }
+    }
 }
 
 impl Watcher {
@@ -33,63 +124,126 @@ impl Watcher {
     ) -> Result<Self> {
         info!("watcher on {:#?}", paths_to_watch);
         let (sender, receiver) = bounded(0);
-        let mut notify_watcher =
-            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
-                Ok(we) => {
-                    match we.kind {
-                        EventKind::Modify(ModifyKind::Metadata(_)) => {
-                            debug!("ignoring metadata change");
-                            return; // useless event
-                        }
-                        EventKind::Modify(ModifyKind::Data(DataChange::Any)) => {
-                            debug!("ignoring 'any' data change");
-                            return; // probably useless event with no real change
-                        }
-                        EventKind::Access(AccessKind::Close(AccessMode::Write)) => {
-                            info!("close write event: {we:?}");
-                        }
-                        EventKind::Access(_) => {
-                            debug!("ignoring access event: {we:?}");
-                            return; // probably useless event
-                        }
-                        _ => {
-                            info!("notify event: {we:?}");
-                        }
-                    }
-                    match time!(Info, ignorer.excludes_all_pathbufs(&we.paths)) {
-                        Ok(true) => {
-                            debug!("all excluded");
-                            return;
-                        }
-                        Ok(false) => {
-                            debug!("at least one is included");
-                        }
-                        Err(e) => {
-                            warn!("exclusion check failed: {e}");
-                        }
-                    }
-                    if let Err(e) = sender.send(()) {
-                        debug!("error when notifying on notify event: {}", e);
-                    }
-                }
-                Err(e) => warn!("watch error: {:?}", e),
-            })?;
-        for path in paths_to_watch {
+        let linked_dirs = LinkedDirs::find(paths_to_watch, &mut ignorer);
+        if !linked_dirs.is_empty() {
+            info!("linked dirs: {:#?}", &linked_dirs);
+        }
+        let mut paths_to_watch = paths_to_watch.to_vec();
+        paths_to_watch.extend(linked_dirs.real_paths().cloned());
+        let handler = WatchEventHandler {
+            sender,
+            ignorer: Arc::new(Mutex::new(ignorer)),
+            linked_dirs: Arc::new(linked_dirs),
+            polling: false,
+        };
+        let mut notify_watcher = notify::recommended_watcher(handler.clone())?;
+        let mut polled_paths = Vec::new();
+        for path in &paths_to_watch {
             if !path.exists() {
                 warn!("watch path doesn't exist: {:?}", path);
                 continue;
             }
-            if path.is_dir() {
-                debug!("add watch dir {:?}", path);
-                notify_watcher.watch(path, RecursiveMode::Recursive)?;
-            } else if path.is_file() {
-                debug!("add watch file {:?}", path);
-                notify_watcher.watch(path, RecursiveMode::NonRecursive)?;
+            debug!("add watch {:?}", path);
+            if let Err(e) = notify_watcher.watch(path, recursive_mode(path)) {
+                if !is_watch_limit_error(&e) {
+                    return Err(e.into());
+                }
+                warn!("watch limit reached on {:?}, falling back to polling", path);
+                // some sub-directories may have been registered before the error
+                let _ = notify_watcher.unwatch(path);
+                polled_paths.push(path.clone());
             }
         }
+        let poll_watcher = if polled_paths.is_empty() {
+            None
+        } else {
+            Some(poll_watcher(&polled_paths, handler)?)
+        };
         Ok(Self {
             receiver,
-            _notify_watcher: notify_watcher,
+            polled_paths,
+            watched_paths: paths_to_watch,
+            notify_watcher,
+            _poll_watcher: poll_watcher,
         })
     }
+    /// Watch additional files, unless they're already watched
+    pub fn watch_files(
+        &mut self,
+        paths: &[PathBuf],
+    ) {
+        for path in paths {
+            if self.watched_paths.iter().any(|w| path.starts_with(w)) {
+                continue;
+            }
+            debug!("add watch file {:?}", path);
+            if let Err(e) = self.notify_watcher.watch(path, RecursiveMode::NonRecursive) {
+                warn!("failed to watch {:?}: {}", path, e);
+                continue;
+            }
+            self.watched_paths.push(path.clone());
+        }
+    }
+    /// Watch the direct content of the directories, eg to be notified
+    /// of the creation of files
+    pub fn watch_directory_contents(
+        &mut self,
+        directories: &[PathBuf],
+    ) {
+        for dir in directories {
+            debug!("add watch of the content of {:?}", dir);
+            if let Err(e) = self.notify_watcher.watch(dir, RecursiveMode::NonRecursive) {
+                warn!("failed to watch {:?}: {}", dir, e);
+            }
+        }
+    }
+    /// Build a message telling the user about the paths which must be
+    /// polled, and how to raise the limit of the system, if necessary
+    pub fn watch_limit_message(&self) -> Option<Message> {
+        let first_path = self.polled_paths.first()?;
+        let mut markdown = format!(
+            "File watch limit reached: polling {}",
+            first_path.to_string_lossy()
+        );
+        if self.polled_paths.len() > 1 {
+            markdown.push_str(&format!(" (+{})", self.polled_paths.len() - 1));
+        }
+        if cfg!(target_os = "linux") {
+            markdown.push_str(" - raise `fs.inotify.max_user_watches` with `sysctl`");
+        }
+        Some(Message::long(markdown))
+    }
+}
+
+fn poll_watcher(
+    paths: &[PathBuf],
+    mut handler: WatchEventHandler,
+) -> Result<PollWatcher> {
+    handler.polling = true;
+    let config = notify::Config::default().with_poll_interval(POLL_INTERVAL);
+    let mut poll_watcher = PollWatcher::new(handler, config)?;
+    for path in paths {
+        poll_watcher.watch(path, recursive_mode(path))?;
+    }
+    Ok(poll_watcher)
+}
+
+fn recursive_mode(path: &Path) -> RecursiveMode {
+    if path.is_dir() {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    }
+}
+
+/// Tell whether the error comes from the limit on the number of watches
+/// of the system (eg `fs.inotify.max_user_watches` on linux)
+pub fn is_watch_limit_error(e: &notify::Error) -> bool {
+    match &e.kind {
+        notify::ErrorKind::MaxFilesWatch => true,
+        // inotify reports a full watch table with ENOSPC
+        #[cfg(target_os = "linux")]
+        notify::ErrorKind::Io(io_error) => io_error.raw_os_error() == Some(libc::ENOSPC),
+        _ => false,
+    }
 }
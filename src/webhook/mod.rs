@@ -0,0 +1,9 @@
+mod webhook_config;
+mod webhook_payload;
+mod webhook_settings;
+
+pub use {
+    webhook_config::*,
+    webhook_payload::*,
+    webhook_settings::*,
+};
@@ -0,0 +1,16 @@
+use {
+    crate::*,
+    serde::Deserialize,
+};
+
+/// The `[webhook]` part of a configuration: where to post the
+/// results of the executions
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WebhookConfig {
+    /// URL to which a JSON payload is posted after each execution
+    pub url: Option<String>,
+    /// Maximal duration of one attempt to post
+    pub timeout: Option<Period>,
+    /// How many times to try again to post when it failed
+    pub retries: Option<usize>,
+}
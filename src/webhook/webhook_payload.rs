@@ -0,0 +1,124 @@
+use {
+    crate::*,
+    serde_json::{
+        Value,
+        json,
+    },
+    std::time::Duration,
+};
+
+/// Build the JSON payload posted to the webhook at the end of
+/// an execution
+pub fn webhook_payload(
+    state: &AppState<'_>,
+    duration: Duration,
+) -> Value {
+    let mut payload = json!({
+        "project": state.mission.location_name,
+        "job": state.mission.concrete_job_ref.badge_label(),
+        "success": state.is_success(),
+        "duration_ms": duration.as_millis() as u64,
+    });
+    match &state.cmd_result {
+        CommandResult::Report(report) => {
+            payload["counts"] = json!({
+                "errors": report.stats.errors,
+                "test_fails": report.stats.test_fails,
+                "warnings": report.stats.warnings,
+                "passed_tests": report.stats.passed_tests,
+            });
+            payload["locations"] = report_locations(report).into();
+        }
+        CommandResult::Failure(failure) => {
+            payload["error_code"] = failure.error_code.into();
+        }
+        CommandResult::None => {}
+    }
+    payload
+}
+
+/// The first location of each item of the report, with its kind
+/// and message
+pub fn report_locations(report: &Report) -> Vec<Value> {
+    let mut locations = Vec::new();
+    let mut item = None;
+    for line in &report.lines {
+        if let LineType::Title(kind) = line.line_type {
+            let kind_name = match kind {
+                Kind::Error => "error",
+                Kind::Warning | Kind::External => "warning",
+                Kind::TestFail | Kind::DoctestFail => "test",
+                Kind::Info => "info",
+                Kind::Sum => {
+                    item = None;
+                    continue;
+                }
+            };
+            item = Some((
+                line.item_idx,
+                kind_name,
+                kind == Kind::External,
+                line.title_message().unwrap_or_default(),
+            ));
+            continue;
+        }
+        let Some(location) = line.location() else {
+            continue;
+        };
+        let Some((item_idx, kind, external, message)) = item else {
+            continue;
+        };
+        if item_idx == line.item_idx {
+            let mut entry = json!({
+                "kind": kind,
+                "location": location,
+                "message": message,
+            });
+            if external {
+                entry["external"] = true.into();
+            }
+            locations.push(entry);
+            // only the first location of the item is kept
+            item = None;
+        }
+    }
+    locations
+}
+
+#[test]
+fn test_report_locations() {
+    let mut lines = Vec::new();
+    let mut add_item = |item_idx, kind, label: &str, message: &str, locations: &[&str]| {
+        lines.push(Line::title(item_idx, kind, label, message));
+        for location in locations {
+            lines.push(Line::from_raw(
+                item_idx,
+                LineType::Location,
+                &format!("  --> {location}"),
+            ));
+        }
+    };
+    add_item(
+        1,
+        Kind::Warning,
+        "warning",
+        "unused variable",
+        &["src/main.rs:3:9"],
+    );
+    add_item(
+        2,
+        Kind::Error,
+        "error",
+        "mismatched types",
+        &["src/lib.rs:8:5", "src/lib.rs:2:1"],
+    );
+    add_item(3, Kind::Sum, "warning", "2 warnings emitted", &[]);
+    let report = Report::from_lines(lines);
+    assert_eq!(
+        report_locations(&report),
+        vec![
+            json!({"kind": "warning", "location": "src/main.rs:3:9", "message": "unused variable"}),
+            json!({"kind": "error", "location": "src/lib.rs:8:5", "message": "mismatched types"}),
+        ],
+    );
+}
@@ -0,0 +1,106 @@
+use {
+    crate::*,
+    std::{
+        io::Write,
+        process::{
+            Command,
+            Stdio,
+        },
+        thread,
+        time::Duration,
+    },
+};
+
+/// Settings of the webhook called after each execution
+#[derive(Debug, Clone)]
+pub struct WebhookSettings {
+    pub url: Option<String>,
+    pub timeout: Period,
+    pub retries: usize,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout: Duration::from_secs(10).into(),
+            retries: 2,
+        }
+    }
+}
+
+impl WebhookSettings {
+    pub fn apply_config(
+        &mut self,
+        config: &WebhookConfig,
+    ) {
+        if config.url.is_some() {
+            self.url.clone_from(&config.url);
+        }
+        if let Some(timeout) = config.timeout {
+            self.timeout = timeout;
+        }
+        if let Some(retries) = config.retries {
+            self.retries = retries;
+        }
+    }
+    /// Post the payload to the webhook, if there's one, in a
+    /// background thread (with `curl`)
+    pub fn post(
+        &self,
+        payload: &serde_json::Value,
+    ) {
+        let Some(url) = self.url.clone() else {
+            return;
+        };
+        let body = payload.to_string();
+        let timeout = self.timeout.duration;
+        let retries = self.retries;
+        thread::spawn(move || {
+            for attempt in 0..=retries {
+                if attempt > 0 {
+                    thread::sleep(Duration::from_secs(attempt as u64));
+                }
+                match post_once(&url, &body, timeout) {
+                    Ok(()) => {
+                        debug!("webhook called");
+                        return;
+                    }
+                    Err(e) => {
+                        warn!("webhook call failed (attempt {}): {e}", attempt + 1);
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn post_once(
+    url: &str,
+    body: &str,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let mut curl = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--request", "POST"])
+        .args(["--header", "Content-Type: application/json"])
+        .args(["--data-binary", "@-"])
+        .arg("--max-time")
+        .arg(timeout.as_secs_f64().to_string())
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = curl.stdin.take() {
+        stdin.write_all(body.as_bytes())?;
+    }
+    let output = curl.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "curl {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+    Ok(())
+}